@@ -0,0 +1,81 @@
+//! Move generation and checkmate detection benchmarks: `cargo bench --features bench`
+//!
+//! Every case runs for about a second after warming up and prints the mean time per iteration.
+//! The harness is hand-rolled instead of using criterion, as the crate has no dependencies
+//! outside of its optional features and criterion would be the only dev-dependency.
+use eliasfl_chess::{bench, Color, Game};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// Time spent warming up, which also decides the number of measured iterations
+const WARM_UP: Duration = Duration::from_millis(200);
+
+/// Run f until about a second has passed and print the mean time per run
+fn measure(name: &str, mut f: impl FnMut()) {
+    let start = Instant::now();
+    let mut runs: u32 = 0;
+    while start.elapsed() < WARM_UP {
+        f();
+        runs += 1;
+    }
+    let iterations = runs * 5;
+    let start = Instant::now();
+    for _ in 0..iterations {
+        f();
+    }
+    println!(
+        "{:<50} {:>12?} ({} iterations)",
+        name,
+        start.elapsed() / iterations,
+        iterations
+    );
+}
+
+fn main() {
+    let middle_game =
+        Game::from_fen("r1bqk2r/pppp1ppp/2n2n2/2b1p3/2B1P3/3P1N2/PPP2PPP/RNBQK2R w KQkq - 1 5")
+            .unwrap();
+    let positions = [("start", Game::new()), ("middle game", middle_game)];
+    for (name, game) in &positions {
+        measure(
+            &format!("get_possible_moves on all squares ({})", name),
+            || {
+                // Clones start with an empty move cache, so every run generates the moves again
+                let game = game.clone();
+                for file in 'a'..='h' {
+                    for rank in 1..=8 {
+                        black_box(game.get_possible_moves(format!("{}{}", file, rank)));
+                    }
+                }
+            },
+        );
+        measure(&format!("legal moves without cache ({})", name), || {
+            black_box(bench::legal_moves_uncached(black_box(game)));
+        });
+    }
+
+    // Positions after the mating move of mate-in-1 puzzles, and one where the king can escape
+    let mates = [
+        (
+            "fool's mate",
+            "rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3",
+            Color::White,
+        ),
+        (
+            "scholar's mate",
+            "r1bqkb1r/pppp1Qpp/2n2n2/4p3/2B1P3/8/PPPP1PPP/RNB1K1NR b KQkq - 0 4",
+            Color::Black,
+        ),
+        (
+            "back rank check with escape",
+            "3R2k1/5p1p/6p1/8/8/8/5PPP/6K1 b - - 0 1",
+            Color::Black,
+        ),
+    ];
+    for (name, fen, color) in mates {
+        let game = Game::from_fen(fen).unwrap();
+        measure(&format!("checkmate detection ({})", name), || {
+            black_box(bench::is_checkmate(black_box(&game), color));
+        });
+    }
+}
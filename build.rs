@@ -0,0 +1,36 @@
+//! Compiles data/eco.tsv into the table of the eco module when the "eco" feature is enabled
+use std::env;
+use std::fs;
+use std::path::Path;
+
+fn main() {
+    println!("cargo:rerun-if-changed=data/eco.tsv");
+    if env::var_os("CARGO_FEATURE_ECO").is_none() {
+        return;
+    }
+    let tsv = fs::read_to_string("data/eco.tsv").expect("data/eco.tsv should be readable");
+    let mut table = String::from("/// ECO code, name and moves in UCI notation of every line\n");
+    table += "const LINES: &[(&str, &str, &str)] = &[\n";
+    for (number, line) in tsv.lines().enumerate() {
+        if line.starts_with('#') || line.trim().is_empty() {
+            continue;
+        }
+        let columns: Vec<&str> = line.split('\t').collect();
+        let valid = columns.len() == 3
+            && columns[2]
+                .split(' ')
+                .all(|uci| (4..=5).contains(&uci.len()));
+        assert!(
+            valid,
+            "data/eco.tsv:{}: expected ECO code, name and UCI moves separated by tabs",
+            number + 1
+        );
+        table += &format!(
+            "    ({:?}, {:?}, {:?}),\n",
+            columns[0], columns[1], columns[2]
+        );
+    }
+    table += "];\n";
+    let out_dir = env::var_os("OUT_DIR").expect("Cargo sets OUT_DIR");
+    fs::write(Path::new(&out_dir).join("eco.rs"), table).expect("OUT_DIR should be writable");
+}
@@ -1,3 +1,4 @@
-# Make sure wasm-pack is installed: https://rustwasm.github.io/wasm-pack/installer/
-wasm-pack build --target web # --release
-Remove-Item .\pkg\.gitignore
\ No newline at end of file
+# Make sure wasm-bindgen is installed: cargo install wasm-bindgen-cli
+# The cdylib is only built here, so the library itself also builds without std
+cargo rustc --lib --target wasm32-unknown-unknown --features wasm --crate-type cdylib # --release
+wasm-bindgen --target web --out-dir pkg .\target\wasm32-unknown-unknown\debug\eliasfl_chess.wasm
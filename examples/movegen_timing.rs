@@ -0,0 +1,51 @@
+//! Times move generation and checkmate detection: `cargo run --release --example movegen_timing`
+use eliasfl_chess::*;
+use std::time::Instant;
+
+fn play(moves: &[(&str, &str)]) -> Game {
+    let mut game = Game::new();
+    for (from, to) in moves {
+        game.make_move(from.to_string(), to.to_string()).unwrap();
+    }
+    game
+}
+
+fn main() {
+    let middle_game = play(&[
+        ("e2", "e4"),
+        ("e7", "e5"),
+        ("g1", "f3"),
+        ("b8", "c6"),
+        ("f1", "c4"),
+        ("g8", "f6"),
+        ("d2", "d3"),
+        ("f8", "c5"),
+    ]);
+    let mate = play(&[("f2", "f3"), ("e7", "e5"), ("g2", "g4"), ("d8", "h4")]);
+    let iterations = 200;
+
+    for (name, game) in [("start", Game::new()), ("middle game", middle_game)] {
+        let start = Instant::now();
+        for _ in 0..iterations {
+            // Clones start with an empty move cache, so every iteration generates the moves again
+            let game = game.clone();
+            for file in 'a'..='h' {
+                for rank in 1..=8 {
+                    game.get_possible_moves(format!("{}{}", file, rank));
+                }
+            }
+        }
+        println!(
+            "get_possible_moves on all squares ({}): {:?}",
+            name,
+            start.elapsed() / iterations
+        );
+    }
+
+    let start = Instant::now();
+    for _ in 0..iterations {
+        let game = mate.clone();
+        assert_eq!(game.get_game_state(), GameState::CheckMate);
+    }
+    println!("checkmate detection: {:?}", start.elapsed() / iterations);
+}
@@ -0,0 +1,16 @@
+//! FEN import never panics, and valid positions are written back to the same FEN
+#![no_main]
+use eliasfl_chess::Game;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|fen: &str| {
+    if let Ok(game) = Game::from_fen(fen) {
+        // Imported positions may be illegal, which must not make the rules panic
+        game.get_game_state();
+        game.legal_moves();
+        if game.validate().is_ok() {
+            let again = Game::from_fen(&game.to_fen()).expect("Exported FEN can be imported");
+            assert_eq!(again.to_fen(), game.to_fen());
+        }
+    }
+});
@@ -0,0 +1,32 @@
+//! Random sequences of moves, both legal and illegal, keep one king per side and a valid state
+#![no_main]
+use eliasfl_chess::Game;
+use libfuzzer_sys::fuzz_target;
+
+/// Name of square 0 to 63, a1 to h8
+fn square(index: u8) -> String {
+    let index = index % 64;
+    format!("{}{}", (b'a' + index % 8) as char, index / 8 + 1)
+}
+
+fuzz_target!(|bytes: &[u8]| {
+    let mut game = Game::new();
+    for pair in bytes.chunks_exact(2) {
+        let moves = game.legal_moves();
+        let (from, to) = if pair[0] % 2 == 0 && !moves.is_empty() {
+            let mov = moves[pair[1] as usize % moves.len()];
+            (mov.from.to_string(), mov.to.to_string())
+        } else {
+            (square(pair[0] / 2), square(pair[1]))
+        };
+        // Illegal moves are rejected without changing the game
+        let before = game.clone();
+        if game.make_move(from, to).is_err() {
+            assert_eq!(game, before);
+            continue;
+        }
+        game.validate()
+            .expect("Legal moves keep the position valid");
+        game.get_game_state();
+    }
+});
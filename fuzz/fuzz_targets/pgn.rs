@@ -0,0 +1,16 @@
+//! PGN import never panics and the moves of imported games keep the position valid
+#![no_main]
+use eliasfl_chess::Game;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|pgn: &str| {
+    if let Ok(game) = Game::from_pgn(pgn) {
+        game.get_game_state();
+        // Games set up from an illegal FEN tag stay illegal
+        if !pgn.contains("FEN") {
+            game.validate()
+                .expect("Legal moves keep the position valid");
+        }
+        Game::from_pgn(&game.to_pgn()).expect("Exported PGN can be imported");
+    }
+});
@@ -0,0 +1,11 @@
+//! Squares parsed from arbitrary strings are on the board and written back the same
+#![no_main]
+use eliasfl_chess::Position;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|text: &str| {
+    if let Ok(position) = Position::from_string(text.to_string()) {
+        assert!(position.is_valid());
+        assert_eq!(position.to_string().parse::<Position>(), Ok(position));
+    }
+});
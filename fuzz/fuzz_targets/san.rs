@@ -0,0 +1,15 @@
+//! Moves in SAN from arbitrary strings are either rejected or legal
+#![no_main]
+use eliasfl_chess::Game;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|san: &str| {
+    let mut game = Game::new();
+    for san in san.split_whitespace() {
+        if game.make_move_san(san).is_err() {
+            break;
+        }
+        game.validate()
+            .expect("Legal moves keep the position valid");
+    }
+});
@@ -0,0 +1,60 @@
+/* C interface of the eliasfl-chess library, built as a shared library with
+   `cargo rustc --release --lib --features ffi --crate-type cdylib` */
+#ifndef ELIASFL_CHESS_H
+#define ELIASFL_CHESS_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Return values of chess_game_make_move and chess_game_best_move */
+#define CHESS_OK 0
+#define CHESS_INVALID_ARGUMENT -1
+#define CHESS_ILLEGAL_MOVE -2
+#define CHESS_NO_MOVE -3
+
+/* Room for the legal moves of any position */
+#define CHESS_MAX_MOVES 256
+
+/* A game, only used through pointers */
+typedef struct ChessGame ChessGame;
+
+/* Squares are 0-63 counting a1, b1, ..., h8, promotion is 'q', 'r', 'b', 'n' or 0 */
+typedef struct ChessMove {
+    uint8_t from;
+    uint8_t to;
+    uint8_t promotion;
+} ChessMove;
+
+/* New game with the standard piece arrangement */
+ChessGame *chess_game_new(void);
+
+/* Game from a position in FEN, NULL if it is invalid */
+ChessGame *chess_game_from_fen(const char *fen);
+
+/* Free a game, NULL is ignored */
+void chess_game_free(ChessGame *game);
+
+/* Make a move in UCI notation, eg. "e2e4" or "e7e8q" */
+int32_t chess_game_make_move(ChessGame *game, const char *uci);
+
+/* Write the FEN into buffer, returns its length without the NUL like snprintf */
+size_t chess_game_fen(const ChessGame *game, char *buffer, size_t length);
+
+/* Write at most capacity legal moves, returns the number of legal moves */
+size_t chess_game_legal_moves(const ChessGame *game, ChessMove *moves, size_t capacity);
+
+/* Write the best move of the engine searching depth plies */
+int32_t chess_game_best_move(const ChessGame *game, uint32_t depth, ChessMove *best);
+
+/* 0 in progress, 1 check, 2 checkmate, 3 stalemate, 4 draw, 5 time forfeit, -1 for NULL */
+int32_t chess_game_state(const ChessGame *game);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,175 @@
+"""Python bindings of the eliasfl-chess library, using the C functions of the "ffi" feature
+
+Build the shared library with `cargo rustc --release --lib --features ffi --crate-type cdylib`.
+It is loaded from the path in the ELIASFL_CHESS_LIB environment variable, or from target/release
+of the repository.
+
+    from eliasfl_chess import Engine, Game
+
+    game = Game()
+    game.make_move("e2e4")
+    print(game.fen(), game.legal_move_count())
+    print(Engine(3).best_move(game))
+"""
+
+import ctypes
+import os
+import sys
+
+__all__ = ["Engine", "Game", "IllegalMove", "Move", "State"]
+
+
+class _ChessMove(ctypes.Structure):
+    _fields_ = [
+        ("from_", ctypes.c_uint8),
+        ("to", ctypes.c_uint8),
+        ("promotion", ctypes.c_uint8),
+    ]
+
+
+def _library_path():
+    if "ELIASFL_CHESS_LIB" in os.environ:
+        return os.environ["ELIASFL_CHESS_LIB"]
+    name = {"win32": "eliasfl_chess.dll", "darwin": "libeliasfl_chess.dylib"}.get(
+        sys.platform, "libeliasfl_chess.so"
+    )
+    root = os.path.dirname(os.path.dirname(os.path.abspath(__file__)))
+    return os.path.join(root, "target", "release", name)
+
+
+_lib = ctypes.CDLL(_library_path())
+_lib.chess_game_new.restype = ctypes.c_void_p
+_lib.chess_game_from_fen.argtypes = [ctypes.c_char_p]
+_lib.chess_game_from_fen.restype = ctypes.c_void_p
+_lib.chess_game_free.argtypes = [ctypes.c_void_p]
+_lib.chess_game_make_move.argtypes = [ctypes.c_void_p, ctypes.c_char_p]
+_lib.chess_game_make_move.restype = ctypes.c_int32
+_lib.chess_game_fen.argtypes = [ctypes.c_void_p, ctypes.c_char_p, ctypes.c_size_t]
+_lib.chess_game_fen.restype = ctypes.c_size_t
+_lib.chess_game_legal_moves.argtypes = [
+    ctypes.c_void_p,
+    ctypes.POINTER(_ChessMove),
+    ctypes.c_size_t,
+]
+_lib.chess_game_legal_moves.restype = ctypes.c_size_t
+_lib.chess_game_best_move.argtypes = [
+    ctypes.c_void_p,
+    ctypes.c_uint32,
+    ctypes.POINTER(_ChessMove),
+]
+_lib.chess_game_best_move.restype = ctypes.c_int32
+_lib.chess_game_state.argtypes = [ctypes.c_void_p]
+_lib.chess_game_state.restype = ctypes.c_int32
+
+_MAX_MOVES = 256
+
+
+class IllegalMove(ValueError):
+    """The move is not legal in the position or not UCI notation"""
+
+
+class State:
+    """Values of Game.state()"""
+
+    IN_PROGRESS = 0
+    CHECK = 1
+    CHECKMATE = 2
+    STALEMATE = 3
+    DRAW = 4
+    TIME_FORFEIT = 5
+
+
+class Move:
+    """Move from and to a square 0-63 (a1, b1, ..., h8), with a promotion letter or None"""
+
+    def __init__(self, from_square, to_square, promotion=None):
+        self.from_square = from_square
+        self.to_square = to_square
+        self.promotion = promotion
+
+    @staticmethod
+    def _from_c(move):
+        promotion = chr(move.promotion) if move.promotion else None
+        return Move(move.from_, move.to, promotion)
+
+    def uci(self):
+        """Move in UCI notation, eg. "e2e4" or "e7e8q" """
+
+        def square(index):
+            return "abcdefgh"[index % 8] + str(index // 8 + 1)
+
+        return square(self.from_square) + square(self.to_square) + (self.promotion or "")
+
+    def __eq__(self, other):
+        return isinstance(other, Move) and self.uci() == other.uci()
+
+    def __hash__(self):
+        return hash(self.uci())
+
+    def __repr__(self):
+        return "Move({!r})".format(self.uci())
+
+    def __str__(self):
+        return self.uci()
+
+
+class Game:
+    """A game from the standard starting position, or from fen"""
+
+    def __init__(self, fen=None):
+        if fen is None:
+            self._game = _lib.chess_game_new()
+        else:
+            self._game = _lib.chess_game_from_fen(fen.encode())
+            if not self._game:
+                raise ValueError("Invalid FEN: " + fen)
+
+    def __del__(self):
+        if getattr(self, "_game", None):
+            _lib.chess_game_free(self._game)
+            self._game = None
+
+    def make_move(self, move):
+        """Make a move in UCI notation or a Move, raise IllegalMove if it is not legal"""
+        uci = move.uci() if isinstance(move, Move) else move
+        if _lib.chess_game_make_move(self._game, uci.encode()) != 0:
+            raise IllegalMove(uci)
+
+    def fen(self):
+        """Position in Forsyth-Edwards Notation"""
+        length = _lib.chess_game_fen(self._game, None, 0)
+        buffer = ctypes.create_string_buffer(length + 1)
+        _lib.chess_game_fen(self._game, buffer, length + 1)
+        return buffer.value.decode()
+
+    def legal_moves(self):
+        """Legal moves of the player who's turn it is"""
+        moves = (_ChessMove * _MAX_MOVES)()
+        count = _lib.chess_game_legal_moves(self._game, moves, _MAX_MOVES)
+        return [Move._from_c(move) for move in moves[:count]]
+
+    def legal_move_count(self):
+        """Number of legal moves of the player who's turn it is"""
+        return _lib.chess_game_legal_moves(self._game, None, 0)
+
+    def state(self):
+        """One of the State values"""
+        return _lib.chess_game_state(self._game)
+
+    def is_over(self):
+        """If no more moves can be made"""
+        return self.state() not in (State.IN_PROGRESS, State.CHECK)
+
+
+class Engine:
+    """The engine of the library searching depth plies"""
+
+    def __init__(self, depth=3):
+        self.depth = depth
+
+    def best_move(self, game):
+        """Best move in game, None if the game is over"""
+        move = _ChessMove()
+        if _lib.chess_game_best_move(game._game, self.depth, ctypes.byref(move)) != 0:
+            return None
+        return Move._from_c(move)
@@ -0,0 +1,57 @@
+"""Tests of the Python bindings
+
+Build the shared library first, then run the tests from the repository:
+
+    cargo rustc --release --lib --features ffi --crate-type cdylib
+    python3 -m unittest discover python
+"""
+
+import unittest
+
+from eliasfl_chess import Engine, Game, IllegalMove, Move, State
+
+
+class GameTest(unittest.TestCase):
+    def test_start_position(self):
+        game = Game()
+        self.assertEqual(game.fen(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1")
+        self.assertEqual(game.legal_move_count(), 20)
+        self.assertIn(Move(12, 28), game.legal_moves())
+        self.assertEqual(game.state(), State.IN_PROGRESS)
+
+    def test_moves(self):
+        game = Game()
+        game.make_move("e2e4")
+        game.make_move(Move(52, 36))
+        self.assertEqual(
+            game.fen(), "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e6 0 2"
+        )
+        with self.assertRaises(IllegalMove):
+            game.make_move("e4e5")
+        with self.assertRaises(IllegalMove):
+            game.make_move("not a move")
+
+    def test_promotion(self):
+        game = Game("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1")
+        self.assertIn(Move(49, 57, "n"), game.legal_moves())
+        game.make_move("b7b8n")
+        self.assertEqual(game.fen(), "1N2k3/8/8/8/8/8/8/4K3 b - - 0 1")
+
+    def test_game_over(self):
+        game = Game("rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3")
+        self.assertEqual(game.state(), State.CHECKMATE)
+        self.assertTrue(game.is_over())
+        self.assertEqual(game.legal_moves(), [])
+        self.assertIsNone(Engine(2).best_move(game))
+
+    def test_invalid_fen(self):
+        with self.assertRaises(ValueError):
+            Game("not a position")
+
+    def test_engine_mates(self):
+        game = Game("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1")
+        self.assertEqual(Engine(2).best_move(game).uci(), "a1a8")
+
+
+if __name__ == "__main__":
+    unittest.main()
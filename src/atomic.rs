@@ -0,0 +1,77 @@
+//! Atomic chess: captures explode the capturing piece and all pieces except pawns around the destination
+//!
+//! A player wins by exploding the opponent's king, which is reported as [`crate::GameState::CheckMate`]
+//! for the player without king. Kings cannot capture and kings standing next to each other cannot
+//! give check, since capturing the opponent's king would explode both of them.
+
+use crate::{Color, Game, Piece, Position, Variant};
+
+/// Pieces removed by an explosion apart from the capturing and captured piece
+pub(crate) type Explosion = [Option<(Position, Piece)>; 8];
+
+impl Game {
+    /// Create an Atomic game with the standard piece arrangement
+    pub fn new_atomic() -> Self {
+        Game {
+            variant: Variant::Atomic,
+            ..Game::new()
+        }
+    }
+
+    /// Remove the piece on `center` and all pieces except pawns around it
+    ///
+    /// Returns the removed surrounding pieces so the explosion can be taken back
+    pub(crate) fn _explode(&mut self, center: &Position) -> Explosion {
+        self.board.remove(center);
+        let mut exploded = [None; 8];
+        let mut count = 0;
+        for file in -1..=1 {
+            for rank in -1..=1 {
+                if let Some(position) = self.config.board_size.relative_pos(center, file, rank) {
+                    if position == *center {
+                        continue;
+                    }
+                    if let Some(piece) = self.board.get(&position).copied() {
+                        if !matches!(piece, Piece::Pawn(_)) {
+                            self.board.remove(&position);
+                            exploded[count] = Some((position, piece));
+                            count += 1;
+                        }
+                    }
+                }
+            }
+        }
+        exploded
+    }
+
+    /// If the king with `color` is missing or attacked by a piece other than the king
+    ///
+    /// Kings next to each other are never attacked. If only the opponent's king is missing
+    /// the player has won, so the king is not threatened.
+    pub(crate) fn _atomic_king_is_threatened(&self, color: Color) -> bool {
+        let king_of = |color| {
+            self.board
+                .iter()
+                .find(|(_, piece)| **piece == Piece::King(color))
+                .map(|(position, _)| position)
+        };
+        let king = match king_of(color) {
+            Some(king) => king,
+            None => return true,
+        };
+        let opponent_king = match king_of(!color) {
+            Some(opponent_king) => opponent_king,
+            None => return false,
+        };
+        let file = (king.file as i32 - opponent_king.file as i32).abs();
+        let rank = (king.rank as i32 - opponent_king.rank as i32).abs();
+        if file <= 1 && rank <= 1 {
+            return false;
+        }
+        self.board.iter().any(|(from, piece)| {
+            piece.color() != color
+                && !matches!(piece, Piece::King(_))
+                && self._attacks(&from, &king)
+        })
+    }
+}
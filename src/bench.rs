@@ -0,0 +1,32 @@
+//! Internal entry points for the benchmarks in `benches/`, only with the `bench` feature
+//!
+//! These are not part of the stable API and may change with the board representation.
+//!
+//! # Example
+//! ```
+//! use eliasfl_chess::{bench, Color, Game};
+//!
+//! let game = Game::from_fen("rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3").unwrap();
+//! assert!(bench::is_checkmate(&game, Color::White));
+//! let stalemate = Game::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+//! assert!(!bench::is_checkmate(&stalemate, Color::Black));
+//! assert!(bench::legal_moves_uncached(&Game::new()).len() == 20);
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::{Color, Game, Move};
+
+/// If color cannot move and is in check, the check behind [`Game::get_game_state`], computed on a
+/// copy of game so the cached legal moves are not reused
+pub fn is_checkmate(game: &Game, color: Color) -> bool {
+    // Clones start with an empty move cache
+    let game = game.clone();
+    game._is_checkmate(color) && game._is_mated(color)
+}
+
+/// Legal moves of the player who's turn it is, generated without the cache of
+/// [`Game::legal_moves`]
+pub fn legal_moves_uncached(game: &Game) -> Vec<Move> {
+    game._legal_moves()
+}
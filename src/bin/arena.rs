@@ -0,0 +1,75 @@
+//! Engine against engine matches, eg. `cargo run --release --bin arena -- 2 3 20` plays 20 games
+//! between search depths 2 and 3 and prints the wins, draws, losses and Elo estimate
+use eliasfl_chess::tournament::{Format, Match};
+use eliasfl_chess::Engine;
+use std::env;
+use std::process;
+
+/// Games played if no number is given
+const DEFAULT_GAMES: u32 = 10;
+
+/// Half moves after which a game counts as a draw if no number is given
+const DEFAULT_MAX_PLIES: usize = 200;
+
+fn usage() -> ! {
+    eprintln!("Usage: arena <depth> <depth> [games] [max plies per game]");
+    eprintln!("Players alternate colors, so an odd number of games gets one more game");
+    process::exit(1);
+}
+
+/// Argument number `index` as a number, the default if it is not given
+fn argument<T: std::str::FromStr>(args: &[String], index: usize, default: Option<T>) -> T {
+    match args.get(index) {
+        Some(arg) => arg.parse().unwrap_or_else(|_| usage()),
+        None => default.unwrap_or_else(|| usage()),
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let depths: [u32; 2] = [argument(&args, 0, None), argument(&args, 1, None)];
+    let games = argument(&args, 2, Some(DEFAULT_GAMES));
+    let max_plies = argument(&args, 3, Some(DEFAULT_MAX_PLIES));
+    let names = depths.map(|depth| format!("Depth {}", depth));
+
+    let mut arena = Match::new(
+        Engine::new(depths[0]),
+        Engine::new(depths[1]),
+        Format::RoundRobin((games + 1) / 2),
+    )
+    .names(&names[0], &names[1])
+    .max_plies(max_plies);
+    let mut round = 0;
+    while let Some(game) = arena.play_game() {
+        round += 1;
+        println!(
+            "Game {}: {} (White) - {} (Black) {} after {} half moves",
+            round,
+            names[game.white],
+            names[1 - game.white],
+            game.simulation.result(),
+            game.simulation.moves.len()
+        );
+    }
+
+    println!();
+    println!(
+        "{:<10} {:>5} {:>5} {:>6} {:>7}",
+        "Player", "Wins", "Draws", "Losses", "Points"
+    );
+    for (player, name) in names.iter().enumerate() {
+        let (wins, draws, losses) = arena.record(player);
+        println!(
+            "{:<10} {:>5} {:>5} {:>6} {:>7}",
+            name,
+            wins,
+            draws,
+            losses,
+            arena.points(player)
+        );
+    }
+    match arena.elo_difference(1) {
+        Some(elo) => println!("{} is {:+.0} Elo compared to {}", names[1], elo, names[0]),
+        None => println!("Elo cannot be estimated when a player scores every point"),
+    }
+}
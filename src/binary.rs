@@ -0,0 +1,272 @@
+//! Compact binary encoding of a position, see [`Game::to_bytes`]
+//!
+//! The same position always gives the same bytes, which makes them usable as snapshots or keys.
+//! The move history, clock and player configuration are not stored.
+//!
+//! # Layout
+//! All integers are little endian:
+//!
+//! | Bytes  | Content                                                                   |
+//! | ------ | ------------------------------------------------------------------------- |
+//! | 0      | `u8` format version, currently [`VERSION`]                                |
+//! | 1..33  | 64 piece nibbles from a1, b1, ... to h8, the first square of a byte in the low nibble: 0 empty, 1 king, 2 queen, 3 rook, 4 bishop, 5 knight, 6 pawn, plus 8 for Black |
+//! | 33     | Flags: bit 0 Black to move, bits 1-4 castling rights of White kingside, White queenside, Black kingside and Black queenside, bit 5 Chess960, bit 6 Atomic, bit 7 Crazyhouse |
+//! | 34     | En passant square index `(rank - 1) * 8 + (file - 1)` plus 1, 0 if none   |
+//! | 35     | Promotion pieces as nibbles, White in the low nibble                      |
+//! | 36..38 | `u16` castling files minus 1: king in bits 0-2, queenside rook in bits 3-5, kingside rook in bits 6-8, then 8 minus the number of files in bits 9-11 and 8 minus the number of ranks in bits 12-14 of the [board size](crate::config::BoardSize) |
+//! | 38..42 | `u32` halfmove clock                                                      |
+//! | 42..46 | `u32` fullmove number                                                     |
+//!
+//! Crazyhouse games continue with the reserves:
+//!
+//! | Bytes  | Content                                                                   |
+//! | ------ | ------------------------------------------------------------------------- |
+//! | 46..51 | `u8` number of queens, rooks, bishops, knights and pawns White can drop   |
+//! | 51..56 | The same for Black                                                        |
+//! | 56     | 1 if captured pieces go to the capturer's reserve, 0 in Bughouse          |
+//! | 57..65 | `u64` with bit `(rank - 1) * 8 + (file - 1)` set if the piece there is a promoted pawn |
+//!
+//! # Example
+//! ```
+//! use eliasfl_chess::binary::POSITION_SIZE;
+//! use eliasfl_chess::Game;
+//!
+//! let mut game = Game::new();
+//! game.make_move_san("e4").unwrap();
+//! let bytes = game.to_bytes();
+//! assert_eq!(bytes.len(), POSITION_SIZE);
+//! assert_eq!(Game::from_bytes(&bytes).unwrap().to_fen(), game.to_fen());
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::config::{BoardSize, GameConfig};
+use crate::crazyhouse::Crazyhouse;
+use crate::{Board, CastlingFiles, CastlingRights, Color, Game, Piece, Position, Variant};
+
+/// Version of the binary format
+pub const VERSION: u8 = 1;
+
+/// Size in bytes of a position without Crazyhouse reserves
+pub const POSITION_SIZE: usize = 46;
+
+/// Size in bytes of a Crazyhouse position
+pub const CRAZYHOUSE_SIZE: usize = 65;
+
+const BLACK_TO_MOVE: u8 = 1;
+/// Lowest of the four castling right bits
+const CASTLING: u8 = 1 << 1;
+const CHESS960: u8 = 1 << 5;
+const ATOMIC: u8 = 1 << 6;
+const CRAZYHOUSE: u8 = 1 << 7;
+
+/// Nibble of a piece, see the [layout](self)
+pub(crate) fn piece_code(piece: &Piece) -> u8 {
+    let kind = match piece {
+        Piece::King(_) => 1,
+        Piece::Queen(_) => 2,
+        Piece::Rook(_) => 3,
+        Piece::Bishop(_) => 4,
+        Piece::Knight(_) => 5,
+        Piece::Pawn(_) => 6,
+    };
+    kind + 8 * piece.color().index() as u8
+}
+
+/// Piece of a nibble, None for empty squares and unused codes
+fn piece_from_code(code: u8) -> Option<Piece> {
+    let color = if code & 8 == 0 {
+        Color::White
+    } else {
+        Color::Black
+    };
+    match code & 7 {
+        1 => Some(Piece::King(color)),
+        2 => Some(Piece::Queen(color)),
+        3 => Some(Piece::Rook(color)),
+        4 => Some(Piece::Bishop(color)),
+        5 => Some(Piece::Knight(color)),
+        6 => Some(Piece::Pawn(color)),
+        _ => None,
+    }
+}
+
+impl Game {
+    /// The position in the compact binary format described in the [`binary`](crate::binary) module
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(CRAZYHOUSE_SIZE);
+        bytes.push(VERSION);
+        let mut squares = [0; 32];
+        for (position, piece) in self.pieces() {
+            let index = position.index();
+            squares[index / 2] |= piece_code(&piece) << (4 * (index % 2));
+        }
+        bytes.extend_from_slice(&squares);
+
+        let mut flags = 0;
+        if self.active_color == Color::Black {
+            flags |= BLACK_TO_MOVE;
+        }
+        for (i, rights) in self.castling.iter().enumerate() {
+            if rights.kingside {
+                flags |= CASTLING << (2 * i);
+            }
+            if rights.queenside {
+                flags |= CASTLING << (2 * i + 1);
+            }
+        }
+        if self.chess960 {
+            flags |= CHESS960;
+        }
+        if self.variant == Variant::Atomic {
+            flags |= ATOMIC;
+        }
+        if self.crazyhouse.is_some() {
+            flags |= CRAZYHOUSE;
+        }
+        bytes.push(flags);
+        bytes.push(self.en_passant.map_or(0, |square| square.index() as u8 + 1));
+        bytes.push(
+            piece_code(&self.promotion_piece(Color::White))
+                | piece_code(&self.promotion_piece(Color::Black)) << 4,
+        );
+        let files = self.castling_files;
+        let files = u16::from(files.king - 1)
+            | u16::from(files.queenside - 1) << 3
+            | u16::from(files.kingside - 1) << 6
+            | u16::from(8 - self.config.board_size.files()) << 9
+            | u16::from(8 - self.config.board_size.ranks()) << 12;
+        bytes.extend_from_slice(&files.to_le_bytes());
+        bytes.extend_from_slice(&self.halfmove_clock.to_le_bytes());
+        bytes.extend_from_slice(&self.fullmove_number.to_le_bytes());
+
+        if let Some(crazyhouse) = &self.crazyhouse {
+            for reserve in &crazyhouse.reserves {
+                bytes.extend_from_slice(&reserve.counts);
+            }
+            bytes.push(crazyhouse.keep_captures as u8);
+            bytes.extend_from_slice(&crazyhouse.promoted.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Load a position written by [`Game::to_bytes`]
+    ///
+    /// Returns Err if the version is unknown, the length is wrong or a field is out of range
+    #[allow(deprecated)]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Game, &'static str> {
+        if bytes.first() != Some(&VERSION) {
+            return Err("Unknown binary format version");
+        }
+        let flags = *bytes.get(33).ok_or("Too few bytes for a position")?;
+        let size = if flags & CRAZYHOUSE != 0 {
+            CRAZYHOUSE_SIZE
+        } else {
+            POSITION_SIZE
+        };
+        if bytes.len() != size {
+            return Err("Wrong number of bytes for a position");
+        }
+
+        let mut board = Board::new();
+        for index in 0..64 {
+            let code = bytes[1 + index / 2] >> (4 * (index % 2)) & 0xF;
+            if code == 0 {
+                continue;
+            }
+            let piece = piece_from_code(code).ok_or("Invalid piece code")?;
+            let position = Position::from_index(index).expect("Index is on the board");
+            board.insert(position, piece);
+        }
+        let mut castling = [CastlingRights {
+            kingside: false,
+            queenside: false,
+        }; 2];
+        for (i, rights) in castling.iter_mut().enumerate() {
+            rights.kingside = flags & CASTLING << (2 * i) != 0;
+            rights.queenside = flags & CASTLING << (2 * i + 1) != 0;
+        }
+        let en_passant = match bytes[34] {
+            0 => None,
+            index => Some(
+                Position::from_index(index as usize - 1)
+                    .filter(|square| matches!(square.rank, 3 | 6))
+                    .ok_or("Invalid en passant square")?,
+            ),
+        };
+        let mut promotion = [Piece::Queen(Color::White); 2];
+        for (i, &color) in [Color::White, Color::Black].iter().enumerate() {
+            promotion[i] = match piece_from_code(bytes[35] >> (4 * i) & 0xF) {
+                Some(Piece::King(_) | Piece::Pawn(_)) | None => {
+                    return Err("Invalid promotion piece")
+                }
+                Some(piece) if piece.color() != color => return Err("Invalid promotion piece"),
+                Some(piece) => piece,
+            };
+        }
+        let files = u16::from_le_bytes([bytes[36], bytes[37]]);
+        if files >> 15 != 0 {
+            return Err("Invalid castling files");
+        }
+        let file = |shift: u16| (files >> shift & 7) as u8 + 1;
+        let board_size = BoardSize::new(8 - (files >> 9 & 7) as u8, 8 - (files >> 12 & 7) as u8)?;
+        if board
+            .iter()
+            .any(|(position, _)| !board_size.contains(&position))
+        {
+            return Err("Piece outside of board");
+        }
+        let u32_at =
+            |i: usize| u32::from_le_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]);
+
+        let crazyhouse = if flags & CRAZYHOUSE != 0 {
+            let mut crazyhouse = Crazyhouse::new(match bytes[56] {
+                0 => false,
+                1 => true,
+                _ => return Err("Invalid Crazyhouse capture flag"),
+            });
+            crazyhouse.reserves[0]
+                .counts
+                .copy_from_slice(&bytes[46..51]);
+            crazyhouse.reserves[1]
+                .counts
+                .copy_from_slice(&bytes[51..56]);
+            let mut promoted = [0; 8];
+            promoted.copy_from_slice(&bytes[57..65]);
+            crazyhouse.promoted = u64::from_le_bytes(promoted);
+            Some(crazyhouse)
+        } else {
+            None
+        };
+
+        let mut game = Game {
+            board,
+            active_color: if flags & BLACK_TO_MOVE != 0 {
+                Color::Black
+            } else {
+                Color::White
+            },
+            promotion,
+            castling,
+            castling_files: CastlingFiles {
+                king: file(0),
+                queenside: file(3),
+                kingside: file(6),
+            },
+            chess960: flags & CHESS960 != 0,
+            en_passant,
+            halfmove_clock: u32_at(38),
+            fullmove_number: u32_at(42),
+            crazyhouse,
+            config: GameConfig::new().board_size(board_size),
+            variant: if flags & ATOMIC != 0 {
+                Variant::Atomic
+            } else {
+                Variant::Standard
+            },
+            ..Game::new()
+        };
+        game._update_state();
+        Ok(game)
+    }
+}
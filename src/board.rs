@@ -0,0 +1,213 @@
+//! Board representation with one slot per square
+
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::iter::FromIterator;
+use core::ops::Index;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{Color, Game, Piece, Position, Square};
+
+/// The pieces on the 64 squares of a chess board
+///
+/// Stored as an array indexed by `(rank - 1) * 8 + (file - 1)`, which makes the board `Copy`
+/// and cheap to clone compared to a HashMap. Methods mirror `HashMap<Position, Piece>`.
+///
+/// Serialized as a list of `(Position, Piece)` pairs ordered from a1 to h8.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(try_from = "Vec<(Position, Piece)>", into = "Vec<(Position, Piece)>")
+)]
+pub struct Board {
+    squares: [Option<Piece>; 64],
+}
+
+impl Board {
+    /// Creates an empty board
+    pub fn new() -> Self {
+        Self {
+            squares: [None; 64],
+        }
+    }
+
+    /// Array index of position or None if it is outside the board
+    fn index(position: &Position) -> Option<usize> {
+        if position.is_valid() {
+            Some((position.rank as usize - 1) * 8 + (position.file as usize - 1))
+        } else {
+            None
+        }
+    }
+
+    /// Position of array index
+    fn position(index: usize) -> Position {
+        Position {
+            file: (index % 8) as u8 + 1,
+            rank: (index / 8) as u8 + 1,
+        }
+    }
+
+    /// Get the piece on position
+    pub fn get(&self, position: &Position) -> Option<&Piece> {
+        Self::index(position).and_then(|i| self.squares[i].as_ref())
+    }
+
+    /// If there is a piece on position
+    pub fn contains_key(&self, position: &Position) -> bool {
+        self.get(position).is_some()
+    }
+
+    /// Place piece on position and return the piece that was there before (or None)
+    ///
+    /// Panics if position is outside the board
+    pub fn insert(&mut self, position: Position, piece: Piece) -> Option<Piece> {
+        let i = Self::index(&position).expect("Position outside of board");
+        self.squares[i].replace(piece)
+    }
+
+    /// Remove and return the piece on position (or None)
+    pub fn remove(&mut self, position: &Position) -> Option<Piece> {
+        Self::index(position).and_then(|i| self.squares[i].take())
+    }
+
+    /// Iterate over occupied positions and their pieces from a1 to h8
+    pub fn iter(&self) -> impl Iterator<Item = (Position, &Piece)> + '_ {
+        self.squares
+            .iter()
+            .enumerate()
+            .filter_map(|(i, square)| square.as_ref().map(|piece| (Self::position(i), piece)))
+    }
+
+    /// Iterate over occupied positions and copies of their pieces from a1 to h8
+    pub fn pieces(&self) -> Pieces<'_> {
+        Pieces {
+            board: self,
+            index: 0,
+        }
+    }
+
+    /// Number of pieces on the board
+    pub fn len(&self) -> usize {
+        self.squares
+            .iter()
+            .filter(|square| square.is_some())
+            .count()
+    }
+
+    /// If there are no pieces on the board
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Iterator over the pieces of a board, see [`Board::pieces`] and [`Game::pieces`]
+#[derive(Debug, Clone)]
+pub struct Pieces<'a> {
+    board: &'a Board,
+    index: usize,
+}
+
+impl Iterator for Pieces<'_> {
+    type Item = (Position, Piece);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.index < self.board.squares.len() {
+            let index = self.index;
+            self.index += 1;
+            if let Some(piece) = self.board.squares[index] {
+                return Some((Board::position(index), piece));
+            }
+        }
+        None
+    }
+}
+
+impl Game {
+    /// Iterate over the occupied positions and their pieces from a1 to h8
+    pub fn pieces(&self) -> Pieces<'_> {
+        self.board.pieces()
+    }
+
+    /// Iterate over the positions and pieces of color from a1 to h8
+    pub fn pieces_of(&self, color: Color) -> impl Iterator<Item = (Position, Piece)> + '_ {
+        self.pieces()
+            .filter(move |(_, piece)| piece.color() == color)
+    }
+}
+
+impl<'a> IntoIterator for &'a Game {
+    type Item = (Position, Piece);
+    type IntoIter = Pieces<'a>;
+
+    /// Same as [`Game::pieces`]
+    fn into_iter(self) -> Self::IntoIter {
+        self.pieces()
+    }
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Index<&Position> for Board {
+    type Output = Piece;
+
+    /// Panics if there is no piece on position
+    fn index(&self, position: &Position) -> &Self::Output {
+        self.get(position).expect("No piece on position")
+    }
+}
+
+impl Index<Square> for Board {
+    type Output = Piece;
+
+    /// Panics if there is no piece on square
+    fn index(&self, square: Square) -> &Self::Output {
+        self.squares[square.index()]
+            .as_ref()
+            .expect("No piece on square")
+    }
+}
+
+impl FromIterator<(Position, Piece)> for Board {
+    fn from_iter<I: IntoIterator<Item = (Position, Piece)>>(iter: I) -> Self {
+        let mut board = Board::new();
+        for (position, piece) in iter {
+            board.insert(position, piece);
+        }
+        board
+    }
+}
+
+impl TryFrom<Vec<(Position, Piece)>> for Board {
+    type Error = &'static str;
+
+    fn try_from(pieces: Vec<(Position, Piece)>) -> Result<Self, Self::Error> {
+        if pieces.iter().all(|(position, _)| position.is_valid()) {
+            Ok(pieces.into_iter().collect())
+        } else {
+            Err("Position outside of board")
+        }
+    }
+}
+
+impl From<Board> for Vec<(Position, Piece)> {
+    fn from(board: Board) -> Self {
+        board
+            .iter()
+            .map(|(position, piece)| (position, *piece))
+            .collect()
+    }
+}
+
+impl core::fmt::Debug for Board {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
@@ -0,0 +1,202 @@
+//! Built-in opening book with common opening lines and their ECO codes
+//!
+//! Openings are recognized by the order the moves were played in, transpositions are not detected.
+//! Only games from the standard starting position are in the book.
+//!
+//! # Example
+//! ```
+//! use eliasfl_chess::Game;
+//!
+//! let mut game = Game::new();
+//! game.make_move_san("e4").unwrap();
+//! game.make_move_san("c5").unwrap();
+//! assert_eq!(game.opening().unwrap().name, "Sicilian Defense");
+//! assert!(!game.book_moves().is_empty());
+//! ```
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::{Game, Move};
+
+/// ECO code, name and moves in UCI notation of every line in the book
+const LINES: &[(&str, &str, &str)] = &[
+    ("A00", "Polish Opening", "b2b4"),
+    ("A01", "Nimzo-Larsen Attack", "b2b3"),
+    ("A02", "Bird's Opening", "f2f4"),
+    ("A04", "Reti Opening", "g1f3"),
+    ("A10", "English Opening", "c2c4"),
+    ("A40", "Queen's Pawn Game", "d2d4"),
+    ("A45", "Indian Defense", "d2d4 g8f6"),
+    ("E60", "King's Indian Defense", "d2d4 g8f6 c2c4 g7g6"),
+    ("D80", "Grünfeld Defense", "d2d4 g8f6 c2c4 g7g6 b1c3 d7d5"),
+    (
+        "E20",
+        "Nimzo-Indian Defense",
+        "d2d4 g8f6 c2c4 e7e6 b1c3 f8b4",
+    ),
+    (
+        "E12",
+        "Queen's Indian Defense",
+        "d2d4 g8f6 c2c4 e7e6 g1f3 b7b6",
+    ),
+    ("A80", "Dutch Defense", "d2d4 f7f5"),
+    ("D00", "Queen's Pawn Game", "d2d4 d7d5"),
+    ("D00", "London System", "d2d4 d7d5 c1f4"),
+    ("D06", "Queen's Gambit", "d2d4 d7d5 c2c4"),
+    ("D20", "Queen's Gambit Accepted", "d2d4 d7d5 c2c4 d5c4"),
+    ("D30", "Queen's Gambit Declined", "d2d4 d7d5 c2c4 e7e6"),
+    ("D10", "Slav Defense", "d2d4 d7d5 c2c4 c7c6"),
+    ("B00", "King's Pawn Opening", "e2e4"),
+    ("B01", "Scandinavian Defense", "e2e4 d7d5"),
+    ("B02", "Alekhine's Defense", "e2e4 g8f6"),
+    ("B06", "Modern Defense", "e2e4 g7g6"),
+    ("B07", "Pirc Defense", "e2e4 d7d6 d2d4 g8f6 b1c3 g7g6"),
+    ("B10", "Caro-Kann Defense", "e2e4 c7c6"),
+    (
+        "B12",
+        "Caro-Kann Defense: Advance Variation",
+        "e2e4 c7c6 d2d4 d7d5 e4e5",
+    ),
+    ("B20", "Sicilian Defense", "e2e4 c7c5"),
+    (
+        "B22",
+        "Sicilian Defense: Alapin Variation",
+        "e2e4 c7c5 c2c3",
+    ),
+    ("B23", "Sicilian Defense: Closed", "e2e4 c7c5 b1c3"),
+    (
+        "B70",
+        "Sicilian Defense: Dragon Variation",
+        "e2e4 c7c5 g1f3 d7d6 d2d4 c5d4 f3d4 g8f6 b1c3 g7g6",
+    ),
+    (
+        "B90",
+        "Sicilian Defense: Najdorf Variation",
+        "e2e4 c7c5 g1f3 d7d6 d2d4 c5d4 f3d4 g8f6 b1c3 a7a6",
+    ),
+    ("C00", "French Defense", "e2e4 e7e6"),
+    (
+        "C02",
+        "French Defense: Advance Variation",
+        "e2e4 e7e6 d2d4 d7d5 e4e5",
+    ),
+    ("C20", "King's Pawn Game", "e2e4 e7e5"),
+    ("C21", "Danish Gambit", "e2e4 e7e5 d2d4 e5d4 c2c3"),
+    ("C22", "Center Game", "e2e4 e7e5 d2d4 e5d4 d1d4"),
+    ("C23", "Bishop's Opening", "e2e4 e7e5 f1c4"),
+    ("C25", "Vienna Game", "e2e4 e7e5 b1c3"),
+    ("C30", "King's Gambit", "e2e4 e7e5 f2f4"),
+    ("C33", "King's Gambit Accepted", "e2e4 e7e5 f2f4 e5f4"),
+    ("C40", "King's Knight Opening", "e2e4 e7e5 g1f3"),
+    ("C41", "Philidor Defense", "e2e4 e7e5 g1f3 d7d6"),
+    ("C42", "Petrov's Defense", "e2e4 e7e5 g1f3 g8f6"),
+    ("C44", "Scotch Game", "e2e4 e7e5 g1f3 b8c6 d2d4"),
+    ("C46", "Four Knights Game", "e2e4 e7e5 g1f3 b8c6 b1c3 g8f6"),
+    ("C50", "Italian Game", "e2e4 e7e5 g1f3 b8c6 f1c4"),
+    ("C50", "Giuoco Piano", "e2e4 e7e5 g1f3 b8c6 f1c4 f8c5"),
+    ("C51", "Evans Gambit", "e2e4 e7e5 g1f3 b8c6 f1c4 f8c5 b2b4"),
+    (
+        "C55",
+        "Two Knights Defense",
+        "e2e4 e7e5 g1f3 b8c6 f1c4 g8f6",
+    ),
+    ("C60", "Ruy Lopez", "e2e4 e7e5 g1f3 b8c6 f1b5"),
+    (
+        "C65",
+        "Ruy Lopez: Berlin Defense",
+        "e2e4 e7e5 g1f3 b8c6 f1b5 g8f6",
+    ),
+    (
+        "C68",
+        "Ruy Lopez: Exchange Variation",
+        "e2e4 e7e5 g1f3 b8c6 f1b5 a7a6 b5c6",
+    ),
+    (
+        "C70",
+        "Ruy Lopez: Morphy Defense",
+        "e2e4 e7e5 g1f3 b8c6 f1b5 a7a6",
+    ),
+];
+
+/// A named line of the opening book
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Opening {
+    /// Encyclopaedia of Chess Openings code, eg. "B20"
+    pub eco: &'static str,
+    /// Name of the opening, eg. "Sicilian Defense"
+    pub name: &'static str,
+    /// Moves in UCI notation separated by spaces
+    pub(crate) uci: &'static str,
+}
+
+impl Opening {
+    /// Moves of the line from the starting position
+    pub fn moves(&self) -> Vec<Move> {
+        self.uci
+            .split(' ')
+            .map(|uci| Move::from_uci(uci).expect("Book moves are valid"))
+            .collect()
+    }
+}
+
+impl fmt::Display for Opening {
+    /// ECO code and name, eg. "B20 Sicilian Defense"
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}", self.eco, self.name)
+    }
+}
+
+/// All lines in the book
+pub fn openings() -> impl Iterator<Item = Opening> {
+    LINES
+        .iter()
+        .map(|&(eco, name, uci)| Opening { eco, name, uci })
+}
+
+/// First book move in the position, eg. for the engine's opening moves
+pub fn book_move(game: &Game) -> Option<Move> {
+    game.book_moves().first().copied()
+}
+
+impl Game {
+    /// Moves continuing a book line from the current position, empty when out of book
+    pub fn book_moves(&self) -> Vec<Move> {
+        let history = match self._book_history() {
+            Some(history) => history,
+            None => return Vec::new(),
+        };
+        let mut moves = Vec::new();
+        for opening in openings() {
+            let line = opening.moves();
+            if line.len() > history.len() && line.starts_with(history) {
+                let next = line[history.len()];
+                if !moves.contains(&next) {
+                    moves.push(next);
+                }
+            }
+        }
+        moves
+    }
+
+    /// The deepest book line played so far, None if the game left the book before any named line
+    pub fn opening(&self) -> Option<Opening> {
+        let history = self._book_history()?;
+        openings()
+            .filter(|opening| history.starts_with(&opening.moves()))
+            .max_by_key(|opening| opening.moves().len())
+    }
+
+    /// Move history if the game started from the standard starting position
+    fn _book_history(&self) -> Option<&[Move]> {
+        let mut replay = Game::new();
+        for mov in self.history() {
+            replay._make_move(*mov).ok()?;
+        }
+        if replay.board == self.board && replay.active_color == self.active_color {
+            Some(self.history())
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,178 @@
+//! Bughouse: two linked Crazyhouse games played by teams of two
+//!
+//! Team A plays White on board 0 and Black on board 1, team B the other two sides.
+//! Pieces captured on one board go to the partner's reserve on the other board.
+//! The first checkmate or fallen flag on either board decides the game for both boards.
+//!
+//! # Example
+//! ```
+//! use std::time::Duration;
+//! use eliasfl_chess::bughouse::{Bughouse, BughouseResult};
+//!
+//! let mut session = Bughouse::new(None);
+//! let second = Duration::from_secs(1);
+//! for (board, mov) in [(0, "e4"), (0, "d5"), (0, "exd5")] {
+//!     assert_eq!(session.play(board, mov, second), Ok(BughouseResult::InProgress));
+//! }
+//! // The pawn captured by White on board 0 can be dropped by Black on board 1
+//! session.play(1, "e4", second).unwrap();
+//! session.play(1, "P@d4", second).unwrap();
+//! ```
+
+use core::time::Duration;
+
+use crate::config::TimeControl;
+use crate::crazyhouse::{parse_drop, Crazyhouse};
+use crate::{san, Color, Game, Piece};
+
+/// One of the two teams
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Team {
+    /// White on board 0 and Black on board 1
+    A,
+    /// Black on board 0 and White on board 1
+    B,
+}
+
+impl Team {
+    /// Team playing color on board (0 or 1)
+    pub fn of(board: usize, color: Color) -> Team {
+        match (board, color) {
+            (0, Color::White) | (1, Color::Black) => Team::A,
+            _ => Team::B,
+        }
+    }
+
+    /// The other team
+    pub fn opponent(&self) -> Team {
+        match self {
+            Team::A => Team::B,
+            Team::B => Team::A,
+        }
+    }
+}
+
+/// Result of a Bughouse session
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BughouseResult {
+    /// Both boards are still being played
+    InProgress,
+    /// The team checkmated an opponent or an opponent ran out of time
+    Won(Team),
+    /// A player without legal moves is not in check
+    Stalemate,
+}
+
+/// Two linked Crazyhouse games with clocks for all four players
+#[derive(Debug, Clone)]
+pub struct Bughouse {
+    /// The games of board 0 and 1
+    pub boards: [Game; 2],
+    /// Time control shared by all players, None for no clocks
+    pub time_control: Option<TimeControl>,
+    /// Remaining time per board and color
+    remaining: [[Duration; 2]; 2],
+    result: BughouseResult,
+}
+
+impl Bughouse {
+    /// Start both boards from the standard piece arrangement with `time_control` for every player
+    pub fn new(time_control: Option<TimeControl>) -> Self {
+        let board = Game {
+            crazyhouse: Some(Crazyhouse::new(false)),
+            ..Game::new()
+        };
+        let initial = time_control.map_or(Duration::ZERO, |t| t.initial);
+        Self {
+            boards: [board.clone(), board],
+            time_control,
+            remaining: [[initial; 2]; 2],
+            result: BughouseResult::InProgress,
+        }
+    }
+
+    /// Result of the session
+    pub fn result(&self) -> BughouseResult {
+        self.result
+    }
+
+    /// Remaining time of color on board, None without clocks
+    pub fn remaining(&self, board: usize, color: Color) -> Option<Duration> {
+        self.time_control
+            .map(|_| self.remaining[board][color.index()])
+    }
+
+    /// Check if the player to move on board has run out of time after thinking for `elapsed`
+    ///
+    /// Useful to end the session while a player is still thinking
+    pub fn check_flag(&mut self, board: usize, elapsed: Duration) -> BughouseResult {
+        if self.result == BughouseResult::InProgress && self.time_control.is_some() {
+            let color = self.boards[board].active_color;
+            if elapsed >= self.remaining[board][color.index()] {
+                self.remaining[board][color.index()] = Duration::ZERO;
+                self.result = BughouseResult::Won(Team::of(board, color).opponent());
+            }
+        }
+        self.result
+    }
+
+    /// Make a move in algebraic notation (eg. "Nf3") or a drop (eg. "N@f3") on board (0 or 1)
+    /// for the player who's turn it is there, who thought for `elapsed`
+    ///
+    /// A player who ran out of time loses without the move being made.
+    /// Return Err if the session is over, the board does not exist or the move is illegal
+    pub fn play(
+        &mut self,
+        board: usize,
+        action: &str,
+        elapsed: Duration,
+    ) -> Result<BughouseResult, &'static str> {
+        if self.result != BughouseResult::InProgress {
+            return Err("Game is over");
+        }
+        if board > 1 {
+            return Err("Board should be 0 or 1");
+        }
+        if self.check_flag(board, elapsed) != BughouseResult::InProgress {
+            return Ok(self.result);
+        }
+        let game = &mut self.boards[board];
+        let color = game.active_color;
+        let partner = 1 - board;
+
+        if action.contains('@') {
+            let (piece, position) = parse_drop(action, color)?;
+            game._drop(piece, position)?;
+        } else {
+            let mov = san::parse_san(game, action)?;
+            let house = game.crazyhouse.ok_or("Board is not Crazyhouse")?;
+            // The partner plays the opposite color on the other board
+            let transfer = game
+                .board
+                .get(&mov.to)
+                .map(|captured| house.reserve_piece(captured, &mov.to, !color))
+                .unwrap_or(Piece::Pawn(!color));
+            if game._make_move(mov)?.is_some() {
+                if let Some(house) = self.boards[partner].crazyhouse.as_mut() {
+                    house.reserves[(!color).index()].add(&transfer);
+                }
+            }
+        }
+
+        if let Some(time_control) = self.time_control {
+            let remaining = &mut self.remaining[board][color.index()];
+            // Increments of public time controls may be too large to add
+            *remaining = (*remaining - elapsed).saturating_add(time_control.increment);
+        }
+
+        let game = &self.boards[board];
+        if game._is_checkmate(game.active_color) {
+            self.result = if game._king_is_threatened(game.active_color) {
+                BughouseResult::Won(Team::of(board, color))
+            } else {
+                BughouseResult::Stalemate
+            };
+        }
+        Ok(self.result)
+    }
+}
@@ -0,0 +1,315 @@
+//! Builder for games starting from custom positions
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::config::GameConfig;
+use crate::crazyhouse::Crazyhouse;
+use crate::{
+    Board, CastlingFiles, CastlingRights, Color, Game, Piece, PieceKind, Position, Variant,
+};
+
+/// Reason a custom position is illegal, see [`GameBuilder::problems`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetupProblem {
+    /// The color has this number of kings instead of one
+    KingCount(Color, usize),
+    /// A pawn stands on the first or last rank of the board
+    PawnOnBackRank(Position),
+    /// The color has this number of pawns, more than 8
+    TooManyPawns(Color, usize),
+    /// The color has this number of pieces more than it started with and its missing pawns could
+    /// have promoted to
+    TooManyPromotions(Color, usize),
+    /// The color may castle without its king and rook on their starting squares
+    CastlingWithoutPieces(Color),
+    /// The color is in check although it is not its turn
+    WaitingPlayerInCheck(Color),
+}
+
+impl SetupProblem {
+    /// Error returned by [`GameBuilder::build`]
+    pub fn error(&self) -> &'static str {
+        match self {
+            SetupProblem::KingCount(..) => "Each color should have exactly one king",
+            SetupProblem::PawnOnBackRank(_) => "Pawns cannot stand on the first or last rank",
+            SetupProblem::TooManyPawns(..) => "Each color can have at most 8 pawns",
+            SetupProblem::TooManyPromotions(..) => {
+                "More pieces than the missing pawns could have promoted to"
+            }
+            SetupProblem::CastlingWithoutPieces(_) => {
+                "Castling requires king and rook on their starting squares"
+            }
+            SetupProblem::WaitingPlayerInCheck(_) => {
+                "The player who is not to move cannot be in check"
+            }
+        }
+    }
+}
+
+impl fmt::Display for SetupProblem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SetupProblem::KingCount(color, count) => {
+                write!(f, "{:?} has {} kings, should have one", color, count)
+            }
+            SetupProblem::PawnOnBackRank(position) => {
+                write!(
+                    f,
+                    "Pawn on {} cannot stand on the first or last rank",
+                    position
+                )
+            }
+            SetupProblem::TooManyPawns(color, count) => {
+                write!(f, "{:?} has {} pawns, at most 8 are possible", color, count)
+            }
+            SetupProblem::TooManyPromotions(color, count) => write!(
+                f,
+                "{:?} has {} pieces more than its missing pawns could have promoted to",
+                color, count
+            ),
+            SetupProblem::CastlingWithoutPieces(color) => write!(
+                f,
+                "{:?} may castle but king or rook is not on its starting square",
+                color
+            ),
+            SetupProblem::WaitingPlayerInCheck(color) => write!(
+                f,
+                "{:?} would be in check with {:?} to move",
+                color, !*color
+            ),
+        }
+    }
+}
+
+/// Builder for a [`Game`] with a custom position, created with [`Game::builder`]
+///
+/// Starts with an empty board, White to move and no castling rights. The position is validated by [`GameBuilder::build`].
+///
+/// # Example
+/// ```
+/// use eliasfl_chess::{Color, Game, Piece};
+///
+/// let game = Game::builder()
+///     .piece("e1", Piece::King(Color::White))
+///     .piece("e8", Piece::King(Color::Black))
+///     .piece("a7", Piece::Pawn(Color::White))
+///     .active_color(Color::Black)
+///     .build()
+///     .unwrap();
+/// assert_eq!(game.legal_move_count(), 5);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameBuilder {
+    pub(crate) board: Board,
+    pub(crate) active_color: Color,
+    pub(crate) castling: [CastlingRights; 2],
+    /// Rules of the game, kept when building from an existing game
+    castling_files: CastlingFiles,
+    chess960: bool,
+    config: GameConfig,
+    crazyhouse: Option<Crazyhouse>,
+    variant: Variant,
+    /// First error from setting up the position, returned by `build`
+    error: Option<&'static str>,
+}
+
+impl GameBuilder {
+    /// Builder with an empty board
+    pub fn new() -> Self {
+        Self {
+            board: Board::new(),
+            active_color: Color::White,
+            castling: [CastlingRights {
+                kingside: false,
+                queenside: false,
+            }; 2],
+            castling_files: CastlingFiles::default(),
+            chess960: false,
+            config: GameConfig::new(),
+            crazyhouse: None,
+            variant: Variant::Standard,
+            error: None,
+        }
+    }
+
+    /// Place piece on position (eg. "e2"), replacing any piece standing there
+    pub fn piece(mut self, position: &str, piece: Piece) -> Self {
+        match position.parse::<Position>() {
+            Ok(position) => {
+                self.board.insert(position, piece);
+            }
+            Err(_) => {
+                self.error.get_or_insert("Invalid position");
+            }
+        }
+        self
+    }
+
+    /// Set the color who's turn it is
+    pub fn active_color(mut self, color: Color) -> Self {
+        self.active_color = color;
+        self
+    }
+
+    /// Set castling rights of color
+    pub fn castling(mut self, color: Color, rights: CastlingRights) -> Self {
+        self.castling[color.index()] = rights;
+        self
+    }
+
+    /// Problems that make the position illegal, empty if the position can be built
+    ///
+    /// Each color needs exactly one king, no pawns may stand on the first or last rank, each color
+    /// has at most 8 pawns and pieces promoted from them, the player who is not to move may not be
+    /// in check and every castling right needs its king and rook on their starting squares
+    pub fn problems(&self) -> Vec<SetupProblem> {
+        self._game()._setup_problems()
+    }
+
+    /// Validate the position and create the game, see [`GameBuilder::problems`]
+    pub fn build(self) -> Result<Game, &'static str> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        if let Some(problem) = self.problems().first() {
+            return Err(problem.error());
+        }
+        let mut game = self._game();
+        game._update_state();
+        Ok(game)
+    }
+
+    /// Game with the position without validating it
+    fn _game(&self) -> Game {
+        Game {
+            board: self.board,
+            active_color: self.active_color,
+            castling: self.castling,
+            castling_files: self.castling_files,
+            chess960: self.chess960,
+            config: self.config,
+            crazyhouse: self.crazyhouse,
+            variant: self.variant,
+            en_passant: None,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            ..Game::new()
+        }
+    }
+}
+
+impl From<&Game> for GameBuilder {
+    /// Builder starting from the position of game, keeping its variant and rules
+    fn from(game: &Game) -> Self {
+        Self {
+            board: game.board,
+            active_color: game.active_color,
+            castling: game.castling,
+            castling_files: game.castling_files,
+            chess960: game.chess960,
+            config: game.config,
+            crazyhouse: game.crazyhouse,
+            variant: game.variant,
+            error: None,
+        }
+    }
+}
+
+impl Default for GameBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Game {
+    /// Builder for a game with a custom position, see [`GameBuilder`]
+    pub fn builder() -> GameBuilder {
+        GameBuilder::new()
+    }
+
+    /// Check that the position could occur in a game, eg. after editing the board or importing FEN
+    ///
+    /// Returns every problem found, see [`GameBuilder::problems`], which are described by their
+    /// `Display`. Castling is checked against [`Game::castling_files`], and in Atomic a king may
+    /// have exploded.
+    pub fn validate(&self) -> Result<(), Vec<SetupProblem>> {
+        let problems = self._setup_problems();
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems)
+        }
+    }
+
+    /// Problems that make the position illegal, see [`GameBuilder::problems`]
+    pub(crate) fn _setup_problems(&self) -> Vec<SetupProblem> {
+        let mut problems = Vec::new();
+        for color in [Color::White, Color::Black] {
+            let kings = self
+                .board
+                .iter()
+                .filter(|(_, piece)| **piece == Piece::King(color))
+                .count();
+            // In Atomic the king of the loser has exploded
+            let exploded = kings == 0 && self.variant == Variant::Atomic;
+            if kings != 1 && !exploded {
+                problems.push(SetupProblem::KingCount(color, kings));
+            }
+        }
+        for (position, piece) in self.board.iter() {
+            if matches!(piece, Piece::Pawn(_)) && self._is_last_rank(&position) {
+                problems.push(SetupProblem::PawnOnBackRank(position));
+            }
+        }
+        // Pieces dropped in Crazyhouse are not promoted
+        if self.crazyhouse.is_none() {
+            for color in [Color::White, Color::Black] {
+                let count = |kind| {
+                    self.board
+                        .iter()
+                        .filter(|(_, piece)| **piece == Piece::new(kind, color))
+                        .count()
+                };
+                let pawns = count(PieceKind::Pawn);
+                let promoted: usize = [
+                    (PieceKind::Queen, 1),
+                    (PieceKind::Rook, 2),
+                    (PieceKind::Bishop, 2),
+                    (PieceKind::Knight, 2),
+                ]
+                .iter()
+                .map(|&(kind, start)| count(kind).saturating_sub(start))
+                .sum();
+                if pawns > 8 {
+                    problems.push(SetupProblem::TooManyPawns(color, pawns));
+                } else if pawns + promoted > 8 {
+                    problems.push(SetupProblem::TooManyPromotions(color, pawns + promoted - 8));
+                }
+            }
+        }
+        for color in [Color::White, Color::Black] {
+            let rights = self.castling[color.index()];
+            let rank = self.config.board_size.home_rank(color);
+            let files = self.castling_files;
+            let at = |file, piece| self.board.get(&Position { file, rank }) == Some(&piece);
+            let king_home = at(files.king, Piece::King(color));
+            if (rights.kingside && !(king_home && at(files.kingside, Piece::Rook(color))))
+                || (rights.queenside && !(king_home && at(files.queenside, Piece::Rook(color))))
+            {
+                problems.push(SetupProblem::CastlingWithoutPieces(color));
+            }
+        }
+        // Check can only be decided with one king per color
+        if !problems
+            .iter()
+            .any(|problem| matches!(problem, SetupProblem::KingCount(..)))
+        {
+            let waiting = !self.active_color;
+            if self._king_is_threatened(waiting) {
+                problems.push(SetupProblem::WaitingPlayerInCheck(waiting));
+            }
+        }
+        problems
+    }
+}
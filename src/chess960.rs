@@ -0,0 +1,95 @@
+//! Chess960 (Fischer Random Chess): the pieces on rank 1 and 8 are shuffled
+//!
+//! The bishops stand on squares of opposite colors and the king between the rooks. Black mirrors White.
+//! After castling the king and rook end up on the same squares as in standard chess:
+//! the king on g or c file and the rook on f or d file. Castling is written as the king moving onto its rook, eg. "b1a1".
+
+use alloc::vec::Vec;
+
+use crate::{CastlingFiles, Color, Game, Piece, Position};
+
+/// Number of Chess960 starting positions
+pub const POSITION_COUNT: u16 = 960;
+
+/// Position id of the standard starting position
+pub const STANDARD_POSITION_ID: u16 = 518;
+
+/// Pieces on rank 1 from file a to h for the position id, numbered as by Reinhard Scharnagl
+///
+/// Return Err if the id is not below [`POSITION_COUNT`]
+pub fn back_rank(position_id: u16, color: Color) -> Result<[Piece; 8], &'static str> {
+    if position_id >= POSITION_COUNT {
+        return Err("Chess960 position id should be below 960");
+    }
+    let mut rank: [Option<Piece>; 8] = [None; 8];
+    let mut n = position_id as usize;
+    // Bishops on a light square (b, d, f, h) and a dark square (a, c, e, g)
+    rank[n % 4 * 2 + 1] = Some(Piece::Bishop(color));
+    n /= 4;
+    rank[n % 4 * 2] = Some(Piece::Bishop(color));
+    n /= 4;
+    // Queen on one of the 6 empty squares, then knights on 2 of the 5 remaining
+    let mut place = |piece: Piece, nth: usize| {
+        let file = (0..8)
+            .filter(|&file| rank[file].is_none())
+            .nth(nth)
+            .expect("Not enough empty squares");
+        rank[file] = Some(piece);
+    };
+    place(Piece::Queen(color), n % 6);
+    n /= 6;
+    let (first, second) = [
+        (0, 1),
+        (0, 2),
+        (0, 3),
+        (0, 4),
+        (1, 2),
+        (1, 3),
+        (1, 4),
+        (2, 3),
+        (2, 4),
+        (3, 4),
+    ][n];
+    // The second knight is placed after the first, which fills one of the squares before it
+    place(Piece::Knight(color), first);
+    place(Piece::Knight(color), second - 1);
+    // Rook, king and rook on the last three squares
+    for piece in [Piece::Rook(color), Piece::King(color), Piece::Rook(color)] {
+        place(piece, 0);
+    }
+    let mut pieces = [Piece::Pawn(color); 8];
+    for (piece, square) in pieces.iter_mut().zip(rank.iter()) {
+        *piece = square.expect("Every square is filled");
+    }
+    Ok(pieces)
+}
+
+impl Game {
+    /// Create a Chess960 game from the starting position with id 0 to 959, where 518 is the standard position
+    ///
+    /// Return Err if the id is not below 960
+    pub fn new_chess960(position_id: u16) -> Result<Game, &'static str> {
+        let mut game = Game::new();
+        for (color, rank) in [(Color::White, 1), (Color::Black, 8)] {
+            for (file, piece) in (1..).zip(back_rank(position_id, color)?) {
+                game.board.insert(Position { file, rank }, piece);
+            }
+        }
+        let mut files = Vec::new();
+        let mut king = 0;
+        for (file, piece) in (1..).zip(back_rank(position_id, Color::White)?) {
+            match piece {
+                Piece::Rook(_) => files.push(file),
+                Piece::King(_) => king = file,
+                _ => {}
+            }
+        }
+        game.castling_files = CastlingFiles {
+            king,
+            queenside: files[0],
+            kingside: files[1],
+        };
+        game.chess960 = true;
+        Ok(game)
+    }
+}
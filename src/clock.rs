@@ -0,0 +1,181 @@
+//! Chess clock counting down the thinking time of both players
+//!
+//! The clock does not measure time itself, the caller passes the time spent on every move.
+//!
+//! # Example
+//! ```
+//! use std::time::Duration;
+//! use eliasfl_chess::clock::Clock;
+//! use eliasfl_chess::config::TimeControl;
+//! use eliasfl_chess::{Color, Game, GameState};
+//!
+//! let mut game = Game::new();
+//! game.clock = Some(Clock::new(TimeControl::new(Duration::from_secs(60), Duration::from_secs(1))));
+//! game.make_move_timed("e2".to_string(), "e4".to_string(), Duration::from_secs(10)).unwrap();
+//! assert_eq!(game.clock.unwrap().remaining(Color::White), Some(Duration::from_secs(51)));
+//!
+//! // Black thinks too long
+//! let result = game.make_move_timed("e7".to_string(), "e5".to_string(), Duration::from_secs(90));
+//! assert!(result.is_err());
+//! assert_eq!(game.get_game_state(), GameState::TimeForfeit);
+//! ```
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::time::Duration;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::config::{GameConfig, TimeControl};
+use crate::{ChessError, Color, Game, GameEvent, GameState, MoveOutcome};
+
+/// Remaining thinking time of both players
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Clock {
+    /// Time control per color, `None` for a player without time limit
+    pub time_controls: [Option<TimeControl>; 2],
+    remaining: [Duration; 2],
+}
+
+impl Clock {
+    /// Clock with the same time control for both players
+    pub fn new(time_control: TimeControl) -> Self {
+        Self::with_time_controls([Some(time_control); 2])
+    }
+
+    /// Clock for the time controls of `config`, `None` if neither player has a time control
+    pub fn from_config(config: &GameConfig) -> Option<Self> {
+        let time_controls = [
+            config.side(Color::White).time_control,
+            config.side(Color::Black).time_control,
+        ];
+        if time_controls.iter().all(Option::is_none) {
+            return None;
+        }
+        Some(Self::with_time_controls(time_controls))
+    }
+
+    /// Clock with a time control per color (White first)
+    fn with_time_controls(time_controls: [Option<TimeControl>; 2]) -> Self {
+        let initial = |time_control: Option<TimeControl>| {
+            time_control.map_or(Duration::ZERO, |time_control| time_control.initial)
+        };
+        Self {
+            time_controls,
+            remaining: [initial(time_controls[0]), initial(time_controls[1])],
+        }
+    }
+
+    /// Time left for color, `None` if color has no time limit
+    pub fn remaining(&self, color: Color) -> Option<Duration> {
+        self.time_controls[color.index()].map(|_| self.remaining[color.index()])
+    }
+
+    /// If color has used up all time
+    pub fn is_flagged(&self, color: Color) -> bool {
+        self.remaining(color) == Some(Duration::ZERO)
+    }
+
+    /// Deduct `elapsed` from color's time, adding the increment if time is left
+    ///
+    /// Returns false if color ran out of time
+    pub fn record_move(&mut self, color: Color, elapsed: Duration) -> bool {
+        let time_control = match self.time_controls[color.index()] {
+            Some(time_control) => time_control,
+            None => return true,
+        };
+        let remaining = &mut self.remaining[color.index()];
+        if elapsed >= *remaining {
+            *remaining = Duration::ZERO;
+            return false;
+        }
+        // Increments of public time controls may be too large to add
+        *remaining = (*remaining - elapsed).saturating_add(time_control.increment);
+        true
+    }
+}
+
+impl fmt::Display for Clock {
+    /// Remaining time of both players, eg. "White 4:59 Black 5:00"
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut sides = Vec::new();
+        for color in [Color::White, Color::Black] {
+            let time = match self.remaining(color) {
+                Some(time) => {
+                    let seconds = time.as_secs();
+                    format!("{}:{:02}", seconds / 60, seconds % 60)
+                }
+                None => "-".to_string(),
+            };
+            sides.push(format!("{:?} {}", color, time));
+        }
+        write!(f, "{}", sides.join(" "))
+    }
+}
+
+impl Game {
+    /// Make a move after the player thought for `elapsed`, see [`Game::make_move`]
+    ///
+    /// Without [`Game::clock`] this is the same as [`Game::make_move`]. If the player ran out of
+    /// time the move is not made and the game ends with [`GameState::TimeForfeit`], which is
+    /// returned as [`ChessError::GameOver`].
+    pub fn make_move_timed(
+        &mut self,
+        from: String,
+        to: String,
+        elapsed: Duration,
+    ) -> Result<MoveOutcome, ChessError> {
+        self._play_timed(elapsed, |game| game.make_move(from, to))
+    }
+
+    /// Make a move in UCI notation after the player thought for `elapsed`, see
+    /// [`Game::make_move_timed`] and [`Game::make_move_uci`]
+    pub fn make_move_uci_timed(
+        &mut self,
+        uci: &str,
+        elapsed: Duration,
+    ) -> Result<MoveOutcome, ChessError> {
+        self._play_timed(elapsed, |game| game.make_move_uci(uci))
+    }
+
+    /// Make a move in algebraic notation after the player thought for `elapsed`, see
+    /// [`Game::make_move_timed`] and [`Game::make_move_san`]
+    pub fn make_move_san_timed(
+        &mut self,
+        san: &str,
+        elapsed: Duration,
+    ) -> Result<MoveOutcome, ChessError> {
+        self._play_timed(elapsed, |game| game.make_move_san(san))
+    }
+
+    /// Charge `elapsed` to the player who's turn it is and make the move with `play`
+    fn _play_timed(
+        &mut self,
+        elapsed: Duration,
+        play: impl FnOnce(&mut Game) -> Result<MoveOutcome, ChessError>,
+    ) -> Result<MoveOutcome, ChessError> {
+        if let Some(result) = self.result() {
+            return Err(ChessError::GameOver(result));
+        }
+        let mut clock = match self.clock {
+            Some(clock) => clock,
+            None => return play(self),
+        };
+        let color = self.active_color;
+        if !clock.record_move(color, elapsed) {
+            self.clock = Some(clock);
+            self._set_state(GameState::TimeForfeit);
+            self._push_event(GameEvent::GameOver(GameState::TimeForfeit));
+            return Err(ChessError::GameOver(
+                self.result().expect("Time forfeit ends the game"),
+            ));
+        }
+        let outcome = play(self)?;
+        self.clock = Some(clock);
+        Ok(outcome)
+    }
+}
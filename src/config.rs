@@ -0,0 +1,303 @@
+//! Per-side game configuration for odds matches and training scenarios
+
+use core::fmt;
+use core::str::FromStr;
+use core::time::Duration;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::clock::Clock;
+use crate::{Board, CastlingRights, Color, Game, Piece, PieceKind, Position};
+
+/// Thinking time of one player
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TimeControl {
+    /// Time at the start of the game
+    pub initial: Duration,
+    /// Time added after every move
+    pub increment: Duration,
+}
+
+impl TimeControl {
+    /// Time control with `initial` time and `increment` per move
+    pub fn new(initial: Duration, increment: Duration) -> Self {
+        Self { initial, increment }
+    }
+}
+
+impl FromStr for TimeControl {
+    type Err = &'static str;
+
+    /// Parse minutes and an optional increment in seconds, eg. "5+3" or "10"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = "Time control should be minutes and increment in seconds, eg. \"5+3\"";
+        let mut parts = s.trim().splitn(2, '+');
+        let minutes: u64 = parts
+            .next()
+            .and_then(|minutes| minutes.trim().parse().ok())
+            .ok_or(error)?;
+        let increment: u64 = match parts.next() {
+            Some(increment) => increment.trim().parse().map_err(|_| error)?,
+            None => 0,
+        };
+        // Both are added to the remaining time of a player, which should not overflow
+        let seconds = minutes
+            .checked_mul(60)
+            .filter(|seconds| seconds.checked_add(increment).is_some())
+            .ok_or("Time control is too long")?;
+        Ok(TimeControl::new(
+            Duration::from_secs(seconds),
+            Duration::from_secs(increment),
+        ))
+    }
+}
+
+impl fmt::Display for TimeControl {
+    /// Whole minutes and increment in seconds, eg. "5+3"
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}+{}",
+            self.initial.as_secs() / 60,
+            self.increment.as_secs()
+        )
+    }
+}
+
+/// Rules that apply to one player
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SideConfig {
+    /// Thinking time of the player, `None` for no clock
+    pub time_control: Option<TimeControl>,
+    /// If the player is allowed to castle
+    pub may_castle: bool,
+    /// Days to make every move in correspondence games, see [`crate::correspondence`]
+    pub days_per_move: Option<u32>,
+}
+
+impl Default for SideConfig {
+    fn default() -> Self {
+        Self {
+            time_control: None,
+            may_castle: true,
+            days_per_move: None,
+        }
+    }
+}
+
+/// Number of files and ranks of the board, from the a file and rank 1
+///
+/// Smaller boards use the same coordinates as a chess board with the squares outside cut off.
+/// Pawns cannot move two squares and there is no castling on boards other than 8x8.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "RawBoardSize"))]
+pub struct BoardSize {
+    files: u8,
+    ranks: u8,
+}
+
+/// Board size as serialized, checked by [`BoardSize::new`] when deserializing
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct RawBoardSize {
+    files: u8,
+    ranks: u8,
+}
+
+#[cfg(feature = "serde")]
+impl core::convert::TryFrom<RawBoardSize> for BoardSize {
+    type Error = &'static str;
+
+    fn try_from(size: RawBoardSize) -> Result<Self, Self::Error> {
+        BoardSize::new(size.files, size.ranks)
+    }
+}
+
+impl BoardSize {
+    /// The standard 8x8 board
+    pub const STANDARD: Self = Self { files: 8, ranks: 8 };
+    /// Gardner's 5x5 minichess
+    pub const MINICHESS: Self = Self { files: 5, ranks: 5 };
+    /// Silverman's 4x5 chess with rooks, queen and king
+    pub const SILVERMAN: Self = Self { files: 4, ranks: 5 };
+    /// Los Alamos 6x6 chess without bishops
+    pub const LOS_ALAMOS: Self = Self { files: 6, ranks: 6 };
+
+    /// Board with `files` and `ranks`, which must be between 4 and 8
+    pub fn new(files: u8, ranks: u8) -> Result<Self, &'static str> {
+        if (4..=8).contains(&files) && (4..=8).contains(&ranks) {
+            Ok(Self { files, ranks })
+        } else {
+            Err("Board should have 4 to 8 files and ranks")
+        }
+    }
+
+    /// Number of files, 4 to 8
+    pub fn files(&self) -> u8 {
+        self.files
+    }
+
+    /// Number of ranks, 4 to 8
+    pub fn ranks(&self) -> u8 {
+        self.ranks
+    }
+
+    /// If position is on the board
+    pub fn contains(&self, position: &Position) -> bool {
+        (1..=self.files).contains(&position.file) && (1..=self.ranks).contains(&position.rank)
+    }
+
+    /// The position offset by the given values, None if it is outside of the board, see
+    /// [`Position::relative_pos`]
+    pub fn relative_pos(
+        &self,
+        position: &Position,
+        file_offset: i32,
+        rank_offset: i32,
+    ) -> Option<Position> {
+        position
+            .relative_pos(file_offset, rank_offset)
+            .filter(|position| self.contains(position))
+    }
+
+    /// Rank where the pieces of color start (1 for White, the last rank for Black)
+    pub fn home_rank(&self, color: Color) -> u8 {
+        match color {
+            Color::White => 1,
+            Color::Black => self.ranks,
+        }
+    }
+
+    /// Pieces of the first rank from the a file, the standard arrangement cut down to size
+    fn back_rank(&self) -> &'static [PieceKind] {
+        use PieceKind::*;
+        match self.files {
+            4 => &[Rook, Queen, King, Rook],
+            5 => &[Rook, Knight, Bishop, Queen, King],
+            6 => &[Rook, Knight, Queen, King, Knight, Rook],
+            7 => &[Rook, Knight, Bishop, Queen, King, Knight, Rook],
+            _ => &[Rook, Knight, Bishop, Queen, King, Bishop, Knight, Rook],
+        }
+    }
+
+    /// Starting position: the back rank with a rank of pawns in front of it for both colors
+    fn starting_board(&self) -> Board {
+        let mut board = Board::new();
+        for color in [Color::White, Color::Black] {
+            let rank = self.home_rank(color);
+            let pawn_rank = (rank as i32 + color.direction()) as u8;
+            for (file, &kind) in (1..).zip(self.back_rank()) {
+                board.insert(Position { file, rank }, Piece::new(kind, color));
+                board.insert(
+                    Position {
+                        file,
+                        rank: pawn_rank,
+                    },
+                    Piece::Pawn(color),
+                );
+            }
+        }
+        board
+    }
+}
+
+impl Default for BoardSize {
+    fn default() -> Self {
+        Self::STANDARD
+    }
+}
+
+/// Game setup where the rules may differ between the players
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use eliasfl_chess::{Color, Game};
+/// use eliasfl_chess::config::{GameConfig, TimeControl};
+///
+/// // Time odds: White gets 5 minutes, Black 1 minute and may not castle
+/// let config = GameConfig::new()
+///     .time_control(Color::White, TimeControl::new(Duration::from_secs(300), Duration::ZERO))
+///     .time_control(Color::Black, TimeControl::new(Duration::from_secs(60), Duration::ZERO))
+///     .forbid_castling(Color::Black);
+/// let game = Game::with_config(config);
+/// assert!(!game.castling[1].kingside);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GameConfig {
+    /// Rules per color
+    pub sides: [SideConfig; 2],
+    /// Size of the board, see [`GameConfig::board_size`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub board_size: BoardSize,
+}
+
+impl GameConfig {
+    /// Standard rules for both players without clocks
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the time control of color
+    pub fn time_control(mut self, color: Color, time_control: TimeControl) -> Self {
+        self.sides[color.index()].time_control = Some(time_control);
+        self
+    }
+
+    /// Give color `days` to make every move, for correspondence games
+    pub fn days_per_move(mut self, color: Color, days: u32) -> Self {
+        self.sides[color.index()].days_per_move = Some(days);
+        self
+    }
+
+    /// Forbid color to castle during the whole game
+    pub fn forbid_castling(mut self, color: Color) -> Self {
+        self.sides[color.index()].may_castle = false;
+        self
+    }
+
+    /// Play on a smaller board, eg. [`BoardSize::MINICHESS`]
+    pub fn board_size(mut self, board_size: BoardSize) -> Self {
+        self.board_size = board_size;
+        self
+    }
+
+    /// Rules of color
+    pub fn side(&self, color: Color) -> &SideConfig {
+        &self.sides[color.index()]
+    }
+}
+
+impl Game {
+    /// Create game with the standard piece arrangement and the rules of `config`
+    ///
+    /// Sides that may not castle start without castling rights and the game has a
+    /// [`Clock`] if any side has a time control. Smaller boards start with their back rank cut
+    /// down from the standard arrangement, eg. "RNBQK" in 5x5 minichess.
+    pub fn with_config(config: GameConfig) -> Self {
+        let mut game = Game {
+            config,
+            clock: Clock::from_config(&config),
+            ..Game::new()
+        };
+        if config.board_size != BoardSize::STANDARD {
+            game.board = config.board_size.starting_board();
+            game.castling = [CastlingRights {
+                kingside: false,
+                queenside: false,
+            }; 2];
+        }
+        for color in [Color::White, Color::Black] {
+            if !config.side(color).may_castle {
+                game.castling[color.index()].kingside = false;
+                game.castling[color.index()].queenside = false;
+            }
+        }
+        game
+    }
+}
@@ -0,0 +1,226 @@
+//! Parsing of square coordinates, eg. "e4", with errors telling what is wrong and where
+//!
+//! `"e4".parse::<Position>()` accepts a file (a-h, either case) and a rank (1-8) with surrounding
+//! whitespace and nothing else. [`Position::parse_target`] also accepts the promotion and check
+//! suffixes that follow the destination of a move, eg. "e8=Q+". Errors are [`ParseError`]s with
+//! the byte offset of the problem in the input, and convert to `&'static str` for `?`.
+//!
+//! # Example
+//! ```
+//! use eliasfl_chess::coordinate::{ParseError, ParseErrorKind};
+//! use eliasfl_chess::{PieceKind, Position};
+//!
+//! assert_eq!(" E4 ".parse(), Ok(Position { file: 5, rank: 4 }));
+//! assert_eq!(
+//!     "e2extra".parse::<Position>(),
+//!     Err(ParseError { kind: ParseErrorKind::TrailingCharacters, offset: 2 })
+//! );
+//! let target = Position::parse_target("e8=Q#").unwrap();
+//! assert_eq!(target.position, Position { file: 5, rank: 8 });
+//! assert_eq!(target.promotion, Some(PieceKind::Queen));
+//! assert!(target.checkmate);
+//! ```
+
+use core::convert::TryFrom;
+use core::fmt;
+use core::str::FromStr;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{PieceKind, Position};
+
+/// What is wrong with a coordinate, see [`ParseError`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ParseErrorKind {
+    /// Nothing but whitespace
+    Empty,
+    /// The first character is not a file a-h
+    InvalidFile,
+    /// The file is not followed by a rank
+    MissingRank,
+    /// The character after the file is not a digit
+    InvalidRank,
+    /// The rank is a number outside of 1-8, eg. "a0" or "a10"
+    RankOutOfRange,
+    /// A promotion suffix without a queen, rook, bishop or knight, eg. "e8=K"
+    InvalidPromotion,
+    /// More characters follow the coordinate, eg. "e2extra"
+    TrailingCharacters,
+}
+
+/// Error of parsing a coordinate, with the byte offset in the input where the problem starts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ParseError {
+    /// What is wrong
+    pub kind: ParseErrorKind,
+    /// Byte offset of the offending character, the length of the input if it ended too early
+    pub offset: usize,
+}
+
+impl ParseError {
+    /// Description of the error without the offset
+    pub fn message(&self) -> &'static str {
+        match self.kind {
+            ParseErrorKind::Empty | ParseErrorKind::MissingRank => {
+                "Position should consist of file and rank (at least 2 characters)"
+            }
+            ParseErrorKind::InvalidFile => "Invalid file, should be in range [a, h]",
+            ParseErrorKind::InvalidRank => "Invalid rank, should be a number",
+            ParseErrorKind::RankOutOfRange => "Rank out of range: [1, 8]",
+            ParseErrorKind::InvalidPromotion => "Invalid promotion piece, should be q, r, b or n",
+            ParseErrorKind::TrailingCharacters => "Unexpected characters after the position",
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} (at byte {})", self.message(), self.offset)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseError {}
+
+impl From<ParseError> for &'static str {
+    fn from(error: ParseError) -> Self {
+        error.message()
+    }
+}
+
+/// Destination of a move with the suffixes written after it, see [`Position::parse_target`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MoveTarget {
+    /// The square moved to
+    pub position: Position,
+    /// Piece to promote to, written "=Q" or "q"
+    pub promotion: Option<PieceKind>,
+    /// If the move is marked as check with "+"
+    pub check: bool,
+    /// If the move is marked as checkmate with "#"
+    pub checkmate: bool,
+}
+
+impl Position {
+    /// Parse the destination of a move with optional promotion and check suffixes, eg. "e8=Q+",
+    /// "e8q" or "d7#"
+    ///
+    /// Whitespace around the input is ignored. The promotion is not checked against the rank, as
+    /// that depends on the piece moved.
+    pub fn parse_target(s: &str) -> Result<MoveTarget, ParseError> {
+        let (position, mut offset) = parse_square(s)?;
+        let end = s.trim_end().len();
+        let next = |offset: usize| s[..end][offset..].chars().next();
+        let promotion = match next(offset) {
+            Some('=') => match next(offset + 1).and_then(promotion_kind) {
+                Some(kind) => {
+                    offset += 2;
+                    Some(kind)
+                }
+                None => {
+                    return Err(ParseError {
+                        kind: ParseErrorKind::InvalidPromotion,
+                        offset: offset + 1,
+                    })
+                }
+            },
+            Some(c) => promotion_kind(c).map(|kind| {
+                offset += 1;
+                kind
+            }),
+            None => None,
+        };
+        let (check, checkmate) = match next(offset) {
+            Some('+') => (true, false),
+            Some('#') => (false, true),
+            _ => (false, false),
+        };
+        if check || checkmate {
+            offset += 1;
+        }
+        if offset < end {
+            return Err(ParseError {
+                kind: ParseErrorKind::TrailingCharacters,
+                offset,
+            });
+        }
+        Ok(MoveTarget {
+            position,
+            promotion,
+            check,
+            checkmate,
+        })
+    }
+}
+
+/// Kind of a promotion letter in either case
+fn promotion_kind(letter: char) -> Option<PieceKind> {
+    match letter.to_ascii_lowercase() {
+        'q' => Some(PieceKind::Queen),
+        'r' => Some(PieceKind::Rook),
+        'b' => Some(PieceKind::Bishop),
+        'n' => Some(PieceKind::Knight),
+        _ => None,
+    }
+}
+
+/// Parse file and rank after any leading whitespace, returning the position and the byte offset
+/// after the rank
+fn parse_square(s: &str) -> Result<(Position, usize), ParseError> {
+    let start = s.len() - s.trim_start().len();
+    let error = |kind, offset| Err(ParseError { kind, offset });
+    let mut chars = s[start..].char_indices().map(|(i, c)| (start + i, c));
+    let file = match chars.next() {
+        // Files are one-indexed, so 'a' is 1
+        Some((_, c)) if matches!(c.to_ascii_lowercase(), 'a'..='h') => {
+            c.to_ascii_lowercase() as u8 - b'a' + 1
+        }
+        Some((offset, _)) => return error(ParseErrorKind::InvalidFile, offset),
+        None => return error(ParseErrorKind::Empty, s.len()),
+    };
+    let rank = match chars.next() {
+        Some((offset, c @ '1'..='8')) => {
+            // A second digit would make a rank of at least 10
+            if chars.next().is_some_and(|(_, c)| c.is_ascii_digit()) {
+                return error(ParseErrorKind::RankOutOfRange, offset);
+            }
+            c as u8 - b'0'
+        }
+        Some((offset, '0' | '9')) => return error(ParseErrorKind::RankOutOfRange, offset),
+        Some((offset, c)) if c.is_whitespace() => {
+            return error(ParseErrorKind::MissingRank, offset)
+        }
+        Some((offset, _)) => return error(ParseErrorKind::InvalidRank, offset),
+        None => return error(ParseErrorKind::MissingRank, s.len()),
+    };
+    Ok((Position { file, rank }, start + 2))
+}
+
+impl FromStr for Position {
+    type Err = ParseError;
+
+    /// Parse string with first character as file (a-h or A-H) and second char as rank (1-8), eg.
+    /// "e4", ignoring surrounding whitespace
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (position, offset) = parse_square(s)?;
+        if offset < s.trim_end().len() {
+            return Err(ParseError {
+                kind: ParseErrorKind::TrailingCharacters,
+                offset,
+            });
+        }
+        Ok(position)
+    }
+}
+
+impl TryFrom<&str> for Position {
+    type Error = ParseError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
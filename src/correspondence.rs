@@ -0,0 +1,116 @@
+//! Correspondence games where each move has a deadline of a number of days
+//!
+//! Times are seconds since the Unix epoch passed by the caller, so a server can store the game
+//! (eg. with [`CorrespondenceGame::to_json`]) and check the deadline whenever it is loaded again.
+//!
+//! # Example
+//! ```
+//! use eliasfl_chess::config::GameConfig;
+//! use eliasfl_chess::correspondence::{CorrespondenceGame, DAY};
+//! use eliasfl_chess::{Color, Game};
+//!
+//! let config = GameConfig::new()
+//!     .days_per_move(Color::White, 3)
+//!     .days_per_move(Color::Black, 3);
+//! let mut game = CorrespondenceGame::new(Game::with_config(config), 0);
+//! game.make_move_san("e4", DAY).unwrap();
+//! let saved = game.to_json();
+//!
+//! // Black did not answer within three days
+//! let loaded = CorrespondenceGame::from_json(&saved, 5 * DAY).unwrap();
+//! assert_eq!(loaded.timed_out, Some(Color::Black));
+//! ```
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{Color, Game, MoveOutcome};
+
+/// Seconds in a day
+pub const DAY: u64 = 24 * 60 * 60;
+
+/// Seconds since the Unix epoch according to the system clock
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
+
+/// A game with a deadline for every move, configured with [`crate::config::GameConfig::days_per_move`]
+///
+/// Players without days per move have no deadline
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CorrespondenceGame {
+    /// The game being played
+    pub game: Game,
+    /// Time when the player who's turn it is must have moved, None without deadline
+    pub deadline: Option<u64>,
+    /// The player who missed a deadline and lost the game
+    pub timed_out: Option<Color>,
+}
+
+impl CorrespondenceGame {
+    /// Start correspondence game at time `now`
+    pub fn new(game: Game, now: u64) -> Self {
+        let mut correspondence = Self {
+            game,
+            deadline: None,
+            timed_out: None,
+        };
+        correspondence._start_deadline(now);
+        correspondence
+    }
+
+    /// Restore a game saved with [`CorrespondenceGame::to_json`] and check the deadline at time `now`
+    pub fn from_json(json: &str, now: u64) -> Result<Self, &'static str> {
+        let mut correspondence: Self =
+            serde_json::from_str(json).map_err(|_| "Invalid correspondence game")?;
+        correspondence.check_deadline(now);
+        Ok(correspondence)
+    }
+
+    /// Game as JSON to store between moves
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("Game should serialize")
+    }
+
+    /// Seconds left for the player who's turn it is at time `now`, None without deadline
+    pub fn time_left(&self, now: u64) -> Option<u64> {
+        self.deadline.map(|deadline| deadline.saturating_sub(now))
+    }
+
+    /// Check if the player who's turn it is missed the deadline at time `now`
+    ///
+    /// Returns the player who timed out, if any
+    pub fn check_deadline(&mut self, now: u64) -> Option<Color> {
+        if self.timed_out.is_none() && self.deadline.is_some_and(|deadline| now > deadline) {
+            self.timed_out = Some(self.game.active_color);
+        }
+        self.timed_out
+    }
+
+    /// Make a move in algebraic notation (eg. "Nf3") at time `now` and start the opponent's deadline
+    ///
+    /// Return Err if a player has timed out or the move is illegal, see [`Game::make_move_san`]
+    pub fn make_move_san(&mut self, san: &str, now: u64) -> Result<MoveOutcome, &'static str> {
+        if self.check_deadline(now).is_some() {
+            return Err("Player has run out of time");
+        }
+        let outcome = self.game.make_move_san(san)?;
+        self._start_deadline(now);
+        Ok(outcome)
+    }
+
+    /// Set the deadline of the player who's turn it is
+    fn _start_deadline(&mut self, now: u64) {
+        self.deadline = self
+            .game
+            .config
+            .side(self.game.active_color)
+            .days_per_move
+            .map(|days| now + days as u64 * DAY);
+    }
+}
@@ -0,0 +1,249 @@
+//! Crazyhouse: captured pieces change sides and can be dropped back onto the board
+//!
+//! A drop is written as the piece letter, "@" and the destination, eg. "N@f3" or "P@e4".
+//! Pawns cannot be dropped on rank 1 or 8 and promoted pieces turn back into pawns when captured.
+
+use alloc::vec::Vec;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{Color, Game, Move, Piece, Position};
+
+/// Pieces a player has captured and may drop, counted per kind
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Reserve {
+    /// Number of queens, rooks, bishops, knights and pawns
+    pub(crate) counts: [u8; 5],
+}
+
+impl Reserve {
+    /// Index of the piece kind, None for kings which never enter a reserve
+    fn index(piece: &Piece) -> Option<usize> {
+        match piece {
+            Piece::King(_) => None,
+            Piece::Queen(_) => Some(0),
+            Piece::Rook(_) => Some(1),
+            Piece::Bishop(_) => Some(2),
+            Piece::Knight(_) => Some(3),
+            Piece::Pawn(_) => Some(4),
+        }
+    }
+
+    /// Number of pieces of the same kind as piece, the color is ignored
+    pub fn count(&self, piece: &Piece) -> u8 {
+        Self::index(piece).map_or(0, |i| self.counts[i])
+    }
+
+    /// Add one piece of the same kind as piece
+    pub fn add(&mut self, piece: &Piece) {
+        if let Some(i) = Self::index(piece) {
+            self.counts[i] += 1;
+        }
+    }
+
+    /// Remove one piece of the same kind as piece, returns false if there is none
+    pub fn remove(&mut self, piece: &Piece) -> bool {
+        match Self::index(piece) {
+            Some(i) if self.counts[i] > 0 => {
+                self.counts[i] -= 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// If there are no pieces to drop
+    pub fn is_empty(&self) -> bool {
+        self.counts.iter().all(|&count| count == 0)
+    }
+}
+
+/// Crazyhouse state of a game: the reserves and which pieces are promoted pawns
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Crazyhouse {
+    /// Reserve per color
+    pub reserves: [Reserve; 2],
+    /// If captured pieces go to the capturer's reserve. Bughouse gives them to the partner instead
+    pub keep_captures: bool,
+    /// Bit `(rank - 1) * 8 + (file - 1)` is set if the piece there is a promoted pawn
+    pub(crate) promoted: u64,
+}
+
+impl Crazyhouse {
+    /// Empty reserves, captured pieces are kept by the capturer if `keep_captures`
+    pub fn new(keep_captures: bool) -> Self {
+        Self {
+            reserves: [Reserve::default(); 2],
+            keep_captures,
+            promoted: 0,
+        }
+    }
+
+    /// Bit of position in the promoted mask
+    fn bit(position: &Position) -> u64 {
+        1 << ((position.rank as u32 - 1) * 8 + (position.file as u32 - 1))
+    }
+
+    /// If the piece on position is a promoted pawn
+    pub fn is_promoted(&self, position: &Position) -> bool {
+        self.promoted & Self::bit(position) != 0
+    }
+
+    /// Piece that capturing `captured` on `position` gives to the reserve of color `to`
+    pub fn reserve_piece(&self, captured: &Piece, position: &Position, to: Color) -> Piece {
+        if self.is_promoted(position) {
+            return Piece::Pawn(to);
+        }
+        match captured {
+            Piece::King(_) => Piece::King(to),
+            Piece::Queen(_) => Piece::Queen(to),
+            Piece::Rook(_) => Piece::Rook(to),
+            Piece::Bishop(_) => Piece::Bishop(to),
+            Piece::Knight(_) => Piece::Knight(to),
+            Piece::Pawn(_) => Piece::Pawn(to),
+        }
+    }
+
+    /// Forget that the piece on position is a promoted pawn, eg. after it was replaced in an editor
+    pub(crate) fn _forget_promoted(&mut self, position: &Position) {
+        self.promoted &= !Self::bit(position);
+    }
+
+    /// Update reserves and promoted pieces after `color` made `mov`
+    fn _record_move(&mut self, mov: &Move, promotion: bool, captured: Option<Piece>, color: Color) {
+        if let Some(captured) = captured {
+            if self.keep_captures {
+                let piece = self.reserve_piece(&captured, &mov.to, color);
+                self.reserves[color.index()].add(&piece);
+            }
+        }
+        let moved_promoted = self.is_promoted(&mov.from);
+        self.promoted &= !(Self::bit(&mov.from) | Self::bit(&mov.to));
+        if moved_promoted || promotion {
+            self.promoted |= Self::bit(&mov.to);
+        }
+    }
+}
+
+impl Game {
+    /// Create a Crazyhouse game with the standard piece arrangement and empty reserves
+    pub fn new_crazyhouse() -> Self {
+        Game {
+            crazyhouse: Some(Crazyhouse::new(true)),
+            ..Game::new()
+        }
+    }
+
+    /// Update the Crazyhouse state after a validated move, see [`Game::_make_move`]
+    pub(crate) fn _record_crazyhouse_move(
+        &mut self,
+        mov: &Move,
+        piece: Piece,
+        captured: Option<Piece>,
+    ) {
+        let promotion = matches!(piece, Piece::Pawn(_)) && self._is_last_rank(&mov.to);
+        if let Some(house) = self.crazyhouse.as_mut() {
+            house._record_move(mov, promotion, captured, piece.color());
+        }
+    }
+
+    /// Drop a piece from the reserve of the player who's turn it is, eg. "N@f3" or "P@e4"
+    ///
+    /// Return Err if the game is not Crazyhouse, the notation is invalid, the piece is not in the reserve,
+    /// the square is occupied, a pawn is dropped on rank 1 or 8 or the drop leaves the own king in check
+    pub fn drop_piece(&mut self, drop: &str) -> Result<(), &'static str> {
+        let (piece, position) = parse_drop(drop, self.active_color)?;
+        self._drop(piece, position)
+    }
+
+    /// Validate and make drop, see [`Game::drop_piece`]
+    pub(crate) fn _drop(&mut self, piece: Piece, position: Position) -> Result<(), &'static str> {
+        let color = self.active_color;
+        let house = self
+            .crazyhouse
+            .as_ref()
+            .ok_or("Drops are only allowed in Crazyhouse")?;
+        if house.reserves[color.index()].count(&piece) == 0 {
+            return Err("Piece is not in the reserve");
+        }
+        if !self._is_legal_drop(piece, &position) {
+            return Err("Illegal drop");
+        }
+        if let Some(house) = self.crazyhouse.as_mut() {
+            house.reserves[color.index()].remove(&piece);
+        }
+        self.board.insert(position, piece);
+        self.clear_move_cache();
+        self.en_passant = None;
+        self.halfmove_clock += 1;
+        if color == Color::Black {
+            self.fullmove_number += 1;
+        }
+        self.active_color = !color;
+        self._update_state();
+        Ok(())
+    }
+
+    /// If the player who's turn it is may drop piece on position, ignoring the reserve
+    fn _is_legal_drop(&self, piece: Piece, position: &Position) -> bool {
+        if self.board.contains_key(position)
+            || !self.config.board_size.contains(position)
+            || (matches!(piece, Piece::Pawn(_)) && self._is_last_rank(position))
+        {
+            return false;
+        }
+        let mut scratch = self.clone();
+        scratch.board.insert(*position, piece);
+        !scratch._king_is_threatened(piece.color())
+    }
+
+    /// All drops the player who's turn it is can make, empty unless the game is Crazyhouse
+    pub fn legal_drops(&self) -> Vec<(Piece, Position)> {
+        let color = self.active_color;
+        let reserve = match &self.crazyhouse {
+            Some(house) => house.reserves[color.index()],
+            None => return Vec::new(),
+        };
+        let mut drops = Vec::new();
+        for piece in [
+            Piece::Queen(color),
+            Piece::Rook(color),
+            Piece::Bishop(color),
+            Piece::Knight(color),
+            Piece::Pawn(color),
+        ] {
+            if reserve.count(&piece) == 0 {
+                continue;
+            }
+            for rank in 1..=8 {
+                for file in 1..=8 {
+                    let position = Position { file, rank };
+                    if self._is_legal_drop(piece, &position) {
+                        drops.push((piece, position));
+                    }
+                }
+            }
+        }
+        drops
+    }
+}
+
+/// Parse a drop like "N@f3" for the player with `color`, the letter may be left out for pawns
+pub(crate) fn parse_drop(drop: &str, color: Color) -> Result<(Piece, Position), &'static str> {
+    let (letter, square) = drop
+        .split_once('@')
+        .ok_or("Drop should be written like \"N@f3\"")?;
+    let piece = match letter {
+        "" | "P" => Piece::Pawn(color),
+        "N" => Piece::Knight(color),
+        "B" => Piece::Bishop(color),
+        "R" => Piece::Rook(color),
+        "Q" => Piece::Queen(color),
+        _ => return Err("Invalid piece letter in drop"),
+    };
+    let position = square.parse::<Position>().map_err(|_| "Invalid position")?;
+    Ok((piece, position))
+}
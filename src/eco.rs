@@ -0,0 +1,59 @@
+//! Classification of games by the Encyclopaedia of Chess Openings (ECO), enabled with the "eco"
+//! feature
+//!
+//! The table of lines is generated at compile time from `data/eco.tsv`, which is larger than the
+//! opening [`book`](crate::book). Unlike [`Game::opening`], positions reached by another move order
+//! are recognized.
+//!
+//! # Example
+//! ```
+//! use eliasfl_chess::Game;
+//!
+//! // The Sicilian Najdorf by way of 2...d6
+//! let game = Game::from_pgn("1. e4 c5 2. Nf3 d6 3. d4 cxd4 4. Nxd4 Nf6 5. Nc3 a6 6. Be3").unwrap();
+//! let opening = game.eco().unwrap();
+//! assert_eq!(opening.eco, "B90");
+//! assert_eq!(opening.to_string(), "B90 Sicilian Defense: Najdorf Variation");
+//!
+//! // The Queen's Gambit Declined reached from the English Opening
+//! let game = Game::from_pgn("1. c4 e6 2. Nc3 d5 3. d4").unwrap();
+//! assert_eq!(game.eco().unwrap().eco, "D31");
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::book::Opening;
+use crate::{fen, Board, Color, Game};
+
+include!(concat!(env!("OUT_DIR"), "/eco.rs"));
+
+/// All lines of the ECO table
+pub fn lines() -> impl Iterator<Item = Opening> {
+    LINES
+        .iter()
+        .map(|&(eco, name, uci)| Opening { eco, name, uci })
+}
+
+impl Game {
+    /// The ECO code and name of the longest line in the table whose position occurred in the
+    /// game, None if there is none or the game did not start from the standard position
+    pub fn eco(&self) -> Option<Opening> {
+        let replay = self.replay().ok()?;
+        if replay.start().to_fen() != fen::STARTING_FEN {
+            return None;
+        }
+        let positions: Vec<(Board, Color)> = replay
+            .map(|snapshot| (snapshot.game.board, snapshot.game.active_color))
+            .collect();
+        lines()
+            .filter(|opening| {
+                // Lines of the table are legal, so the moves are not checked
+                let mut game = Game::new();
+                for mov in opening.moves() {
+                    game._make(mov);
+                }
+                positions.contains(&(game.board, game.active_color))
+            })
+            .max_by_key(|opening| opening.moves().len())
+    }
+}
@@ -0,0 +1,278 @@
+//! Board editor for setting up positions with undo, redo and live legality hints
+//!
+//! # Example
+//! ```
+//! use eliasfl_chess::editor::BoardEditor;
+//! use eliasfl_chess::{Color, Piece};
+//!
+//! let mut editor = BoardEditor::new();
+//! editor.put("e1", Piece::King(Color::White)).unwrap();
+//! editor.put("e8", Piece::King(Color::Black)).unwrap();
+//! editor.put("e4", Piece::Rook(Color::White)).unwrap();
+//! assert_eq!(editor.hints()[0].to_string(), "Black would be in check with White to move");
+//! editor.undo();
+//! assert!(editor.hints().is_empty());
+//! let game = editor.build().unwrap();
+//! ```
+//!
+//! Games in progress can be edited with [`Game::put_piece`], [`Game::remove_piece`],
+//! [`Game::set_board`] and [`Game::set_active_color`], which reject edits that make the position
+//! illegal.
+
+use alloc::vec::Vec;
+
+use crate::{Board, CastlingRights, Color, Game, GameBuilder, Piece, Position, SetupProblem};
+
+/// Editable position with its own undo stack, see the [module documentation](self)
+#[derive(Debug, Clone, Default)]
+pub struct BoardEditor {
+    builder: GameBuilder,
+    undo: Vec<GameBuilder>,
+    redo: Vec<GameBuilder>,
+}
+
+impl BoardEditor {
+    /// Editor with an empty board, White to move and no castling rights
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Editor starting from the position of game
+    pub fn from_game(game: &Game) -> Self {
+        Self {
+            builder: GameBuilder::from(game),
+            ..Self::default()
+        }
+    }
+
+    /// The position being edited
+    pub fn board(&self) -> &Board {
+        &self.builder.board
+    }
+
+    /// The color who's turn it is in the position
+    pub fn active_color(&self) -> Color {
+        self.builder.active_color
+    }
+
+    /// Castling rights of color
+    pub fn castling(&self, color: Color) -> CastlingRights {
+        self.builder.castling[color.index()]
+    }
+
+    /// Place piece on position (eg. "e2"), replacing any piece standing there
+    ///
+    /// Returns the replaced piece or Err if the position is invalid
+    pub fn put(&mut self, position: &str, piece: Piece) -> Result<Option<Piece>, &'static str> {
+        let position = Self::_position(position)?;
+        Ok(self._edit(|builder| builder.board.insert(position, piece)))
+    }
+
+    /// Remove the piece on position (eg. "e2")
+    ///
+    /// Returns the removed piece or Err if the position is invalid
+    pub fn remove(&mut self, position: &str) -> Result<Option<Piece>, &'static str> {
+        let position = Self::_position(position)?;
+        if !self.builder.board.contains_key(&position) {
+            return Ok(None);
+        }
+        Ok(self._edit(|builder| builder.board.remove(&position)))
+    }
+
+    /// Remove all pieces
+    pub fn clear(&mut self) {
+        self._edit(|builder| builder.board = Board::new());
+    }
+
+    /// Set the color who's turn it is
+    pub fn set_active_color(&mut self, color: Color) {
+        self._edit(|builder| builder.active_color = color);
+    }
+
+    /// Set castling rights of color
+    pub fn set_castling(&mut self, color: Color, rights: CastlingRights) {
+        self._edit(|builder| builder.castling[color.index()] = rights);
+    }
+
+    /// Take back the last edit, returns false if there is nothing to undo
+    pub fn undo(&mut self) -> bool {
+        match self.undo.pop() {
+            Some(previous) => {
+                self.redo
+                    .push(core::mem::replace(&mut self.builder, previous));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Make the last undone edit again, returns false if there is nothing to redo
+    pub fn redo(&mut self) -> bool {
+        match self.redo.pop() {
+            Some(next) => {
+                self.undo.push(core::mem::replace(&mut self.builder, next));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Reasons the current position is illegal, eg. "Black would be in check with White to move"
+    pub fn hints(&self) -> Vec<SetupProblem> {
+        self.builder.problems()
+    }
+
+    /// Create a game from the current position, see [`GameBuilder::build`]
+    pub fn build(&self) -> Result<Game, &'static str> {
+        self.builder.clone().build()
+    }
+
+    /// Parse position or return Err
+    fn _position(position: &str) -> Result<Position, &'static str> {
+        position.parse().map_err(|_| "Invalid position")
+    }
+
+    /// Apply edit to the position, saving the previous position for undo
+    fn _edit<T>(&mut self, edit: impl FnOnce(&mut GameBuilder) -> T) -> T {
+        self.undo.push(self.builder.clone());
+        self.redo.clear();
+        edit(&mut self.builder)
+    }
+}
+
+impl Game {
+    /// Place piece on position, replacing any piece standing there
+    ///
+    /// Placing a king moves the color's king from its old square, so each color keeps one king.
+    /// Castling rights whose king or rook is moved or replaced are lost. Returns the replaced piece,
+    /// or Err without changing the game if the position would be illegal.
+    pub fn put_piece(
+        &mut self,
+        position: Position,
+        piece: Piece,
+    ) -> Result<Option<Piece>, &'static str> {
+        if !self.config.board_size.contains(&position) {
+            return Err("Invalid position");
+        }
+        if matches!(piece, Piece::Pawn(_)) && self._is_last_rank(&position) {
+            return Err(SetupProblem::PawnOnBackRank(position).error());
+        }
+        if matches!(self.board.get(&position), Some(Piece::King(color)) if piece != Piece::King(*color))
+        {
+            return Err("Cannot replace king");
+        }
+        let mut edited = self.clone();
+        if let Piece::King(color) = piece {
+            let king = edited.pieces_of(color).find(|(_, p)| *p == piece);
+            if let Some((king, _)) = king {
+                edited._take(&king);
+            }
+        }
+        let replaced = edited._take(&position);
+        edited.board.insert(position, piece);
+        edited._finish_edit()?;
+        *self = edited;
+        Ok(replaced)
+    }
+
+    /// Remove the piece on position, kings cannot be removed
+    ///
+    /// Returns the removed piece, or Err without changing the game if the position would be illegal
+    pub fn remove_piece(&mut self, position: Position) -> Result<Option<Piece>, &'static str> {
+        if !self.config.board_size.contains(&position) {
+            return Err("Invalid position");
+        }
+        if matches!(self.board.get(&position), Some(Piece::King(_))) {
+            return Err("Cannot remove king");
+        }
+        let mut edited = self.clone();
+        let removed = edited._take(&position);
+        edited._finish_edit()?;
+        *self = edited;
+        Ok(removed)
+    }
+
+    /// Set the color who's turn it is, clearing the en passant square
+    ///
+    /// Returns Err without changing the game if the player who would wait is in check
+    pub fn set_active_color(&mut self, color: Color) -> Result<(), &'static str> {
+        if color == self.active_color {
+            return Ok(());
+        }
+        let mut edited = self.clone();
+        edited.active_color = color;
+        edited.en_passant = None;
+        edited._finish_edit()?;
+        *self = edited;
+        Ok(())
+    }
+
+    /// Replace every piece with those of board, eg. to set up a position drawn in a frontend
+    ///
+    /// Castling rights without their king and rook on the starting squares are dropped and the en
+    /// passant square is cleared. Returns Err without changing the game if the position would be
+    /// illegal, see [`Game::validate`].
+    pub fn set_board(&mut self, board: Board) -> Result<(), &'static str> {
+        if board
+            .iter()
+            .any(|(position, _)| !self.config.board_size.contains(&position))
+        {
+            return Err("Invalid position");
+        }
+        let mut edited = self.clone();
+        let changed: Vec<Position> = (0..64)
+            .filter_map(Position::from_index)
+            .filter(|position| self.board.get(position) != board.get(position))
+            .collect();
+        for position in &changed {
+            edited._take(position);
+        }
+        edited.board = board;
+        edited.en_passant = None;
+        if let Some(problem) = edited._setup_problems().first() {
+            return Err(problem.error());
+        }
+        edited._finish_edit()?;
+        *self = edited;
+        Ok(())
+    }
+
+    /// Remove the piece on position with the castling rights, en passant square and Crazyhouse
+    /// promotion that depend on it
+    fn _take(&mut self, position: &Position) -> Option<Piece> {
+        for color in [Color::White, Color::Black] {
+            if position.rank == self.config.board_size.home_rank(color) {
+                let rights = &mut self.castling[color.index()];
+                let files = self.castling_files;
+                if position.file == files.king {
+                    rights.kingside = false;
+                    rights.queenside = false;
+                }
+                if position.file == files.kingside {
+                    rights.kingside = false;
+                }
+                if position.file == files.queenside {
+                    rights.queenside = false;
+                }
+            }
+        }
+        if self.en_passant.map(|square| square.file) == Some(position.file) {
+            self.en_passant = None;
+        }
+        if let Some(house) = self.crazyhouse.as_mut() {
+            house._forget_promoted(position);
+        }
+        self.board.remove(position)
+    }
+
+    /// Check the edited position and update the cached data
+    fn _finish_edit(&mut self) -> Result<(), &'static str> {
+        let waiting = !self.active_color;
+        if self._king_is_threatened(waiting) {
+            return Err(SetupProblem::WaitingPlayerInCheck(waiting).error());
+        }
+        self.clear_move_cache();
+        self._update_state();
+        Ok(())
+    }
+}
@@ -0,0 +1,706 @@
+//! Computer player: position evaluation and alpha-beta search
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::str::FromStr;
+#[cfg(feature = "std")]
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::time::Instant;
+
+use crate::eval::{self, EvalDetails, EvalWeights};
+use crate::simulate::Rng;
+#[cfg(feature = "std")]
+use crate::tablebase::EndgameResult;
+use crate::{Color, Game, Move};
+
+/// Score of a checkmate in centipawns, reduced by the number of plies until mate
+pub const MATE_SCORE: i32 = 100_000;
+
+/// Scores further from zero than this are forced mates
+const MATE_THRESHOLD: i32 = MATE_SCORE - 1000;
+
+/// Scale of the logistic model converting centipawns to expected score (fitted to online game results)
+#[cfg(feature = "std")]
+const LOGISTIC_SCALE: f64 = 0.003_682_08;
+
+/// Centipawn margin separating a win from a draw in the logistic model
+#[cfg(feature = "std")]
+const DRAW_MARGIN: f64 = 200.0;
+
+/// Centipawns an opponent's move must gain to be reported by [`Engine::threat`]
+pub const THREAT_MARGIN: i32 = 50;
+
+/// Chess engine searching a fixed number of plies ahead
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Engine {
+    /// Number of plies (half moves) to search
+    pub depth: u32,
+    /// Weights of the evaluation, see [`Engine::with_weights`]
+    pub weights: EvalWeights,
+}
+
+/// Strength presets for casual play, from the weakest to full strength, see
+/// [`Engine::best_move_with_level`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum EngineLevel {
+    Beginner,
+    Casual,
+    Intermediate,
+    Advanced,
+    Master,
+}
+
+impl EngineLevel {
+    /// Every level from the weakest to the strongest
+    pub const ALL: [EngineLevel; 5] = [
+        EngineLevel::Beginner,
+        EngineLevel::Casual,
+        EngineLevel::Intermediate,
+        EngineLevel::Advanced,
+        EngineLevel::Master,
+    ];
+
+    /// Name of the level in lowercase, eg. "beginner"
+    pub fn name(&self) -> &'static str {
+        match self {
+            EngineLevel::Beginner => "beginner",
+            EngineLevel::Casual => "casual",
+            EngineLevel::Intermediate => "intermediate",
+            EngineLevel::Advanced => "advanced",
+            EngineLevel::Master => "master",
+        }
+    }
+
+    /// Deepest search in plies, None to search as deep as the engine
+    pub fn max_depth(&self) -> Option<u32> {
+        match self {
+            EngineLevel::Beginner => Some(1),
+            EngineLevel::Casual | EngineLevel::Intermediate => Some(2),
+            EngineLevel::Advanced => Some(3),
+            EngineLevel::Master => None,
+        }
+    }
+
+    /// Most centipawns of random noise added to or subtracted from the score of every move
+    pub fn noise(&self) -> i32 {
+        match self {
+            EngineLevel::Beginner => 200,
+            EngineLevel::Casual => 100,
+            EngineLevel::Intermediate => 40,
+            EngineLevel::Advanced => 15,
+            EngineLevel::Master => 0,
+        }
+    }
+
+    /// Percent of the moves where the second-best move is played instead of the best
+    pub fn second_best_percent(&self) -> usize {
+        match self {
+            EngineLevel::Beginner => 30,
+            EngineLevel::Casual => 15,
+            EngineLevel::Intermediate => 5,
+            EngineLevel::Advanced | EngineLevel::Master => 0,
+        }
+    }
+}
+
+impl FromStr for EngineLevel {
+    type Err = &'static str;
+
+    /// Parse the name of a level, eg. "beginner", in any case
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        EngineLevel::ALL
+            .iter()
+            .copied()
+            .find(|level| level.name().eq_ignore_ascii_case(s.trim()))
+            .ok_or("Unknown level, should be beginner, casual, intermediate, advanced or master")
+    }
+}
+
+/// Deepest search of [`Engine::search_with_limit`]
+pub const MAX_DEPTH: u32 = 64;
+
+/// Result of a search
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchResult {
+    /// Best move for the player who's turn it is, None if there are no legal moves
+    pub best_move: Option<Move>,
+    /// Score in centipawns from White's perspective (positive is good for White)
+    pub score: i32,
+    /// Number of plies searched
+    pub depth: u32,
+}
+
+/// When to stop searching, see [`Engine::search_with_limit`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchLimit {
+    /// Search exactly this many plies
+    Depth(u32),
+    /// Stop after searching about this many positions
+    Nodes(u64),
+    /// Stop after about this much time, not available on wasm32-unknown-unknown which has no clock
+    #[cfg(feature = "std")]
+    Time(Duration),
+}
+
+/// Counts the searched positions and decides when to stop
+#[derive(Debug)]
+struct SearchControl {
+    limit: SearchLimit,
+    /// Set for time limits only, so searches without one work without a clock
+    #[cfg(feature = "std")]
+    start: Option<Instant>,
+    nodes: u64,
+    /// If the limit was reached, the current iteration is incomplete
+    aborted: bool,
+    /// Best move of the previous iteration, searched first
+    root_best: Option<Move>,
+}
+
+impl SearchControl {
+    /// Number of positions between looking at the clock
+    #[cfg(feature = "std")]
+    const CLOCK_INTERVAL: u64 = 1024;
+
+    fn new(limit: SearchLimit) -> Self {
+        Self {
+            limit,
+            #[cfg(feature = "std")]
+            start: match limit {
+                SearchLimit::Time(_) => Some(Instant::now()),
+                _ => None,
+            },
+            nodes: 0,
+            aborted: false,
+            root_best: None,
+        }
+    }
+
+    /// Control that never stops the search
+    fn unlimited() -> Self {
+        Self::new(SearchLimit::Depth(MAX_DEPTH))
+    }
+
+    /// Count a position and check if the limit was reached
+    fn should_stop(&mut self) -> bool {
+        self.nodes += 1;
+        if !self.aborted {
+            self.aborted = match self.limit {
+                SearchLimit::Depth(_) => false,
+                SearchLimit::Nodes(nodes) => self.nodes > nodes,
+                #[cfg(feature = "std")]
+                SearchLimit::Time(time) => {
+                    self.nodes % Self::CLOCK_INTERVAL == 0
+                        && self.start.is_some_and(|start| start.elapsed() >= time)
+                }
+            };
+        }
+        self.aborted
+    }
+}
+
+/// Evaluation of a line starting with a given move, see [`Engine::compare`] and [`Game::analyze`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Line {
+    /// Score in centipawns from White's perspective (positive is good for White)
+    pub score: i32,
+    /// Principal variation: the compared move followed by the best play of both players
+    pub moves: Vec<Move>,
+}
+
+/// Two moves in the same position evaluated side by side, see [`Engine::compare`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comparison {
+    /// The player making the compared moves
+    pub color: Color,
+    /// Line of the first move
+    pub a: Line,
+    /// Line of the second move
+    pub b: Line,
+}
+impl Comparison {
+    /// Centipawns the first move is better than the second for the player making them, negative if worse
+    pub fn difference(&self) -> i32 {
+        match self.color {
+            Color::White => self.a.score - self.b.score,
+            Color::Black => self.b.score - self.a.score,
+        }
+    }
+}
+
+/// Estimated outcome probabilities of a position, summing to 1
+///
+/// Needs the `std` feature for floating point math
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WinProbability {
+    /// Probability that White wins
+    pub white: f64,
+    /// Probability of a draw
+    pub draw: f64,
+    /// Probability that Black wins
+    pub black: f64,
+}
+#[cfg(feature = "std")]
+impl WinProbability {
+    /// Probabilities for a score in centipawns from White's perspective
+    ///
+    /// Uses a logistic model where a win requires the score to exceed a draw margin.
+    /// Forced mates are certain wins.
+    pub fn from_score(score: i32) -> Self {
+        if score >= MATE_THRESHOLD {
+            return Self {
+                white: 1.0,
+                draw: 0.0,
+                black: 0.0,
+            };
+        }
+        if score <= -MATE_THRESHOLD {
+            return Self {
+                white: 0.0,
+                draw: 0.0,
+                black: 1.0,
+            };
+        }
+        let logistic = |cp: f64| 1.0 / (1.0 + (-LOGISTIC_SCALE * cp).exp());
+        let white = logistic(score as f64 - DRAW_MARGIN);
+        let black = logistic(-score as f64 - DRAW_MARGIN);
+        Self {
+            white,
+            draw: 1.0 - white - black,
+            black,
+        }
+    }
+
+    /// Expected score for White (win counts 1, draw counts 0.5)
+    pub fn expected_score(&self) -> f64 {
+        self.white + self.draw / 2.0
+    }
+}
+
+impl Engine {
+    /// Engine searching `depth` plies ahead with the default evaluation
+    pub fn new(depth: u32) -> Self {
+        Self {
+            depth,
+            weights: EvalWeights::default(),
+        }
+    }
+
+    /// Evaluate positions with weights, eg. to compare tuned weights in a
+    /// [`Match`](crate::tournament::Match)
+    pub fn with_weights(mut self, weights: EvalWeights) -> Self {
+        self.weights = weights;
+        self
+    }
+
+    /// Static evaluation of the position in centipawns from White's perspective
+    ///
+    /// Counts material, piece-square tables, mobility and king safety, see
+    /// [`Engine::evaluate_details`]
+    pub fn evaluate(&self, game: &Game) -> i32 {
+        self.evaluate_details(game).total()
+    }
+
+    /// The terms of [`Engine::evaluate`], eg. to show why the engine prefers a move
+    pub fn evaluate_details(&self, game: &Game) -> EvalDetails {
+        eval::evaluate_with(game, &self.weights)
+    }
+
+    /// Search for the best move for the player who's turn it is
+    ///
+    /// KQK, KRK and KPK endgames are played perfectly from the [`tablebase`](crate::tablebase)
+    pub fn search(&self, game: &Game) -> SearchResult {
+        #[cfg(feature = "std")]
+        if let Some(result) = Self::_tablebase_search(game) {
+            return result;
+        }
+        let mut game = game.clone();
+        let (best_move, score) = self._negamax(
+            &mut game,
+            self.depth.max(1),
+            0,
+            -MATE_SCORE,
+            MATE_SCORE,
+            &mut SearchControl::unlimited(),
+        );
+        SearchResult {
+            best_move,
+            score: match game.active_color {
+                Color::White => score,
+                Color::Black => -score,
+            },
+            depth: self.depth.max(1),
+        }
+    }
+
+    /// Best move and exact score of a tablebase endgame, None for other positions and positions
+    /// without legal moves
+    #[cfg(feature = "std")]
+    fn _tablebase_search(game: &Game) -> Option<SearchResult> {
+        let result = game.endgame_result()?;
+        let best_move = Some(game.endgame_best_move()?);
+        let (score, plies) = match result {
+            EndgameResult::Win { winner, plies } => {
+                let score = MATE_SCORE - plies as i32;
+                match winner {
+                    Color::White => (score, plies),
+                    Color::Black => (-score, plies),
+                }
+            }
+            EndgameResult::Draw => (0, 1),
+        };
+        Some(SearchResult {
+            best_move,
+            score,
+            depth: plies.max(1),
+        })
+    }
+
+    /// Best move for the player who's turn it is, None if there are no legal moves
+    pub fn best_move(&self, game: &Game) -> Option<Move> {
+        self.search(game).best_move
+    }
+
+    /// Move for the player who's turn it is played at the strength of level, None if there are
+    /// no legal moves
+    ///
+    /// The search is at most [`EngineLevel::max_depth`] plies deep, the score of every move gets
+    /// random [`EngineLevel::noise`] and sometimes the second-best move is played. The same seed
+    /// gives the same move, [`EngineLevel::Master`] plays the [`Engine::best_move`].
+    pub fn best_move_with_level(&self, game: &Game, level: EngineLevel, seed: u64) -> Option<Move> {
+        let depth = level
+            .max_depth()
+            .map_or(self.depth, |max_depth| self.depth.min(max_depth))
+            .max(1);
+        if level.noise() == 0 && level.second_best_percent() == 0 {
+            return Engine { depth, ..*self }.best_move(game);
+        }
+        let mut rng = Rng::new(seed);
+        let mut moves = game._legal_moves();
+        // Legal moves are not generated in a fixed order, the same seed should give the same move
+        moves.sort_unstable();
+        let mut scored: Vec<(i32, Move)> = moves
+            .into_iter()
+            .map(|mov| {
+                let mut after = game.clone();
+                after._make(mov);
+                let (_, score) = self._negamax(
+                    &mut after,
+                    depth - 1,
+                    1,
+                    -MATE_SCORE,
+                    MATE_SCORE,
+                    &mut SearchControl::unlimited(),
+                );
+                let noise = rng.below(2 * level.noise() as usize + 1) as i32 - level.noise();
+                (-score + noise, mov)
+            })
+            .collect();
+        scored.sort_by_key(|&(score, _)| -score);
+        let second_best = scored.len() > 1 && rng.below(100) < level.second_best_percent();
+        scored.get(usize::from(second_best)).map(|&(_, mov)| mov)
+    }
+
+    /// Search one ply deeper at a time until `limit` is reached (iterative deepening)
+    ///
+    /// Returns the result of the deepest completed search, so a move is found even if time runs
+    /// out. [`SearchLimit::Depth`] searches like [`Engine::search`] with that depth and the same
+    /// weights.
+    pub fn search_with_limit(&self, game: &Game, limit: SearchLimit) -> SearchResult {
+        if let SearchLimit::Depth(depth) = limit {
+            return Engine { depth, ..*self }.search(game);
+        }
+        #[cfg(feature = "std")]
+        if let Some(result) = Self::_tablebase_search(game) {
+            return result;
+        }
+        let mut control = SearchControl::new(limit);
+        let mut game = game.clone();
+        let color = game.active_color;
+        let from_white = |score: i32| match color {
+            Color::White => score,
+            Color::Black => -score,
+        };
+        let mut result = SearchResult {
+            best_move: Self::_ordered_moves(&game).first().copied(),
+            score: self.evaluate(&game),
+            depth: 0,
+        };
+        if result.best_move.is_none() {
+            return Engine { depth: 1, ..*self }.search(&game);
+        }
+        for depth in 1..=MAX_DEPTH {
+            control.root_best = result.best_move;
+            let (best_move, score) =
+                self._negamax(&mut game, depth, 0, -MATE_SCORE, MATE_SCORE, &mut control);
+            if control.aborted {
+                break;
+            }
+            result = SearchResult {
+                best_move,
+                score: from_white(score),
+                depth,
+            };
+            // Deeper searches cannot find a faster mate
+            if score.abs() >= MATE_THRESHOLD {
+                break;
+            }
+        }
+        result
+    }
+
+    /// Best move found within `limit`, see [`Engine::search_with_limit`]
+    pub fn best_move_with_limit(&self, game: &Game, limit: SearchLimit) -> Option<Move> {
+        self.search_with_limit(game, limit).best_move
+    }
+
+    /// [`Engine::search`] with every move from the root position searched on its own thread
+    ///
+    /// Gives the same score as [`Engine::search`], but the root moves don't share alpha-beta bounds
+    /// so more positions are searched in total.
+    #[cfg(feature = "parallel")]
+    pub fn search_parallel(&self, game: &Game) -> SearchResult {
+        // The parallel feature requires std
+        if let Some(result) = Self::_tablebase_search(game) {
+            return result;
+        }
+        let moves = Self::_ordered_moves(game);
+        if moves.is_empty() {
+            return self.search(game);
+        }
+        let scores: Vec<i32> = moves
+            .par_iter()
+            .map(|&mov| {
+                let mut game = game.clone();
+                game._make(mov);
+                let depth = self.depth.max(1) - 1;
+                -self
+                    ._negamax(
+                        &mut game,
+                        depth,
+                        1,
+                        -MATE_SCORE,
+                        MATE_SCORE,
+                        &mut SearchControl::unlimited(),
+                    )
+                    .1
+            })
+            .collect();
+        let mut best = 0;
+        for (i, &score) in scores.iter().enumerate() {
+            if score > scores[best] {
+                best = i;
+            }
+        }
+        SearchResult {
+            best_move: Some(moves[best]),
+            score: match game.active_color {
+                Color::White => scores[best],
+                Color::Black => -scores[best],
+            },
+            depth: self.depth.max(1),
+        }
+    }
+
+    /// Best move for the player who's turn it is using all cores, see [`Engine::search_parallel`]
+    #[cfg(feature = "parallel")]
+    pub fn best_move_parallel(&self, game: &Game) -> Option<Move> {
+        self.search_parallel(game).best_move
+    }
+
+    /// The move the opponent would make if the player who's turn it is could pass (null move),
+    /// if it would gain the opponent at least [`THREAT_MARGIN`] centipawns
+    ///
+    /// None if there is no such threat or if the player is in check (passing would leave the king capturable)
+    pub fn threat(&self, game: &Game) -> Option<Move> {
+        if game._king_is_threatened(game.active_color) {
+            return None;
+        }
+        let mut passed = game.clone();
+        passed.active_color = !passed.active_color;
+        let result = self.search(&passed);
+        let gain = match passed.active_color {
+            Color::White => result.score - self.evaluate(game),
+            Color::Black => self.evaluate(game) - result.score,
+        };
+        if gain >= THREAT_MARGIN {
+            result.best_move
+        } else {
+            None
+        }
+    }
+
+    /// Estimated probabilities of White winning, a draw and Black winning, based on the search score
+    #[cfg(feature = "std")]
+    pub fn win_probability(&self, game: &Game) -> WinProbability {
+        WinProbability::from_score(self.search(game).score)
+    }
+
+    /// Evaluate `move_a` and `move_b` in `game` searching `depth` plies, including the moves themselves
+    ///
+    /// Useful to show "your move vs best move" after a mistake.
+    /// Return Err if either move is illegal
+    pub fn compare(
+        &self,
+        game: &Game,
+        move_a: Move,
+        move_b: Move,
+        depth: u32,
+    ) -> Result<Comparison, &'static str> {
+        Ok(Comparison {
+            color: game.active_color,
+            a: self._line(game, move_a, depth)?,
+            b: self._line(game, move_b, depth)?,
+        })
+    }
+
+    /// Score and principal variation after playing `mov`, searching `depth` plies in total
+    fn _line(&self, game: &Game, mov: Move, depth: u32) -> Result<Line, &'static str> {
+        let mov = game
+            ._legal_moves()
+            .into_iter()
+            .find(|m| {
+                m.from == mov.from
+                    && m.to == mov.to
+                    && (mov.promotion.is_none() || m.promotion == mov.promotion)
+            })
+            .ok_or("Illegal move")?;
+        let mut game = game.clone();
+        game._make(mov);
+        let remaining = depth.max(1) - 1;
+        let (_, score) = self._negamax(
+            &mut game,
+            remaining,
+            1,
+            -MATE_SCORE,
+            MATE_SCORE,
+            &mut SearchControl::unlimited(),
+        );
+        let score = match game.active_color {
+            Color::White => score,
+            Color::Black => -score,
+        };
+        // Follow the best replies to build the principal variation
+        let mut moves = vec![mov];
+        for depth in (1..=remaining).rev() {
+            let ply = moves.len() as i32;
+            match self
+                ._negamax(
+                    &mut game,
+                    depth,
+                    ply,
+                    -MATE_SCORE,
+                    MATE_SCORE,
+                    &mut SearchControl::unlimited(),
+                )
+                .0
+            {
+                Some(best) => {
+                    game._make(best);
+                    moves.push(best);
+                }
+                None => break,
+            }
+        }
+        Ok(Line { score, moves })
+    }
+
+    /// Legal moves with captures of valuable pieces and promotions first, which makes alpha-beta pruning more effective
+    fn _ordered_moves(game: &Game) -> Vec<Move> {
+        let mut moves = game._legal_moves();
+        moves.sort_by_cached_key(|mov| {
+            let captured = game
+                .board
+                .get(&mov.to)
+                .filter(|piece| piece.color() != game.active_color)
+                .map_or(0, eval::piece_value);
+            let promotion = mov.promotion.as_ref().map_or(0, eval::piece_value);
+            -(captured + promotion)
+        });
+        moves
+    }
+
+    /// Alpha-beta negamax search returning best move and score from the perspective of the player to move
+    fn _negamax(
+        &self,
+        game: &mut Game,
+        depth: u32,
+        ply: i32,
+        mut alpha: i32,
+        beta: i32,
+        control: &mut SearchControl,
+    ) -> (Option<Move>, i32) {
+        if control.should_stop() {
+            // The result is thrown away
+            return (None, 0);
+        }
+        let mut moves = Self::_ordered_moves(game);
+        if ply == 0 {
+            // The best move of the previous iteration is searched first
+            if let Some(i) = moves.iter().position(|mov| Some(*mov) == control.root_best) {
+                moves[..=i].rotate_right(1);
+            }
+        }
+        if moves.is_empty() {
+            // Checkmate (prefer shorter mates) or stalemate
+            return if game._king_is_threatened(game.active_color) {
+                (None, -MATE_SCORE + ply)
+            } else {
+                (None, 0)
+            };
+        }
+        if depth == 0 {
+            let score = self.evaluate(game);
+            return match game.active_color {
+                Color::White => (None, score),
+                Color::Black => (None, -score),
+            };
+        }
+        let mut best = (None, -MATE_SCORE);
+        for mov in moves {
+            let undo = game._make(mov);
+            let (_, score) = self._negamax(game, depth - 1, ply + 1, -beta, -alpha, control);
+            let score = -score;
+            game._unmake(undo);
+            if score > best.1 || best.0.is_none() {
+                best = (Some(mov), score);
+            }
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+        best
+    }
+}
+
+impl Game {
+    /// Every legal move with its score and principal variation, searching `depth` plies
+    ///
+    /// Sorted with the best move for the player who's turn it is first.
+    pub fn analyze(&self, depth: u32) -> Vec<Line> {
+        let engine = Engine::new(depth);
+        let mut moves = self._legal_moves();
+        // Legal moves are not generated in a fixed order, equal scores keep this order
+        moves.sort_unstable();
+        let mut lines: Vec<Line> = moves
+            .into_iter()
+            .map(|mov| engine._line(self, mov, depth).expect("Move is legal"))
+            .collect();
+        lines.sort_by_key(|line| match self.active_color {
+            Color::White => -line.score,
+            Color::Black => line.score,
+        });
+        lines
+    }
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        Self::new(3)
+    }
+}
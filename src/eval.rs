@@ -0,0 +1,380 @@
+//! Static evaluation: material, piece-square tables tapered by game phase, mobility and king safety
+//!
+//! Piece-square tables give bonuses for good squares, eg. knights in the center and a sheltered
+//! king. They are blended between middlegame and endgame values by the material left on the board.
+//! The weights of the terms are [`EvalWeights`], which can be changed to tune the evaluation.
+//! [`Game::is_endgame`] and [`Game::king_zone_attackers`] describe the phase and the pressure on
+//! the kings, eg. to warn about a weak king.
+//!
+//! # Example
+//! ```
+//! use eliasfl_chess::eval::{evaluate, evaluate_with, EvalWeights};
+//! use eliasfl_chess::{Color, Game};
+//!
+//! let mut game = Game::new();
+//! game.make_move_san("Nf3").unwrap();
+//! let details = evaluate(&game);
+//! assert_eq!(details.material, 0);
+//! assert!(details.piece_squares > 0);
+//! assert_eq!(details.total(), details.material + details.piece_squares + details.mobility);
+//!
+//! let mut weights = EvalWeights::default();
+//! weights.mobility = 0;
+//! assert_eq!(evaluate_with(&game, &weights).mobility, 0);
+//!
+//! assert!(!game.is_endgame());
+//! let zone = game.king_zone_attackers(Color::Black).unwrap();
+//! assert_eq!(zone.king, "e8".parse().unwrap());
+//! assert!(zone.attackers.is_empty());
+//! ```
+
+use alloc::vec::Vec;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::config::BoardSize;
+use crate::{Color, Game, Piece, Position};
+
+/// Game phase of the starting position, the phase counts down to 0 as pieces are traded
+pub const MAX_PHASE: i32 = 24;
+
+/// Centipawns per pseudo-legal move of a knight, bishop, rook or queen
+pub const MOBILITY_WEIGHT: i32 = 4;
+
+/// Middlegame centipawns per own pawn shielding the king, see [`EvalWeights::king_shelter`]
+pub const KING_SHELTER_WEIGHT: i32 = 10;
+
+/// Weights of the evaluation terms in centipawns, the default is the evaluation of the engine
+///
+/// Changing them, eg. to tune the evaluation on game results, needs no change to the crate, see
+/// [`evaluate_with`] and [`Engine::with_weights`](crate::Engine::with_weights).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EvalWeights {
+    /// Value of a pawn
+    pub pawn: i32,
+    /// Value of a knight
+    pub knight: i32,
+    /// Value of a bishop
+    pub bishop: i32,
+    /// Value of a rook
+    pub rook: i32,
+    /// Value of a queen
+    pub queen: i32,
+    /// Percent of the piece-square table bonuses counted, 100 for the tables as they are
+    pub piece_squares: i32,
+    /// Bonus per pseudo-legal move of a knight, bishop, rook or queen
+    pub mobility: i32,
+    /// Middlegame bonus per own pawn on the three squares in front of the king, tapered by
+    /// [`EvalDetails::phase`] like the piece-square tables
+    pub king_shelter: i32,
+}
+
+impl EvalWeights {
+    /// Value of piece with these weights, kings have no value
+    pub fn piece_value(&self, piece: &Piece) -> i32 {
+        match piece {
+            Piece::King(_) => 0,
+            Piece::Queen(_) => self.queen,
+            Piece::Rook(_) => self.rook,
+            Piece::Bishop(_) => self.bishop,
+            Piece::Knight(_) => self.knight,
+            Piece::Pawn(_) => self.pawn,
+        }
+    }
+}
+
+impl Default for EvalWeights {
+    fn default() -> Self {
+        Self {
+            pawn: 100,
+            knight: 320,
+            bishop: 330,
+            rook: 500,
+            queen: 900,
+            piece_squares: 100,
+            mobility: MOBILITY_WEIGHT,
+            king_shelter: KING_SHELTER_WEIGHT,
+        }
+    }
+}
+
+/// Why the position is good or bad, in centipawns from White's perspective (positive is good
+/// for White)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct EvalDetails {
+    /// Value of the pieces on the board
+    pub material: i32,
+    /// Bonuses of the squares the pieces stand on, tapered by [`EvalDetails::phase`]
+    pub piece_squares: i32,
+    /// Bonus for the number of moves of knights, bishops, rooks and queens
+    pub mobility: i32,
+    /// Bonus for pawns shielding the kings, tapered by [`EvalDetails::phase`]
+    pub king_safety: i32,
+    /// From [`MAX_PHASE`] with all pieces on the board to 0 with only kings and pawns left
+    pub phase: i32,
+}
+
+impl EvalDetails {
+    /// Sum of all terms
+    pub fn total(&self) -> i32 {
+        self.material + self.piece_squares + self.mobility + self.king_safety
+    }
+}
+
+/// Value of piece in centipawns with the default weights
+pub fn piece_value(piece: &Piece) -> i32 {
+    EvalWeights::default().piece_value(piece)
+}
+
+/// Contribution of piece to the game phase
+fn phase_weight(piece: &Piece) -> i32 {
+    match piece {
+        Piece::Queen(_) => 4,
+        Piece::Rook(_) => 2,
+        Piece::Bishop(_) | Piece::Knight(_) => 1,
+        Piece::King(_) | Piece::Pawn(_) => 0,
+    }
+}
+
+// Tables from White's side with rank 8 first, so they read like a board diagram
+
+#[rustfmt::skip]
+const PAWN_MIDDLEGAME: [i32; 64] = [
+     0,  0,  0,  0,  0,  0,  0,  0,
+    50, 50, 50, 50, 50, 50, 50, 50,
+    10, 10, 20, 30, 30, 20, 10, 10,
+     5,  5, 10, 25, 25, 10,  5,  5,
+     0,  0,  0, 20, 20,  0,  0,  0,
+     5, -5,-10,  0,  0,-10, -5,  5,
+     5, 10, 10,-20,-20, 10, 10,  5,
+     0,  0,  0,  0,  0,  0,  0,  0,
+];
+
+#[rustfmt::skip]
+const PAWN_ENDGAME: [i32; 64] = [
+     0,  0,  0,  0,  0,  0,  0,  0,
+    80, 80, 80, 80, 80, 80, 80, 80,
+    50, 50, 50, 50, 50, 50, 50, 50,
+    30, 30, 30, 30, 30, 30, 30, 30,
+    20, 20, 20, 20, 20, 20, 20, 20,
+    10, 10, 10, 10, 10, 10, 10, 10,
+     0,  0,  0,  0,  0,  0,  0,  0,
+     0,  0,  0,  0,  0,  0,  0,  0,
+];
+
+#[rustfmt::skip]
+const KNIGHT: [i32; 64] = [
+    -50,-40,-30,-30,-30,-30,-40,-50,
+    -40,-20,  0,  0,  0,  0,-20,-40,
+    -30,  0, 10, 15, 15, 10,  0,-30,
+    -30,  5, 15, 20, 20, 15,  5,-30,
+    -30,  0, 15, 20, 20, 15,  0,-30,
+    -30,  5, 10, 15, 15, 10,  5,-30,
+    -40,-20,  0,  5,  5,  0,-20,-40,
+    -50,-40,-30,-30,-30,-30,-40,-50,
+];
+
+#[rustfmt::skip]
+const BISHOP: [i32; 64] = [
+    -20,-10,-10,-10,-10,-10,-10,-20,
+    -10,  0,  0,  0,  0,  0,  0,-10,
+    -10,  0,  5, 10, 10,  5,  0,-10,
+    -10,  5,  5, 10, 10,  5,  5,-10,
+    -10,  0, 10, 10, 10, 10,  0,-10,
+    -10, 10, 10, 10, 10, 10, 10,-10,
+    -10,  5,  0,  0,  0,  0,  5,-10,
+    -20,-10,-10,-10,-10,-10,-10,-20,
+];
+
+#[rustfmt::skip]
+const ROOK: [i32; 64] = [
+     0,  0,  0,  0,  0,  0,  0,  0,
+     5, 10, 10, 10, 10, 10, 10,  5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+     0,  0,  0,  5,  5,  0,  0,  0,
+];
+
+#[rustfmt::skip]
+const QUEEN: [i32; 64] = [
+    -20,-10,-10, -5, -5,-10,-10,-20,
+    -10,  0,  0,  0,  0,  0,  0,-10,
+    -10,  0,  5,  5,  5,  5,  0,-10,
+     -5,  0,  5,  5,  5,  5,  0, -5,
+      0,  0,  5,  5,  5,  5,  0, -5,
+    -10,  5,  5,  5,  5,  5,  0,-10,
+    -10,  0,  5,  0,  0,  0,  0,-10,
+    -20,-10,-10, -5, -5,-10,-10,-20,
+];
+
+#[rustfmt::skip]
+const KING_MIDDLEGAME: [i32; 64] = [
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -20,-30,-30,-40,-40,-30,-30,-20,
+    -10,-20,-20,-20,-20,-20,-20,-10,
+     20, 20,  0,  0,  0,  0, 20, 20,
+     20, 30, 10,  0,  0, 10, 30, 20,
+];
+
+#[rustfmt::skip]
+const KING_ENDGAME: [i32; 64] = [
+    -50,-40,-30,-20,-20,-30,-40,-50,
+    -30,-20,-10,  0,  0,-10,-20,-30,
+    -30,-10, 20, 30, 30, 20,-10,-30,
+    -30,-10, 30, 40, 40, 30,-10,-30,
+    -30,-10, 30, 40, 40, 30,-10,-30,
+    -30,-10, 20, 30, 30, 20,-10,-30,
+    -30,-30,  0,  0,  0,  0,-30,-30,
+    -50,-30,-30,-30,-30,-30,-30,-50,
+];
+
+/// Middlegame and endgame bonus of piece on position of a board of size, for the piece's own
+/// color
+///
+/// Smaller boards use the squares of the tables from a1 seen from each player's side, so both
+/// players get the same bonuses.
+pub fn piece_square(piece: &Piece, position: &Position, size: BoardSize) -> (i32, i32) {
+    // Black's pieces use the tables mirrored vertically, from the last rank of the board
+    let row = match piece.color() {
+        Color::White => 8 - position.rank as usize,
+        Color::Black => position.rank as usize + 7 - size.ranks() as usize,
+    };
+    let index = row * 8 + position.file as usize - 1;
+    match piece {
+        Piece::Pawn(_) => (PAWN_MIDDLEGAME[index], PAWN_ENDGAME[index]),
+        Piece::Knight(_) => (KNIGHT[index], KNIGHT[index]),
+        Piece::Bishop(_) => (BISHOP[index], BISHOP[index]),
+        Piece::Rook(_) => (ROOK[index], ROOK[index]),
+        Piece::Queen(_) => (QUEEN[index], QUEEN[index]),
+        Piece::King(_) => (KING_MIDDLEGAME[index], KING_ENDGAME[index]),
+    }
+}
+
+/// Evaluate the position term by term with the default weights, see [`EvalDetails`]
+pub fn evaluate(game: &Game) -> EvalDetails {
+    evaluate_with(game, &EvalWeights::default())
+}
+
+/// Evaluate the position term by term with weights, see [`EvalDetails`]
+pub fn evaluate_with(game: &Game, weights: &EvalWeights) -> EvalDetails {
+    let mut material = 0;
+    let (mut middlegame, mut endgame) = (0, 0);
+    let mut mobility = 0;
+    let mut shelter = 0;
+    let mut phase = 0;
+    for (position, piece) in game.pieces() {
+        let sign = match piece.color() {
+            Color::White => 1,
+            Color::Black => -1,
+        };
+        material += sign * weights.piece_value(&piece);
+        let (mg, eg) = piece_square(&piece, &position, game.config.board_size);
+        middlegame += sign * mg;
+        endgame += sign * eg;
+        phase += phase_weight(&piece);
+        if phase_weight(&piece) > 0 {
+            let moves = game
+                ._get_possible_moves(&position)
+                .map_or(0, |destinations| destinations.len() as i32);
+            mobility += sign * moves * weights.mobility;
+        }
+        if let Piece::King(color) = piece {
+            shelter += sign * _shelter_pawns(game, &position, color);
+        }
+    }
+    // Promotions can give more material than at the start
+    let phase = phase.min(MAX_PHASE);
+    let piece_squares = (middlegame * phase + endgame * (MAX_PHASE - phase)) / MAX_PHASE;
+    EvalDetails {
+        material,
+        piece_squares: piece_squares * weights.piece_squares / 100,
+        mobility,
+        king_safety: shelter * weights.king_shelter * phase / MAX_PHASE,
+        phase,
+    }
+}
+
+/// Number of pawns of color on the three squares in front of its king on position
+fn _shelter_pawns(game: &Game, position: &Position, color: Color) -> i32 {
+    // Squares off the board have no pieces
+    let rank = (position.rank as i32 + color.direction()) as u8;
+    (-1..=1)
+        .map(|offset| Position {
+            file: (position.file as i32 + offset) as u8,
+            rank,
+        })
+        .filter(|shield| game.board.get(shield) == Some(&Piece::Pawn(color)))
+        .count() as i32
+}
+
+/// Pressure on a king from the opponent's pieces, see [`Game::king_zone_attackers`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct KingZone {
+    /// Square of the king
+    pub king: Position,
+    /// Opponent pieces attacking the king or a square next to it, from a1 to h8
+    pub attackers: Vec<Position>,
+    /// Squares of the zone, the king and the squares next to it, that are attacked
+    pub attacked_squares: Vec<Position>,
+    /// Attacks on the squares of the zone, a piece attacking several squares counts once per
+    /// square
+    pub attacks: usize,
+}
+
+impl Game {
+    /// If the game is in the endgame: neither player has a queen, or every player with a queen
+    /// has at most one knight or bishop and no rook besides it
+    pub fn is_endgame(&self) -> bool {
+        [Color::White, Color::Black].iter().all(|&color| {
+            let mut queens = 0;
+            let mut others = 0;
+            for (_, piece) in self.pieces_of(color) {
+                match piece {
+                    Piece::Queen(_) => queens += 1,
+                    Piece::Rook(_) => others += 2,
+                    Piece::Bishop(_) | Piece::Knight(_) => others += 1,
+                    Piece::King(_) | Piece::Pawn(_) => {}
+                }
+            }
+            queens == 0 || queens == 1 && others <= 1
+        })
+    }
+
+    /// Opponent pieces attacking the king of color or the squares next to it, None if color has
+    /// no king
+    pub fn king_zone_attackers(&self, color: Color) -> Option<KingZone> {
+        let (king, _) = self
+            .pieces_of(color)
+            .find(|(_, piece)| *piece == Piece::King(color))?;
+        let mut zone = KingZone {
+            king,
+            attackers: Vec::new(),
+            attacked_squares: Vec::new(),
+            attacks: 0,
+        };
+        let squares = (-1..=1)
+            .flat_map(|rank| (-1..=1).map(move |file| (file, rank)))
+            .filter_map(|(file, rank)| self.config.board_size.relative_pos(&king, file, rank));
+        for square in squares {
+            let attackers = self.attackers_of(square, !color);
+            if attackers.is_empty() {
+                continue;
+            }
+            zone.attacks += attackers.len();
+            zone.attacked_squares.push(square);
+            zone.attackers.extend(attackers);
+        }
+        zone.attackers.sort_by_key(Position::index);
+        zone.attackers.dedup();
+        Some(zone)
+    }
+}
@@ -0,0 +1,121 @@
+//! Events describing what a move changed on the board, eg. to animate it in a frontend
+//!
+//! Moves made with [`Game::make_move`], [`Game::make_move_san`] and [`Game::make_move_uci`] (also
+//! timed) queue events until they are taken with [`Game::events`]. Running out of time queues
+//! [`GameEvent::GameOver`]. Only the latest [`MAX_EVENTS`] are kept, so games whose events are
+//! never taken, eg. in engine or self-play loops, don't grow.
+//!
+//! # Example
+//! ```
+//! use eliasfl_chess::{Game, GameEvent, Piece, Color};
+//!
+//! let mut game = Game::from_fen("4k3/8/8/8/8/8/8/4K2R w K - 0 1").unwrap();
+//! game.make_move_san("O-O").unwrap();
+//! let events: Vec<GameEvent> = game.events().collect();
+//! assert_eq!(events.len(), 2);
+//! assert!(matches!(events[1], GameEvent::PieceMoved { piece: Piece::Rook(Color::White), .. }));
+//! assert_eq!(game.events().count(), 0);
+//! ```
+
+use alloc::vec::Drain;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{Color, Game, GameState, MoveOutcome, Piece, Position};
+
+/// Most events kept until they are taken, older events are dropped first
+pub const MAX_EVENTS: usize = 256;
+
+/// Something that happened on the board, see [`Game::events`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum GameEvent {
+    /// A piece moved, castling moves both the king and the rook
+    PieceMoved {
+        piece: Piece,
+        from: Position,
+        to: Position,
+    },
+    /// A piece was removed from the board, beside the destination for en passant and around it
+    /// for explosions in Atomic
+    PieceCaptured { piece: Piece, position: Position },
+    /// The pawn on position became piece
+    Promotion { position: Position, piece: Piece },
+    /// The king of color is attacked, also when it is checkmate
+    Check(Color),
+    /// The game ended with the state
+    GameOver(GameState),
+}
+
+impl Game {
+    /// Take the events of the moves made since the last call, oldest first
+    ///
+    /// At most [`MAX_EVENTS`] are returned, the older ones are dropped.
+    pub fn events(&mut self) -> Drain<'_, GameEvent> {
+        self.events.drain(..)
+    }
+
+    /// Queue event, dropping the oldest events when more than [`MAX_EVENTS`] are queued
+    pub(crate) fn _push_event(&mut self, event: GameEvent) {
+        if self.events.len() >= MAX_EVENTS {
+            self.events.drain(..self.events.len() + 1 - MAX_EVENTS);
+        }
+        self.events.push(event);
+    }
+
+    /// Queue the events of the move from `before` that led to the current position
+    pub(crate) fn _record_events(&mut self, before: &Game, outcome: &MoveOutcome) {
+        let mov = outcome.mov;
+        let piece = *before.board.get(&mov.from).expect("A piece moved");
+        let castle = before._castling(&mov);
+        let (to, rook) = match castle {
+            Some((king_to, rook_from, rook_to)) => (king_to, Some((rook_from, rook_to))),
+            None => (mov.to, None),
+        };
+        self._push_event(GameEvent::PieceMoved {
+            piece,
+            from: mov.from,
+            to,
+        });
+        if let Some((from, to)) = rook {
+            self._push_event(GameEvent::PieceMoved {
+                piece: Piece::Rook(piece.color()),
+                from,
+                to,
+            });
+        }
+        if let Some(promotion) = outcome.promotion {
+            self._push_event(GameEvent::Promotion {
+                position: to,
+                piece: promotion,
+            });
+        }
+        for (position, captured) in before.pieces() {
+            let moved = position == mov.from || rook.map(|(from, _)| from) == Some(position);
+            let remaining = self.board.get(&position);
+            if !moved && remaining.map(Piece::color) != Some(captured.color()) {
+                self._push_event(GameEvent::PieceCaptured {
+                    piece: captured,
+                    position,
+                });
+            }
+        }
+        if self.board.get(&to).map(Piece::color) != Some(piece.color()) {
+            // The capturing piece exploded in Atomic
+            self._push_event(GameEvent::PieceCaptured {
+                piece: outcome.promotion.unwrap_or(piece),
+                position: to,
+            });
+        }
+        if outcome.check {
+            self._push_event(GameEvent::Check(self.active_color));
+        }
+        if matches!(
+            outcome.state,
+            GameState::CheckMate | GameState::Draw(_) | GameState::TimeForfeit
+        ) {
+            self._push_event(GameEvent::GameOver(outcome.state));
+        }
+    }
+}
@@ -0,0 +1,251 @@
+//! Forsyth–Edwards Notation (FEN), eg. "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::config::{BoardSize, GameConfig};
+use crate::{Board, CastlingFiles, CastlingRights, Color, Game, Piece, Position};
+
+/// FEN of the standard starting position
+pub const STARTING_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+/// FEN letter of piece, uppercase for White and lowercase for Black
+pub(crate) fn piece_char(piece: &Piece) -> char {
+    let letter = match piece {
+        Piece::King(_) => 'k',
+        Piece::Queen(_) => 'q',
+        Piece::Rook(_) => 'r',
+        Piece::Bishop(_) => 'b',
+        Piece::Knight(_) => 'n',
+        Piece::Pawn(_) => 'p',
+    };
+    match piece.color() {
+        Color::White => letter.to_ascii_uppercase(),
+        Color::Black => letter,
+    }
+}
+
+/// Piece from FEN letter, uppercase for White and lowercase for Black
+pub(crate) fn piece_from_char(c: char) -> Option<Piece> {
+    let color = if c.is_ascii_uppercase() {
+        Color::White
+    } else {
+        Color::Black
+    };
+    match c.to_ascii_lowercase() {
+        'k' => Some(Piece::King(color)),
+        'q' => Some(Piece::Queen(color)),
+        'r' => Some(Piece::Rook(color)),
+        'b' => Some(Piece::Bishop(color)),
+        'n' => Some(Piece::Knight(color)),
+        'p' => Some(Piece::Pawn(color)),
+        _ => None,
+    }
+}
+
+/// Parse piece placement field, ranks from the last to 1 separated by "/"
+///
+/// Returns the board and its size, which is smaller than 8x8 for eg. "rnbqk/ppppp/5/PPPPP/RNBQK"
+fn parse_placement(placement: &str) -> Result<(Board, BoardSize), &'static str> {
+    let ranks: Vec<&str> = placement.split('/').collect();
+    if !(4..=8).contains(&ranks.len()) {
+        return Err("Piece placement should have 4 to 8 ranks separated by \"/\"");
+    }
+    let mut board = Board::new();
+    let mut files = None;
+    for (row, rank_str) in ranks.iter().enumerate() {
+        let rank = (ranks.len() - row) as u8;
+        let mut file = 1;
+        for c in rank_str.chars() {
+            if let Some(empty) = c.to_digit(10).filter(|d| (1..=8).contains(d)) {
+                file += empty as u8;
+            } else {
+                let piece = piece_from_char(c).ok_or("Invalid piece letter in piece placement")?;
+                if file > 8 {
+                    return Err("Too many squares in rank");
+                }
+                board.insert(Position { file, rank }, piece);
+                file += 1;
+            }
+            if file > 9 {
+                return Err("Too many squares in rank");
+            }
+        }
+        if *files.get_or_insert(file - 1) != file - 1 {
+            return Err("Every rank should have the same number of squares");
+        }
+    }
+    let board_size = BoardSize::new(files.unwrap_or(0), ranks.len() as u8)?;
+    Ok((board, board_size))
+}
+
+impl Game {
+    /// Create game from a position in Forsyth–Edwards Notation (FEN)
+    ///
+    /// The halfmove clock and fullmove number may be left out and default to 0 and 1.
+    /// Promotion pieces are set to queens.
+    /// Castling availability written with the files of the rooks (Shredder-FEN), eg. "HAha", creates a Chess960 game.
+    /// Fewer ranks or squares per rank create a game on a smaller board, see [`BoardSize`].
+    pub fn from_fen(fen: &str) -> Result<Game, &'static str> {
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        if !(4..=6).contains(&fields.len()) {
+            return Err("FEN should have 6 fields separated by spaces");
+        }
+        let (board, board_size) = parse_placement(fields[0])?;
+        if board_size != BoardSize::STANDARD && fields[2] != "-" {
+            return Err("Castling is only possible on a standard board");
+        }
+        let active_color = match fields[1] {
+            "w" => Color::White,
+            "b" => Color::Black,
+            _ => return Err("Active color should be \"w\" or \"b\""),
+        };
+        let mut castling = [CastlingRights {
+            kingside: false,
+            queenside: false,
+        }; 2];
+        let mut castling_files = CastlingFiles::default();
+        let mut chess960 = false;
+        if fields[2] != "-" {
+            for c in fields[2].chars() {
+                match c {
+                    'K' => castling[0].kingside = true,
+                    'Q' => castling[0].queenside = true,
+                    'k' => castling[1].kingside = true,
+                    'q' => castling[1].queenside = true,
+                    'A'..='H' | 'a'..='h' => {
+                        // Chess960 rook file, on the side of the king it stands on
+                        let color = if c.is_ascii_uppercase() {
+                            Color::White
+                        } else {
+                            Color::Black
+                        };
+                        let rook_file = c.to_ascii_lowercase() as u8 - b'a' + 1;
+                        let king_file = (1..=8)
+                            .find(|&file| {
+                                board.get(&Position {
+                                    file,
+                                    rank: board_size.home_rank(color),
+                                }) == Some(&Piece::King(color))
+                            })
+                            .ok_or("Castling availability requires king on first rank")?;
+                        castling_files.king = king_file;
+                        if rook_file > king_file {
+                            castling_files.kingside = rook_file;
+                            castling[color.index()].kingside = true;
+                        } else {
+                            castling_files.queenside = rook_file;
+                            castling[color.index()].queenside = true;
+                        }
+                        chess960 = true;
+                    }
+                    _ => return Err("Castling availability should be \"-\" or letters KQkq"),
+                }
+            }
+        }
+        let en_passant = match fields[3] {
+            "-" => None,
+            square => Some(
+                square
+                    .parse::<Position>()
+                    .ok()
+                    .filter(|p| matches!(p.rank, 3 | 6))
+                    .ok_or("Invalid en passant square")?,
+            ),
+        };
+        let halfmove_clock = match fields.get(4) {
+            Some(field) => field.parse().map_err(|_| "Invalid halfmove clock")?,
+            None => 0,
+        };
+        let fullmove_number = match fields.get(5) {
+            Some(field) => field.parse().map_err(|_| "Invalid fullmove number")?,
+            None => 1,
+        };
+
+        let mut game = Game {
+            board,
+            active_color,
+            castling,
+            castling_files,
+            chess960,
+            en_passant,
+            halfmove_clock,
+            fullmove_number,
+            config: GameConfig::new().board_size(board_size),
+            ..Game::new()
+        };
+        game._update_state();
+        Ok(game)
+    }
+
+    /// Position in Forsyth–Edwards Notation (FEN)
+    pub fn to_fen(&self) -> String {
+        let mut fen = String::new();
+        let board_size = self.config.board_size;
+        for rank in (1..=board_size.ranks()).rev() {
+            let mut empty = 0;
+            for file in 1..=board_size.files() {
+                match self.board.get(&Position { file, rank }) {
+                    Some(piece) => {
+                        if empty > 0 {
+                            fen.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        fen.push(piece_char(piece));
+                    }
+                    None => empty += 1,
+                }
+            }
+            if empty > 0 {
+                fen.push_str(&empty.to_string());
+            }
+            if rank > 1 {
+                fen.push('/');
+            }
+        }
+        fen.push_str(match self.active_color {
+            Color::White => " w ",
+            Color::Black => " b ",
+        });
+        let mut castling = String::new();
+        let file_letter = |file: u8| (b'a' + file - 1) as char;
+        let (kingside, queenside) = if self.chess960 {
+            (
+                file_letter(self.castling_files.kingside),
+                file_letter(self.castling_files.queenside),
+            )
+        } else {
+            ('k', 'q')
+        };
+        for (rights, kingside, queenside) in [
+            (
+                self.castling[0],
+                kingside.to_ascii_uppercase(),
+                queenside.to_ascii_uppercase(),
+            ),
+            (self.castling[1], kingside, queenside),
+        ] {
+            if rights.kingside {
+                castling.push(kingside);
+            }
+            if rights.queenside {
+                castling.push(queenside);
+            }
+        }
+        if castling.is_empty() {
+            castling.push('-');
+        }
+        fen.push_str(&castling);
+        fen.push(' ');
+        match self.en_passant {
+            Some(square) => fen.push_str(&square.to_string()),
+            None => fen.push('-'),
+        }
+        fen.push_str(&format!(
+            " {} {}",
+            self.halfmove_clock, self.fullmove_number
+        ));
+        fen
+    }
+}
@@ -0,0 +1,220 @@
+//! C bindings, enabled with the "ffi" feature
+//!
+//! The shared library built with `cargo rustc --release --lib --features ffi --crate-type cdylib`
+//! exports the functions declared in `include/eliasfl_chess.h`, so Python, C# or Unity frontends
+//! can load the library like any C library. The cdylib is only built on request, so the library
+//! builds without `std` for targets that have no allocator or panic handler of their own.
+//!
+//! Games are opaque pointers created with [`chess_game_new`] or [`chess_game_from_fen`] and freed
+//! with [`chess_game_free`]. Text is passed as NUL-terminated UTF-8 and written into buffers of
+//! the caller, which are never overrun.
+//!
+//! `python/eliasfl_chess.py` wraps the functions in Python classes with `ctypes`.
+//!
+//! ```c
+//! ChessGame *game = chess_game_new();
+//! chess_game_make_move(game, "e2e4");
+//! char fen[128];
+//! chess_game_fen(game, fen, sizeof fen);
+//! ChessMove moves[CHESS_MAX_MOVES];
+//! size_t count = chess_game_legal_moves(game, moves, CHESS_MAX_MOVES);
+//! chess_game_free(game);
+//! ```
+
+use std::ffi::{c_char, CStr};
+use std::ptr;
+
+use crate::{DrawReason, Engine, Game, GameState, Move, Piece};
+
+/// The move was made or found, see [`chess_game_make_move`]
+pub const CHESS_OK: i32 = 0;
+
+/// A pointer was null or the text was not UTF-8
+pub const CHESS_INVALID_ARGUMENT: i32 = -1;
+
+/// The move was not legal or not UCI notation
+pub const CHESS_ILLEGAL_MOVE: i32 = -2;
+
+/// No legal move was found, see [`chess_game_best_move`]
+pub const CHESS_NO_MOVE: i32 = -3;
+
+/// Room for the legal moves of any position, see [`chess_game_legal_moves`]
+pub const CHESS_MAX_MOVES: usize = 256;
+
+/// Legal move as written by [`chess_game_legal_moves`]
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ChessMove {
+    /// Square of the moving piece, 0-63 counting a1, b1, ..., h8
+    pub from: u8,
+    /// Destination square, 0-63
+    pub to: u8,
+    /// Promotion piece as in UCI ('q', 'r', 'b' or 'n'), 0 for other moves
+    pub promotion: u8,
+}
+
+impl From<Move> for ChessMove {
+    fn from(mov: Move) -> Self {
+        Self {
+            from: mov.from.index() as u8,
+            to: mov.to.index() as u8,
+            promotion: match mov.promotion {
+                Some(Piece::Queen(_)) => b'q',
+                Some(Piece::Rook(_)) => b'r',
+                Some(Piece::Bishop(_)) => b'b',
+                Some(Piece::Knight(_)) => b'n',
+                _ => 0,
+            },
+        }
+    }
+}
+
+/// New game with the standard piece arrangement, free it with [`chess_game_free`]
+#[no_mangle]
+pub extern "C" fn chess_game_new() -> *mut Game {
+    Box::into_raw(Box::new(Game::new()))
+}
+
+/// Game from a position in Forsyth–Edwards Notation, null if the FEN is invalid
+///
+/// # Safety
+/// `fen` must be null or a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn chess_game_from_fen(fen: *const c_char) -> *mut Game {
+    match _str(fen).map(Game::from_fen) {
+        Some(Ok(game)) => Box::into_raw(Box::new(game)),
+        _ => ptr::null_mut(),
+    }
+}
+
+/// Free a game, null is ignored
+///
+/// # Safety
+/// `game` must be null or created by this library and not freed before.
+#[no_mangle]
+pub unsafe extern "C" fn chess_game_free(game: *mut Game) {
+    if !game.is_null() {
+        drop(Box::from_raw(game));
+    }
+}
+
+/// Make a move in UCI notation, eg. "e2e4" or "e7e8q", returns [`CHESS_OK`],
+/// [`CHESS_INVALID_ARGUMENT`] or [`CHESS_ILLEGAL_MOVE`]
+///
+/// # Safety
+/// `game` must be null or a live game of this library, `uci` null or a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn chess_game_make_move(game: *mut Game, uci: *const c_char) -> i32 {
+    let (game, uci) = match (game.as_mut(), _str(uci)) {
+        (Some(game), Some(uci)) => (game, uci),
+        _ => return CHESS_INVALID_ARGUMENT,
+    };
+    match game.make_move_uci(uci) {
+        Ok(_) => CHESS_OK,
+        Err(_) => CHESS_ILLEGAL_MOVE,
+    }
+}
+
+/// Write the position in Forsyth–Edwards Notation into `buffer` of `length` bytes
+///
+/// Returns the length of the FEN without the NUL, like `snprintf`. If it is not less than
+/// `length` the FEN was cut short. Nothing is written if `game` or `buffer` is null.
+///
+/// # Safety
+/// `game` must be null or a live game of this library, `buffer` null or valid for `length` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn chess_game_fen(
+    game: *const Game,
+    buffer: *mut c_char,
+    length: usize,
+) -> usize {
+    let fen = match game.as_ref() {
+        Some(game) => game.to_fen(),
+        None => return 0,
+    };
+    if !buffer.is_null() && length > 0 {
+        let written = fen.len().min(length - 1);
+        ptr::copy_nonoverlapping(fen.as_ptr().cast(), buffer, written);
+        *buffer.add(written) = 0;
+    }
+    fen.len()
+}
+
+/// Write the legal moves of the player who's turn it is into `moves`, at most `capacity`
+///
+/// Returns the number of legal moves, which may be more than `capacity` but never more than
+/// [`CHESS_MAX_MOVES`].
+///
+/// # Safety
+/// `game` must be null or a live game of this library, `moves` null or valid for `capacity` moves.
+#[no_mangle]
+pub unsafe extern "C" fn chess_game_legal_moves(
+    game: *const Game,
+    moves: *mut ChessMove,
+    capacity: usize,
+) -> usize {
+    let game = match game.as_ref() {
+        Some(game) => game,
+        None => return 0,
+    };
+    let legal = game.legal_moves();
+    if !moves.is_null() {
+        for (i, mov) in legal.iter().take(capacity).enumerate() {
+            *moves.add(i) = ChessMove::from(*mov);
+        }
+    }
+    legal.len()
+}
+
+/// Write the best move of the engine searching `depth` plies into `best`, returns [`CHESS_OK`],
+/// [`CHESS_INVALID_ARGUMENT`] or [`CHESS_NO_MOVE`] when the game is over
+///
+/// # Safety
+/// `game` must be null or a live game of this library, `best` null or valid for one move.
+#[no_mangle]
+pub unsafe extern "C" fn chess_game_best_move(
+    game: *const Game,
+    depth: u32,
+    best: *mut ChessMove,
+) -> i32 {
+    let (game, best) = match (game.as_ref(), best.as_mut()) {
+        (Some(game), Some(best)) => (game, best),
+        _ => return CHESS_INVALID_ARGUMENT,
+    };
+    match Engine::new(depth).best_move(game) {
+        Some(mov) => {
+            *best = ChessMove::from(mov);
+            CHESS_OK
+        }
+        None => CHESS_NO_MOVE,
+    }
+}
+
+/// State of the game: 0 in progress, 1 check, 2 checkmate, 3 stalemate, 4 draw, 5 time
+/// forfeit, -1 if `game` is null
+///
+/// # Safety
+/// `game` must be null or a live game of this library.
+#[no_mangle]
+pub unsafe extern "C" fn chess_game_state(game: *const Game) -> i32 {
+    let game = match game.as_ref() {
+        Some(game) => game,
+        None => return -1,
+    };
+    match game.get_game_state() {
+        GameState::InProgress => 0,
+        GameState::Check => 1,
+        GameState::CheckMate => 2,
+        GameState::Draw(DrawReason::Stalemate) => 3,
+        GameState::Draw(_) => 4,
+        GameState::TimeForfeit => 5,
+    }
+}
+
+/// Text of a C string, None if it is null or not UTF-8
+unsafe fn _str<'a>(text: *const c_char) -> Option<&'a str> {
+    if text.is_null() {
+        return None;
+    }
+    CStr::from_ptr(text).to_str().ok()
+}
@@ -0,0 +1,94 @@
+//! Per-square statistics for visualizing a position: attacked squares and mobility of pieces
+//!
+//! Every statistic is an 8x8 array indexed by rank and then file, from `[0][0]` for a1 to
+//! `[7][7]` for h8, which is passed as nested arrays to JavaScript by the WebAssembly bindings.
+//!
+//! # Example
+//! ```
+//! use eliasfl_chess::{Color, Game};
+//!
+//! let heatmap = Game::new().heatmap();
+//! // The pawns on d2 and f2 attack e3, but no Black piece does
+//! assert_eq!(heatmap.attacks(Color::White)[2][4], 2);
+//! assert_eq!(heatmap.control("e3".parse().unwrap()), 2);
+//! // The knight on g1 can move to f3 and h3, the rook on a1 cannot move
+//! assert_eq!(heatmap.mobility[0][6], 2);
+//! assert_eq!(heatmap.mobility[0][0], 0);
+//! ```
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{Color, Game, Position};
+
+/// A number per square indexed by rank and then file, `[0][0]` is a1
+pub type SquareCounts = [[u8; 8]; 8];
+
+/// Attacks of both colors and mobility of the pieces on every square, see [`Game::heatmap`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Heatmap {
+    /// Number of White pieces attacking each square, including squares of White pieces
+    pub white: SquareCounts,
+    /// Number of Black pieces attacking each square, including squares of Black pieces
+    pub black: SquareCounts,
+    /// Number of squares the piece on each square can legally move to, 0 for empty squares
+    pub mobility: SquareCounts,
+}
+
+impl Heatmap {
+    /// Number of pieces of color attacking each square
+    pub fn attacks(&self, color: Color) -> &SquareCounts {
+        match color {
+            Color::White => &self.white,
+            Color::Black => &self.black,
+        }
+    }
+
+    /// Number of White attackers minus Black attackers of the square, positive if White controls
+    /// it
+    pub fn control(&self, position: Position) -> i32 {
+        let (rank, file) = _index(position);
+        self.white[rank][file] as i32 - self.black[rank][file] as i32
+    }
+}
+
+/// Indices of the square in [`SquareCounts`]
+fn _index(position: Position) -> (usize, usize) {
+    (position.rank as usize - 1, position.file as usize - 1)
+}
+
+impl Game {
+    /// Attacks and mobility per square, see [`Heatmap`]
+    ///
+    /// Mobility is counted for both players, as if it were their turn
+    pub fn heatmap(&self) -> Heatmap {
+        let mut heatmap = Heatmap {
+            white: [[0; 8]; 8],
+            black: [[0; 8]; 8],
+            mobility: [[0; 8]; 8],
+        };
+        for index in 0..64 {
+            let target = Position::from_index(index).expect("Index is on the board");
+            let (rank, file) = _index(target);
+            for (from, piece) in self.pieces() {
+                if self._attacks(&from, &target) {
+                    match piece.color() {
+                        Color::White => heatmap.white[rank][file] += 1,
+                        Color::Black => heatmap.black[rank][file] += 1,
+                    }
+                }
+            }
+        }
+        for color in [Color::White, Color::Black] {
+            let mut moves = self.legal_moves_for(color);
+            // Promotions have one move per piece
+            moves.dedup_by_key(|mov| (mov.from, mov.to));
+            for mov in moves {
+                let (rank, file) = _index(mov.from);
+                heatmap.mobility[rank][file] += 1;
+            }
+        }
+        heatmap
+    }
+}
@@ -0,0 +1,212 @@
+//! Why a move is illegal, eg. for teaching frontends explaining rejected moves
+//!
+//! [`Game::why_illegal`] gives an [`IllegalReason`] for a move of the player who's turn it is,
+//! with the square of the piece in the way or attacking the king when there is one.
+//!
+//! # Example
+//! ```
+//! use eliasfl_chess::illegal::IllegalReason;
+//! use eliasfl_chess::Game;
+//!
+//! let game = Game::new();
+//! let square = |name: &str| name.parse().unwrap();
+//! assert_eq!(game.why_illegal(square("e2"), square("e4")), None);
+//! assert_eq!(
+//!     game.why_illegal(square("a1"), square("a3")),
+//!     Some(IllegalReason::Blocked { by: square("a2") })
+//! );
+//! assert_eq!(
+//!     game.why_illegal(square("e7"), square("e5")),
+//!     Some(IllegalReason::NotYourPiece)
+//! );
+//! ```
+
+use core::fmt;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::config::BoardSize;
+use crate::ray::{self, Ray};
+use crate::{Game, Move, Piece, Position};
+
+/// Why a move is illegal, see [`Game::why_illegal`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum IllegalReason {
+    /// The game has ended, no more moves can be made
+    GameOver,
+    /// There is no piece on the square moved from
+    NoPiece,
+    /// The piece belongs to the player who is not to move
+    NotYourPiece,
+    /// The piece does not move that way, eg. a bishop moving along a file or a pawn moving
+    /// diagonally without capturing
+    WrongPieceMovement,
+    /// A piece stands between the squares, or in front of a pawn
+    Blocked {
+        /// Square of the first piece in the way
+        by: Position,
+    },
+    /// A piece of the same color stands on the destination
+    CaptureOwnPiece,
+    /// Kings are never captured, the game ends by checkmate before
+    CannotCaptureKing,
+    /// The king or rook has moved, the squares between them are not empty or the king is in,
+    /// passes or lands in check
+    CastlingNotAllowed,
+    /// The king is in check and stays in check after the move
+    MustResolveCheck {
+        /// Square of a piece giving check after the move
+        by: Position,
+    },
+    /// The move puts the own king in check, eg. moving a pinned piece or the king onto an
+    /// attacked square
+    WouldExposeKing {
+        /// Square of a piece attacking the king after the move, the destination if the king
+        /// explodes in Atomic
+        by: Position,
+    },
+}
+
+impl fmt::Display for IllegalReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IllegalReason::GameOver => write!(f, "The game is over"),
+            IllegalReason::NoPiece => write!(f, "There is no piece to move"),
+            IllegalReason::NotYourPiece => write!(f, "Trying to move opponents piece"),
+            IllegalReason::WrongPieceMovement => write!(f, "The piece does not move like that"),
+            IllegalReason::Blocked { by } => write!(f, "The piece on {} is in the way", by),
+            IllegalReason::CaptureOwnPiece => write!(f, "Cannot capture own piece"),
+            IllegalReason::CannotCaptureKing => write!(f, "Cannot capture king"),
+            IllegalReason::CastlingNotAllowed => write!(f, "Castling is not allowed"),
+            IllegalReason::MustResolveCheck { by } => {
+                write!(f, "The king is in check from the piece on {}", by)
+            }
+            IllegalReason::WouldExposeKing { by } => {
+                write!(f, "Move exposes the king to the piece on {}", by)
+            }
+        }
+    }
+}
+
+impl Game {
+    /// Why moving the piece on `from` to `to` is illegal for the player who's turn it is, None if
+    /// the move is legal
+    ///
+    /// Pawns reaching the last rank are checked without a promotion piece.
+    pub fn why_illegal(&self, from: Position, to: Position) -> Option<IllegalReason> {
+        if self.is_game_over() {
+            return Some(IllegalReason::GameOver);
+        }
+        let piece = match self.board.get(&from) {
+            Some(&piece) => piece,
+            None => return Some(IllegalReason::NoPiece),
+        };
+        let color = piece.color();
+        if color != self.active_color {
+            return Some(IllegalReason::NotYourPiece);
+        }
+        let destinations = self._get_possible_moves(&from).unwrap_or_default();
+        if !destinations.contains(&to) {
+            return Some(self._unreachable_reason(piece, from, to));
+        }
+        if matches!(self.board.get(&to), Some(Piece::King(_))) {
+            return Some(IllegalReason::CannotCaptureKing);
+        }
+        let mut after = self.clone();
+        after._make(Move {
+            from,
+            to,
+            promotion: None,
+        });
+        if !after._king_is_threatened(color) {
+            return None;
+        }
+        let by = after
+            .pieces_of(color)
+            .find(|(_, piece)| *piece == Piece::King(color))
+            .and_then(|(king, _)| after.attackers_of(king, !color).first().copied())
+            .unwrap_or(to);
+        if self._king_is_threatened(color) {
+            Some(IllegalReason::MustResolveCheck { by })
+        } else {
+            Some(IllegalReason::WouldExposeKing { by })
+        }
+    }
+
+    /// Why the piece on `from` cannot reach `to` even when ignoring checks
+    fn _unreachable_reason(&self, piece: Piece, from: Position, to: Position) -> IllegalReason {
+        let color = piece.color();
+        let rank = self.config.board_size.home_rank(color);
+        let castling = piece == Piece::King(color)
+            && from.file == self.castling_files.king
+            && from.rank == rank
+            && to.rank == rank
+            && (from.file.abs_diff(to.file) == 2
+                || self.board.get(&to) == Some(&Piece::Rook(color)));
+        if castling {
+            return IllegalReason::CastlingNotAllowed;
+        }
+        if let Some(blocker) = self._blocker(piece, from, to) {
+            return blocker;
+        }
+        match self.board.get(&to) {
+            Some(target) if target.color() == color => IllegalReason::CaptureOwnPiece,
+            _ => IllegalReason::WrongPieceMovement,
+        }
+    }
+
+    /// [`IllegalReason::WrongPieceMovement`] if the piece cannot move from `from` to `to` on an
+    /// empty board, [`IllegalReason::Blocked`] if a piece is in the way
+    fn _blocker(&self, piece: Piece, from: Position, to: Position) -> Option<IllegalReason> {
+        let (file, rank) = (
+            to.file as i32 - from.file as i32,
+            to.rank as i32 - from.rank as i32,
+        );
+        if let Piece::Pawn(color) = piece {
+            let forward = color.direction();
+            let start_rank = self.config.board_size.home_rank(color) as i32 + forward;
+            let pushes = if rank == forward {
+                1
+            } else if rank == 2 * forward
+                && from.rank as i32 == start_rank
+                && self.config.board_size == BoardSize::STANDARD
+            {
+                2
+            } else {
+                0
+            };
+            if file.abs() == 1 && rank == forward {
+                // A diagonal step is only wrong when there is nothing to capture
+                return None;
+            }
+            if file != 0 || pushes == 0 {
+                return Some(IllegalReason::WrongPieceMovement);
+            }
+            return (1..=pushes)
+                .filter_map(|step| {
+                    self.config
+                        .board_size
+                        .relative_pos(&from, 0, step * forward)
+                })
+                .find(|square| self.board.contains_key(square))
+                .map(|by| IllegalReason::Blocked { by });
+        }
+        let direction = match piece {
+            Piece::Knight(_) if ray::KNIGHT_OFFSETS.contains(&(file, rank)) => return None,
+            Piece::King(_) if from.distance(&to) == 1 => return None,
+            Piece::Knight(_) | Piece::King(_) => None,
+            _ => ray::direction(&from, &to)
+                .filter(|direction| ray::sliding_directions(&piece).contains(direction)),
+        };
+        let direction = match direction {
+            Some(direction) => direction,
+            None => return Some(IllegalReason::WrongPieceMovement),
+        };
+        Ray::new(&self.board, self.config.board_size, from, direction)
+            .find(|square| self.board.contains_key(square))
+            .filter(|square| *square != to)
+            .map(|by| IllegalReason::Blocked { by })
+    }
+}
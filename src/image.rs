@@ -0,0 +1,508 @@
+//! Board images as SVG and GIF, enabled with the "render-image" feature
+//!
+//! [`Game::to_svg`] draws the position as an SVG document and [`Game::to_gif`] as a GIF image, eg.
+//! for bots posting positions to chat platforms. [`Game::to_animated_gif`] shows every position of
+//! the game, one frame per move. The pieces are simple polygons embedded in the library, so no
+//! fonts or image files are needed.
+//!
+//! # Example
+//! ```
+//! use eliasfl_chess::image::ImageOptions;
+//! use eliasfl_chess::{Color, Game};
+//!
+//! let mut game = Game::new();
+//! game.make_move_san("e4").unwrap();
+//! let options = ImageOptions {
+//!     perspective: Color::Black,
+//!     ..ImageOptions::default()
+//! };
+//! assert!(game.to_svg(&options).starts_with("<svg"));
+//! let gif = game.to_animated_gif(&options).unwrap();
+//! assert!(gif.starts_with(b"GIF89a"));
+//! ```
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+use crate::{Color, Game, Piece, Position, SquareShade};
+
+/// Width of a square in the coordinates of the piece outlines
+const UNITS: u32 = 45;
+
+/// Colors of the image as RGB: light and dark squares, light and dark squares of the last move,
+/// white and black pieces and the outlines. Their order is the GIF palette, filled up to 8 colors.
+const PALETTE: [[u8; 3]; 8] = [
+    [0xf0, 0xd9, 0xb5],
+    [0xb5, 0x88, 0x63],
+    [0xcd, 0xd2, 0x6a],
+    [0xaa, 0xa2, 0x3a],
+    [0xff, 0xff, 0xff],
+    [0x40, 0x40, 0x40],
+    [0x00, 0x00, 0x00],
+    [0x00, 0x00, 0x00],
+];
+
+/// Palette index of the white pieces, followed by the black pieces and the outlines
+const WHITE_PIECE: u8 = 4;
+const BLACK_PIECE: u8 = 5;
+const OUTLINE: u8 = 6;
+
+/// Bits of the smallest GIF codes, enough for the 8 colors of the palette
+const MIN_CODE_SIZE: u8 = 3;
+
+/// Largest GIF code, the code table is started over when it is full
+const MAX_CODE: u16 = 4095;
+
+/// How the board is drawn, see [`Game::to_svg`] and [`Game::to_gif`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageOptions {
+    /// Width and height of a square in pixels, at least 1 and at most 8191
+    pub square_size: u32,
+    /// Player at the bottom of the board
+    pub perspective: Color,
+    /// Highlight the squares of the last move
+    pub last_move: bool,
+    /// Time each frame of an animated GIF is shown, in hundredths of a second
+    pub frame_delay: u16,
+}
+
+impl Default for ImageOptions {
+    /// Squares of 45 pixels seen from White, with the last move highlighted and a second per move
+    fn default() -> Self {
+        Self {
+            square_size: 45,
+            perspective: Color::White,
+            last_move: true,
+            frame_delay: 100,
+        }
+    }
+}
+
+/// Polygons of the outline of a piece, as points in a square of [`UNITS`]
+fn outline(piece: Piece) -> &'static [&'static [(u8, u8)]] {
+    match piece {
+        Piece::King(_) => &[
+            &[
+                (11, 39),
+                (34, 39),
+                (34, 35),
+                (31, 33),
+                (36, 22),
+                (31, 17),
+                (23, 21),
+                (22, 21),
+                (14, 17),
+                (9, 22),
+                (14, 33),
+                (11, 35),
+            ],
+            &[
+                (21, 6),
+                (24, 6),
+                (24, 9),
+                (27, 9),
+                (27, 12),
+                (24, 12),
+                (24, 19),
+                (21, 19),
+                (21, 12),
+                (18, 12),
+                (18, 9),
+                (21, 9),
+            ],
+        ],
+        Piece::Queen(_) => &[&[
+            (11, 39),
+            (34, 39),
+            (34, 35),
+            (31, 33),
+            (36, 14),
+            (29, 25),
+            (28, 10),
+            (25, 23),
+            (23, 8),
+            (22, 8),
+            (20, 23),
+            (17, 10),
+            (16, 25),
+            (9, 14),
+            (14, 33),
+            (11, 35),
+        ]],
+        Piece::Rook(_) => &[&[
+            (10, 39),
+            (35, 39),
+            (35, 35),
+            (31, 35),
+            (31, 18),
+            (34, 15),
+            (34, 9),
+            (30, 9),
+            (30, 12),
+            (26, 12),
+            (26, 9),
+            (19, 9),
+            (19, 12),
+            (15, 12),
+            (15, 9),
+            (11, 9),
+            (11, 15),
+            (14, 18),
+            (14, 35),
+            (10, 35),
+        ]],
+        Piece::Bishop(_) => &[&[
+            (10, 39),
+            (35, 39),
+            (35, 36),
+            (28, 34),
+            (29, 30),
+            (31, 25),
+            (29, 19),
+            (24, 13),
+            (24, 11),
+            (23, 8),
+            (22, 8),
+            (21, 11),
+            (21, 13),
+            (16, 19),
+            (14, 25),
+            (16, 30),
+            (17, 34),
+            (10, 36),
+        ]],
+        Piece::Knight(_) => &[&[
+            (12, 39),
+            (35, 39),
+            (34, 30),
+            (33, 22),
+            (30, 14),
+            (25, 10),
+            (22, 7),
+            (20, 10),
+            (15, 13),
+            (9, 22),
+            (10, 26),
+            (14, 25),
+            (19, 22),
+            (21, 24),
+            (15, 31),
+        ]],
+        Piece::Pawn(_) => &[&[
+            (12, 39),
+            (33, 39),
+            (33, 36),
+            (28, 32),
+            (26, 24),
+            (29, 22),
+            (29, 18),
+            (26, 15),
+            (19, 15),
+            (16, 18),
+            (16, 22),
+            (19, 24),
+            (17, 32),
+            (12, 36),
+        ]],
+    }
+}
+
+/// Width of a square in pixels, between 1 and the largest size a GIF can have
+fn square_size(options: &ImageOptions) -> u32 {
+    options.square_size.clamp(1, u32::from(u16::MAX) / 8)
+}
+
+/// Column and row of position counted from the top left corner of the image
+fn cell(position: Position, perspective: Color) -> (u32, u32) {
+    let (file, rank) = (u32::from(position.file), u32::from(position.rank));
+    match perspective {
+        Color::White => (file - 1, 8 - rank),
+        Color::Black => (8 - file, rank - 1),
+    }
+}
+
+/// Palette index of the square, highlighted if it is part of the last move
+fn square_color(game: &Game, position: Position, options: &ImageOptions) -> u8 {
+    let highlighted = options.last_move
+        && game
+            .last_move()
+            .is_some_and(|mov| mov.from == position || mov.to == position);
+    let dark = position.shade() == SquareShade::Dark;
+    u8::from(dark) + if highlighted { 2 } else { 0 }
+}
+
+/// Hexadecimal notation of a palette color, eg. "#f0d9b5"
+fn hex(index: u8) -> String {
+    let [red, green, blue] = PALETTE[usize::from(index)];
+    format!("#{:02x}{:02x}{:02x}", red, green, blue)
+}
+
+impl Game {
+    /// The position as an SVG document, see [`image`](crate::image)
+    pub fn to_svg(&self, options: &ImageOptions) -> String {
+        let size = 8 * square_size(options);
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">\n",
+            size,
+            size,
+            8 * UNITS,
+            8 * UNITS
+        );
+        for index in 0..64 {
+            let position = Position::from_index(index).expect("Index is on the board");
+            let (column, row) = cell(position, options.perspective);
+            // Writing to a String cannot fail
+            let _ = writeln!(
+                svg,
+                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>",
+                column * UNITS,
+                row * UNITS,
+                UNITS,
+                UNITS,
+                hex(square_color(self, position, options))
+            );
+        }
+        for (position, piece) in self.pieces() {
+            let (column, row) = cell(position, options.perspective);
+            let fill = match piece.color() {
+                Color::White => WHITE_PIECE,
+                Color::Black => BLACK_PIECE,
+            };
+            for polygon in outline(piece) {
+                let points: Vec<String> = polygon
+                    .iter()
+                    .map(|(x, y)| {
+                        format!(
+                            "{},{}",
+                            column * UNITS + u32::from(*x),
+                            row * UNITS + u32::from(*y)
+                        )
+                    })
+                    .collect();
+                let _ = writeln!(
+                    svg,
+                    "<polygon points=\"{}\" fill=\"{}\" stroke=\"{}\" stroke-width=\"1.5\" stroke-linejoin=\"round\"/>",
+                    points.join(" "),
+                    hex(fill),
+                    hex(OUTLINE)
+                );
+            }
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// The position as a GIF image, see [`image`](crate::image)
+    pub fn to_gif(&self, options: &ImageOptions) -> Vec<u8> {
+        let mut gif = _gif_header(options, false);
+        _gif_frame(&mut gif, &self._pixels(options), options, 0);
+        gif.push(0x3b);
+        gif
+    }
+
+    /// An animated GIF of the starting position followed by the position after every move, see
+    /// [`image`](crate::image)
+    ///
+    /// Returns Err if the history cannot be replayed, see [`Game::replay`]
+    pub fn to_animated_gif(&self, options: &ImageOptions) -> Result<Vec<u8>, &'static str> {
+        let replay = self.replay()?;
+        let mut gif = _gif_header(options, true);
+        _gif_frame(
+            &mut gif,
+            &replay.start()._pixels(options),
+            options,
+            options.frame_delay,
+        );
+        for snapshot in replay {
+            let pixels = snapshot.game._pixels(options);
+            _gif_frame(&mut gif, &pixels, options, options.frame_delay);
+        }
+        gif.push(0x3b);
+        Ok(gif)
+    }
+
+    /// Palette indices of the pixels of the image, row by row from the top left corner
+    fn _pixels(&self, options: &ImageOptions) -> Vec<u8> {
+        let square_size = square_size(options) as usize;
+        let width = 8 * square_size;
+        let mut pixels = vec![0; width * width];
+        for index in 0..64 {
+            let position = Position::from_index(index).expect("Index is on the board");
+            let mut square = vec![square_color(self, position, options); square_size * square_size];
+            if let Some(&piece) = self.board.get(&position) {
+                let fill = match piece.color() {
+                    Color::White => WHITE_PIECE,
+                    Color::Black => BLACK_PIECE,
+                };
+                for polygon in outline(piece) {
+                    _fill_polygon(&mut square, square_size, polygon, fill);
+                }
+            }
+            let (column, row) = cell(position, options.perspective);
+            let (left, top) = (column as usize * square_size, row as usize * square_size);
+            for (y, line) in square.chunks(square_size).enumerate() {
+                let start = (top + y) * width + left;
+                pixels[start..start + square_size].copy_from_slice(line);
+            }
+        }
+        pixels
+    }
+}
+
+/// Paint the inside of polygon with fill and its edge with the outline color, in a square of
+/// `size` pixels
+fn _fill_polygon(square: &mut [u8], size: usize, polygon: &[(u8, u8)], fill: u8) {
+    // Pixel centers in the coordinates of the outlines
+    let scale = UNITS as f32 / size as f32;
+    let inside = |x: isize, y: isize| {
+        if x < 0 || y < 0 || x >= size as isize || y >= size as isize {
+            return false;
+        }
+        let (px, py) = ((x as f32 + 0.5) * scale, (y as f32 + 0.5) * scale);
+        // Even-odd rule: count the edges crossed by a ray to the right
+        let mut crossings = false;
+        let mut previous = polygon[polygon.len() - 1];
+        for &point in polygon {
+            let (x1, y1) = (f32::from(previous.0), f32::from(previous.1));
+            let (x2, y2) = (f32::from(point.0), f32::from(point.1));
+            if (y1 > py) != (y2 > py) && px < x1 + (py - y1) / (y2 - y1) * (x2 - x1) {
+                crossings = !crossings;
+            }
+            previous = point;
+        }
+        crossings
+    };
+    // Outlines are about as thick as the stroke of the SVG
+    let stroke = ((size as f32 / 30.0) as isize).max(1);
+    for y in 0..size as isize {
+        for x in 0..size as isize {
+            if !inside(x, y) {
+                continue;
+            }
+            let edge = [(-stroke, 0), (stroke, 0), (0, -stroke), (0, stroke)]
+                .iter()
+                .any(|&(dx, dy)| !inside(x + dx, y + dy));
+            square[y as usize * size + x as usize] = if edge { OUTLINE } else { fill };
+        }
+    }
+}
+
+/// GIF header with the size of the board and the palette, looping forever if animated
+fn _gif_header(options: &ImageOptions, animated: bool) -> Vec<u8> {
+    let size = (8 * square_size(options)) as u16;
+    let mut gif = Vec::new();
+    gif.extend_from_slice(b"GIF89a");
+    gif.extend_from_slice(&size.to_le_bytes());
+    gif.extend_from_slice(&size.to_le_bytes());
+    // Global palette of 2^(2 + 1) colors, background color 0 and square pixels
+    gif.extend_from_slice(&[0xf2, 0, 0]);
+    for color in PALETTE {
+        gif.extend_from_slice(&color);
+    }
+    if animated {
+        // Netscape extension repeating the animation forever
+        gif.extend_from_slice(&[0x21, 0xff, 0x0b]);
+        gif.extend_from_slice(b"NETSCAPE2.0");
+        gif.extend_from_slice(&[0x03, 0x01, 0x00, 0x00, 0x00]);
+    }
+    gif
+}
+
+/// Append a frame of pixels covering the whole image, shown for `delay` hundredths of a second
+fn _gif_frame(gif: &mut Vec<u8>, pixels: &[u8], options: &ImageOptions, delay: u16) {
+    let size = (8 * square_size(options)) as u16;
+    gif.extend_from_slice(&[0x21, 0xf9, 0x04, 0x00]);
+    gif.extend_from_slice(&delay.to_le_bytes());
+    gif.extend_from_slice(&[0x00, 0x00]);
+    gif.push(0x2c);
+    gif.extend_from_slice(&[0, 0, 0, 0]);
+    gif.extend_from_slice(&size.to_le_bytes());
+    gif.extend_from_slice(&size.to_le_bytes());
+    gif.push(0x00);
+    gif.push(MIN_CODE_SIZE);
+    // Compressed data in blocks of at most 255 bytes, ended by an empty block
+    for block in _lzw(pixels).chunks(255) {
+        gif.push(block.len() as u8);
+        gif.extend_from_slice(block);
+    }
+    gif.push(0x00);
+}
+
+/// Compress palette indices with the variable-length LZW codes of GIF
+fn _lzw(pixels: &[u8]) -> Vec<u8> {
+    let clear = 1u16 << MIN_CODE_SIZE;
+    let end = clear + 1;
+    let colors = usize::from(clear);
+    // Code of a known sequence followed by a color, 0 if not in the table
+    let mut table = vec![0u16; (usize::from(MAX_CODE) + 1) * colors];
+    let mut next_code = end + 1;
+    let mut code_size = u32::from(MIN_CODE_SIZE) + 1;
+    let mut output = Codes::default();
+    output.push(clear, code_size);
+    let mut pixels = pixels.iter().map(|&pixel| u16::from(pixel));
+    let mut prefix = match pixels.next() {
+        Some(pixel) => pixel,
+        None => {
+            output.push(end, code_size);
+            return output.finish();
+        }
+    };
+    for pixel in pixels {
+        let entry = usize::from(prefix) * colors + usize::from(pixel);
+        if table[entry] != 0 {
+            prefix = table[entry];
+            continue;
+        }
+        output.push(prefix, code_size);
+        if next_code > MAX_CODE {
+            // The table is full, start over
+            output.push(clear, code_size);
+            table.iter_mut().for_each(|code| *code = 0);
+            next_code = end + 1;
+            code_size = u32::from(MIN_CODE_SIZE) + 1;
+        } else {
+            table[entry] = next_code;
+            next_code += 1;
+            // Decoders add codes one step later, so the wider codes start after the next one
+            if u32::from(next_code) > 1 << code_size && code_size < 12 {
+                code_size += 1;
+            }
+        }
+        prefix = pixel;
+    }
+    output.push(prefix, code_size);
+    output.push(end, code_size);
+    output.finish()
+}
+
+/// Codes packed into bytes from the least significant bit, see [`_lzw`]
+#[derive(Default)]
+struct Codes {
+    bytes: Vec<u8>,
+    bits: u32,
+    bit_count: u32,
+}
+
+impl Codes {
+    /// Append code written with `size` bits
+    fn push(&mut self, code: u16, size: u32) {
+        self.bits |= u32::from(code) << self.bit_count;
+        self.bit_count += size;
+        while self.bit_count >= 8 {
+            self.bytes.push(self.bits as u8);
+            self.bits >>= 8;
+            self.bit_count -= 8;
+        }
+    }
+
+    /// The bytes with the last incomplete byte
+    fn finish(mut self) -> Vec<u8> {
+        if self.bit_count > 0 {
+            self.bytes.push(self.bits as u8);
+        }
+        self.bytes
+    }
+}
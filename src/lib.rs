@@ -3,10 +3,11 @@
 //!
 //! # How to run the program
 //! 1. Download and run the binary (for x86 systems):  
-//!     [Windows](https://elias.floreteng.se/chess/bin/eliasfl-chess.exe)  
-//!     [Linux](https://elias.floreteng.se/chess/bin/eliasfl-chess)
+//!    [Windows](https://elias.floreteng.se/chess/bin/eliasfl-chess.exe)  
+//!    [Linux](https://elias.floreteng.se/chess/bin/eliasfl-chess)
 //!
-//! Pass "fancy" as an argument when running to use unicode symbols for the pieces.
+//! Pass "fancy" as an argument when running to use unicode symbols for the pieces, or "blindfold" to
+//! hide the board and announce the moves in words (see [`Game::describe_move`]).
 //!
 // You can alternatively install it on your system with `cargo install eliasfl-chess`
 //!
@@ -17,13 +18,49 @@
 //! 3. (Optional) View documentation locally: `cargo doc --open`
 //!
 //! # Library usage
+//! The stable core types are exported by the [`prelude`], eg. `use eliasfl_chess::prelude::*;`  
 //! Parameters to public functions are of type String and consists of a file (a-h) and rank (1-8) eg. "e2" or "d7"  
 //! The functionality of the library is encapsulated in the [`Game`] struct:  
 //! - [`Game::new`] is used to create a new game with the standard piece arrangement
+//! - [`Game::pieces`] iterates over the pieces on the board, as does `for (position, piece) in &game`
+//! - [`Square`] names the squares, eg. `Square::E4`, and converts to and from [`Position`]
 //! - [`Game::get_possible_moves`] returns the possible moves for a certain square
-//! - [`Game::make_move`] moves a piece to a destination
+//! - [`Game::make_move`] moves a piece to a destination and returns a [`MoveOutcome`]
+//! - [`Game::make_move_san`] makes a move written in algebraic notation, eg. "Nf3" or "O-O"
+//! - [`notation::Notation`] writes and reads moves with German, Swedish or figurine piece letters
+//! - `"e4".parse::<Position>()` reads coordinates, with a [`coordinate::ParseError`] locating mistakes
+//! - [`Game::make_move_uci`] makes a move written in UCI notation, eg. "e2e4" or "e7e8q"
+//! - [`Game::why_illegal`] explains why a move is rejected, see [`illegal::IllegalReason`]
 //! - [`Game::set_promotion`] sets the piece to turn pawns into during promotion, applies for current player
 //! - [`Game::get_game_state`] returns the current state of the game
+//! - [`Game::events`] takes the [`GameEvent`]s of the moves made, eg. pieces moved and captured
+//! - [`Game::from_fen`] and [`Game::to_fen`] load and save positions in Forsyth–Edwards Notation
+//! - [`Game::to_bytes`] and [`Game::from_bytes`] store positions in a compact [`binary`] format
+//! - [`Game::from_pgn`] replays a game in Portable Game Notation, see [`pgn`] for tags and positions, and
+//!   [`Game::to_pgn`] writes one
+//! - [`Game::is_endgame`] and [`Game::king_zone_attackers`] describe the game phase and king safety,
+//!   see [`eval`]
+//! - [`Game::analyze`] ranks the legal moves with their scores and principal variations
+//! - [`Game::analyze_game`] classifies every played move from best to blunder, see [`report`]
+//! - [`Game::eco`] classifies the opening of a game by its ECO code, see [`eco`]
+//! - [`Game::heatmap`] counts the attackers of every square and the mobility of every piece, eg. for visualizers
+//! - [`Game::pawn_structure`] finds doubled, isolated, backward and passed pawns, see [`pawns`]
+//! - [`Game::endgame_result`] knows the exact result of KQK, KRK and KPK endgames, see [`tablebase`]
+//! - [`Game::find_mate`] solves mate-in-N puzzles, see [`mate`]
+//! - [`Game::is_dead_position`] finds positions where neither player can checkmate, which are drawn
+//! - [`puzzle::Puzzle`] loads tactics puzzles from the Lichess puzzle database and checks solutions
+//! - `Game::to_svg` and `Game::to_animated_gif` draw positions and games as images with the "render-image" feature
+//! - [`Game::replay`] steps through the positions after every move of a game, eg. for a viewer
+//! - [`Game::flipped`] and [`Game::mirrored`] show the position from another side, see [`orientation`]
+//! - [`Game::random_position`] sets up a random legal position from a seed, eg. a K+R vs K endgame
+//! - [`Game::play_random_game`] plays random moves to the end of the game, see [`simulate`] for engine players
+//! - [`tournament::Match`] plays a series of games between engines or other players
+//! - [`Game::new_chess960`] creates a game from one of the 960 Fischer Random starting positions
+//! - [`Game::builder`] sets up a custom position piece by piece
+//! - [`Game::with_config`] starts a game with different rules per player, eg. time odds
+//! - [`Game::new_with_odds`] starts a handicap game without one of the stronger player's pieces
+//! - [`settings::Settings`] stores the preferences of the command-line program in a small TOML file
+//! - [`Game::make_move_timed`] makes a move and deducts the thinking time from the [`clock::Clock`]
 //!
 //! # Examples
 //! ```
@@ -34,75 +71,237 @@
 //!     println!("{:?}", game.get_possible_moves("e2".to_string()));
 //!     game.make_move("e2".to_string(), "e3".to_string());
 //!     println!("{:?}", game.get_possible_moves("d1".to_string()));
-//!     println!("{:?}", game);
+//!     println!("{}", game);
 //! }
 //! ```
 //!
 //! ### Implementation notes:
 //! - Getting moves during the opposite player's turn ignores if move checks their king.
-//! - En passant is not possible.
-//! - Castling is not possible.
+//! - Sliding moves, attacks and pins are found by casting [`ray`]s that stop at the first piece.
 //!
 // How to publish https://doc.rust-lang.org/book/ch14-02-publishing-to-crates-io.html
 // How to install as binary https://doc.rust-lang.org/book/ch14-04-installing-binaries.html
 
-use std::cmp::{max, min};
-use std::collections::{HashMap, HashSet};
+#![cfg_attr(not(feature = "std"), no_std)]
+// The deprecated fields of Game are only deprecated for users, the crate keeps using them
+#![allow(deprecated)]
+
+extern crate alloc;
+
+use alloc::borrow::Cow;
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::cmp::{max, min};
+use core::convert::TryFrom;
+use core::fmt;
+use core::hash::Hasher;
+use core::ops::Not;
+use core::str::FromStr;
+#[cfg(feature = "std")]
 use std::error::Error;
-use std::fmt;
-use std::ops::Not;
+pub mod atomic;
+#[cfg(feature = "bench")]
+#[doc(hidden)]
+pub mod bench;
+pub mod binary;
+mod board;
+pub mod book;
+pub mod bughouse;
+mod builder;
+pub mod chess960;
+pub mod clock;
+pub mod config;
+pub mod coordinate;
+#[cfg(feature = "serde")]
+pub mod correspondence;
+pub mod crazyhouse;
+#[cfg(feature = "eco")]
+pub mod eco;
+pub mod editor;
+pub mod engine;
+pub mod eval;
+pub mod events;
+pub mod fen;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod heatmap;
+pub mod illegal;
+#[cfg(feature = "render-image")]
+pub mod image;
+pub mod mate;
+#[cfg(feature = "net")]
+pub mod net;
+pub mod notation;
+pub mod odds;
+pub mod orientation;
+pub mod pawns;
+pub mod pgn;
+pub mod prelude;
+pub mod puzzle;
+pub mod quiz;
+pub mod random;
+pub mod ray;
+pub mod reach;
+pub mod render;
+pub mod replay;
+pub mod report;
+pub mod san;
+pub mod scenario;
+pub mod settings;
+pub mod simulate;
+pub mod square;
+pub mod tablebase;
+#[cfg(all(test, feature = "std"))]
 mod tests;
+pub mod tournament;
+#[cfg(feature = "std")]
+pub mod training;
+pub mod transcribe;
+pub mod variation;
+pub mod verification;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
+pub use board::{Board, Pieces};
+pub use builder::{GameBuilder, SetupProblem};
+use config::{BoardSize, GameConfig};
+use crazyhouse::Crazyhouse;
+pub use engine::Engine;
+pub use events::GameEvent;
+use ray::Ray;
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
-use serde_with::serde_as;
-use wasm_bindgen::prelude::*;
+pub use square::Square;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum GameState {
+    InProgress,
+    Check,
+    CheckMate,
+    /// The player who's turn it is ran out of time, see [`clock`]
+    TimeForfeit,
+    /// The game ended in a draw, automatically or claimed with [`Game::claim_draw`]
+    Draw(DrawReason),
+}
 
-#[wasm_bindgen]
-pub fn new_game() -> String {
-    let game = Game::new();
-    serde_json::to_string(&game).unwrap()
+/// Why a game was drawn, see [`GameState::Draw`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DrawReason {
+    /// 75 moves by each player without a pawn move or capture
+    SeventyFiveMoves,
+    /// The same position occurred five times with the same player to move
+    FivefoldRepetition,
+    /// Claimed after 50 moves by each player without a pawn move or capture
+    FiftyMoves,
+    /// Claimed when the same position occurred three times with the same player to move
+    ThreefoldRepetition,
+    /// Neither player can checkmate by any sequence of legal moves, see [`Game::is_dead_position`]
+    DeadPosition,
+    /// The player who's turn it is has no legal moves but is not in check
+    Stalemate,
 }
 
-#[wasm_bindgen]
-pub fn get_possible_moves(json: &str, pos: &str) -> String {
-    let game: Game = serde_json::from_str(json).unwrap();
-    if let Some(moves) = game.get_possible_moves(pos.to_string()) {
-        serde_json::to_string(&moves).unwrap()
-    } else {
-        let empty: Vec<String> = Vec::new();
-        serde_json::to_string(&empty).unwrap()
+/// How a finished game ended, see [`Game::result`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum GameResult {
+    /// Black was checkmated, ran out of time or lost the king in Atomic
+    WhiteWins,
+    /// White was checkmated, ran out of time or lost the king in Atomic
+    BlackWins,
+    /// The game was drawn
+    Draw(DrawReason),
+}
+
+/// Why a move was not made, see [`Game::make_move`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ChessError {
+    /// The game has ended with the result, no more moves can be made
+    GameOver(GameResult),
+    /// The move is illegal or its notation invalid, with a description
+    InvalidMove(&'static str),
+}
+
+impl ChessError {
+    /// Description of the error, eg. "The game is drawn"
+    pub fn message(&self) -> &'static str {
+        match self {
+            ChessError::GameOver(GameResult::Draw(_)) => "The game is drawn",
+            ChessError::GameOver(_) => "The game is over",
+            ChessError::InvalidMove(message) => message,
+        }
     }
 }
 
-#[wasm_bindgen]
-pub fn make_move(json: &str, pos: &str, dest: &str) -> String {
-    let mut game: Game = serde_json::from_str(json).unwrap();
-    if let Ok(_) = game.make_move(pos.to_string(), dest.to_string()) {
-        serde_json::to_string(&game).unwrap()
-    } else {
-        serde_json::to_string(&game).unwrap()
+impl fmt::Display for ChessError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.message())
     }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
-pub enum GameState {
-    InProgress,
-    Check,
-    CheckMate,
+#[cfg(feature = "std")]
+impl Error for ChessError {}
+
+impl From<&'static str> for ChessError {
+    fn from(message: &'static str) -> Self {
+        ChessError::InvalidMove(message)
+    }
+}
+
+impl From<ChessError> for &'static str {
+    fn from(error: ChessError) -> Self {
+        error.message()
+    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// Half moves without a pawn move or capture after which the game is drawn automatically
+pub const SEVENTY_FIVE_MOVES: u32 = 150;
+
+/// Number of times the same position must occur for the game to be drawn automatically
+pub const FIVEFOLD_REPETITION: usize = 5;
+
+/// Half moves without a pawn move or capture after which a player may claim a draw
+pub const FIFTY_MOVES: u32 = 100;
+
+/// Number of times the same position must occur for a player to claim a draw
+pub const THREEFOLD_REPETITION: usize = 3;
+
+/// Rules for captures and winning, see [`Game::variant`]
+///
+/// Crazyhouse and Chess960 are set up with [`Game::crazyhouse`] and [`Game::new_chess960`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Variant {
+    /// Standard chess
+    Standard,
+    /// Captures explode surrounding pieces, see [`atomic`]
+    Atomic,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Color {
     White,
     Black,
 }
 impl Color {
-    pub fn direction(&self) -> i32 {
+    pub(crate) fn direction(&self) -> i32 {
         match self {
             Self::White => 1,
             Self::Black => -1,
         }
     }
+
+    /// Index of color in arrays with one value per color (White first)
+    fn index(&self) -> usize {
+        match self {
+            Self::White => 0,
+            Self::Black => 1,
+        }
+    }
 }
 impl Not for Color {
     type Output = Self;
@@ -114,7 +313,16 @@ impl Not for Color {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// Shade of a square on the board, see [`Position::shade`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SquareShade {
+    Light,
+    Dark,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Piece {
     King(Color),
     Queen(Color),
@@ -123,8 +331,59 @@ pub enum Piece {
     Knight(Color),
     Pawn(Color),
 }
+/// Kind of a piece regardless of its color
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PieceKind {
+    King,
+    Queen,
+    Rook,
+    Bishop,
+    Knight,
+    Pawn,
+}
+
+impl PieceKind {
+    /// Name of the kind in lowercase English, eg. "knight"
+    pub fn name(&self) -> &'static str {
+        match self {
+            PieceKind::King => "king",
+            PieceKind::Queen => "queen",
+            PieceKind::Rook => "rook",
+            PieceKind::Bishop => "bishop",
+            PieceKind::Knight => "knight",
+            PieceKind::Pawn => "pawn",
+        }
+    }
+}
+
 impl Piece {
-    fn color(&self) -> Color {
+    /// Piece of `kind` with `color`
+    pub fn new(kind: PieceKind, color: Color) -> Self {
+        match kind {
+            PieceKind::King => Piece::King(color),
+            PieceKind::Queen => Piece::Queen(color),
+            PieceKind::Rook => Piece::Rook(color),
+            PieceKind::Bishop => Piece::Bishop(color),
+            PieceKind::Knight => Piece::Knight(color),
+            PieceKind::Pawn => Piece::Pawn(color),
+        }
+    }
+
+    /// Kind of the piece, eg. [`PieceKind::Knight`] for `Piece::Knight(Color::Black)`
+    pub fn kind(&self) -> PieceKind {
+        match self {
+            Piece::King(_) => PieceKind::King,
+            Piece::Queen(_) => PieceKind::Queen,
+            Piece::Rook(_) => PieceKind::Rook,
+            Piece::Bishop(_) => PieceKind::Bishop,
+            Piece::Knight(_) => PieceKind::Knight,
+            Piece::Pawn(_) => PieceKind::Pawn,
+        }
+    }
+
+    /// Color of the piece
+    pub fn color(&self) -> Color {
         use Color::*;
         use Piece::*;
         match *self {
@@ -152,97 +411,10 @@ impl Piece {
             Pawn(Black) => '♙',
         }
     }
-
-    /// Get valid destinations for a piece in a certain position.
-    ///
-    /// This function returns all possible destinations on the board, regardless of what is located in that position.
-    pub fn valid_destinations(&self, pos: &Position) -> HashSet<Position> {
-        use Piece::*;
-        let mut valid_positions = HashSet::new();
-
-        match self {
-            King(_) => {
-                for f in -1..=1 {
-                    for r in -1..=1 {
-                        let new_pos = pos.relative_pos(f, r);
-                        if let Some(x) = new_pos {
-                            valid_positions.insert(x);
-                        }
-                    }
-                }
-            }
-            Queen(_) => {
-                // White as arbitrary color (neither are dependent on color)
-                valid_positions.extend(Rook(Color::White).valid_destinations(pos));
-                valid_positions.extend(Bishop(Color::White).valid_destinations(pos));
-            }
-            Rook(_) => {
-                for file_offset in -8..=8 {
-                    let new_pos = pos.relative_pos(file_offset, 0);
-                    if let Some(x) = new_pos {
-                        valid_positions.insert(x);
-                    }
-                }
-                for rank_offset in -8..=8 {
-                    let new_pos = pos.relative_pos(0, rank_offset);
-                    if let Some(x) = new_pos {
-                        valid_positions.insert(x);
-                    }
-                }
-            }
-            Bishop(_) => {
-                let directions = [(1, 1), (1, -1), (-1, 1), (-1, -1)];
-                for (file_dir, rank_dir) in directions {
-                    for diag in 1..=8 {
-                        let new_pos = pos.relative_pos(diag * file_dir, diag * rank_dir);
-                        if let Some(x) = new_pos {
-                            valid_positions.insert(x);
-                        }
-                    }
-                }
-            }
-            Knight(_) => {
-                // (file, rank)
-                let offsets = [
-                    (2, 1),
-                    (2, -1),
-                    (-2, 1),
-                    (-2, -1),
-                    (1, 2),
-                    (1, -2),
-                    (-1, 2),
-                    (-1, -2),
-                ];
-                for (file_offset, rank_offset) in offsets {
-                    let new_pos = pos.relative_pos(file_offset, rank_offset);
-                    if let Some(x) = new_pos {
-                        valid_positions.insert(x);
-                    }
-                }
-            }
-            Pawn(color) => {
-                let direction = color.direction();
-                let steps = if matches!(pos.rank, 2 | 7) { 2 } else { 1 };
-                for i in 1..=steps {
-                    let new_pos = pos.relative_pos(0, i * direction);
-                    if let Some(x) = new_pos {
-                        valid_positions.insert(x);
-                    }
-                }
-                for file_offset in [-1, 1] {
-                    let new_pos = pos.relative_pos(file_offset, direction);
-                    if let Some(x) = new_pos {
-                        valid_positions.insert(x);
-                    }
-                }
-            }
-        };
-        valid_positions.remove(&pos);
-        valid_positions
-    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Position {
     /// The column: 1-8 -> a-h (king on file "e")
     pub file: u8,
@@ -250,34 +422,45 @@ pub struct Position {
     pub rank: u8,
 }
 impl Position {
-    /// Get Position from string with first character as file (a-h) and second char as rank (1-8).
-    pub fn from_string(_from: String) -> Result<Position, Box<dyn Error>> {
-        if _from.chars().count() < 2 {
-            return Err("Position should consist of file and rank (at least 2 characters)".into());
-        }
-        let (_file, _rank) = _from.split_at(1);
-        let file = match _file.to_lowercase().chars().next() {
-            // 97 is char code for 'a', 96 is used because file is one-indexed
-            Some(c @ 'a'..='h') => c as u8 - 96,
-            _ => return Err("Invalid file, should be in range [a, h]".into()),
-        };
-        let rank = match _rank.parse()? {
-            rank @ 1..=8 => rank,
-            _ => return Err("Rank out of range: [1, 8]".into()),
-        };
-        Ok(Position { file, rank })
+    /// Position on `file` (1-8 -> a-h) and `rank` (1-8)
+    pub fn new(file: u8, rank: u8) -> Result<Position, &'static str> {
+        let position = Position { file, rank };
+        if position.is_valid() {
+            Ok(position)
+        } else {
+            Err("File and rank should be in range [1, 8]")
+        }
     }
 
-    /// Get string with first character as file (a-h) and second char as rank (1-8).
-    pub fn to_string(&self) -> String {
-        let mut output = String::with_capacity(2);
-        // 97 is char code for 'a', 96 is used because file is one-indexed
-        output.push((self.file + 96) as char);
-        output.push(char::from_digit(self.rank as u32, 10).unwrap_or(' '));
-        output
+    /// Position from index 0-63 counting a1, b1, ..., h1, a2, ..., h8, None if out of range
+    pub fn from_index(index: usize) -> Option<Position> {
+        if index < 64 {
+            Some(Position {
+                file: (index % 8) as u8 + 1,
+                rank: (index / 8) as u8 + 1,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Index 0-63 of the position, see [`Position::from_index`]
+    pub fn index(&self) -> usize {
+        (self.rank as usize - 1) * 8 + self.file as usize - 1
+    }
+
+    /// Get Position from string with first character as file (a-h) and second char as rank (1-8).
+    ///
+    /// Same as `_from.parse()`, which doesn't need an owned string, see [`coordinate`] for the errors
+    #[cfg(feature = "std")]
+    pub fn from_string(_from: String) -> Result<Position, Box<dyn Error>> {
+        Ok(_from.parse()?)
     }
 
-    /// Get the position on the board offset by given values or None if it is outside the board
+    /// Get the position offset by given values or None if it is outside the 8x8 board
+    ///
+    /// Games on smaller boards use [`BoardSize::relative_pos`] of their
+    /// [board size](GameConfig::board_size) instead.
     pub fn relative_pos(&self, file_offset: i32, rank_offset: i32) -> Option<Position> {
         let file = i32::from(self.file) + file_offset;
         let rank = i32::from(self.rank) + rank_offset;
@@ -291,31 +474,431 @@ impl Position {
         }
     }
 
-    /// If position is located on the chess board
+    /// If position is located on the 8x8 chess board, see [`BoardSize::contains`] for smaller
+    /// boards
     pub fn is_valid(&self) -> bool {
         (1..=8).contains(&self.file) && (1..=8).contains(&self.rank)
     }
+
+    /// The square seen from the other player's side of a board of size, on the same file and the
+    /// opposite rank, eg. e2 becomes e7 on the standard board. None if the square is not on the
+    /// board
+    pub fn flipped(&self, size: BoardSize) -> Option<Position> {
+        size.contains(self).then(|| Position {
+            file: self.file,
+            rank: size.ranks() + 1 - self.rank,
+        })
+    }
+
+    /// The square on the same rank and the opposite file of a board of size, eg. b1 becomes g1 on
+    /// the standard board. None if the square is not on the board
+    pub fn mirrored(&self, size: BoardSize) -> Option<Position> {
+        size.contains(self).then(|| Position {
+            file: size.files() + 1 - self.file,
+            rank: self.rank,
+        })
+    }
+
+    /// Shade of the square, eg. a1 is dark and h1 is light
+    pub fn shade(&self) -> SquareShade {
+        if (self.file + self.rank) % 2 == 0 {
+            SquareShade::Dark
+        } else {
+            SquareShade::Light
+        }
+    }
+
+    /// Letter of the file, 'a'-'h'
+    pub fn file_char(&self) -> char {
+        // 97 is char code for 'a', 96 is used because file is one-indexed
+        (self.file + 96) as char
+    }
+
+    /// Digit of the rank, '1'-'8'
+    pub fn rank_char(&self) -> char {
+        (self.rank + b'0') as char
+    }
+
+    /// Number of king moves to other, the larger of the file and rank differences
+    pub fn distance(&self, other: &Position) -> u8 {
+        self.file
+            .abs_diff(other.file)
+            .max(self.rank.abs_diff(other.rank))
+    }
+
+    /// Number of rook moves of one square to other, the sum of the file and rank differences
+    pub fn manhattan_distance(&self, other: &Position) -> u8 {
+        self.file.abs_diff(other.file) + self.rank.abs_diff(other.rank)
+    }
+
+    /// If other is on the same file
+    pub fn is_same_file(&self, other: &Position) -> bool {
+        self.file == other.file
+    }
+
+    /// If other is on the same rank
+    pub fn is_same_rank(&self, other: &Position) -> bool {
+        self.rank == other.rank
+    }
+
+    /// If other is on one of the two diagonals through position
+    pub fn is_same_diagonal(&self, other: &Position) -> bool {
+        self.file.abs_diff(other.file) == self.rank.abs_diff(other.rank)
+    }
+
+    /// Squares strictly between position and other, starting next to position
+    ///
+    /// Empty unless they are on the same file, rank or diagonal, see [`Position::is_same_diagonal`]
+    pub fn between(&self, other: &Position) -> impl Iterator<Item = Position> {
+        let file_step = other.file as i32 - self.file as i32;
+        let rank_step = other.rank as i32 - self.rank as i32;
+        let on_line =
+            self.is_same_file(other) || self.is_same_rank(other) || self.is_same_diagonal(other);
+        let squares = if on_line {
+            self.distance(other).saturating_sub(1)
+        } else {
+            0
+        };
+        let (file, rank) = (self.file as i32, self.rank as i32);
+        (1..=squares as i32).map(move |offset| Position {
+            file: (file + offset * file_step.signum()) as u8,
+            rank: (rank + offset * rank_step.signum()) as u8,
+        })
+    }
+}
+
+impl fmt::Display for Position {
+    /// File (a-h) followed by rank (1-8), eg. "e4"
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.file_char(), self.rank)
+    }
+}
+
+/// Legal moves of the player who's turn it is, computed on first use and cleared when the position changes
+///
+/// The moves are stored with the position they belong to, so writing the public fields directly
+/// computes them again instead of returning stale moves. Clones start empty so that scratch copies
+/// of a game stay cheap.
+#[derive(Debug, Default)]
+struct MoveCache(OnceLock<(MoveCacheKey, Vec<Move>)>);
+
+/// Everything the legal moves depend on: pieces, player to move, castling, en passant square,
+/// rules and variant
+type MoveCacheKey = (
+    Board,
+    Color,
+    [CastlingRights; 2],
+    CastlingFiles,
+    bool,
+    Option<Position>,
+    GameConfig,
+    Option<Crazyhouse>,
+    Variant,
+);
+
+impl MoveCache {
+    /// Cached moves of the position with key, computing them if the cache is empty
+    ///
+    /// The moves of another position may still be borrowed, so when the cache holds another
+    /// position the moves are computed without being stored. The methods changing the position
+    /// empty the cache.
+    fn get(&self, key: MoveCacheKey, compute: impl Fn() -> Vec<Move>) -> Cow<'_, [Move]> {
+        let (cached_key, moves) = self.0.get_or_init(|| (key, compute()));
+        if *cached_key == key {
+            Cow::Borrowed(moves)
+        } else {
+            Cow::Owned(compute())
+        }
+    }
+}
+
+/// Shared between threads with the standard library
+#[cfg(feature = "std")]
+type OnceLock<T> = std::sync::OnceLock<T>;
+/// Without the standard library a game can't be shared between threads
+#[cfg(not(feature = "std"))]
+type OnceLock<T> = core::cell::OnceCell<T>;
+
+impl Clone for MoveCache {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+/// Pieces, player to move, castling rights, en passant square and Crazyhouse reserves, see
+/// [`Game::is_same_position`]
+type PositionIdentity = (
+    Board,
+    Color,
+    [CastlingRights; 2],
+    Option<Position>,
+    Option<[crazyhouse::Reserve; 2]>,
+);
+
+/// FNV-1a hash for position keys, fed with a fixed encoding of the position so that keys stored
+/// with a game stay valid in later program runs and on other platforms
+struct KeyHasher(u64);
+
+impl Default for KeyHasher {
+    fn default() -> Self {
+        Self(0xCBF2_9CE4_8422_2325)
+    }
+}
+
+impl Hasher for KeyHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01B3);
+        }
+    }
+}
+
+/// Information needed to take back a move
+#[derive(Clone, Copy, Debug)]
+struct Undo {
+    mov: Move,
+    /// The piece that moved (before promotion)
+    piece: Piece,
+    captured: Option<Piece>,
+    state: GameState,
+    castling: [CastlingRights; 2],
+    en_passant: Option<Position>,
+    halfmove_clock: u32,
+    /// King destination, rook start and rook destination if the move is castling
+    castle: Option<(Position, Position, Position)>,
+    /// Pieces removed around the destination by an Atomic capture
+    exploded: atomic::Explosion,
+}
+
+/// What happened when a move was made, see [`Game::make_move`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MoveOutcome {
+    /// The move that was made, with the piece the pawn became if it was a promotion
+    pub mov: Move,
+    /// Piece removed from the board by the move (or None)
+    pub captured: Option<Piece>,
+    /// Piece the pawn became, None if the move was not a promotion
+    pub promotion: Option<Piece>,
+    /// Game state after the move
+    pub state: GameState,
+    /// If the opponent's king is attacked after the move, also when it is checkmate
+    pub check: bool,
+    /// The move in standard algebraic notation, eg. "exd8=Q+"
+    pub san: String,
+}
+
+/// A move of the piece on `from` to `to`
+///
+/// Castling is a move of the king two squares towards the rook, or onto the rook in Chess960
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Move {
+    /// Position of the piece to move
+    pub from: Position,
+    /// Destination of the piece
+    pub to: Position,
+    /// Piece a pawn becomes when reaching the last rank, None to use the promotion piece set for the player
+    pub promotion: Option<Piece>,
+}
+
+impl Move {
+    /// If the move has a recorded promotion piece, see [`Move::promoted_to`]
+    ///
+    /// Moves of [`Game::history`] record every promotion. Moves that are not made yet, eg. "a7a8"
+    /// from [`Move::from_uci`], are false as the board is not known.
+    pub fn is_promotion(&self) -> bool {
+        self.promotion.is_some()
+    }
+
+    /// Piece the pawn became, for moves of [`Game::history`] and [`MoveOutcome::mov`]
+    ///
+    /// Moves that are not made yet may leave the piece out to use the player's promotion piece,
+    /// see [`Game::promotion_piece`]
+    pub fn promoted_to(&self) -> Option<Piece> {
+        self.promotion
+    }
+
+    /// Move in UCI notation: start and destination squares followed by the promotion piece
+    /// in lowercase if any, eg. "e2e4" or "e7e8q"
+    pub fn to_uci(&self) -> String {
+        let mut uci = self.from.to_string() + &self.to.to_string();
+        if let Some(promotion) = self.promotion {
+            match promotion {
+                Piece::Queen(_) => uci.push('q'),
+                Piece::Rook(_) => uci.push('r'),
+                Piece::Bishop(_) => uci.push('b'),
+                Piece::Knight(_) => uci.push('n'),
+                _ => {}
+            }
+        }
+        uci
+    }
+
+    /// Parse move in UCI notation, eg. "e2e4" or "e7e8q"
+    ///
+    /// The color of the promotion piece is Black if the destination is on rank 1, otherwise White
+    pub fn from_uci(uci: &str) -> Result<Move, &'static str> {
+        let uci = uci.trim();
+        if !uci.is_ascii() || !(4..=5).contains(&uci.len()) {
+            return Err("UCI move should be 4 or 5 characters, eg. \"e2e4\" or \"e7e8q\"");
+        }
+        let from: Position = uci[0..2].parse().map_err(|_| "Invalid start square")?;
+        let to: Position = uci[2..4]
+            .parse()
+            .map_err(|_| "Invalid destination square")?;
+        let color = if to.rank == 1 {
+            Color::Black
+        } else {
+            Color::White
+        };
+        let promotion = match &uci[4..] {
+            "" => None,
+            "q" => Some(Piece::Queen(color)),
+            "r" => Some(Piece::Rook(color)),
+            "b" => Some(Piece::Bishop(color)),
+            "n" => Some(Piece::Knight(color)),
+            _ => return Err("Invalid promotion piece, should be q, r, b or n"),
+        };
+        Ok(Move {
+            from,
+            to,
+            promotion,
+        })
+    }
+}
+
+impl FromStr for Move {
+    type Err = &'static str;
+
+    /// Parse move in UCI notation, see [`Move::from_uci`]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Move::from_uci(s)
+    }
+}
+
+impl TryFrom<&str> for Move {
+    type Error = &'static str;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Move::from_uci(value)
+    }
 }
 
-#[serde_as]
-#[derive(Clone, PartialEq, Serialize, Deserialize)]
+impl fmt::Display for Move {
+    /// Move in UCI notation, see [`Move::to_uci`]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.to_uci())
+    }
+}
+
+/// If a player may still castle on either side
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CastlingRights {
+    /// Castling with the rook on the h file (or the kingside rook file in Chess960)
+    pub kingside: bool,
+    /// Castling with the rook on the a file (or the queenside rook file in Chess960)
+    pub queenside: bool,
+}
+
+/// Starting files of the king and the rooks it may castle with, the same for both colors
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CastlingFiles {
+    /// File of the king
+    pub king: u8,
+    /// File of the rook on the a-side of the king
+    pub queenside: u8,
+    /// File of the rook on the h-side of the king
+    pub kingside: u8,
+}
+
+impl Default for CastlingFiles {
+    fn default() -> Self {
+        Self {
+            king: 5,
+            queenside: 1,
+            kingside: 8,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Game {
-    /// Board HashMap with Position keys and Piece values
-    #[serde_as(as = "Vec<(_, _)>")]
-    pub board: HashMap<Position, Piece>,
+    /// Board with the piece on every occupied Position
+    #[deprecated(
+        note = "use Game::board, Game::put_piece, Game::remove_piece and Game::set_board"
+    )]
+    pub board: Board,
     /// The color who's turn it is
+    #[deprecated(note = "use Game::side_to_move and Game::set_active_color")]
     pub active_color: Color,
     /// Promotion piece per color
+    #[deprecated(note = "use Game::promotion_piece and Game::set_promotion_piece")]
     pub promotion: [Piece; 2],
-    /// Current game state. Call `get_game_state` to check for checkmate
+    /// Game state after the last move, never [`GameState::CheckMate`]
+    #[deprecated(note = "use Game::get_game_state, which also detects checkmate")]
     pub state: GameState,
+    /// Castling rights per color, lost when the king or rook moves
+    pub castling: [CastlingRights; 2],
+    /// Starting files of the king and rooks used for castling
+    pub castling_files: CastlingFiles,
+    /// If castling moves are written as the king moving onto its rook, see [`Game::new_chess960`]
+    pub chess960: bool,
+    /// Square passed by a pawn moving two squares in the previous move, where it can be captured en passant
+    pub en_passant: Option<Position>,
+    /// Number of half moves since the last capture or pawn move
+    pub halfmove_clock: u32,
+    /// Number of the full move, starts at 1 and increases after Black's move
+    pub fullmove_number: u32,
+    /// Rules per player, see [`Game::with_config`]
+    pub config: GameConfig,
+    /// Reserves of captured pieces in Crazyhouse, None in standard chess
+    pub crazyhouse: Option<Crazyhouse>,
+    /// Rules for captures and winning
+    pub variant: Variant,
+    /// Thinking time of the players, see [`Game::make_move_timed`]
+    pub clock: Option<clock::Clock>,
+    /// Handicap the game started with, see [`Game::new_with_odds`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub odds: Option<odds::Odds>,
+    /// Moves made since the game was created, see [`Game::history`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    history: Vec<Move>,
+    /// Moves made with side lines and annotations, the history leads to [`Game::current_node`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    tree: variation::MoveTree,
+    /// Node of the last move made, see [`Game::go_to`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    node: variation::NodeId,
+    /// Position before the first move of the history in FEN, see [`Game::to_pgn`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    start_fen: Option<String>,
+    /// Keys of the positions since the last pawn move or capture, see [`Game::repetition_count`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    repetitions: Vec<u64>,
+    /// Cached result of [`Game::legal_moves`]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    legal_moves: MoveCache,
+    /// Cached result of [`Game::is_dead_position`]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    dead_position: reach::DeadPositionCache,
+    /// Events of the moves made since they were last taken, see [`Game::events`]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    events: Vec<GameEvent>,
 }
 impl Game {
     /// Initializes a new board with standard piece positions.
+    #[allow(deprecated)]
     pub fn new() -> Self {
         use Color::*;
         use Piece::*;
-        let mut starting_board: HashMap<Position, Piece> = HashMap::new();
+        let mut starting_board = Board::new();
         // Generate starting board
         // Place respective pieces on ranks 1 and 8 for White and Black
         for (r, color) in [(1, White), (8, Black)] {
@@ -359,99 +942,102 @@ impl Game {
             state: GameState::InProgress,
             active_color: Color::White,
             promotion: [Piece::Queen(Color::White), Piece::Queen(Color::Black)],
+            castling: [CastlingRights {
+                kingside: true,
+                queenside: true,
+            }; 2],
+            castling_files: CastlingFiles::default(),
+            chess960: false,
+            en_passant: None,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            config: GameConfig::new(),
+            crazyhouse: None,
+            variant: Variant::Standard,
+            clock: None,
+            odds: None,
+            history: Vec::new(),
+            tree: variation::MoveTree::new(),
+            node: variation::MoveTree::ROOT,
+            start_fen: None,
+            repetitions: Vec::new(),
+            legal_moves: MoveCache::default(),
+            dead_position: reach::DeadPositionCache::default(),
+            events: Vec::new(),
         }
     }
 
     /// Detects if there is a piece in the way for a move
     fn _is_piece_in_way(&self, piece: &Piece, position: &Position, destination: &Position) -> bool {
-        match piece {
+        match (piece, ray::direction(position, destination)) {
             // Knight and king can move over pieces (king can't move over because he can only move 1)
-            Piece::Knight(_) | Piece::King(_) => {}
-            Piece::Queen(c) => {
-                return self._is_piece_in_way(&Piece::Bishop(*c), position, destination)
-                    || self._is_piece_in_way(&Piece::Rook(*c), position, destination);
-            }
-            Piece::Bishop(_) => {
-                let file = destination.file as i32 - position.file as i32;
-                let rank = destination.rank as i32 - position.rank as i32;
-                for offset in 1..max(file.abs(), rank.abs()) {
-                    // Iterate over positions between position and destination (offset is equal with different sign because bishop)
-                    if let Some(between_pos) =
-                        position.relative_pos(offset * file.signum(), offset * rank.signum())
-                    {
-                        if let Some(_) = self.board.get(&between_pos) {
-                            // If any of the pieces between are occupied
-                            return true;
-                        }
-                    }
-                }
-            }
-            Piece::Rook(_) | Piece::Pawn(_) => {
-                if position.file == destination.file {
-                    // If in same file: iterate over locations in between
-                    // + 1 to ignore current piece and noninclusive range
-                    for between in min(position.rank, destination.rank) + 1
-                        ..max(position.rank, destination.rank)
-                    {
-                        let between_pos = Position {
-                            rank: between,
-                            file: position.file,
-                        };
-                        if let Some(_) = self.board.get(&between_pos) {
-                            // If any of the pieces between are occupied
-                            return true;
-                        }
-                    }
-                }
-                if position.rank == destination.rank {
-                    // If in same rank: iterate over locations in between
-                    // + 1 to ignore current piece and noninclusive range
-                    for between in min(position.file, destination.file) + 1
-                        ..max(position.file, destination.file)
-                    {
-                        let between_pos = Position {
-                            rank: position.rank,
-                            file: between,
-                        };
-                        if let Some(_) = self.board.get(&between_pos) {
-                            // If any of the pieces between are occupied
-                            return true;
-                        }
-                    }
-                }
+            (Piece::Knight(_) | Piece::King(_), _) | (_, None) => false,
+            // The ray stops at the first piece in the way
+            (_, Some(direction)) => {
+                !Ray::new(&self.board, self.config.board_size, *position, direction)
+                    .any(|square| square == *destination)
             }
         }
-        false
     }
 
     /// Get possible moves for provided Position
     ///
     /// Includes destinations that expose king
     ///
-    /// None if invalid position or no piece, empty set if no possible moves
-    fn _get_possible_moves(&self, position: &Position) -> Option<HashSet<Position>> {
-        if let Some(piece) = self.board.get(&position) {
-            let mut destinations = piece.valid_destinations(position);
-            // Filter out moves that land on own piece or has piece in way
-            destinations.retain(|destination| {
-                // Keep if destination is opposite color and no pieces are in the way of move
-                if let Some(p) = self.board.get(&destination) {
-                    // If pawn and dest is occupied -> deny straight move/capture
-                    if matches!(piece, Piece::Pawn(_)) && position.file == destination.file {
-                        return false;
-                    }
-                    p.color() != piece.color()
-                        && !self._is_piece_in_way(piece, position, destination)
-                } else {
-                    // If pawn and dest is empty -> deny diagonal capture
-                    if matches!(piece, Piece::Pawn(_)) {
-                        position.file == destination.file
-                            && !self._is_piece_in_way(piece, position, destination)
+    /// None if invalid position or no piece, empty if no possible moves
+    fn _get_possible_moves(&self, position: &Position) -> Option<Vec<Position>> {
+        if let Some(piece) = self.board.get(position) {
+            let board_size = self.config.board_size;
+            let ray = |direction| Ray::new(&self.board, board_size, *position, direction);
+            let steps = |offsets: &[ray::Direction]| -> Vec<Position> {
+                offsets
+                    .iter()
+                    .filter_map(|&(file, rank)| board_size.relative_pos(position, file, rank))
+                    .collect()
+            };
+            let mut destinations: Vec<Position> = match piece {
+                Piece::Pawn(color) => {
+                    // Pawns only move two squares from their starting rank on a standard board
+                    let start_rank =
+                        self.config.board_size.home_rank(*color) as i32 + color.direction();
+                    let pushes = if board_size == BoardSize::STANDARD
+                        && position.rank as i32 == start_rank
+                    {
+                        2
                     } else {
-                        !self._is_piece_in_way(piece, position, destination) // Destination has no piece
-                    }
+                        1
+                    };
+                    // Pawns capture diagonally, also en passant, and cannot capture forward
+                    let captures = steps(&[(-1, color.direction()), (1, color.direction())]);
+                    ray((0, color.direction()))
+                        .take(pushes)
+                        .filter(|destination| !self.board.contains_key(destination))
+                        .chain(captures.into_iter().filter(|destination| {
+                            self.board.contains_key(destination)
+                                || self._is_en_passant(piece, position, destination)
+                        }))
+                        .collect()
                 }
+                Piece::Knight(_) => steps(&ray::KNIGHT_OFFSETS),
+                Piece::King(_) => steps(&ray::QUEEN_DIRECTIONS),
+                _ => ray::sliding_directions(piece)
+                    .iter()
+                    .flat_map(|&direction| ray(direction))
+                    .collect(),
+            };
+            // Filter out moves that land on own piece
+            destinations.retain(|destination| {
+                self.board
+                    .get(destination)
+                    .map_or(true, |p| p.color() != piece.color())
             });
+            if let Piece::King(color) = piece {
+                if self.variant == Variant::Atomic {
+                    // Kings cannot capture since they would explode
+                    destinations.retain(|destination| !self.board.contains_key(destination));
+                }
+                destinations.extend(self._castling_destinations(*color, position));
+            }
 
             Some(destinations)
         } else {
@@ -459,21 +1045,176 @@ impl Game {
         }
     }
 
+    /// If moving `piece` from `from` to `to` is an en passant capture
+    ///
+    /// Only pawns of the player who did not make the two square move can capture en passant
+    fn _is_en_passant(&self, piece: &Piece, from: &Position, to: &Position) -> bool {
+        match piece {
+            Piece::Pawn(color) => {
+                Some(*to) == self.en_passant
+                    && from.file != to.file
+                    // The square passed by a two square move, the third rank from the opponent
+                    && to.rank as i32
+                        == (self.config.board_size.home_rank(!*color) as i32
+                            - 2 * color.direction())
+            }
+            _ => false,
+        }
+    }
+
+    /// Destinations of the king on `king` when castling, written as the king moving onto the rook in Chess960
+    ///
+    /// Squares between the king, the rook and their destinations must be empty and
+    /// the king may not be in check or pass an attacked square
+    fn _castling_destinations(&self, color: Color, king: &Position) -> Vec<Position> {
+        let rank = self.config.board_size.home_rank(color);
+        let rights = self.castling[color.index()];
+        let files = self.castling_files;
+        if *king
+            != (Position {
+                file: files.king,
+                rank,
+            })
+            || !self.config.side(color).may_castle
+        {
+            return Vec::new();
+        }
+        let mut destinations = Vec::new();
+        // (castling allowed, rook file, king destination file, rook destination file)
+        for (allowed, rook_file, king_file, rook_to) in [
+            (rights.kingside, files.kingside, 7, 6),
+            (rights.queenside, files.queenside, 3, 4),
+        ] {
+            if !allowed
+                || self.board.get(&Position {
+                    file: rook_file,
+                    rank,
+                }) != Some(&Piece::Rook(color))
+            {
+                continue;
+            }
+            let low = min(min(files.king, rook_file), min(king_file, rook_to));
+            let high = max(max(files.king, rook_file), max(king_file, rook_to));
+            if (low..=high).any(|file| {
+                file != files.king
+                    && file != rook_file
+                    && self.board.contains_key(&Position { file, rank })
+            }) {
+                continue;
+            }
+            // Squares the king stands on, passes and lands on
+            let (low, high) = (min(files.king, king_file), max(files.king, king_file));
+            if (low..=high).any(|file| self._is_attacked(&Position { file, rank }, !color)) {
+                continue;
+            }
+            destinations.push(Position {
+                file: if self.chess960 { rook_file } else { king_file },
+                rank,
+            });
+        }
+        destinations
+    }
+
+    /// If the piece on `from` attacks `target`, ie. could capture a piece standing there
+    fn _attacks(&self, from: &Position, target: &Position) -> bool {
+        match self.board.get(from) {
+            Some(Piece::Pawn(color)) => [-1, 1].iter().any(|&file| {
+                self.config
+                    .board_size
+                    .relative_pos(from, file, color.direction())
+                    == Some(*target)
+            }),
+            Some(Piece::Knight(_)) => ray::KNIGHT_OFFSETS.contains(&(
+                target.file as i32 - from.file as i32,
+                target.rank as i32 - from.rank as i32,
+            )),
+            Some(Piece::King(_)) => from.distance(target) == 1,
+            Some(piece) => {
+                ray::direction(from, target)
+                    .is_some_and(|direction| ray::sliding_directions(piece).contains(&direction))
+                    && !self._is_piece_in_way(piece, from, target)
+            }
+            None => false,
+        }
+    }
+
+    /// Positions of the pieces of color `by` attacking `target`, from a1 to h8
+    ///
+    /// A piece attacks a square if it could capture a piece standing there, so pieces of the same
+    /// color as the piece on `target` defend it. Pins are ignored.
+    pub fn attackers_of(&self, target: Position, by: Color) -> Vec<Position> {
+        self.pieces_of(by)
+            .filter(|(from, _)| self._attacks(from, &target))
+            .map(|(from, _)| from)
+            .collect()
+    }
+
+    /// If any piece of color `by` attacks `target`
+    ///
+    /// Looks outward from `target` instead of at every piece: rays for rooks, bishops and queens
+    /// and single steps for knights, kings and pawns. Gives the same result as
+    /// [`Game::attackers_of`] being non-empty.
+    fn _is_attacked(&self, target: &Position, by: Color) -> bool {
+        let board_size = self.config.board_size;
+        let piece_on = |file, rank| {
+            board_size
+                .relative_pos(target, file, rank)
+                .and_then(|square| self.board.get(&square).copied())
+        };
+        let steps = |offsets: &[ray::Direction], piece: Piece| {
+            offsets
+                .iter()
+                .any(|&(file, rank)| piece_on(file, rank) == Some(piece))
+        };
+        // Pawns attack diagonally forward, so they stand diagonally behind the target
+        let pawn_rank = -by.direction();
+        if steps(&[(-1, pawn_rank), (1, pawn_rank)], Piece::Pawn(by))
+            || steps(&ray::KNIGHT_OFFSETS, Piece::Knight(by))
+            || steps(&ray::QUEEN_DIRECTIONS, Piece::King(by))
+        {
+            return true;
+        }
+        // The first piece along a ray attacks the target if it slides in that direction, the
+        // directions of sliding pieces go both ways
+        ray::QUEEN_DIRECTIONS.iter().any(|&direction| {
+            Ray::new(&self.board, board_size, *target, direction)
+                .last()
+                .and_then(|square| self.board.get(&square))
+                .is_some_and(|piece| {
+                    piece.color() == by && ray::sliding_directions(piece).contains(&direction)
+                })
+        })
+    }
+
     /// If a piece is standing on the given tile, return all possible
     /// new positions of that piece.
     ///
-    /// Does not include destinations that expose king
+    /// Does not include destinations that expose king. For the player who is not to move the king
+    /// is assumed to be safe, see [`Game::legal_moves_for`] for fully legal moves of either player
     ///
     /// Returns None if invalid position or no piece there
     ///
     /// Returns empty Vec if no moves are available for piece
     pub fn get_possible_moves(&self, _position: String) -> Option<Vec<String>> {
-        if let Ok(position) = Position::from_string(_position) {
+        if let Ok(position) = _position.parse::<Position>() {
+            if self.board.get(&position).map(|piece| piece.color()) == Some(self.active_color) {
+                let mut move_vec: Vec<String> = self
+                    .legal_moves()
+                    .iter()
+                    .filter(|mov| mov.from == position)
+                    .map(|mov| mov.to.to_string())
+                    .collect();
+                move_vec.sort_unstable();
+                // Promoting pawns have one move per promotion piece
+                move_vec.dedup();
+                return Some(move_vec);
+            }
             if let Some(mut moves) = self._get_possible_moves(&position) {
                 // Cannot move to/capture king -> filter king destinations
                 moves.retain(|_p| !matches!(self.board.get(_p), Some(Piece::King(_))));
-                // Filter out moves that threaten own king
-                moves.retain(|_p| self._ok_to_make_move(&position, _p));
+                // Filter out moves that threaten own king (moves are tested on a copy of the game)
+                let mut scratch = self.clone();
+                moves.retain(|_p| scratch._ok_to_make_move(&position, _p));
                 let mut move_vec: Vec<String> = moves.iter().map(|_p| _p.to_string()).collect();
                 move_vec.sort_unstable();
                 Some(move_vec)
@@ -487,134 +1228,698 @@ impl Game {
 
     /// If ok to make move
     ///
-    /// Returns false if own king is threatened by move or if move cannot be made
-    fn _ok_to_make_move(&self, from: &Position, to: &Position) -> bool {
+    /// Returns false if own king is threatened by move. The move is made and unmade on self to test it
+    fn _ok_to_make_move(&mut self, from: &Position, to: &Position) -> bool {
         // If getting moves for opposite player -> assume king cannot be threatened
         // Unwrap _should_ never panic
         if self.active_color != self.board.get(from).unwrap().color() {
             return true;
         }
-        let mut new_game = self.clone();
-        new_game.make_move(from.to_string(), to.to_string()).is_ok()
+        !self._exposes_king(from, to)
     }
 
-    /// If the current game state is not CheckMate and the move is legal,
-    /// move a piece.
+    /// If moving the piece on `from` to `to` leaves the king of its color threatened, also for the
+    /// player who is not to move
     ///
-    /// Return Err if move is illegal or if piece has no possible moves, otherwise Ok with removed piece or None if no piece is removed
-    pub fn make_move(&mut self, _from: String, _to: String) -> Result<Option<Piece>, &str> {
-        if let (Ok(from), Ok(to)) = (
-            Position::from_string(_from.clone()),
-            Position::from_string(_to.clone()),
-        ) {
-            if let Some(piece) = self.board.get(&from) {
-                if piece.color() != self.active_color {
-                    return Err("Trying to move opponents piece");
-                }
-
-                if let Some(possible_moves) =
-                    self._get_possible_moves(&Position::from_string(_from).unwrap())
-                {
-                    if let Some(_) = possible_moves.get(&to) {
-                        // Cannot move to/capture king
-                        if matches!(self.board.get(&to), Some(Piece::King(_))) {
-                            return Err("Cannot capture king");
-                        }
-                        // Capture piece (or move to square if empty)
-                        let new_piece =
-                            if matches!(to.rank, 1 | 8) && matches!(piece, Piece::Pawn(_)) {
-                                // Set new piece to promotion piece if pawn and dest rank is 1 or 8
-                                if let Some(prom_piece) = self
-                                    .promotion
-                                    .iter()
-                                    .find(|p| p.color() == self.active_color)
-                                {
-                                    *prom_piece
-                                } else {
-                                    // Promotion piece not found for current player -> use queen
-                                    Piece::Queen(self.active_color)
-                                }
-                            } else {
-                                piece.clone() // Not pawn -> clone old piece to new location
-                            };
-                        // Actual piece move
-                        let before_move = self.board.clone();
-                        let removed = self.board.insert(to, new_piece); // returns removed piece (or None)
-                        self.board.remove(&from);
-                        if self._king_is_threatened(self.active_color) {
-                            // Own king is threatened -> invalid move
-                            self.board = before_move;
-                            return Err("Move threatens own king");
-                        }
-                        // If piece is able to move and doesn't threaten own king -> remove check state
-                        self.state = GameState::InProgress;
+    /// The move is made and unmade on self to test it
+    fn _exposes_king(&mut self, from: &Position, to: &Position) -> bool {
+        let color = self.board.get(from).expect("No piece to move").color();
+        let undo = self._make(Move {
+            from: *from,
+            to: *to,
+            promotion: None,
+        });
+        let threatened = self._king_is_threatened(color);
+        self._unmake(undo);
+        threatened
+    }
 
-                        // If oppoiste king is threatened after move -> check other player
-                        if self._king_is_threatened(!self.active_color) {
-                            self.state = GameState::Check;
-                        }
+    /// If position is on the first or last rank of the board, where pawns promote
+    pub(crate) fn _is_last_rank(&self, position: &Position) -> bool {
+        position.rank == 1 || position.rank == self.config.board_size.ranks()
+    }
 
-                        // Change to opposite players turn
-                        self.active_color = !self.active_color;
+    /// Piece that ends up on the destination when `piece` moves to `to`
+    ///
+    /// Pawns reaching the first or last rank become the promotion piece of their color
+    fn _piece_after_move(&self, piece: Piece, to: &Position) -> Piece {
+        if matches!(piece, Piece::Pawn(_)) && self._is_last_rank(to) {
+            self.promotion_piece(piece.color())
+        } else {
+            piece // Not pawn -> same piece on new location
+        }
+    }
 
-                        Ok(removed)
-                    } else {
-                        Err("Destination move is invalid")
+    /// Move piece without checking if the move is legal and change to opposite players turn
+    ///
+    /// Moves the rook as well when castling and updates castling rights.
+    /// Returns what is needed to take back the move with [`Game::_unmake`].
+    /// Panics if there is no piece on `mov.from`
+    fn _make(&mut self, mov: Move) -> Undo {
+        self.legal_moves = MoveCache::default();
+        let en_passant = self._is_en_passant(
+            self.board.get(&mov.from).expect("No piece to move"),
+            &mov.from,
+            &mov.to,
+        );
+        let castle = self._castling(&mov);
+        let piece = self.board.remove(&mov.from).expect("No piece to move");
+        let mut captured = None;
+        if let Some((king_to, rook_from, rook_to)) = castle {
+            // The rook is removed first since the king may move onto it in Chess960
+            let rook = self
+                .board
+                .remove(&rook_from)
+                .expect("No rook to castle with");
+            self.board.insert(king_to, piece);
+            self.board.insert(rook_to, rook);
+        } else {
+            let new_piece = match mov.promotion {
+                Some(promotion)
+                    if matches!(piece, Piece::Pawn(_)) && self._is_last_rank(&mov.to) =>
+                {
+                    promotion
+                }
+                _ => self._piece_after_move(piece, &mov.to),
+            };
+            captured = self.board.insert(mov.to, new_piece);
+            if en_passant {
+                // Captured pawn is beside the moving pawn
+                captured = self.board.remove(&Position {
+                    file: mov.to.file,
+                    rank: mov.from.rank,
+                });
+            }
+        }
+        let mut exploded = [None; 8];
+        if self.variant == Variant::Atomic && captured.is_some() {
+            exploded = self._explode(&mov.to);
+        }
+        let undo = Undo {
+            mov,
+            piece,
+            captured,
+            state: self._state(),
+            castling: self.castling,
+            en_passant: self.en_passant,
+            halfmove_clock: self.halfmove_clock,
+            castle,
+            exploded,
+        };
+        // Moving the king loses both castling rights, moving or capturing a rook loses that side
+        if let Piece::King(color) = piece {
+            self.castling[color.index()] = CastlingRights {
+                kingside: false,
+                queenside: false,
+            };
+        }
+        let exploded_positions = exploded.iter().flatten().map(|(position, _)| *position);
+        for position in [mov.from, mov.to].iter().copied().chain(exploded_positions) {
+            for color in [Color::White, Color::Black] {
+                if position.rank == self.config.board_size.home_rank(color) {
+                    if position.file == self.castling_files.king && position != mov.from {
+                        // The king was exploded
+                        self.castling[color.index()] = CastlingRights {
+                            kingside: false,
+                            queenside: false,
+                        };
+                    }
+                    if position.file == self.castling_files.queenside {
+                        self.castling[color.index()].queenside = false;
+                    }
+                    if position.file == self.castling_files.kingside {
+                        self.castling[color.index()].kingside = false;
                     }
-                } else {
-                    Err("No possible moves")
                 }
+            }
+        }
+        // Pawn moving two squares can be captured en passant on the square it passed
+        self.en_passant = match piece {
+            Piece::Pawn(color) if (mov.to.rank as i32 - mov.from.rank as i32).abs() == 2 => self
+                .config
+                .board_size
+                .relative_pos(&mov.from, 0, color.direction()),
+            _ => None,
+        };
+        if matches!(piece, Piece::Pawn(_)) || captured.is_some() {
+            self.halfmove_clock = 0;
+            self.dead_position = reach::DeadPositionCache::default();
+        } else {
+            self.halfmove_clock += 1;
+        }
+        if self.active_color == Color::Black {
+            self.fullmove_number += 1;
+        }
+        self.active_color = !self.active_color;
+        undo
+    }
+
+    /// Take back a move made with [`Game::_make`]
+    fn _unmake(&mut self, undo: Undo) {
+        self.legal_moves = MoveCache::default();
+        if matches!(undo.piece, Piece::Pawn(_)) || undo.captured.is_some() {
+            self.dead_position = reach::DeadPositionCache::default();
+        }
+        self.active_color = !self.active_color;
+        if self.active_color == Color::Black {
+            self.fullmove_number -= 1;
+        }
+        self._set_state(undo.state);
+        self.castling = undo.castling;
+        self.en_passant = undo.en_passant;
+        self.halfmove_clock = undo.halfmove_clock;
+        if let Some((king_to, rook_from, rook_to)) = undo.castle {
+            let rook = self.board.remove(&rook_to).expect("No rook to uncastle");
+            self.board.remove(&king_to);
+            self.board.insert(rook_from, rook);
+            self.board.insert(undo.mov.from, undo.piece);
+            return;
+        }
+        self.board.remove(&undo.mov.to);
+        if let Some(captured) = undo.captured {
+            if self._is_en_passant(&undo.piece, &undo.mov.from, &undo.mov.to) {
+                self.board.insert(
+                    Position {
+                        file: undo.mov.to.file,
+                        rank: undo.mov.from.rank,
+                    },
+                    captured,
+                );
             } else {
-                Err("No piece in position(s)")
+                self.board.insert(undo.mov.to, captured);
+            }
+        }
+        self.board.insert(undo.mov.from, undo.piece);
+        for (position, piece) in undo.exploded.iter().flatten() {
+            self.board.insert(*position, *piece);
+        }
+    }
+
+    /// King destination, rook start and rook destination if the move is castling
+    ///
+    /// Castling is the king moving two squares from its starting square, or onto its own rook in Chess960
+    fn _castling(&self, mov: &Move) -> Option<(Position, Position, Position)> {
+        let color = match self.board.get(&mov.from) {
+            Some(Piece::King(color)) => *color,
+            _ => return None,
+        };
+        let rank = self.config.board_size.home_rank(color);
+        let files = self.castling_files;
+        if mov.from
+            != (Position {
+                file: files.king,
+                rank,
+            })
+            || mov.to.rank != rank
+        {
+            return None;
+        }
+        let kingside = if self.chess960 {
+            if self.board.get(&mov.to) != Some(&Piece::Rook(color)) {
+                return None;
             }
+            mov.to.file > mov.from.file
         } else {
-            Err("Invalid position(s)")
+            match mov.to.file as i32 - mov.from.file as i32 {
+                2 => true,
+                -2 => false,
+                _ => return None,
+            }
+        };
+        let (rook_file, king_file, rook_to) = if kingside {
+            (files.kingside, 7, 6)
+        } else {
+            (files.queenside, 3, 4)
+        };
+        Some((
+            Position {
+                file: king_file,
+                rank,
+            },
+            Position {
+                file: rook_file,
+                rank,
+            },
+            Position {
+                file: rook_to,
+                rank,
+            },
+        ))
+    }
+
+    /// If the current game state is not CheckMate and the move is legal,
+    /// move a piece.
+    ///
+    /// Return Err if move is illegal or if piece has no possible moves, [`ChessError::GameOver`]
+    /// once the game has ended, otherwise Ok with what happened, eg. the removed piece
+    pub fn make_move(&mut self, _from: String, _to: String) -> Result<MoveOutcome, ChessError> {
+        if let (Ok(from), Ok(to)) = (_from.parse::<Position>(), _to.parse::<Position>()) {
+            self._play(Move {
+                from,
+                to,
+                promotion: None,
+            })
+        } else {
+            Err(ChessError::InvalidMove("Invalid position(s)"))
+        }
+    }
+
+    /// Make a move written in standard algebraic notation (SAN), eg. "Nf3", "exd5", "O-O" or "e8=Q+"
+    ///
+    /// The piece to move is found among the legal moves, pawns reaching the last rank without
+    /// a promotion piece in the notation become the promotion piece set for the player.
+    ///
+    /// Return Err if the notation is invalid, the move is illegal or ambiguous, otherwise Ok with what happened
+    pub fn make_move_san(&mut self, san: &str) -> Result<MoveOutcome, ChessError> {
+        // Finished games have no legal moves to match the notation against
+        if let Some(result) = self.result() {
+            return Err(ChessError::GameOver(result));
         }
+        let mov = san::parse_san(self, san)?;
+        self._play(mov)
     }
 
-    /// Returns true if king with `color` is threatened by piece in `position`
-    fn _threatens_king(&self, position: &Position, color: Color) -> bool {
-        if let Some(moves) = self._get_possible_moves(position) {
-            for mov in moves {
-                if let Some(p) = self.board.get(&mov) {
-                    match p {
-                        Piece::King(_c) if *_c == color => {
-                            return true;
+    /// Make a move written in UCI notation, eg. "e2e4", "e1g1" (castling) or "e7e8q"
+    ///
+    /// Pawns reaching the last rank without a promotion suffix become the promotion piece set for the player.
+    ///
+    /// Return Err if the notation is invalid, has a promotion suffix on a move that is not a
+    /// promotion or the move is illegal, otherwise Ok with what happened
+    pub fn make_move_uci(&mut self, uci: &str) -> Result<MoveOutcome, ChessError> {
+        let mov = Move::from_uci(uci)?;
+        let pawn = matches!(self.board.get(&mov.from), Some(Piece::Pawn(_)));
+        if mov.promotion.is_some() && !(pawn && self._is_last_rank(&mov.to)) {
+            return Err("Promotion piece given for a move that is not a promotion".into());
+        }
+        self._play(mov)
+    }
+
+    /// Make move and describe what happened, see [`Game::make_move`]
+    fn _play(&mut self, mov: Move) -> Result<MoveOutcome, ChessError> {
+        let before = self.clone();
+        let captured = self._make_move(mov)?;
+        let mov = self.last_move().expect("Move was recorded");
+        let san = san::_san_before_move(&before, mov, mov.promoted_to()) + san::_check_suffix(self);
+        let outcome = MoveOutcome {
+            mov,
+            captured,
+            promotion: mov.promoted_to(),
+            state: self.get_game_state(),
+            check: self._king_is_threatened(self.active_color),
+            san,
+        };
+        self._record_events(&before, &outcome);
+        Ok(outcome)
+    }
+
+    /// Moves made since the game was created or loaded, oldest first
+    ///
+    /// Promotions are recorded with the piece the pawn became. Drops in Crazyhouse are not moves
+    /// and are not recorded.
+    pub fn history(&self) -> &[Move] {
+        &self.history
+    }
+
+    /// The most recent move, eg. to highlight its squares
+    pub fn last_move(&self) -> Option<Move> {
+        self.history.last().copied()
+    }
+
+    /// Validate and make move, see [`Game::make_move`]
+    fn _make_move(&mut self, mov: Move) -> Result<Option<Piece>, ChessError> {
+        let Move { from, to, .. } = mov;
+        if let Some(result) = self.result() {
+            return Err(ChessError::GameOver(result));
+        }
+        if let Some(&piece) = self.board.get(&from) {
+            if piece.color() != self.active_color {
+                return Err(ChessError::InvalidMove("Trying to move opponents piece"));
+            }
+
+            if let Some(possible_moves) = self._get_possible_moves(&from) {
+                if possible_moves.contains(&to) {
+                    // Cannot move to/capture king
+                    if matches!(self.board.get(&to), Some(Piece::King(_))) {
+                        return Err(ChessError::InvalidMove("Cannot capture king"));
+                    }
+                    let promotion = match piece {
+                        Piece::Pawn(_) if self._is_last_rank(&to) => {
+                            Some(mov.promotion.unwrap_or(self._piece_after_move(piece, &to)))
                         }
-                        _ => continue,
+                        _ => None,
+                    };
+                    let before_key = self.position_key();
+                    let start_fen = if self.history.is_empty() {
+                        Some(self.to_fen())
+                    } else {
+                        None
+                    };
+                    // Actual piece move, captures piece (or moves to square if empty)
+                    let undo = self._make(mov);
+                    if self._king_is_threatened(piece.color()) {
+                        // Own king is threatened -> invalid move
+                        self._unmake(undo);
+                        return Err(ChessError::InvalidMove("Move threatens own king"));
+                    }
+                    self._record_crazyhouse_move(&mov, piece, undo.captured);
+                    if start_fen.is_some() {
+                        self.start_fen = start_fen;
                     }
+                    if self.halfmove_clock == 0 {
+                        // Earlier positions cannot occur again after a pawn move or capture
+                        self.repetitions.clear();
+                    } else {
+                        self.repetitions.push(before_key);
+                    }
+                    self.history.push(Move { promotion, ..mov });
+                    self.node = self.tree.add(self.node, Move { promotion, ..mov });
+                    self._update_state();
+
+                    Ok(undo.captured)
+                } else {
+                    Err(ChessError::InvalidMove("Destination move is invalid"))
                 }
+            } else {
+                Err(ChessError::InvalidMove("No possible moves"))
             }
-            false // No piece threatens king
         } else {
-            false // No piece in position
+            Err(ChessError::InvalidMove("No piece in position(s)"))
         }
     }
 
     /// Returns if king with provided color is threatened by opposite color
     ///
-    /// Iterates over all pieces to find if any of them threatens king with `color`
+    /// Casts rays from the king to find if any piece of the opposite color attacks it
     fn _king_is_threatened(&self, color: Color) -> bool {
-        for (position, piece) in self.board.iter() {
-            if piece.color() != color && self._threatens_king(position, color) {
-                return true;
+        if self.variant == Variant::Atomic {
+            return self._atomic_king_is_threatened(color);
+        }
+        self.board
+            .iter()
+            .filter(|(_, piece)| **piece == Piece::King(color))
+            .any(|(king, _)| self._is_attacked(&king, !color))
+    }
+
+    /// Positions of the pieces of `color` pinned to their king, ie. that would expose the king to
+    /// a rook, bishop or queen if moved off the line. Sorted by file and rank
+    pub fn pinned_pieces(&self, color: Color) -> Vec<Position> {
+        let king = match self.board.iter().find(|(_, p)| **p == Piece::King(color)) {
+            Some((position, _)) => position,
+            None => return Vec::new(),
+        };
+        let board_size = self.config.board_size;
+        let mut pinned = Vec::new();
+        for direction in ray::QUEEN_DIRECTIONS {
+            // The first piece seen from the king is pinned by a piece sliding towards the king
+            // right behind it
+            let blocker = match Ray::new(&self.board, board_size, king, direction).last() {
+                Some(blocker) if self.board.get(&blocker).map(Piece::color) == Some(color) => {
+                    blocker
+                }
+                _ => continue,
+            };
+            let pinner = Ray::new(&self.board, board_size, blocker, direction)
+                .last()
+                .and_then(|square| self.board.get(&square));
+            if pinner.is_some_and(|pinner| {
+                pinner.color() != color && ray::sliding_directions(pinner).contains(&direction)
+            }) {
+                pinned.push(blocker);
             }
         }
-        false
+        pinned.sort();
+        pinned
+    }
+
+    /// Positions of the opponent's pieces giving check to the king of `color`, sorted by file and rank
+    pub fn checking_pieces(&self, color: Color) -> Vec<Position> {
+        let king = match self.board.iter().find(|(_, p)| **p == Piece::King(color)) {
+            Some((position, _)) => position,
+            None => return Vec::new(),
+        };
+        let mut checking: Vec<Position> = self
+            .board
+            .iter()
+            .filter(|(from, piece)| piece.color() != color && self._attacks(from, &king))
+            .map(|(from, _)| from)
+            .collect();
+        checking.sort();
+        checking
     }
 
     /// Returns if there is a checkmate for the provided color
     ///
-    /// Iterates over all moves for `color`'s pieces and if no moves can be made, the game is check mate
+    /// If no moves can be made by `color`'s pieces (or drops in Crazyhouse), the game is check mate
     fn _is_checkmate(&self, color: Color) -> bool {
-        for (position, _) in self.board.iter().filter(|(_, &p)| p.color() == color) {
-            if let Some(moves) = self.get_possible_moves(position.to_string()) {
-                if moves.len() > 0 {
-                    return false;
+        let move_count = if color == self.active_color {
+            self.legal_move_count()
+        } else {
+            self._legal_move_count(color)
+        };
+        move_count == 0 && (color != self.active_color || self.legal_drops().is_empty())
+    }
+
+    /// Number of moves `color` can make without collecting them into position strings
+    fn _legal_move_count(&self, color: Color) -> usize {
+        let mut count = 0;
+        self._for_each_legal_move(color, |_| count += 1);
+        count
+    }
+
+    /// Calls `f` with every legal move for `color`, also if it is not `color`'s turn
+    ///
+    /// Check and the king position are computed once. Only pieces that are aligned with their own king
+    /// (or all pieces if in check) need the expensive check for exposing the king.
+    fn _for_each_legal_move(&self, color: Color, mut f: impl FnMut(Move)) {
+        let has_king = self.board.iter().any(|(_, p)| *p == Piece::King(color));
+        let in_check = !self.checking_pieces(color).is_empty();
+        let pinned = self.pinned_pieces(color);
+        // Moves are tested on a copy of the game
+        let mut scratch = self.clone();
+        for (from, piece) in self.board.iter().filter(|(_, p)| p.color() == color) {
+            // Outside of check only pinned pieces and the king can expose the king
+            let may_expose_king = in_check
+                || !has_king
+                // Explosions can remove any piece
+                || self.variant == Variant::Atomic
+                || matches!(piece, Piece::King(_))
+                // En passant removes a second piece from the board
+                || (matches!(piece, Piece::Pawn(_)) && self.en_passant.is_some())
+                || pinned.contains(&from);
+            if let Some(destinations) = self._get_possible_moves(&from) {
+                for to in destinations {
+                    // Cannot move to/capture king
+                    if matches!(self.board.get(&to), Some(Piece::King(_))) {
+                        continue;
+                    }
+                    if may_expose_king && scratch._exposes_king(&from, &to) {
+                        continue;
+                    }
+                    self._with_promotions(from, to, *piece, &mut f);
+                }
+            }
+        }
+    }
+
+    /// Calls `f` with the move of piece from `from` to `to`, once per possible promotion piece if
+    /// a pawn reaches the last rank
+    fn _with_promotions(
+        &self,
+        from: Position,
+        to: Position,
+        piece: Piece,
+        mut f: impl FnMut(Move),
+    ) {
+        let color = piece.color();
+        if matches!(piece, Piece::Pawn(_)) && self._is_last_rank(&to) {
+            for promotion in [
+                Piece::Queen(color),
+                Piece::Rook(color),
+                Piece::Bishop(color),
+                Piece::Knight(color),
+            ] {
+                f(Move {
+                    from,
+                    to,
+                    promotion: Some(promotion),
+                });
+            }
+        } else {
+            f(Move {
+                from,
+                to,
+                promotion: None,
+            });
+        }
+    }
+
+    /// Moves of the piece on position following how the piece moves, without checking if they
+    /// leave the own king in check
+    ///
+    /// This is the cheap part of move generation: a search can generate pseudo-legal moves and
+    /// only check the ones it looks at with [`Game::is_legal`]. Works for pieces of both colors.
+    /// Captures of a king are left out. A pawn reaching the last rank has one move per promotion
+    /// piece. Empty if there is no piece on position.
+    pub fn pseudo_legal_moves(&self, position: Position) -> Vec<Move> {
+        let mut moves = Vec::new();
+        let piece = match self.board.get(&position) {
+            Some(&piece) => piece,
+            None => return moves,
+        };
+        if let Some(destinations) = self._get_possible_moves(&position) {
+            for to in destinations {
+                if !matches!(self.board.get(&to), Some(Piece::King(_))) {
+                    self._with_promotions(position, to, piece, |mov| moves.push(mov));
                 }
-            } // Invalid position or no piece, should not be reached
+            }
+        }
+        moves.sort_unstable();
+        moves
+    }
+
+    /// Legal moves of `color` as if it was its turn, sorted by origin and destination
+    ///
+    /// Unlike [`Game::get_possible_moves`] for the player who is not to move, moves leaving the own
+    /// king in check are left out, eg. for analysis from the other player's perspective. The
+    /// player not to move cannot capture en passant.
+    pub fn legal_moves_for(&self, color: Color) -> Vec<Move> {
+        let mut moves = if color == self.active_color {
+            self.legal_moves().to_vec()
+        } else {
+            let mut moves = Vec::new();
+            self._for_each_legal_move(color, |mov| moves.push(mov));
+            moves
+        };
+        moves.sort_unstable();
+        moves
+    }
+
+    /// Legal moves of the piece on position, sorted by destination
+    ///
+    /// Empty if the piece belongs to the player who is not to move. Taken from the cached
+    /// [`Game::legal_moves`], so the legality of all moves is checked on the first call.
+    pub fn legal_moves_from(&self, position: Position) -> Vec<Move> {
+        let mut moves: Vec<Move> = self
+            .legal_moves()
+            .iter()
+            .filter(|mov| mov.from == position)
+            .copied()
+            .collect();
+        moves.sort_unstable();
+        moves
+    }
+
+    /// If the pseudo-legal move is legal, ie. it is made by the player who's turn it is and does not
+    /// leave the own king in check
+    ///
+    /// Checks only this move, see [`Game::pseudo_legal_moves`]. Promotions need the promotion piece.
+    pub fn is_legal(&self, mov: Move) -> bool {
+        match self.board.get(&mov.from) {
+            Some(piece) if piece.color() == self.active_color => {}
+            _ => return false,
+        }
+        if !self.pseudo_legal_moves(mov.from).contains(&mov) {
+            return false;
+        }
+        self.clone()._ok_to_make_move(&mov.from, &mov.to)
+    }
+
+    /// All legal moves for the player who's turn it is
+    fn _legal_moves(&self) -> Vec<Move> {
+        let mut moves = Vec::new();
+        self._for_each_legal_move(self.active_color, |mov| moves.push(mov));
+        moves
+    }
+
+    /// Get all legal moves for the player who's turn it is, grouped by the position of the moving piece
+    ///
+    /// Faster than calling [`Game::get_possible_moves`] for every square since check and king lines are only computed once
+    ///
+    /// Pieces without any legal moves are left out. Moves for every piece are sorted by destination.
+    /// A pawn reaching the last rank has one move per possible promotion piece
+    pub fn all_moves_by_square(&self) -> BTreeMap<Position, Vec<Move>> {
+        let mut all_moves: BTreeMap<Position, Vec<Move>> = BTreeMap::new();
+        for mov in self.legal_moves().iter() {
+            all_moves.entry(mov.from).or_default().push(*mov);
         }
-        true
+        for moves in all_moves.values_mut() {
+            moves.sort_unstable();
+        }
+        all_moves
+    }
+
+    /// Returns the number of legal moves for the player who's turn it is
+    ///
+    /// Cheaper than calling [`Game::get_possible_moves`] for every piece when only the count is needed (eg. mobility or checkmate detection)
+    ///
+    /// A pawn reaching the last rank counts as one move per possible promotion piece
+    pub fn legal_move_count(&self) -> usize {
+        self.legal_moves().len()
+    }
+
+    /// All legal moves for the player who's turn it is, in no particular order
+    ///
+    /// The moves are computed once per position and cached until the position changes. After the
+    /// public fields are changed directly (eg. [`Game::castling`]) the moves are computed on every
+    /// call until a move is made or [`Game::clear_move_cache`] is called.
+    pub fn legal_moves(&self) -> Cow<'_, [Move]> {
+        let key = (
+            self.board,
+            self.active_color,
+            self.castling,
+            self.castling_files,
+            self.chess960,
+            self.en_passant,
+            self.config,
+            self.crazyhouse,
+            self.variant,
+        );
+        self.legal_moves.get(key, || self._legal_moves())
+    }
+
+    /// Board with the piece on every occupied position
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    /// The color who's turn it is
+    pub fn side_to_move(&self) -> Color {
+        self.active_color
+    }
+
+    /// The game state, see [`Game::get_game_state`]
+    pub fn state(&self) -> GameState {
+        self.get_game_state()
+    }
+
+    /// Piece that pawns of color become when reaching the last rank without a chosen promotion
+    #[allow(deprecated)]
+    pub fn promotion_piece(&self, color: Color) -> Piece {
+        self.promotion
+            .iter()
+            .find(|p| p.color() == color)
+            .copied()
+            // Promotion piece not found for color -> use queen
+            .unwrap_or(Piece::Queen(color))
+    }
+
+    /// Set the promotion piece of the piece's color
+    ///
+    /// Returns Err if the piece is a king or pawn
+    #[allow(deprecated)]
+    pub fn set_promotion_piece(&mut self, piece: Piece) -> Result<(), &'static str> {
+        if matches!(piece, Piece::King(_) | Piece::Pawn(_)) {
+            return Err("Invalid promotion piece");
+        }
+        self.promotion[piece.color().index()] = piece;
+        Ok(())
+    }
+
+    /// Forget the cached legal moves and [dead position](Game::is_dead_position) verdict, eg. to
+    /// cache them again after changing the public fields
+    pub fn clear_move_cache(&mut self) {
+        self.legal_moves = MoveCache::default();
+        self.dead_position = reach::DeadPositionCache::default();
     }
 
     /// Set promotion piece for the current player.
@@ -622,60 +1927,254 @@ impl Game {
     /// String must be "queen", "rook", "bishop" or "knight". Otherwise error is returned
     pub fn set_promotion(&mut self, _piece: String) -> Result<(), &str> {
         let color = self.active_color;
-        for prom_piece in self.promotion.iter_mut() {
-            if prom_piece.color() == color {
-                *prom_piece = match &_piece.to_lowercase()[..] {
-                    "queen" => Piece::Queen(color),
-                    "rook" => Piece::Rook(color),
-                    "bishop" => Piece::Bishop(color),
-                    "knight" => Piece::Knight(color),
-                    _ => return Err("Invalid promotion piece"),
-                };
-                break;
+        let piece = match &_piece.to_lowercase()[..] {
+            "queen" => Piece::Queen(color),
+            "rook" => Piece::Rook(color),
+            "bishop" => Piece::Bishop(color),
+            "knight" => Piece::Knight(color),
+            _ => return Err("Invalid promotion piece"),
+        };
+        self.set_promotion_piece(piece)
+    }
+
+    /// Gets the current game state
+    ///
+    /// Detects checkmate and stalemate, computing the legal moves once per position
+    pub fn get_game_state(&self) -> GameState {
+        if let state @ (GameState::TimeForfeit | GameState::Draw(_)) = self._state() {
+            state
+        } else if self._is_checkmate(self.active_color) {
+            if self._is_mated(self.active_color) {
+                GameState::CheckMate
+            } else {
+                GameState::Draw(DrawReason::Stalemate)
             }
+        } else if let Some(reason) = self._automatic_draw() {
+            GameState::Draw(reason)
+        } else if self._king_is_threatened(self.active_color) {
+            GameState::Check
+        } else {
+            GameState::InProgress
+        }
+    }
+
+    /// How the game ended, None while it is in progress
+    ///
+    /// Running out of time loses the game.
+    pub fn result(&self) -> Option<GameResult> {
+        let loser = self.active_color;
+        match self.get_game_state() {
+            GameState::CheckMate | GameState::TimeForfeit => Some(match loser {
+                Color::White => GameResult::BlackWins,
+                Color::Black => GameResult::WhiteWins,
+            }),
+            GameState::Draw(reason) => Some(GameResult::Draw(reason)),
+            GameState::InProgress | GameState::Check => None,
         }
+    }
+
+    /// If the game has ended by checkmate, stalemate, running out of time or a draw,
+    /// after which no more moves can be made
+    pub fn is_game_over(&self) -> bool {
+        self.result().is_some()
+    }
+
+    /// If color, who has no legal moves, lost the king or is in check rather than stalemated
+    fn _is_mated(&self, color: Color) -> bool {
+        let has_king = self.board.iter().any(|(_, p)| *p == Piece::King(color));
+        !has_king || self._king_is_threatened(color)
+    }
+
+    /// Number of times the current position occurred since the game was created or loaded, see
+    /// [`Game::is_same_position`]
+    pub fn repetition_count(&self) -> usize {
+        let key = self.position_key();
+        1 + self.repetitions.iter().filter(|&&k| k == key).count()
+    }
+
+    /// Draw the player who's turn it is may claim, by the fifty-move rule or threefold repetition
+    pub fn claimable_draw(&self) -> Option<DrawReason> {
+        if self.halfmove_clock >= FIFTY_MOVES {
+            Some(DrawReason::FiftyMoves)
+        } else if self.repetition_count() >= THREEFOLD_REPETITION {
+            Some(DrawReason::ThreefoldRepetition)
+        } else {
+            None
+        }
+    }
+
+    /// End the game in a draw claimed for reason, by the fifty-move rule or threefold repetition
+    ///
+    /// Return Err and leave the game unchanged if the game is over, reason cannot be claimed or
+    /// the halfmove clock or repetitions do not allow the claim
+    pub fn claim_draw(&mut self, reason: DrawReason) -> Result<(), &'static str> {
+        if self.is_game_over() {
+            return Err("The game is over");
+        }
+        let claimable = match reason {
+            DrawReason::FiftyMoves => self.halfmove_clock >= FIFTY_MOVES,
+            DrawReason::ThreefoldRepetition => self.repetition_count() >= THREEFOLD_REPETITION,
+            _ => return Err("Only the fifty-move rule and threefold repetition can be claimed"),
+        };
+        if !claimable {
+            return Err(match reason {
+                DrawReason::FiftyMoves => "Fewer than 50 moves without a pawn move or capture",
+                _ => "The position has not occurred three times",
+            });
+        }
+        let state = GameState::Draw(reason);
+        self._set_state(state);
+        self._push_event(GameEvent::GameOver(state));
         Ok(())
     }
 
-    /// Gets the current game state
+    /// Draw by the 75-move rule, fivefold repetition or a dead position, which end the game
+    /// without a claim
+    fn _automatic_draw(&self) -> Option<DrawReason> {
+        if self.halfmove_clock >= SEVENTY_FIVE_MOVES {
+            Some(DrawReason::SeventyFiveMoves)
+        } else if self.repetitions.len() + 1 >= FIVEFOLD_REPETITION
+            && self.repetition_count() >= FIVEFOLD_REPETITION
+        {
+            Some(DrawReason::FivefoldRepetition)
+        } else if self.is_dead_position() {
+            Some(DrawReason::DeadPosition)
+        } else {
+            None
+        }
+    }
+
+    /// Hash identifying the position for repetitions, see [`Game::is_same_position`]
     ///
-    /// Detects and returns checkmate (private field game.state does not)
-    pub fn get_game_state(&mut self) -> GameState {
-        if self._is_checkmate(self.active_color) {
-            self.state = GameState::CheckMate;
+    /// Keys are the same in every program run and on every platform, so they are stored with
+    /// serialized games.
+    pub fn position_key(&self) -> u64 {
+        let (board, color, castling, en_passant, reserves) = self._position_identity();
+        let mut hasher = KeyHasher::default();
+        for index in 0..64 {
+            let square = Position::from_index(index).expect("Index is on the board");
+            hasher.write_u8(board.get(&square).map_or(0, binary::piece_code));
+        }
+        hasher.write_u8(color.index() as u8);
+        for rights in castling {
+            hasher.write(&[rights.kingside as u8, rights.queenside as u8]);
+        }
+        // Squares are counted from 1 so that 0 is no en passant square
+        hasher.write_u8(en_passant.map_or(0, |square| square.index() as u8 + 1));
+        if let Some(reserves) = reserves {
+            for reserve in reserves {
+                hasher.write(&reserve.counts);
+            }
         }
-        return self.state;
+        hasher.finish()
+    }
+
+    /// If both games have the same position in the sense of the repetition rules
+    ///
+    /// The pieces, player to move, castling rights and Crazyhouse reserves must be the same, and
+    /// the en passant square if a pawn can capture en passant. Unlike `==`, the move counters,
+    /// clocks and promotion pieces are ignored.
+    pub fn is_same_position(&self, other: &Game) -> bool {
+        self._position_identity() == other._position_identity()
+    }
+
+    /// Everything compared by [`Game::is_same_position`]
+    fn _position_identity(&self) -> PositionIdentity {
+        // An en passant square without a legal en passant capture makes no difference
+        let en_passant = self.en_passant.filter(|&square| {
+            self.legal_moves().iter().any(|mov| {
+                mov.to == square && matches!(self.board.get(&mov.from), Some(Piece::Pawn(_)))
+            })
+        });
+        (
+            self.board,
+            self.active_color,
+            self.castling,
+            en_passant,
+            self.crazyhouse.map(|crazyhouse| crazyhouse.reserves),
+        )
+    }
+
+    /// The stored state, which only knows about check, time forfeits and claimed draws
+    #[allow(deprecated)]
+    fn _state(&self) -> GameState {
+        self.state
+    }
+
+    /// Set the stored state, see [`Game::_state`]
+    #[allow(deprecated)]
+    fn _set_state(&mut self, state: GameState) {
+        self.state = state;
+    }
+
+    /// Store [`GameState::Check`] or [`GameState::InProgress`] after the position changed
+    pub(crate) fn _update_state(&mut self) {
+        if self._king_is_threatened(self.active_color) {
+            self._set_state(GameState::Check);
+        } else {
+            self._set_state(GameState::InProgress);
+        }
+    }
+}
+
+impl PartialEq for Game {
+    /// Games are equal if everything needed to continue the game is, including the move counters,
+    /// clock and promotion pieces. The move history with its side lines and annotations and the
+    /// [`odds`] the game started with are not compared, see [`Game::is_same_position`] for
+    /// comparing positions as in repetitions
+    #[allow(deprecated)]
+    fn eq(&self, other: &Self) -> bool {
+        let Game {
+            board,
+            active_color,
+            promotion,
+            state,
+            castling,
+            castling_files,
+            chess960,
+            en_passant,
+            halfmove_clock,
+            fullmove_number,
+            config,
+            crazyhouse,
+            variant,
+            clock,
+            odds: _,
+            history: _,
+            tree: _,
+            node: _,
+            start_fen: _,
+            repetitions: _,
+            legal_moves: _,
+            dead_position: _,
+            events: _,
+        } = self;
+        *board == other.board
+            && *active_color == other.active_color
+            && *promotion == other.promotion
+            && *state == other.state
+            && *castling == other.castling
+            && *castling_files == other.castling_files
+            && *chess960 == other.chess960
+            && *en_passant == other.en_passant
+            && *halfmove_clock == other.halfmove_clock
+            && *fullmove_number == other.fullmove_number
+            && *config == other.config
+            && *crazyhouse == other.crazyhouse
+            && *variant == other.variant
+            && *clock == other.clock
+    }
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
-impl fmt::Debug for Game {
+impl fmt::Display for Game {
+    /// Board with Unicode pieces and coordinates, see [`render::BoardFormatter`] for other options
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut output = String::new();
-        output.push_str(
-            &format!(
-                "{} a b c d e f g h\n",
-                match self.active_color {
-                    Color::White => "W", // ⚑
-                    Color::Black => "B", // ⚐
-                }
-            )[..],
-        );
-        for rank in (1..=8).rev() {
-            for file in 1..=8 {
-                if file == 1 {
-                    output.push(char::from_digit(rank as u32, 10).unwrap_or(' '));
-                }
-                output.push(' ');
-                let pos = Position { file, rank };
-                let board = &self.board;
-                output.push(if board.contains_key(&pos) {
-                    board[&pos].symbol()
-                } else {
-                    ' '
-                });
-            }
-            output.push_str("\n");
-        }
-        write!(f, "\n{}", output)
+        write!(f, "{}", render::BoardFormatter::new(self))
     }
 }
@@ -1,63 +1,148 @@
 //! Interactive chess game through terminal
+use eliasfl_chess::engine::EngineLevel;
+use eliasfl_chess::notation::Notation;
+use eliasfl_chess::settings::Settings;
 use eliasfl_chess::*;
 use std::env;
+use std::fs;
 use std::io::{self, BufRead};
+use std::sync::Mutex;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 
-const ASCII_SYMBOLS: [(&str, &str); 12] = [
-    ("♚", "K"),
-    ("♔", "k"),
-    ("♛", "Q"),
-    ("♕", "q"),
-    ("♜", "R"),
-    ("♖", "r"),
-    ("♝", "B"),
-    ("♗", "b"),
-    ("♞", "N"),
-    ("♘", "n"),
-    ("♟", "P"),
-    ("♙", "p"),
-];
+/// Settings read from the settings file at startup and changed with "set"
+static SETTINGS: Mutex<Option<Settings>> = Mutex::new(None);
+
+/// The current settings, see [`SETTINGS`]
+fn settings() -> Settings {
+    SETTINGS.lock().unwrap().clone().unwrap_or_default()
+}
 
 fn rerender(game: &Game) {
-    let mut gameboard = format!("{:?}", game);
-    match env::args().nth(1) {
-        Some(arg) if arg.contains("fancy") => {
-            // Clear terminal screen
-            println!("\x1B[2J\x1B[1;1H");
-            // Print gameboard
-            print!("{}", gameboard);
-        }
-        _ => {
-            for (from, to) in ASCII_SYMBOLS {
-                gameboard = gameboard.replace(from, to);
-            }
-            print!("{}", gameboard);
+    print_board(
+        render::BoardFormatter::new(game)
+            .last_move(true)
+            .check(true),
+    );
+}
+
+/// If "blindfold" is passed as argument: the board is hidden and moves are announced in words
+fn blindfold() -> bool {
+    env::args().skip(1).any(|arg| arg.contains("blindfold"))
+}
+
+/// Notation of the moves, "german", "swedish" or "figurine" can be passed as argument,
+/// otherwise the notation of the settings
+fn notation() -> Notation {
+    env::args()
+        .skip(1)
+        .find_map(|arg| arg.parse().ok())
+        .unwrap_or_else(|| settings().notation)
+}
+
+/// Print board, with ASCII pieces unless "fancy" is passed as argument or set in the settings
+fn print_board(board: render::BoardFormatter) {
+    if blindfold() {
+        return;
+    }
+    let fancy = env::args().nth(1).is_some_and(|arg| arg.contains("fancy"));
+    if fancy || settings().fancy {
+        // Clear terminal screen
+        println!("\x1B[2J\x1B[1;1H");
+        // Print gameboard
+        print!("{}", board);
+    } else {
+        print!("\n{}", board.ascii(true));
+    }
+}
+
+/// Game with the clock of the settings, if any
+fn with_clock(mut game: Game) -> Game {
+    game.clock = settings().clock.map(clock::Clock::new);
+    game
+}
+
+/// Save game as PGN to the autosave file of the settings, if any
+fn autosave(game: &Game) {
+    if let Some(path) = settings().autosave {
+        if let Err(err) = fs::write(&path, game.to_pgn()) {
+            println!("Could not autosave game to {}: {}", path, err);
         }
     }
 }
 
 fn main() {
-    let mut game = Game::new();
+    if let Some(path) = Settings::default_path() {
+        match Settings::load(&path) {
+            Ok(settings) => *SETTINGS.lock().unwrap() = Some(settings),
+            Err(err) => println!("Could not read settings from {}: {}", path.display(), err),
+        }
+    }
+
+    #[cfg(feature = "net")]
+    if let Some((host, address)) = online_arguments() {
+        if let Err(err) = play_online(host, &address) {
+            println!("Connection lost: {}", err);
+        }
+        return;
+    }
+
+    let mut game = with_clock(Game::new());
 
     let help = r#"
 Possible commands:
 Enter one coordinate (eg. "e2") to get possible moves
-Enter two coordinates (eg. "e2 e3") to try to move piece
-Type name of piece to be set as promotion piece for current player (eg. "knight")
+Enter two coordinates (eg. "e2 e3") or a move in algebraic notation (eg. "e3", "Nf3" or "O-O") to try to move piece
+Pawns moved to the last rank with two coordinates ask which piece to promote to
+Type name of piece to be set as promotion piece for current player (eg. "knight"), chosen by pressing enter when asked
+Type "analyze" to show the best move (green) and the opponent's threat (red)
+Type "ai" followed by the computer's color and optionally search depth or level (eg. "ai black 3" or "ai black beginner") to play against the computer, "ai off" to stop
+Levels are beginner, casual, intermediate, advanced and master
+Type "hint" to get a suggested move
+Type "quiz" to answer a question about the position, eg. the legal moves of a piece, and get scored
+Type "save" followed by a file name to save the game as PGN, or only the position if the name ends with ".fen"
+Type "load" followed by a file name to load a game saved as PGN or FEN
+Type "clock" followed by minutes and increment in seconds (eg. "clock 5+3") to start a chess clock
 Type "state" to get current game state
+Type "history" to list the moves played in algebraic notation
 Type "color" to get which color's turn it is (also shown in upper left corner of board)
 Type "restart" to restart the game
+Type "odds" followed by the handicap (eg. "odds queen" or "odds pawn and move") to restart as a handicap game
+Type "set" followed by a setting and its value to change and save it (eg. "set fancy true", "set level casual", "set clock 5+3", "set autosave game.pgn" or "set notation swedish"), "set" alone to show the settings
 Type "help" to show this again
+Run with argument "blindfold" to hide the board and hear the moves in words
+Run with argument "german", "swedish" or "figurine" to write moves with those piece letters (eg. "Sf3" for "Nf3")
+Run with arguments "host" and a port (eg. "host 7878") or "join" and an address (eg. "join 192.168.0.2:7878") to play another player over the network, if built with the "net" feature
 Type "q", "quit" or "exit" anytime to quit
 Press enter to start game or update board
         "#
     .trim();
     println!("{}", help);
 
+    // Time the current player started thinking, used with the chess clock
+    let mut turn_start = Instant::now();
+    // Color played by the computer, its engine and level (None for full strength)
+    let mut computer: Option<(Color, Engine, Option<EngineLevel>)> = None;
+    // Quiz score and the question waiting for an answer
+    let seed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_nanos() as u64);
+    let mut quiz = quiz::Quiz::new(seed);
+    let mut question: Option<quiz::Question> = None;
+
     let stdin = io::stdin();
-    for line in stdin.lock().lines().map(|l| l.unwrap()) {
+    let mut lines = stdin.lock().lines().map(|l| l.unwrap());
+    while let Some(line) = lines.next() {
         rerender(&game);
 
+        if let Some(question) = question.take() {
+            if quiz.answer(&game, &question, &line) {
+                print!("Correct!");
+            } else {
+                print!("Wrong, the answer is {}.", question.answer(&game));
+            }
+            println!(" Score: {}/{}", quiz.correct, quiz.asked);
+            continue;
+        }
         match &line.to_lowercase()[..] {
             "q" | "quit" | "exit" | "\u{4}" => {
                 break;
@@ -67,21 +152,171 @@ Press enter to start game or update board
                 continue;
             }
             "restart" => {
-                game = Game::new();
+                game = with_clock(Game::new());
                 rerender(&game);
                 continue;
             }
+            "analyze" => {
+                let engine = Engine::default();
+                print_board(
+                    render::BoardFormatter::new(&game)
+                        .annotations(&render::analysis_annotations(&engine, &game)),
+                );
+                let result = engine.search(&game);
+                if let Some(best) = result.best_move {
+                    println!("Best move: {} {}", best.from, best.to);
+                }
+                if let Some(threat) = engine.threat(&game) {
+                    println!("Threat: {} {}", threat.from, threat.to);
+                }
+                let probability = engine::WinProbability::from_score(result.score);
+                println!(
+                    "Score: {:+.2} (White {:.0}%, draw {:.0}%, Black {:.0}%)",
+                    result.score as f64 / 100.0,
+                    probability.white * 100.0,
+                    probability.draw * 100.0,
+                    probability.black * 100.0
+                );
+                continue;
+            }
+            command if command.starts_with("odds") => {
+                match command["odds".len()..].parse::<odds::Odds>() {
+                    Ok(odds) => {
+                        game = with_clock(Game::new_with_odds(odds));
+                        turn_start = Instant::now();
+                        rerender(&game);
+                        println!("New game with {}", odds.to_string().to_lowercase());
+                    }
+                    Err(err) => println!("{}", err),
+                }
+                continue;
+            }
+            command if command.starts_with("clock") => {
+                match command["clock".len()..].parse() {
+                    Ok(time_control) => {
+                        game.clock = Some(clock::Clock::new(time_control));
+                        turn_start = Instant::now();
+                        println!("Clock started: {}", game.clock.unwrap());
+                    }
+                    Err(_) => println!("Invalid time control, eg. \"clock 5+3\""),
+                }
+                continue;
+            }
+            command if command.starts_with("ai") => {
+                match parse_computer(&command["ai".len()..]) {
+                    Ok(Some((color, engine, level))) => {
+                        computer = Some((color, engine, level));
+                        match level {
+                            Some(level) => {
+                                println!("Computer plays {:?} at {} level", color, level.name())
+                            }
+                            None => println!(
+                                "Computer plays {:?} searching {} plies",
+                                color, engine.depth
+                            ),
+                        }
+                        if game.side_to_move() == color {
+                            play_computer_move(&mut game, &engine, level, seed);
+                            autosave(&game);
+                            turn_start = Instant::now();
+                        }
+                    }
+                    Ok(None) => {
+                        computer = None;
+                        println!("Computer stopped playing");
+                    }
+                    Err(err) => println!("{}", err),
+                }
+                continue;
+            }
+            command if command == "set" || command.starts_with("set ") => {
+                let mut parts = line["set".len()..].trim().splitn(2, ' ');
+                let (key, value) = match (parts.next(), parts.next()) {
+                    (Some(key), Some(value)) if !key.is_empty() => (key, value),
+                    _ => {
+                        print!("{}", settings());
+                        continue;
+                    }
+                };
+                let mut changed = settings();
+                match changed.set(key, value) {
+                    Ok(()) => {
+                        *SETTINGS.lock().unwrap() = Some(changed.clone());
+                        match Settings::default_path().map(|path| changed.save(path)) {
+                            Some(Ok(())) => println!("Set {} to {}", key, value.trim()),
+                            Some(Err(err)) => println!("Could not save settings: {}", err),
+                            None => {
+                                println!("Set {} to {} until the program exits", key, value.trim())
+                            }
+                        }
+                    }
+                    Err(err) => println!("{}", err),
+                }
+                continue;
+            }
+            "quiz" => {
+                let next = quiz.next_question(&game);
+                println!("{}", next.text(&game));
+                question = Some(next);
+                continue;
+            }
+            "hint" => {
+                let engine = computer.map_or_else(Engine::default, |(_, engine, _)| engine);
+                match suggested_move(&game, &engine) {
+                    Some(hint) => println!(
+                        "Hint: {} {} ({})",
+                        hint.from,
+                        hint.to,
+                        notation().to_san(&game, hint).unwrap_or_default()
+                    ),
+                    None => println!("No legal moves"),
+                }
+                continue;
+            }
+            command if command.starts_with("save ") => {
+                let path = line["save ".len()..].trim();
+                let contents = if path.to_lowercase().ends_with(".fen") {
+                    game.to_fen() + "\n"
+                } else {
+                    game.to_pgn()
+                };
+                match fs::write(path, contents) {
+                    Ok(()) => println!("Saved game to {}", path),
+                    Err(err) => println!("Could not save game: {}", err),
+                }
+                continue;
+            }
+            command if command.starts_with("load ") => {
+                let path = line["load ".len()..].trim();
+                match load_game(path) {
+                    Ok(loaded) => {
+                        game = loaded;
+                        turn_start = Instant::now();
+                        rerender(&game);
+                        println!("Loaded game from {}", path);
+                    }
+                    Err(err) => println!("Could not load game: {}", err),
+                }
+                continue;
+            }
+            "history" => {
+                println!("{}", notation().localize(&game.movetext()));
+                continue;
+            }
             "state" => {
                 println!("{:?}", game.get_game_state());
                 continue;
             }
             "color" => {
-                println!("{:?}", game.active_color);
+                println!("{:?}", game.side_to_move());
                 continue;
             }
             "queen" | "rook" | "bishop" | "knight" => {
                 if game.set_promotion(line).is_ok() {
-                    println!("Promotion piece set to {:?}", game.promotion);
+                    println!(
+                        "Promotion piece set to {:?}",
+                        game.promotion_piece(game.side_to_move())
+                    );
                 }
                 continue;
             }
@@ -90,37 +325,293 @@ Press enter to start game or update board
 
         let positions: Vec<Option<Position>> = line
             .split_whitespace()
-            .map(|f| Position::from_string(f.to_string()).ok())
+            .map(|f| f.parse::<Position>().ok())
             .collect();
-        match &positions[..] {
-            // Single position provided -> get moves
-            [Some(x)] => {
+        let before = game.clone();
+        let result = match &positions[..] {
+            // Single position with a piece provided -> get moves
+            [Some(x)] if game.board().contains_key(x) => {
                 if let Some(moves) = game.get_possible_moves(x.to_string()) {
                     if !moves.is_empty() {
-                        println!("Moves for {}: [{}]", x.to_string(), moves.join(", "));
+                        println!("Moves for {}: [{}]", x, moves.join(", "));
                     } else {
-                        println!("No valid moves for {}", x.to_string());
+                        println!("No valid moves for {}", x);
                     }
-                } else {
-                    println!("There is no piece on {}", x.to_string());
+                }
+                continue;
+            }
+            // Pawn reaching the last rank -> ask for the promotion piece
+            [Some(x), Some(y)] if is_promotion(&game, *x, *y) => {
+                match ask_promotion(&game, &mut lines) {
+                    Some(piece) => game
+                        .make_move_uci_timed(&format!("{}{}{}", x, y, piece), turn_start.elapsed()),
+                    None => continue,
                 }
             }
             // Two positions provided -> movie piece
-            [Some(x), Some(y)] => match game.make_move(x.to_string(), y.to_string()) {
-                Ok(_) => {
-                    rerender(&game);
-                    print!("Moved piece from {} to {}", x.to_string(), y.to_string());
-                    if game.get_game_state() != GameState::InProgress {
-                        print!(", new game state: {:?}", game.get_game_state());
-                    }
-                    println!();
-                    continue;
-                }
-                Err(err) => println!("Illegal move: {}", err),
-            },
+            [Some(x), Some(y)] => {
+                game.make_move_timed(x.to_string(), y.to_string(), turn_start.elapsed())
+            }
+            // Anything else is a move in algebraic notation, eg. "Nf3" or "e4"
+            [_] => {
+                game.make_move_san_timed(&notation().to_standard(line.trim()), turn_start.elapsed())
+            }
             _ => {
                 println!();
+                continue;
+            }
+        };
+        match result {
+            Ok(outcome) => {
+                turn_start = Instant::now();
+                rerender(&game);
+                if blindfold() {
+                    print!("{}", announcement(&before, &outcome));
+                } else {
+                    print!(
+                        "Moved piece from {} to {} ({})",
+                        outcome.mov.from,
+                        outcome.mov.to,
+                        notation().localize(&outcome.san)
+                    );
+                }
+                if outcome.state != GameState::InProgress {
+                    print!(", new game state: {:?}", outcome.state);
+                }
+                println!();
+                if let Some(opening) = game.opening() {
+                    println!("Position: {}", opening.name);
+                }
+                if let Some(clock) = game.clock {
+                    println!("{}", clock);
+                }
+                if let Some((color, engine, level)) = computer {
+                    if game.side_to_move() == color {
+                        play_computer_move(&mut game, &engine, level, seed);
+                        turn_start = Instant::now();
+                    }
+                }
+                autosave(&game);
+            }
+            Err(err) => println!("Illegal move: {}", err),
+        }
+    }
+}
+
+/// If moving the piece on from to to is a legal promotion
+fn is_promotion(game: &Game, from: Position, to: Position) -> bool {
+    game.legal_moves()
+        .iter()
+        .any(|mov| mov.from == from && mov.to == to && mov.is_promotion())
+}
+
+/// Ask for the piece a pawn promotes to until one is named, its UCI letter or None if the input
+/// ended
+///
+/// An empty answer chooses the promotion piece set for the player.
+fn ask_promotion(game: &Game, lines: &mut impl Iterator<Item = String>) -> Option<char> {
+    let default = match game.promotion_piece(game.side_to_move()).kind() {
+        PieceKind::Rook => "rook",
+        PieceKind::Bishop => "bishop",
+        PieceKind::Knight => "knight",
+        _ => "queen",
+    };
+    loop {
+        println!(
+            "Promote to queen, rook, bishop or knight? (enter for {})",
+            default
+        );
+        let answer = lines.next()?.trim().to_lowercase();
+        let answer = if answer.is_empty() { default } else { &answer };
+        match answer {
+            "q" | "queen" => return Some('q'),
+            "r" | "rook" => return Some('r'),
+            "b" | "bishop" => return Some('b'),
+            "n" | "knight" => return Some('n'),
+            _ => println!("Invalid piece: {}", answer),
+        }
+    }
+}
+
+/// Read a game saved as PGN or FEN, FEN files are recognized by not starting with a tag
+fn load_game(path: &str) -> Result<Game, String> {
+    let contents = fs::read_to_string(path).map_err(|err| err.to_string())?;
+    let contents = contents.trim();
+    let game = if contents.starts_with('[') || Game::from_fen(contents).is_err() {
+        Game::from_pgn(contents)
+    } else {
+        Game::from_fen(contents)
+    };
+    game.map_err(|err| err.to_string())
+}
+
+/// Parse computer options written as color and optionally search depth or level, eg. "black 3"
+/// or "black beginner", with only the color the level of the settings is used
+///
+/// Returns None for "off"
+#[allow(clippy::type_complexity)]
+fn parse_computer(
+    text: &str,
+) -> Result<Option<(Color, Engine, Option<EngineLevel>)>, &'static str> {
+    let mut parts = text.split_whitespace();
+    let color = match parts.next() {
+        Some("white") => Color::White,
+        Some("black") => Color::Black,
+        Some("off") => return Ok(None),
+        _ => return Err("Choose the computer's color, eg. \"ai black 3\""),
+    };
+    let (engine, level) = match parts.next() {
+        Some(option) => match option.parse() {
+            Ok(depth) if depth > 0 => (Engine::new(depth), None),
+            Ok(_) => return Err("Search depth should be a positive number"),
+            Err(_) => (Engine::default(), Some(option.parse::<EngineLevel>()?)),
+        },
+        None => (Engine::default(), settings().level),
+    };
+    Ok(Some((color, engine, level)))
+}
+
+/// Book move if the game is in the opening book, otherwise the engine's best move
+fn suggested_move(game: &Game, engine: &Engine) -> Option<Move> {
+    book::book_move(game).or_else(|| engine.best_move(game))
+}
+
+/// Let the computer make a move unless the game is over, weakened to level if any
+///
+/// Moves of weaker levels are random, seed and the number of moves made decide them
+fn play_computer_move(game: &mut Game, engine: &Engine, level: Option<EngineLevel>, seed: u64) {
+    if game.is_game_over() {
+        return;
+    }
+    let mov = match level {
+        Some(level) => book::book_move(game).or_else(|| {
+            let seed = seed.wrapping_add(game.history().len() as u64);
+            engine.best_move_with_level(game, level, seed)
+        }),
+        None => suggested_move(game, engine),
+    };
+    let mov = match mov {
+        Some(mov) => mov,
+        None => return,
+    };
+    let before = game.clone();
+    match game.make_move_uci(&mov.to_uci()) {
+        Ok(outcome) => {
+            rerender(game);
+            if blindfold() {
+                print!("Computer played {}", announcement(&before, &outcome));
+            } else {
+                print!("Computer played {}", notation().localize(&outcome.san));
+            }
+            if outcome.state != GameState::InProgress {
+                print!(", new game state: {:?}", outcome.state);
+            }
+            println!();
+        }
+        Err(err) => println!("Computer move failed: {}", err),
+    }
+}
+
+/// Move in algebraic notation and in words, eg. "Nf3: knight from g1 to f3"
+fn announcement(before: &Game, outcome: &MoveOutcome) -> String {
+    let description = before.describe_move(outcome.mov).unwrap_or_default();
+    format!("{}: {}", notation().localize(&outcome.san), description)
+}
+
+/// If the program should host a network game on a port or join one at an address, from the
+/// arguments "host <port>" or "join <address>"
+#[cfg(feature = "net")]
+fn online_arguments() -> Option<(bool, String)> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let index = args.iter().position(|arg| arg == "host" || arg == "join")?;
+    let address = args
+        .get(index + 1)
+        .cloned()
+        .unwrap_or_else(|| "7878".to_string());
+    Some((args[index] == "host", address))
+}
+
+/// Play against another player running the program, the host plays White
+#[cfg(feature = "net")]
+fn play_online(host: bool, address: &str) -> io::Result<()> {
+    use net::{Connection, Message, Session};
+    use std::net::{TcpListener, TcpStream};
+
+    let (mut connection, mut session) = if host {
+        let listener = TcpListener::bind(format!("0.0.0.0:{}", address))?;
+        println!("Waiting for an opponent on port {}", address);
+        let (stream, peer) = listener.accept()?;
+        let mut connection = Connection::new(stream);
+        let (session, hello) = Session::host(Game::new(), Color::White);
+        connection.send(&hello)?;
+        println!("{} joined", peer);
+        (connection, session)
+    } else {
+        let mut connection = Connection::new(TcpStream::connect(address)?);
+        let session = Session::join(&connection.receive()?)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        (connection, session)
+    };
+    println!(
+        "You play {:?}. Enter moves as on the board, or type \"draw\" to offer or accept a draw, \"resign\" to give up or \"quit\" to leave",
+        session.color()
+    );
+
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    while session.ending().is_none() {
+        if session.is_my_turn() {
+            rerender(session.game());
+            let line = match lines.next() {
+                Some(line) => line?,
+                None => return Ok(()),
+            };
+            let message = match line.trim() {
+                "q" | "quit" | "exit" => return Ok(()),
+                "resign" => session.resign(),
+                "draw" if session.draw_offer() == Some(!session.color()) => {
+                    session.answer_draw(true)
+                }
+                "draw" => session.offer_draw(),
+                text => parse_move(session.game(), text).and_then(|mov| session.propose_move(mov)),
+            };
+            match message {
+                Ok(message) => connection.send(&message)?,
+                Err(err) => println!("{}", err),
             }
+            continue;
         }
+        if session.pending_move().is_none() {
+            println!("Waiting for the opponent...");
+        }
+        let message = connection.receive()?;
+        match &message {
+            Message::Move { uci, .. } => println!("Opponent played {}", uci),
+            Message::Reject { reason, .. } => println!("Move rejected: {}", reason),
+            Message::OfferDraw => println!("Opponent offers a draw, type \"draw\" to accept"),
+            Message::DeclineDraw => println!("Opponent declined the draw"),
+            _ => {}
+        }
+        match session.receive(&message) {
+            Ok(Some(reply)) => connection.send(&reply)?,
+            Ok(None) => {}
+            Err(err) => println!("{}", err),
+        }
+    }
+    rerender(session.game());
+    println!("Game over: {:?}", session.ending().unwrap());
+    Ok(())
+}
+
+/// Move written as two squares (eg. "e2 e4") or in algebraic notation (eg. "Nf3")
+#[cfg(feature = "net")]
+fn parse_move(game: &Game, text: &str) -> Result<Move, &'static str> {
+    match text.split_whitespace().collect::<Vec<_>>()[..] {
+        [from, to] => Ok(Move {
+            from: from.parse()?,
+            to: to.parse()?,
+            promotion: None,
+        }),
+        _ => san::parse_san(game, text),
     }
 }
@@ -0,0 +1,89 @@
+//! Mate-in-N solver for puzzles, finding forced checkmates by searching every move
+//!
+//! The shortest mate is found, and the defender resists as long as possible. Drops in Crazyhouse,
+//! repetitions and the fifty-move rule are not considered.
+//!
+//! # Example
+//! ```
+//! use eliasfl_chess::{Game, Move};
+//!
+//! let game = Game::from_fen("k7/8/2K5/8/8/8/8/7R w - - 0 1").unwrap();
+//! assert_eq!(game.find_mate(1), None);
+//! let mate = game.find_mate(3).unwrap();
+//! assert_eq!(mate.len(), 3);
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::{Game, Move};
+
+impl Game {
+    /// Shortest forced checkmate by the player who's turn it is within `max_plies` half moves
+    ///
+    /// Returns the moves of both players, starting and ending with a move of the attacker, eg.
+    /// one move for a mate in one and three for a mate in two. None if there is no forced mate.
+    pub fn find_mate(&self, max_plies: u32) -> Option<Vec<Move>> {
+        // Nothing to search when no moves can lead to checkmate
+        if self.is_dead_position() {
+            return None;
+        }
+        _shortest_mate(&mut self.clone(), max_plies)
+    }
+}
+
+/// Shortest forced mate for the player who's turn it is within `max_plies` half moves
+fn _shortest_mate(game: &mut Game, max_plies: u32) -> Option<Vec<Move>> {
+    (1..=max_plies)
+        .step_by(2)
+        .find_map(|plies| _mate_within(game, plies))
+}
+
+/// First move (in sorted order) forcing mate within `plies` half moves, followed by the longest
+/// defence
+fn _mate_within(game: &mut Game, plies: u32) -> Option<Vec<Move>> {
+    let mut moves = game.legal_moves().to_vec();
+    // Legal moves are not generated in a fixed order
+    moves.sort_unstable();
+    for mov in moves {
+        let undo = game._make(mov);
+        let defence = _longest_defence(game, plies - 1);
+        game._unmake(undo);
+        if let Some(mut line) = defence {
+            line.insert(0, mov);
+            return Some(line);
+        }
+    }
+    None
+}
+
+/// The defence delaying mate the longest if every move of the player who's turn it is gets mated
+/// within `plies` half moves, empty if the player is already mated
+fn _longest_defence(game: &mut Game, plies: u32) -> Option<Vec<Move>> {
+    let mut defences = game.legal_moves().to_vec();
+    if defences.is_empty() {
+        return if game._is_mated(game.active_color) {
+            Some(Vec::new())
+        } else {
+            None
+        };
+    }
+    if plies < 2 {
+        return None;
+    }
+    defences.sort_unstable();
+    let mut longest: Option<Vec<Move>> = None;
+    for defence in defences {
+        let undo = game._make(defence);
+        let mate = _shortest_mate(game, plies - 1);
+        game._unmake(undo);
+        let mut line = mate?;
+        line.insert(0, defence);
+        if longest
+            .as_ref()
+            .map_or(true, |longest| line.len() > longest.len())
+        {
+            longest = Some(line);
+        }
+    }
+    longest
+}
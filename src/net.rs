@@ -0,0 +1,383 @@
+//! Network play between two players exchanging JSON messages, enabled with the "net" feature
+//!
+//! A [`Session`] keeps the game of one player in sync with the opponent's. Moves are proposed with
+//! [`Session::propose_move`] and only made when the opponent accepts them, so both games stay the
+//! same. Every [`Message`] is one line of JSON, eg. `{"type":"move","ply":0,"uci":"e2e4"}`, which
+//! [`Connection`] sends over a TCP stream. Web clients can send the same JSON over a WebSocket.
+//!
+//! # Example
+//! ```
+//! use eliasfl_chess::net::{Ending, Session};
+//! use eliasfl_chess::{Color, Game, Move};
+//!
+//! let (mut host, hello) = Session::host(Game::new(), Color::White);
+//! let mut guest = Session::join(&hello).unwrap();
+//! assert_eq!(guest.color(), Color::Black);
+//!
+//! let proposal = host.propose_move(Move::from_uci("e2e4").unwrap()).unwrap();
+//! let accept = guest.receive(&proposal).unwrap().unwrap();
+//! host.receive(&accept).unwrap();
+//! assert_eq!(host.game().to_fen(), guest.game().to_fen());
+//!
+//! let resign = guest.resign().unwrap();
+//! host.receive(&resign).unwrap();
+//! assert_eq!(host.ending(), Some(Ending::Resignation(Color::Black)));
+//! ```
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::BoardSize;
+use crate::{Color, Game, GameResult, Move, Piece};
+
+/// A message between the two players of a [`Session`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Message {
+    /// Start from the game with its history, variant and rules, where the receiver plays color
+    NewGame { game: Box<Game>, color: Color },
+    /// Propose the move in UCI notation as half move number ply, counted from 0
+    Move { ply: usize, uci: String },
+    /// The proposed move of ply was made
+    Accept { ply: usize },
+    /// The proposed move of ply was not made, with the reason and the receiver's position in FEN
+    Reject {
+        ply: usize,
+        reason: String,
+        fen: String,
+    },
+    /// The sender gives up
+    Resign,
+    /// The sender offers a draw
+    OfferDraw,
+    /// The sender accepts the draw offered by the receiver
+    AcceptDraw,
+    /// The sender declines the draw offered by the receiver
+    DeclineDraw,
+}
+
+impl Message {
+    /// Message as one line of JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("Message should serialize")
+    }
+
+    /// Message from JSON written by [`Message::to_json`]
+    pub fn from_json(json: &str) -> Result<Message, &'static str> {
+        serde_json::from_str(json).map_err(|_| "Invalid message")
+    }
+}
+
+/// How a session ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ending {
+    /// The game ended on the board, see [`Game::result`]
+    Game(GameResult),
+    /// The color resigned
+    Resignation(Color),
+    /// The players agreed to a draw
+    DrawAgreed,
+}
+
+/// One player's side of a game played over the network
+#[derive(Debug, Clone, PartialEq)]
+pub struct Session {
+    game: Game,
+    /// Color of the local player
+    color: Color,
+    /// Move proposed by the local player, made when the opponent accepts it
+    pending: Option<Move>,
+    /// Player who offered a draw that is not answered yet
+    draw_offer: Option<Color>,
+    /// Ending by resignation or agreement
+    ending: Option<Ending>,
+}
+
+impl Session {
+    /// Session of the local player playing color from game, which may already have moves made
+    ///
+    /// Returns the message to send to the opponent to start the game, see [`Session::join`]. The
+    /// message holds the whole game, so the opponent gets the same history, variant and rules.
+    pub fn host(game: Game, color: Color) -> (Session, Message) {
+        let message = Message::NewGame {
+            game: Box::new(game.clone()),
+            color: !color,
+        };
+        (Session::new(game, color), message)
+    }
+
+    /// Session of the opponent of a host, from the [`Message::NewGame`] it sent
+    ///
+    /// Err if the game has pieces, castling files or an en passant square outside of its board, or
+    /// if its position is not accepted by [`Game::validate`].
+    pub fn join(message: &Message) -> Result<Session, &'static str> {
+        match message {
+            Message::NewGame { game, color } => {
+                Self::_check_game(game)?;
+                Ok(Session::new(Game::clone(game), *color))
+            }
+            _ => Err("Expected a new game"),
+        }
+    }
+
+    fn _check_game(game: &Game) -> Result<(), &'static str> {
+        let size = game.config.board_size;
+        if game
+            .board
+            .iter()
+            .any(|(position, _)| !size.contains(&position))
+        {
+            return Err("Piece outside of the board");
+        }
+        // Castling is only possible on a standard board, between the rooks on the first rank
+        let files = game.castling_files;
+        let castling = game
+            .castling
+            .iter()
+            .any(|rights| rights.kingside || rights.queenside);
+        if !(1 <= files.queenside && files.queenside < files.king && files.king < files.kingside)
+            || files.kingside > 8
+            || (castling && size != BoardSize::STANDARD)
+        {
+            return Err("Invalid castling files");
+        }
+        if game
+            .en_passant
+            .is_some_and(|square| !size.contains(&square))
+        {
+            return Err("En passant square outside of the board");
+        }
+        game.validate().map_err(|_| "Invalid position")
+    }
+
+    fn new(game: Game, color: Color) -> Self {
+        Self {
+            game,
+            color,
+            pending: None,
+            draw_offer: None,
+            ending: None,
+        }
+    }
+
+    /// The game, the same for both players once proposed moves are answered
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    /// Color of the local player
+    pub fn color(&self) -> Color {
+        self.color
+    }
+
+    /// Move proposed by the local player that the opponent has not answered yet
+    pub fn pending_move(&self) -> Option<Move> {
+        self.pending
+    }
+
+    /// Player who offered a draw that is not answered yet
+    pub fn draw_offer(&self) -> Option<Color> {
+        self.draw_offer
+    }
+
+    /// How the game ended, None while it is in progress
+    pub fn ending(&self) -> Option<Ending> {
+        self.ending.or_else(|| self.game.result().map(Ending::Game))
+    }
+
+    /// If the local player may propose a move
+    pub fn is_my_turn(&self) -> bool {
+        self.ending().is_none() && self.pending.is_none() && self.game.side_to_move() == self.color
+    }
+
+    /// Propose a legal move of the local player, which is made when the opponent accepts it
+    ///
+    /// Pawns promote to the local player's promotion piece if the move has none. Returns the
+    /// message to send, or Err if it is not the player's turn or the move is illegal.
+    pub fn propose_move(&mut self, mov: Move) -> Result<Message, &'static str> {
+        if !self.is_my_turn() {
+            return Err("It is not your turn");
+        }
+        let mut mov = mov;
+        let pawn = matches!(self.game.board().get(&mov.from), Some(Piece::Pawn(_)));
+        if pawn && self.game._is_last_rank(&mov.to) {
+            mov.promotion
+                .get_or_insert(self.game.promotion_piece(self.color));
+        }
+        if !self.game.is_legal(mov) {
+            return Err("Illegal move");
+        }
+        self.pending = Some(mov);
+        Ok(Message::Move {
+            ply: self.game.history().len(),
+            uci: mov.to_uci(),
+        })
+    }
+
+    /// Give up the game, returns the message to send
+    pub fn resign(&mut self) -> Result<Message, &'static str> {
+        self._check_in_progress()?;
+        self.ending = Some(Ending::Resignation(self.color));
+        Ok(Message::Resign)
+    }
+
+    /// Offer the opponent a draw, returns the message to send
+    pub fn offer_draw(&mut self) -> Result<Message, &'static str> {
+        self._check_in_progress()?;
+        if self.draw_offer.is_some() {
+            return Err("A draw is already offered");
+        }
+        self.draw_offer = Some(self.color);
+        Ok(Message::OfferDraw)
+    }
+
+    /// Accept or decline the draw offered by the opponent, returns the message to send
+    pub fn answer_draw(&mut self, accept: bool) -> Result<Message, &'static str> {
+        self._check_in_progress()?;
+        if self.draw_offer != Some(!self.color) {
+            return Err("The opponent has not offered a draw");
+        }
+        self.draw_offer = None;
+        if accept {
+            self.ending = Some(Ending::DrawAgreed);
+            Ok(Message::AcceptDraw)
+        } else {
+            Ok(Message::DeclineDraw)
+        }
+    }
+
+    /// Handle a message from the opponent
+    ///
+    /// Returns the reply to send, if any. Illegal moves of the opponent are rejected with a
+    /// reply rather than an error, and a rejected move of the local player is taken back. Returns
+    /// Err if the message does not fit the session, eg. an answer to a draw that was not offered.
+    pub fn receive(&mut self, message: &Message) -> Result<Option<Message>, &'static str> {
+        match message {
+            Message::NewGame { .. } => Err("The game has already started"),
+            Message::Move { ply, uci } => Ok(Some(self._receive_move(*ply, uci))),
+            Message::Accept { ply } => {
+                let mov = self.pending.ok_or("No move was proposed")?;
+                if *ply != self.game.history().len() {
+                    return Err("Accepted move is out of sync");
+                }
+                self.pending = None;
+                self.game._make_move(mov)?;
+                // Moving declines a draw offered by the opponent
+                self.draw_offer = None;
+                Ok(None)
+            }
+            Message::Reject { fen, .. } => {
+                self.pending.take().ok_or("No move was proposed")?;
+                if *fen != self.game.to_fen() {
+                    return Err("The games are out of sync");
+                }
+                Ok(None)
+            }
+            Message::Resign => {
+                self._check_in_progress()?;
+                self.ending = Some(Ending::Resignation(!self.color));
+                Ok(None)
+            }
+            Message::OfferDraw => {
+                self._check_in_progress()?;
+                self.draw_offer = Some(!self.color);
+                Ok(None)
+            }
+            Message::AcceptDraw | Message::DeclineDraw => {
+                if self.draw_offer != Some(self.color) {
+                    return Err("No draw was offered");
+                }
+                self.draw_offer = None;
+                if *message == Message::AcceptDraw {
+                    self.ending = Some(Ending::DrawAgreed);
+                }
+                Ok(None)
+            }
+        }
+    }
+
+    /// Make the opponent's move of ply if it is legal, returns the reply
+    fn _receive_move(&mut self, ply: usize, uci: &str) -> Message {
+        let reject = |game: &Game, reason: &str| Message::Reject {
+            ply,
+            reason: reason.to_string(),
+            fen: game.to_fen(),
+        };
+        if self.ending().is_some() {
+            return reject(&self.game, "The game is over");
+        }
+        if ply != self.game.history().len() || self.game.side_to_move() == self.color {
+            return reject(&self.game, "Move is out of sync");
+        }
+        let mov = match Move::from_uci(uci) {
+            Ok(mov) if self.game.is_legal(mov) => mov,
+            Ok(_) => return reject(&self.game, "Illegal move"),
+            Err(error) => return reject(&self.game, error),
+        };
+        match self.game._make_move(mov) {
+            Ok(_) => {
+                self.draw_offer = None;
+                Message::Accept { ply }
+            }
+            Err(error) => reject(&self.game, error.message()),
+        }
+    }
+
+    /// Err if the game has ended
+    fn _check_in_progress(&self) -> Result<(), &'static str> {
+        match self.ending() {
+            Some(_) => Err("The game is over"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Longest line in bytes read by [`Connection::receive`], enough for the longest games
+pub const MAX_LINE_LENGTH: usize = 1 << 20;
+
+/// Messages sent and received as lines of JSON over a stream, eg. a `TcpStream`
+#[derive(Debug)]
+pub struct Connection<S> {
+    stream: BufReader<S>,
+}
+
+impl<S: Read + Write> Connection<S> {
+    /// Connection over a stream that is read from and written to
+    pub fn new(stream: S) -> Self {
+        Self {
+            stream: BufReader::new(stream),
+        }
+    }
+
+    /// The stream, eg. to shut down a `TcpStream`
+    pub fn into_inner(self) -> S {
+        self.stream.into_inner()
+    }
+
+    /// Write the message as one line
+    pub fn send(&mut self, message: &Message) -> io::Result<()> {
+        let stream = self.stream.get_mut();
+        writeln!(stream, "{}", message.to_json())?;
+        stream.flush()
+    }
+
+    /// Wait for the next message, Err if the stream is closed or the line is not a message
+    ///
+    /// Lines longer than [`MAX_LINE_LENGTH`] are not read to the end, so the connection should be
+    /// closed after such an error.
+    pub fn receive(&mut self) -> io::Result<Message> {
+        let mut line = String::new();
+        let limit = MAX_LINE_LENGTH as u64 + 1;
+        if (&mut self.stream).take(limit).read_line(&mut line)? == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        if line.len() > MAX_LINE_LENGTH {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Message is too long",
+            ));
+        }
+        Message::from_json(&line).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+}
@@ -0,0 +1,139 @@
+//! Piece letters of algebraic notation in other languages and with figurines
+//!
+//! [`Notation::to_san`] and [`Notation::parse_san`] write and read moves like [`san`](crate::san)
+//! with the letters of a [`Notation`], eg. "Sf3" for Nf3 in German and Swedish. Figurine notation
+//! draws the pieces, eg. "♞f3", the same for both colors. Castling and squares are written the
+//! same in every notation.
+//!
+//! # Example
+//! ```
+//! use eliasfl_chess::notation::Notation;
+//! use eliasfl_chess::Game;
+//!
+//! let game = Game::new();
+//! let mov = Notation::Swedish.parse_san(&game, "Sf3").unwrap();
+//! assert_eq!(Notation::Figurine.to_san(&game, mov).unwrap(), "♞f3");
+//! assert_eq!(Notation::German.localize("exd8=Q+"), "exd8=D+");
+//! assert_eq!(Notation::German.to_standard("Lb5"), "Bb5");
+//! ```
+
+use alloc::string::String;
+use core::str::FromStr;
+
+use crate::fen::piece_char;
+use crate::san;
+use crate::{Color, Game, Move, Piece, PieceKind};
+
+/// Language of the piece letters, see the [module documentation](self)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Notation {
+    /// K, Q, R, B and N, as in PGN
+    #[default]
+    English,
+    /// K (König), D (Dame), T (Turm), L (Läufer) and S (Springer)
+    German,
+    /// K (kung), D (dam), T (torn), L (löpare) and S (springare)
+    Swedish,
+    /// ♚, ♛, ♜, ♝ and ♞, as drawn on the board
+    Figurine,
+}
+
+impl Notation {
+    /// Every notation
+    pub const ALL: [Notation; 4] = [
+        Notation::English,
+        Notation::German,
+        Notation::Swedish,
+        Notation::Figurine,
+    ];
+
+    /// Name of the notation in lowercase, eg. "swedish"
+    pub fn name(&self) -> &'static str {
+        match self {
+            Notation::English => "english",
+            Notation::German => "german",
+            Notation::Swedish => "swedish",
+            Notation::Figurine => "figurine",
+        }
+    }
+
+    /// Letter or figurine of kind, None for pawns which have none
+    pub fn letter(&self, kind: PieceKind) -> Option<char> {
+        let piece = Piece::new(kind, Color::White);
+        match (self, kind) {
+            (_, PieceKind::Pawn) => None,
+            (Notation::English, _) => Some(piece_char(&piece)),
+            (Notation::Figurine, _) => Some(piece.symbol()),
+            (_, PieceKind::King) => Some('K'),
+            (_, PieceKind::Queen) => Some('D'),
+            (_, PieceKind::Rook) => Some('T'),
+            (_, PieceKind::Bishop) => Some('L'),
+            (_, PieceKind::Knight) => Some('S'),
+        }
+    }
+
+    /// Kind of piece written with letter, figurines of either color are recognized
+    pub fn kind(&self, letter: char) -> Option<PieceKind> {
+        [
+            PieceKind::King,
+            PieceKind::Queen,
+            PieceKind::Rook,
+            PieceKind::Bishop,
+            PieceKind::Knight,
+        ]
+        .iter()
+        .copied()
+        .find(|&kind| {
+            self.letter(kind) == Some(letter)
+                || (*self == Notation::Figurine
+                    && Piece::new(kind, Color::Black).symbol() == letter)
+        })
+    }
+
+    /// Write the standard algebraic notation san with the letters of the notation, eg. "Nf3"
+    /// becomes "Sf3" in German
+    pub fn localize(&self, san: &str) -> String {
+        san.chars()
+            .map(|c| match san::piece_from_letter(c, Color::White) {
+                Some(piece) => self.letter(piece.kind()).unwrap_or(c),
+                None => c,
+            })
+            .collect()
+    }
+
+    /// Write san in the notation with English letters, eg. "Sf3" becomes "Nf3" for German
+    ///
+    /// Letters that are not pieces in the notation are kept, so English moves are understood too.
+    pub fn to_standard(&self, san: &str) -> String {
+        san.chars()
+            .map(|c| match self.kind(c) {
+                Some(kind) => Notation::English.letter(kind).unwrap_or(c),
+                None => c,
+            })
+            .collect()
+    }
+
+    /// Write the legal move `mov` of the player who's turn it is in the notation, see
+    /// [`san::to_san`]
+    pub fn to_san(&self, game: &Game, mov: Move) -> Result<String, &'static str> {
+        Ok(self.localize(&san::to_san(game, mov)?))
+    }
+
+    /// Find the legal move written in the notation, see [`san::parse_san`]
+    pub fn parse_san(&self, game: &Game, san: &str) -> Result<Move, &'static str> {
+        san::parse_san(game, &self.to_standard(san))
+    }
+}
+
+impl FromStr for Notation {
+    type Err = &'static str;
+
+    /// Parse the name of a notation, eg. "german", in any case
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Notation::ALL
+            .iter()
+            .copied()
+            .find(|notation| notation.name().eq_ignore_ascii_case(s.trim()))
+            .ok_or("Unknown notation, should be english, german, swedish or figurine")
+    }
+}
@@ -0,0 +1,117 @@
+//! Handicap games where the stronger player starts without a piece, eg. for lessons
+//!
+//! The player giving odds plays White, except for pawn and move where the stronger player plays
+//! Black so the weaker player moves first. The odds are written as an `Odds` tag in PGN.
+//!
+//! # Example
+//! ```
+//! use eliasfl_chess::odds::Odds;
+//! use eliasfl_chess::Game;
+//!
+//! let game = Game::new_with_odds(Odds::QueenOdds);
+//! assert!(!game.board().contains_key(&"d1".parse().unwrap()));
+//! assert!(game.to_pgn().contains("[Odds \"Queen odds\"]"));
+//! assert_eq!("rook".parse(), Ok(Odds::RookOdds));
+//! ```
+
+use core::fmt;
+use core::str::FromStr;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{Color, Game, Piece, Position};
+
+/// Piece removed from the starting position of a handicap game
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Odds {
+    /// Black plays without the f7 pawn and White moves first
+    PawnAndMove,
+    /// White plays without the f2 pawn
+    PawnOdds,
+    /// White plays without the b1 knight
+    KnightOdds,
+    /// White plays without the a1 rook
+    RookOdds,
+    /// White plays without the queen
+    QueenOdds,
+}
+
+impl Odds {
+    /// All handicaps from the smallest to the largest
+    pub const ALL: [Odds; 5] = [
+        Odds::PawnAndMove,
+        Odds::PawnOdds,
+        Odds::KnightOdds,
+        Odds::RookOdds,
+        Odds::QueenOdds,
+    ];
+
+    /// Color of the stronger player, who gives the odds
+    pub fn giver(&self) -> Color {
+        match self {
+            Odds::PawnAndMove => Color::Black,
+            _ => Color::White,
+        }
+    }
+
+    /// Square and piece removed from the starting position
+    pub fn removed(&self) -> (Position, Piece) {
+        let (square, piece) = match self {
+            Odds::PawnAndMove => ("f7", Piece::Pawn(Color::Black)),
+            Odds::PawnOdds => ("f2", Piece::Pawn(Color::White)),
+            Odds::KnightOdds => ("b1", Piece::Knight(Color::White)),
+            Odds::RookOdds => ("a1", Piece::Rook(Color::White)),
+            Odds::QueenOdds => ("d1", Piece::Queen(Color::White)),
+        };
+        (square.parse().expect("Square is valid"), piece)
+    }
+}
+
+impl fmt::Display for Odds {
+    /// Name of the handicap as in the PGN tag, eg. "Queen odds"
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Odds::PawnAndMove => "Pawn and move",
+            Odds::PawnOdds => "Pawn odds",
+            Odds::KnightOdds => "Knight odds",
+            Odds::RookOdds => "Rook odds",
+            Odds::QueenOdds => "Queen odds",
+        })
+    }
+}
+
+impl FromStr for Odds {
+    type Err = &'static str;
+
+    /// Parse the name of the handicap in any case, with or without " odds", eg. "Queen odds",
+    /// "rook" or "pawn and move"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_ascii_lowercase();
+        match name.strip_suffix(" odds").unwrap_or(&name) {
+            "pawn and move" => Ok(Odds::PawnAndMove),
+            "pawn" => Ok(Odds::PawnOdds),
+            "knight" => Ok(Odds::KnightOdds),
+            "rook" => Ok(Odds::RookOdds),
+            "queen" => Ok(Odds::QueenOdds),
+            _ => Err("Odds should be pawn and move, pawn, knight, rook or queen"),
+        }
+    }
+}
+
+impl Game {
+    /// Create a handicap game from the standard starting position without the piece of `odds`
+    ///
+    /// Giving rook odds also loses the right to castle on that side.
+    pub fn new_with_odds(odds: Odds) -> Self {
+        let mut game = Game::new();
+        let (position, piece) = odds.removed();
+        game.board.remove(&position);
+        if let Piece::Rook(color) = piece {
+            game.castling[color.index()].queenside = false;
+        }
+        game.odds = Some(odds);
+        game
+    }
+}
@@ -0,0 +1,112 @@
+//! The same position seen from another side of the board, eg. to test that evaluation is
+//! symmetric
+//!
+//! - [`Game::flipped`] turns the board upside down and swaps the colors, so the player to move
+//!   has the position the other player had
+//! - [`Game::mirrored`] swaps the a and h sides of the board, with the same player to move
+//!
+//! Both start a new game from the position, without the history. Drawing the board from Black's
+//! side is chosen per call with
+//! [`BoardFormatter::perspective`](crate::render::BoardFormatter::perspective).
+//!
+//! # Example
+//! ```
+//! use eliasfl_chess::{Color, Engine, Game};
+//!
+//! let mut game = Game::new();
+//! game.make_move_san("e4").unwrap();
+//! let flipped = game.flipped();
+//! assert_eq!(flipped.side_to_move(), Color::White);
+//! assert_eq!(
+//!     flipped.to_fen(),
+//!     "rnbqkbnr/pppp1ppp/8/4p3/8/8/PPPPPPPP/RNBQKBNR w KQkq e6 0 1"
+//! );
+//! let engine = Engine::new(1);
+//! assert_eq!(engine.evaluate(&flipped), -engine.evaluate(&game));
+//! assert_eq!(game.mirrored().legal_move_count(), game.legal_move_count());
+//! ```
+
+use crate::config::BoardSize;
+use crate::{Board, Game, Piece, Position};
+
+impl Game {
+    /// The position with the board upside down and the colors swapped, see
+    /// [`orientation`](crate::orientation)
+    ///
+    /// The other player is to move, with the castling rights, reserve and rules of the player
+    /// they replace.
+    pub fn flipped(&self) -> Game {
+        let mut game = self._reoriented(Position::flipped, true);
+        game.active_color = !self.active_color;
+        game.castling.swap(0, 1);
+        game.config.sides.swap(0, 1);
+        if let Some(crazyhouse) = &mut game.crazyhouse {
+            crazyhouse.reserves.swap(0, 1);
+        }
+        game._update_state();
+        game
+    }
+
+    /// The position with the a and h sides of the board swapped and the same player to move, see
+    /// [`orientation`](crate::orientation)
+    ///
+    /// Castling rights are lost, as the kings and rooks leave their starting squares.
+    pub fn mirrored(&self) -> Game {
+        let mut game = self._reoriented(Position::mirrored, false);
+        for rights in &mut game.castling {
+            rights.kingside = false;
+            rights.queenside = false;
+        }
+        game._update_state();
+        game
+    }
+
+    /// New game with every piece moved to `square(position, board size)`, the rest of the
+    /// position kept
+    ///
+    /// Pieces outside of the board are left out.
+    fn _reoriented(
+        &self,
+        square: fn(&Position, BoardSize) -> Option<Position>,
+        swap_colors: bool,
+    ) -> Game {
+        let size = self.config.board_size;
+        let square = |position: &Position| square(position, size);
+        let mut board = Board::new();
+        for (position, piece) in self.pieces() {
+            let target = match square(&position) {
+                Some(target) => target,
+                None => continue,
+            };
+            let piece = if swap_colors {
+                Piece::new(piece.kind(), !piece.color())
+            } else {
+                piece
+            };
+            board.insert(target, piece);
+        }
+        let mut crazyhouse = self.crazyhouse;
+        if let Some(crazyhouse) = &mut crazyhouse {
+            // Promoted pawns are marked by the bit of their square
+            crazyhouse.promoted = (0..64)
+                .filter(|index| crazyhouse.promoted & 1 << index != 0)
+                .filter_map(Position::from_index)
+                .filter_map(|position| square(&position))
+                .fold(0, |promoted, position| promoted | 1 << position.index());
+        }
+        Game {
+            board,
+            active_color: self.active_color,
+            castling: self.castling,
+            castling_files: self.castling_files,
+            chess960: self.chess960,
+            en_passant: self.en_passant.as_ref().and_then(square),
+            halfmove_clock: self.halfmove_clock,
+            fullmove_number: self.fullmove_number,
+            config: self.config,
+            crazyhouse,
+            variant: self.variant,
+            ..Game::new()
+        }
+    }
+}
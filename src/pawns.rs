@@ -0,0 +1,105 @@
+//! Pawn structure: doubled, isolated, backward and passed pawns of a player
+//!
+//! - Doubled pawns share a file with another pawn of the same color
+//! - Isolated pawns have no pawn of the same color on the neighbouring files
+//! - Backward pawns are behind the pawns of the same color on the neighbouring files and cannot
+//!   advance safely, as an opponent pawn attacks the square in front of them
+//! - Passed pawns have no opponent pawn in front of them on the same or neighbouring files, nor a
+//!   pawn of the same color in front of them on the same file
+//!
+//! # Example
+//! ```
+//! use eliasfl_chess::{Color, Game, Position};
+//!
+//! let game = Game::from_fen("4k3/8/8/1P6/8/2P1p3/2P5/4K3 w - - 0 1").unwrap();
+//! let squares = |positions: Vec<Position>| -> Vec<String> {
+//!     positions.iter().map(|square| square.to_string()).collect()
+//! };
+//! let white = game.pawn_structure(Color::White);
+//! assert_eq!(squares(white.doubled), ["c2", "c3"]);
+//! assert_eq!(squares(white.passed), ["c3", "b5"]);
+//! let black = game.pawn_structure(Color::Black);
+//! assert_eq!(squares(black.isolated), ["e3"]);
+//! assert_eq!(squares(black.passed), ["e3"]);
+//! ```
+
+use alloc::vec::Vec;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{Color, Game, Piece, Position};
+
+/// Squares of the weak and strong pawns of one player, each from a1 to h8, see
+/// [`Game::pawn_structure`]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PawnStructure {
+    /// Pawns on a file with another pawn of the same color
+    pub doubled: Vec<Position>,
+    /// Pawns without pawns of the same color on the neighbouring files
+    pub isolated: Vec<Position>,
+    /// Pawns behind the pawns on the neighbouring files that cannot advance safely
+    pub backward: Vec<Position>,
+    /// Pawns without opponent pawns in front of them on the same or neighbouring files, the
+    /// frontmost of doubled pawns
+    pub passed: Vec<Position>,
+}
+
+impl Game {
+    /// Doubled, isolated, backward and passed pawns of color, see [`pawns`](crate::pawns)
+    pub fn pawn_structure(&self, color: Color) -> PawnStructure {
+        let own = self._pawns(color);
+        let opponent = self._pawns(!color);
+        let forward: i32 = match color {
+            Color::White => 1,
+            Color::Black => -1,
+        };
+        // Rank of square relative to the player, so "ahead" is always a higher rank
+        let relative = |square: &Position| square.rank as i32 * forward;
+        let neighbours = |a: &Position, b: &Position| (a.file as i32 - b.file as i32).abs() == 1;
+
+        let mut structure = PawnStructure::default();
+        for pawn in &own {
+            if own
+                .iter()
+                .any(|other| other != pawn && other.file == pawn.file)
+            {
+                structure.doubled.push(*pawn);
+            }
+            let beside: Vec<&Position> =
+                own.iter().filter(|other| neighbours(other, pawn)).collect();
+            if beside.is_empty() {
+                structure.isolated.push(*pawn);
+            } else if beside.iter().all(|other| relative(other) > relative(pawn)) {
+                let stop = self.config.board_size.relative_pos(pawn, 0, forward);
+                let stop_attacked = stop.is_some_and(|stop| {
+                    opponent.iter().any(|enemy| {
+                        neighbours(enemy, &stop) && enemy.rank as i32 == stop.rank as i32 + forward
+                    })
+                });
+                if stop_attacked {
+                    structure.backward.push(*pawn);
+                }
+            }
+            let blocked = opponent.iter().any(|enemy| {
+                (enemy.file == pawn.file || neighbours(enemy, pawn))
+                    && relative(enemy) > relative(pawn)
+            }) || own
+                .iter()
+                .any(|other| other.file == pawn.file && relative(other) > relative(pawn));
+            if !blocked {
+                structure.passed.push(*pawn);
+            }
+        }
+        structure
+    }
+
+    /// Squares of the pawns of color from a1 to h8
+    fn _pawns(&self, color: Color) -> Vec<Position> {
+        self.pieces_of(color)
+            .filter(|(_, piece)| matches!(piece, Piece::Pawn(_)))
+            .map(|(square, _)| square)
+            .collect()
+    }
+}
@@ -0,0 +1,496 @@
+//! Portable Game Notation (PGN) import and export, eg. `[Event "Casual"] 1. e4 e5 2. Nf3 {Good move} Nc6 *`
+//!
+//! Comments and numeric annotation glyphs (NAGs) are kept as [`MoveAnnotation`]s and variations in
+//! the game's [`MoveTree`](crate::variation::MoveTree). A `FEN` tag sets the starting position and an `Odds` tag the
+//! [handicap](crate::odds).
+//!
+//! # Example
+//! ```
+//! use eliasfl_chess::pgn;
+//!
+//! let pgn = pgn::parse("[White \"Morphy\"]\n\n1. e4 e5 2. Nf3 (2. f4) d6?! {Passive} 1-0").unwrap();
+//! assert_eq!(pgn.tag("White"), Some("Morphy"));
+//! assert_eq!(pgn.moves.len(), 4);
+//! assert_eq!(pgn.positions.len(), 5);
+//! assert_eq!(pgn.result.as_deref(), Some("1-0"));
+//! let annotation = pgn.game().annotation(3).unwrap();
+//! assert_eq!((annotation.nags.as_slice(), annotation.comment.as_deref()), (&[6][..], Some("Passive")));
+//! assert_eq!(pgn.game().movetext(), "1. e4 e5 2. Nf3 (2. f4) 2... d6 $6 {Passive}");
+//! ```
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::{format, vec};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::crazyhouse::Crazyhouse;
+use crate::variation::{MoveTree, NodeId};
+use crate::{san, Color, Game, GameResult, Move};
+
+/// Standard starting position, games starting elsewhere are written with a `FEN` tag
+const START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+/// Maximum length of a movetext line when writing PGN
+const LINE_LENGTH: usize = 80;
+
+/// Tag pairs, eg. `("Event", "Casual")`
+type Tags = Vec<(String, String)>;
+
+/// Move suffixes and their numeric annotation glyphs
+const SUFFIXES: [(&str, u8); 6] = [
+    ("!", 1),
+    ("?", 2),
+    ("!!", 3),
+    ("??", 4),
+    ("!?", 5),
+    ("?!", 6),
+];
+
+/// Numeric annotation glyphs and comment attached to a move, see [`Game::annotate`]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MoveAnnotation {
+    /// Numeric annotation glyphs, eg. 1 for a good move (!) or 4 for a blunder (??)
+    pub nags: Vec<u8>,
+    /// Text comment after the move
+    pub comment: Option<String>,
+}
+
+impl MoveAnnotation {
+    /// Annotation with the glyph of a move suffix, eg. "!?", None for unknown suffixes
+    pub fn from_suffix(suffix: &str) -> Option<Self> {
+        let (_, nag) = SUFFIXES.iter().find(|(symbol, _)| *symbol == suffix)?;
+        Some(Self {
+            nags: vec![*nag],
+            comment: None,
+        })
+    }
+}
+
+/// Part of the movetext
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    /// A move in SAN or the result
+    Move(String),
+    /// Numeric annotation glyph, written `$1` or as move suffix
+    Nag(u8),
+    /// Text between braces or after a semicolon
+    Comment(String),
+    /// Opening parenthesis, the following moves replace the last move
+    VariationStart,
+    /// Closing parenthesis, returning to the line before the variation
+    VariationEnd,
+}
+
+/// A game read from PGN
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pgn {
+    /// Tag pairs in the order they were written, eg. `("Event", "Casual")`
+    pub tags: Tags,
+    /// Moves of the main line
+    pub moves: Vec<Move>,
+    /// The game before the first move and after every move, so one more than the number of moves
+    pub positions: Vec<Game>,
+    /// Result at the end of the movetext, "1-0", "0-1", "1/2-1/2" or "*"
+    pub result: Option<String>,
+}
+
+impl Pgn {
+    /// Value of the tag with `name`
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The game after the last move
+    pub fn game(&self) -> &Game {
+        self.positions
+            .last()
+            .expect("Starting position is always included")
+    }
+}
+
+impl Game {
+    /// Load the game after the last move of a PGN game, see [`pgn::parse`](crate::pgn::parse)
+    pub fn from_pgn(pgn: &str) -> Result<Game, &'static str> {
+        parse(pgn).map(|pgn| pgn.game().clone())
+    }
+
+    /// The game as PGN with the moves made since it was created or loaded, see [`Game::history`]
+    ///
+    /// Games not starting from the standard position get `SetUp` and `FEN` tags. If the moves
+    /// don't lead to the current position, eg. after editing the board, only the current position
+    /// is written.
+    pub fn to_pgn(&self) -> String {
+        self._to_pgn_with(&[])
+    }
+
+    /// [`Game::to_pgn`] with `tags` replacing the values of the seven tag roster, eg.
+    /// `("White", "Engine")` or `("Result", "1-0")` for a game won by resignation
+    pub(crate) fn _to_pgn_with(&self, tags: &[(&str, &str)]) -> String {
+        let start = self._replayable_history().unwrap_or_else(|| self.to_fen());
+        let value = |tag: &str, default: &str| -> String {
+            tags.iter()
+                .find(|(name, _)| *name == tag)
+                .map_or(default, |(_, value)| value)
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+        };
+        let result = value("Result", self._pgn_result());
+        let mut pgn = String::new();
+        for (tag, default) in [
+            ("Event", "?"),
+            ("Site", "?"),
+            ("Date", "????.??.??"),
+            ("Round", "?"),
+            ("White", "?"),
+            ("Black", "?"),
+        ] {
+            pgn += &format!("[{} \"{}\"]\n", tag, value(tag, default));
+        }
+        pgn += &format!("[Result \"{}\"]\n", result);
+        if let Some(odds) = self.odds {
+            pgn += &format!("[Odds \"{}\"]\n", odds);
+        }
+        if start != START_FEN {
+            pgn += &format!("[SetUp \"1\"]\n[FEN \"{}\"]\n", start);
+        }
+        pgn.push('\n');
+
+        let movetext = self.movetext();
+        let mut tokens: Vec<&str> = movetext.split_whitespace().collect();
+        tokens.push(&result);
+        let mut line = String::new();
+        for token in tokens {
+            if !line.is_empty() && line.len() + 1 + token.len() > LINE_LENGTH {
+                pgn += &line;
+                pgn.push('\n');
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line += token;
+        }
+        pgn + &line + "\n"
+    }
+
+    /// Attach annotation to the move with index `ply` in [`Game::history`], adding its glyphs to
+    /// the earlier ones and replacing the comment if it has one
+    ///
+    /// Returns Err if there is no such move
+    pub fn annotate(&mut self, ply: usize, annotation: MoveAnnotation) -> Result<(), &'static str> {
+        let node = self
+            .tree
+            .path(self.node)
+            .and_then(|path| path.get(ply).copied())
+            .ok_or("No move to annotate")?;
+        let existing = self.tree.annotation_mut(node);
+        for nag in annotation.nags {
+            if !existing.nags.contains(&nag) {
+                existing.nags.push(nag);
+            }
+        }
+        if annotation.comment.is_some() {
+            existing.comment = annotation.comment;
+        }
+        Ok(())
+    }
+
+    /// Annotation of the move with index `ply` in [`Game::history`]
+    pub fn annotation(&self, ply: usize) -> Option<&MoveAnnotation> {
+        let node = *self.tree.path(self.node)?.get(ply)?;
+        self.tree.node(node)?.annotation.as_ref()
+    }
+
+    /// Moves made since the game was created or loaded in standard algebraic notation with move
+    /// numbers, annotations and [variations](crate::variation), eg.
+    /// "1. e4 $1 e5 {Symmetrical} (1... c5) 2. Nf3" or "12... Nf6" if Black moved first
+    ///
+    /// Empty if the moves don't lead to the current position, see [`Game::to_pgn`]
+    pub fn movetext(&self) -> String {
+        let start = match self._replayable_history() {
+            Some(start) => start,
+            None => return String::new(),
+        };
+        let mut game = self
+            ._start_position(&start)
+            .expect("Positions from the game are valid FEN");
+        let mut tokens = Vec::new();
+        self._write_line(&mut game, MoveTree::ROOT, true, &mut tokens);
+        tokens.join(" ")
+    }
+
+    /// Add the moves continuing from node to tokens, the main line with the variations after
+    /// each move, game is the position after the move of node
+    fn _write_line(&self, game: &mut Game, node: NodeId, number: bool, tokens: &mut Vec<String>) {
+        let mut node = node;
+        let mut number = number;
+        while let Some((&main, variations)) = self.tree[node].children.split_first() {
+            let before = game.clone();
+            self._write_move(game, main, number, tokens);
+            for &variation in variations {
+                let mut side_line = before.clone();
+                let first = tokens.len();
+                self._write_move(&mut side_line, variation, true, tokens);
+                self._write_line(&mut side_line, variation, false, tokens);
+                tokens[first].insert(0, '(');
+                tokens.last_mut().expect("Variation has a move").push(')');
+            }
+            // The move number is repeated for Black after a variation
+            number = !variations.is_empty();
+            node = main;
+        }
+    }
+
+    /// Add the move of node with its annotation to tokens and make it, with the move number if
+    /// White moves or `number` is set
+    fn _write_move(&self, game: &mut Game, node: NodeId, number: bool, tokens: &mut Vec<String>) {
+        let node = &self.tree[node];
+        let mov = node.mov.expect("Only the root has no move");
+        if game.active_color == Color::White {
+            tokens.push(format!("{}.", game.fullmove_number));
+        } else if number {
+            tokens.push(format!("{}...", game.fullmove_number));
+        }
+        let before = game.clone();
+        game._make_move(mov).expect("Moves of the tree are legal");
+        tokens.push(
+            san::_san_before_move(&before, mov, mov.promoted_to()) + san::_check_suffix(game),
+        );
+        if let Some(annotation) = &node.annotation {
+            for nag in &annotation.nags {
+                tokens.push(format!("${}", nag));
+            }
+            if let Some(comment) = &annotation.comment {
+                // Braces cannot be escaped inside comments
+                tokens.push(format!("{{{}}}", comment.replace('}', ")")));
+            }
+        }
+    }
+
+    /// Starting position in FEN if replaying the history from it leads to the current position
+    pub(crate) fn _replayable_history(&self) -> Option<String> {
+        let start = self.start_fen.clone().unwrap_or_else(|| self.to_fen());
+        let mut replay = self._start_position(&start).ok()?;
+        for mov in self.history() {
+            replay._make_move(*mov).ok()?;
+        }
+        if replay.board == self.board && replay.active_color == self.active_color {
+            Some(start)
+        } else {
+            None
+        }
+    }
+
+    /// Game from the position in FEN with the variant and rules of this game, which FEN leaves out
+    ///
+    /// Crazyhouse reserves start empty, so histories with captures before the start position
+    /// cannot be replayed.
+    pub(crate) fn _start_position(&self, fen: &str) -> Result<Game, &'static str> {
+        let mut game = Game::from_fen(fen)?;
+        game.variant = self.variant;
+        game.config = self.config;
+        game.chess960 = self.chess960;
+        game.castling_files = self.castling_files;
+        game.crazyhouse = self
+            .crazyhouse
+            .map(|crazyhouse| Crazyhouse::new(crazyhouse.keep_captures));
+        Ok(game)
+    }
+
+    /// Result as in PGN: "1-0" or "0-1" when a player has won, "1/2-1/2" for draws and "*"
+    /// while the game is in progress
+    pub(crate) fn _pgn_result(&self) -> &'static str {
+        match self.result() {
+            Some(GameResult::WhiteWins) => "1-0",
+            Some(GameResult::BlackWins) => "0-1",
+            Some(GameResult::Draw(_)) => "1/2-1/2",
+            None => "*",
+        }
+    }
+}
+
+/// Parse the first game of a PGN text and play its moves
+///
+/// Variations are added to the [move tree](crate::variation) of every position. Returns Err if a
+/// tag is malformed, the FEN tag is invalid or a move is illegal
+pub fn parse(text: &str) -> Result<Pgn, &'static str> {
+    let (tags, movetext) = _tags(text)?;
+    let mut start = match tags.iter().find(|(tag, _)| tag == "FEN") {
+        Some((_, fen)) => Game::from_fen(fen)?,
+        None => Game::new(),
+    };
+    if let Some((_, odds)) = tags.iter().find(|(tag, _)| tag == "Odds") {
+        start.odds = Some(odds.parse()?);
+    }
+    let mut game = start.clone();
+    // Nodes to return to at the end of each variation
+    let mut variations: Vec<NodeId> = Vec::new();
+    let mut result = None;
+    for token in _movetext_tokens(movetext)? {
+        let annotation = match token {
+            Token::Move(token) if matches!(token.as_str(), "1-0" | "0-1" | "1/2-1/2" | "*") => {
+                if variations.is_empty() {
+                    result = Some(token);
+                    break;
+                }
+                continue;
+            }
+            Token::Move(token) => {
+                let mov = san::parse_san(&game, &token)?;
+                game._make_move(mov)?;
+                continue;
+            }
+            Token::VariationStart => {
+                let parent = game.tree[game.node]
+                    .parent
+                    .ok_or("Variation before the first move")?;
+                variations.push(game.node);
+                game.go_to(parent)?;
+                continue;
+            }
+            Token::VariationEnd => {
+                let node = variations.pop().expect("Variations are balanced");
+                game.go_to(node)?;
+                continue;
+            }
+            Token::Nag(nag) => MoveAnnotation {
+                nags: vec![nag],
+                comment: None,
+            },
+            Token::Comment(comment) => MoveAnnotation {
+                nags: Vec::new(),
+                comment: Some(comment),
+            },
+        };
+        // Annotations before the first move have no move to belong to
+        if let Some(ply) = game.history().len().checked_sub(1) {
+            game.annotate(ply, annotation)?;
+        }
+    }
+    let mut position = start;
+    position.tree = game.tree.clone();
+    let mut pgn = Pgn {
+        tags,
+        moves: Vec::new(),
+        positions: vec![position.clone()],
+        result,
+    };
+    for node in game.tree.main_line() {
+        let mov = game.tree[node].mov.expect("Only the root has no move");
+        position._make_move(mov)?;
+        position.tree = game.tree.clone();
+        position.node = node;
+        pgn.moves.push(mov);
+        pgn.positions.push(position.clone());
+    }
+    Ok(pgn)
+}
+
+/// Tag pairs at the start of text and the remaining movetext
+///
+/// Values are quoted strings where `\"` and `\\` escape a quote and a backslash, so they may
+/// contain `]`.
+fn _tags(text: &str) -> Result<(Tags, &str), &'static str> {
+    let mut tags = Vec::new();
+    let mut rest = text.trim_start();
+    while let Some(tag) = rest.strip_prefix('[') {
+        let (name, quoted) = tag
+            .trim_start()
+            .split_once(char::is_whitespace)
+            .ok_or("Tag without value")?;
+        let quoted = quoted
+            .trim_start()
+            .strip_prefix('"')
+            .ok_or("Tag value should be quoted")?;
+        let mut value = String::new();
+        let mut chars = quoted.char_indices();
+        let end = loop {
+            match chars.next() {
+                Some((i, '"')) => break i,
+                Some((_, '\\')) => match chars.next() {
+                    Some((_, c)) => value.push(c),
+                    None => return Err("Unclosed tag"),
+                },
+                Some((_, c)) => value.push(c),
+                None => return Err("Unclosed tag"),
+            }
+        };
+        rest = quoted[end + 1..]
+            .trim_start()
+            .strip_prefix(']')
+            .ok_or("Unclosed tag")?
+            .trim_start();
+        tags.push((name.to_string(), value));
+    }
+    Ok((tags, rest))
+}
+
+/// Moves, glyphs, comments, variations and result, without move numbers
+fn _movetext_tokens(movetext: &str) -> Result<Vec<Token>, &'static str> {
+    let mut tokens = Vec::new();
+    let mut word = String::new();
+    let mut variation_depth = 0;
+    let mut chars = movetext.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' | ';' => {
+                let end = if c == '{' { '}' } else { '\n' };
+                let mut comment = String::new();
+                loop {
+                    match chars.next() {
+                        Some(c) if c == end => break,
+                        Some(c) => comment.push(c),
+                        None if c == '{' => return Err("Unclosed comment"),
+                        None => break,
+                    }
+                }
+                _push_word(&mut word, &mut tokens);
+                tokens.push(Token::Comment(comment.trim().to_string()));
+            }
+            '(' => {
+                _push_word(&mut word, &mut tokens);
+                tokens.push(Token::VariationStart);
+                variation_depth += 1;
+            }
+            ')' if variation_depth == 0 => return Err("Unmatched variation end"),
+            ')' => {
+                _push_word(&mut word, &mut tokens);
+                tokens.push(Token::VariationEnd);
+                variation_depth -= 1;
+            }
+            // Move numbers may be written without space, eg. "1.e4" or "12...Nf6"
+            '.' => _push_word(&mut word, &mut tokens),
+            _ if c.is_whitespace() => _push_word(&mut word, &mut tokens),
+            _ => word.push(c),
+        }
+    }
+    if variation_depth > 0 {
+        return Err("Unclosed variation");
+    }
+    _push_word(&mut word, &mut tokens);
+    Ok(tokens)
+}
+
+/// Add the move or glyph in word to tokens and clear it, move numbers are left out
+fn _push_word(word: &mut String, tokens: &mut Vec<Token>) {
+    if let Some(nag) = word.strip_prefix('$') {
+        // Unknown glyphs are skipped
+        if let Ok(nag) = nag.parse() {
+            tokens.push(Token::Nag(nag));
+        }
+    } else if !word.is_empty() && !word.chars().all(|c| c.is_ascii_digit()) {
+        let san = word.trim_end_matches(['!', '?']);
+        let annotation = MoveAnnotation::from_suffix(&word[san.len()..]);
+        tokens.push(Token::Move(san.to_string()));
+        if let Some(annotation) = annotation {
+            tokens.extend(annotation.nags.into_iter().map(Token::Nag));
+        }
+    }
+    word.clear();
+}
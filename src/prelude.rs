@@ -0,0 +1,18 @@
+//! The stable core of the library for glob import
+//!
+//! ```
+//! use eliasfl_chess::prelude::*;
+//!
+//! let mut game = Game::new();
+//! game.make_move_san("e4").unwrap();
+//! assert_eq!(game.board().get(&Position::from_string("e4".to_string()).unwrap()), Some(&Piece::Pawn(Color::White)));
+//! ```
+//!
+//! Types exported here only change in breaking releases. The modules for variants, analysis and
+//! tooling are still evolving and are imported from their modules. Errors are returned as
+//! `&'static str` messages, except for [`SetupProblem`] when setting up positions.
+
+pub use crate::{
+    Board, CastlingRights, Color, DrawReason, Game, GameBuilder, GameState, Move, Piece, PieceKind,
+    Position, SetupProblem, Square, SquareShade, Variant,
+};
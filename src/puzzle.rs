@@ -0,0 +1,148 @@
+//! Tactics puzzles from the [Lichess puzzle database](https://database.lichess.org/#puzzles)
+//!
+//! Every line of the CSV file is a puzzle with the columns `PuzzleId,FEN,Moves,Rating,
+//! RatingDeviation,Popularity,NbPlays,Themes,GameUrl,OpeningTags`. The FEN is the position before
+//! the opponent's last move, which is the first of the UCI moves. The user then plays every other
+//! move, and the moves in between are the opponent's replies.
+//!
+//! # Example
+//! ```
+//! use eliasfl_chess::puzzle::{Puzzle, Verdict};
+//! use eliasfl_chess::Move;
+//!
+//! let csv = "PuzzleId,FEN,Moves,Rating,RatingDeviation,Popularity,NbPlays,Themes,GameUrl,OpeningTags
+//! 00sHx,q3k1nr/1pp1nQpp/3p4/1P2p3/4P3/B1PP1b2/B5PP/5K2 b k - 0 17,e8d7 a2e6 d7d8 f7f8,1760,80,83,72,mate mateIn2 middlegame short,https://lichess.org/yyznGmXs/black#34,Italian_Game";
+//! let puzzles = Puzzle::parse_lichess_csv(csv).unwrap();
+//! let puzzle = &puzzles[0];
+//! assert_eq!(puzzle.id, "00sHx");
+//! assert_eq!(puzzle.rating, Some(1760));
+//!
+//! let first = Move::from_uci("a2e6").unwrap();
+//! assert_eq!(puzzle.verify_solution(&[first]), Verdict::Correct);
+//! let mate = Move::from_uci("f7f8").unwrap();
+//! assert_eq!(puzzle.verify_solution(&[first, mate]), Verdict::Solved);
+//! assert_eq!(puzzle.verify_solution(&[mate]), Verdict::Wrong(0));
+//! ```
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::{Game, GameResult, Move};
+
+/// Result of checking the user's moves against the solution of a puzzle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    /// Every move of the solution was played
+    Solved,
+    /// The moves are correct so far, but the solution continues
+    Correct,
+    /// The user's move with this index is illegal, not part of the solution or after the end of it
+    Wrong(usize),
+}
+
+/// A position with the only good moves for the player who's turn it is
+#[derive(Debug, Clone, PartialEq)]
+pub struct Puzzle {
+    /// Identifier in the puzzle database, eg. "00sHx"
+    pub id: String,
+    /// Position the user starts solving from, after the opponent's move
+    pub game: Game,
+    /// Moves of the user, each followed by the opponent's reply except for the last
+    pub solution: Vec<Move>,
+    /// Glicko rating of the puzzle, None if it is missing
+    pub rating: Option<u32>,
+    /// Motifs of the puzzle, eg. "fork" or "mateIn2"
+    pub themes: Vec<String>,
+}
+
+impl Puzzle {
+    /// Puzzle from one line of the Lichess puzzle CSV, see the [module documentation](self)
+    ///
+    /// Return Err if a column is missing, the FEN or a move is invalid or the moves are illegal
+    pub fn from_lichess_csv(line: &str) -> Result<Puzzle, &'static str> {
+        let columns: Vec<&str> = line.trim().split(',').collect();
+        if columns.len() < 3 {
+            return Err("Puzzle should have the columns PuzzleId, FEN and Moves");
+        }
+        let mut moves = columns[2]
+            .split_whitespace()
+            .map(Move::from_uci)
+            .collect::<Result<Vec<Move>, _>>()?;
+        if moves.len() < 2 {
+            return Err("Puzzle should have the opponent's move and a solution");
+        }
+        let mut game = Game::from_fen(columns[1])?;
+        game._make_move(moves.remove(0))?;
+        let mut replay = game.clone();
+        for &mov in &moves {
+            replay
+                ._make_move(mov)
+                .map_err(|_| "Solution has an illegal move")?;
+        }
+        Ok(Puzzle {
+            id: columns[0].to_string(),
+            game,
+            solution: moves,
+            rating: columns.get(3).and_then(|rating| rating.parse().ok()),
+            themes: columns
+                .get(7)
+                .map(|themes| themes.split_whitespace().map(str::to_string).collect())
+                .unwrap_or_default(),
+        })
+    }
+
+    /// Puzzles from the lines of a Lichess puzzle CSV file, skipping the header and empty lines
+    ///
+    /// Return Err if any puzzle is invalid, see [`Puzzle::from_lichess_csv`]
+    pub fn parse_lichess_csv(csv: &str) -> Result<Vec<Puzzle>, &'static str> {
+        csv.lines()
+            .filter(|line| !line.trim().is_empty() && !line.starts_with("PuzzleId,"))
+            .map(Puzzle::from_lichess_csv)
+            .collect()
+    }
+
+    /// Number of moves the user has to play
+    pub fn user_moves(&self) -> usize {
+        (self.solution.len() + 1) / 2
+    }
+
+    /// Check the user's moves, without the opponent's replies, against the solution
+    ///
+    /// A move giving checkmate is also accepted instead of the last move of the solution, as
+    /// there may be several mates in one. Pawns promote to the player's promotion piece if the
+    /// move has no promotion piece.
+    pub fn verify_solution(&self, user_moves: &[Move]) -> Verdict {
+        let mut game = self.game.clone();
+        for (index, &mov) in user_moves.iter().enumerate() {
+            let expected = match self.solution.get(index * 2) {
+                Some(&expected) => expected,
+                None => return Verdict::Wrong(index),
+            };
+            let color = game.side_to_move();
+            let promotion = |mov: Move| mov.promotion.unwrap_or(game.promotion_piece(color));
+            let is_expected = mov.from == expected.from
+                && mov.to == expected.to
+                && promotion(mov) == promotion(expected);
+            let last = index * 2 + 1 == self.solution.len();
+            if game._make_move(mov).is_err() {
+                return Verdict::Wrong(index);
+            }
+            let mates = matches!(
+                game.result(),
+                Some(GameResult::WhiteWins | GameResult::BlackWins)
+            );
+            if !(is_expected || last && mates) {
+                return Verdict::Wrong(index);
+            }
+            if last && index + 1 < user_moves.len() {
+                return Verdict::Wrong(index + 1);
+            } else if last {
+                return Verdict::Solved;
+            }
+            let reply = self.solution[index * 2 + 1];
+            game._make_move(reply)
+                .expect("Replies of the solution are legal");
+        }
+        Verdict::Correct
+    }
+}
@@ -0,0 +1,179 @@
+//! Training questions about a position, eg. "List all legal moves of the knight on c3"
+//!
+//! A [`Quiz`] picks random questions about a game and keeps the score. Answers are checked
+//! leniently: squares may be listed in any order, separated by spaces or commas.
+//!
+//! # Example
+//! ```
+//! use eliasfl_chess::quiz::{Question, Quiz};
+//! use eliasfl_chess::Game;
+//!
+//! let game = Game::new();
+//! let question = Question::LegalMoves("g1".parse().unwrap());
+//! assert_eq!(question.text(&game), "List all legal moves of the knight on g1");
+//! assert!(question.is_correct(&game, "h3, f3"));
+//!
+//! let mut quiz = Quiz::new(7);
+//! let question = quiz.next_question(&game);
+//! let answer = question.answer(&game);
+//! assert!(quiz.answer(&game, &question, &answer));
+//! assert_eq!((quiz.correct, quiz.asked), (1, 1));
+//! ```
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::simulate::Rng;
+use crate::{Game, Piece, Position, SquareShade};
+
+/// A question about a position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Question {
+    /// If the square is light or dark, for learning the coordinates
+    SquareColor(Position),
+    /// Destinations of the legal moves of the piece on the square, "none" if it cannot move
+    LegalMoves(Position),
+    /// If the piece on the square is defended by a piece of its own color
+    Defended(Position),
+}
+
+impl Question {
+    /// The question as shown to the user
+    pub fn text(&self, game: &Game) -> String {
+        let name = |position: &Position| {
+            game.board
+                .get(position)
+                .map_or("piece", |piece| piece.kind().name())
+        };
+        match self {
+            Question::SquareColor(position) => format!("Is {} a light or dark square?", position),
+            Question::LegalMoves(position) => format!(
+                "List all legal moves of the {} on {}",
+                name(position),
+                position
+            ),
+            Question::Defended(position) => {
+                format!("Is the {} on {} defended?", name(position), position)
+            }
+        }
+    }
+
+    /// The correct answer, eg. "light", "f3 h3" or "yes"
+    pub fn answer(&self, game: &Game) -> String {
+        match self {
+            Question::SquareColor(position) if position.shade() == SquareShade::Dark => {
+                "dark".to_string()
+            }
+            Question::SquareColor(_) => "light".to_string(),
+            Question::LegalMoves(position) => {
+                let destinations = _destinations(game, *position);
+                if destinations.is_empty() {
+                    return "none".to_string();
+                }
+                let squares: Vec<String> = destinations.iter().map(Position::to_string).collect();
+                squares.join(" ")
+            }
+            Question::Defended(position) => {
+                let defended = game
+                    .board
+                    .get(position)
+                    .is_some_and(|piece| !game.attackers_of(*position, piece.color()).is_empty());
+                if defended { "yes" } else { "no" }.to_string()
+            }
+        }
+    }
+
+    /// If `answer` is correct, ignoring case, the order of squares and separators
+    pub fn is_correct(&self, game: &Game, answer: &str) -> bool {
+        let answer = answer.trim().to_ascii_lowercase();
+        match self {
+            Question::LegalMoves(position) => {
+                let mut squares: Vec<Position> = Vec::new();
+                for word in answer.split(|c: char| c == ',' || c.is_whitespace()) {
+                    match word.parse() {
+                        Ok(square) if !squares.contains(&square) => squares.push(square),
+                        Ok(_) => {}
+                        Err(_) if word.is_empty() || word == "none" => {}
+                        Err(_) => return false,
+                    }
+                }
+                squares.sort_unstable();
+                squares == _destinations(game, *position)
+            }
+            _ => answer == self.answer(game),
+        }
+    }
+}
+
+/// Sorted destinations of the legal moves of the piece on position
+fn _destinations(game: &Game, position: Position) -> Vec<Position> {
+    let mut destinations: Vec<Position> = game
+        .legal_moves_from(position)
+        .iter()
+        .map(|mov| mov.to)
+        .collect();
+    // Promotions have one move per piece
+    destinations.dedup();
+    destinations
+}
+
+/// Random questions with the number of correct answers
+#[derive(Debug, Clone)]
+pub struct Quiz {
+    rng: Rng,
+    /// Number of questions answered
+    pub asked: u32,
+    /// Number of correct answers
+    pub correct: u32,
+}
+
+impl Quiz {
+    /// Quiz asking the same questions for the same seed and games
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: Rng::new(seed),
+            asked: 0,
+            correct: 0,
+        }
+    }
+
+    /// Random question about game: a square color, the legal moves of a piece of the player
+    /// who's turn it is or if a piece other than a king is defended
+    pub fn next_question(&mut self, game: &Game) -> Question {
+        let color = game.active_color;
+        let movable: Vec<Position> = game
+            .pieces_of(color)
+            .map(|(position, _)| position)
+            .collect();
+        let defendable: Vec<Position> = game
+            .pieces()
+            .filter(|(_, piece)| !matches!(piece, Piece::King(_)))
+            .map(|(position, _)| position)
+            .collect();
+        match self.rng.below(3) {
+            1 if !movable.is_empty() => {
+                Question::LegalMoves(movable[self.rng.below(movable.len())])
+            }
+            2 if !defendable.is_empty() => {
+                Question::Defended(defendable[self.rng.below(defendable.len())])
+            }
+            _ => {
+                let board_size = game.config.board_size;
+                let file = self.rng.below(board_size.files() as usize) as u8 + 1;
+                let rank = self.rng.below(board_size.ranks() as usize) as u8 + 1;
+                Question::SquareColor(Position { file, rank })
+            }
+        }
+    }
+
+    /// Check the answer to question and count it in the score
+    pub fn answer(&mut self, game: &Game, question: &Question, answer: &str) -> bool {
+        let correct = question.is_correct(game, answer);
+        self.asked += 1;
+        if correct {
+            self.correct += 1;
+        }
+        correct
+    }
+}
@@ -0,0 +1,202 @@
+//! Random legal positions from a seed, eg. endgames for training or positions for tests
+//!
+//! The same seed and [`Constraints`] always give the same position. Positions are set up like
+//! with [`Game::builder`], so they pass [`Game::validate`] and the player who is not to move is
+//! never in check. They have no castling rights or en passant square.
+//!
+//! # Example
+//! ```
+//! use eliasfl_chess::random::Constraints;
+//! use eliasfl_chess::{Color, Game, Piece};
+//!
+//! let constraints = Constraints::endgame("KRvK").unwrap().side_to_move(Color::White);
+//! let game = Game::random_position(7, &constraints).unwrap();
+//! assert!(game.validate().is_ok());
+//! assert_eq!(game.pieces().count(), 3);
+//! assert!(game.pieces().any(|(_, piece)| piece == Piece::Rook(Color::White)));
+//! assert_eq!(game, Game::random_position(7, &constraints).unwrap());
+//!
+//! let game = Game::random_position(7, &Constraints::default().no_queens()).unwrap();
+//! assert!(game.pieces().all(|(_, piece)| !matches!(piece, Piece::Queen(_))));
+//! ```
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::builder::GameBuilder;
+use crate::san::piece_from_letter;
+use crate::simulate::Rng;
+use crate::{Color, Game, Piece, Position};
+
+/// Positions tried before giving up, most fail only when the kings attack each other or the
+/// player who is not to move is in check
+const MAX_ATTEMPTS: usize = 1000;
+
+/// What random positions may contain, see [`Game::random_position`]
+///
+/// The default is up to 16 random pieces besides the kings, including queens, with a random
+/// player to move.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Constraints {
+    /// Pieces besides the kings, None for random pieces
+    pub pieces: Option<Vec<Piece>>,
+    /// Most random pieces besides the kings
+    pub max_pieces: usize,
+    /// If random pieces may be queens
+    pub queens: bool,
+    /// Player to move, None for a random player
+    pub side_to_move: Option<Color>,
+}
+
+impl Default for Constraints {
+    fn default() -> Self {
+        Self {
+            pieces: None,
+            max_pieces: 16,
+            queens: true,
+            side_to_move: None,
+        }
+    }
+}
+
+impl Constraints {
+    /// Positions with exactly the pieces of an endgame, written as White's pieces and then
+    /// Black's, eg. "KRvK" or "KPK"
+    ///
+    /// Return Err if a side does not start with its king or a letter is not a piece
+    pub fn endgame(material: &str) -> Result<Constraints, &'static str> {
+        let material = material.trim().replace('v', "");
+        let black_king = match material.strip_prefix('K').and_then(|rest| rest.find('K')) {
+            Some(index) => index + 1,
+            None => return Err("Endgame should be the pieces of both kings, eg. \"KRvK\""),
+        };
+        let sides = [
+            (Color::White, &material[1..black_king]),
+            (Color::Black, &material[black_king + 1..]),
+        ];
+        let mut pieces = Vec::new();
+        for (color, letters) in sides {
+            for letter in letters.chars() {
+                let piece = match letter {
+                    'P' => Some(Piece::Pawn(color)),
+                    'K' => None,
+                    _ => piece_from_letter(letter, color),
+                };
+                pieces.push(piece.ok_or("Invalid piece in endgame, should be Q, R, B, N or P")?);
+            }
+        }
+        Ok(Constraints {
+            pieces: Some(pieces),
+            ..Constraints::default()
+        })
+    }
+
+    /// Random pieces are never queens
+    pub fn no_queens(mut self) -> Self {
+        self.queens = false;
+        self
+    }
+
+    /// At most `max_pieces` random pieces besides the kings
+    pub fn max_pieces(mut self, max_pieces: usize) -> Self {
+        self.max_pieces = max_pieces;
+        self
+    }
+
+    /// Color is always the player to move
+    pub fn side_to_move(mut self, color: Color) -> Self {
+        self.side_to_move = Some(color);
+        self
+    }
+
+    /// Pieces besides the kings for one attempt
+    fn _pieces(&self, rng: &mut Rng) -> Vec<Piece> {
+        if let Some(pieces) = &self.pieces {
+            return pieces.clone();
+        }
+        // Pawns are the most common pieces, as in games
+        let kinds: &[fn(Color) -> Piece] = if self.queens {
+            &[
+                Piece::Pawn,
+                Piece::Pawn,
+                Piece::Pawn,
+                Piece::Knight,
+                Piece::Bishop,
+                Piece::Rook,
+                Piece::Queen,
+            ]
+        } else {
+            &[
+                Piece::Pawn,
+                Piece::Pawn,
+                Piece::Pawn,
+                Piece::Knight,
+                Piece::Bishop,
+                Piece::Rook,
+            ]
+        };
+        let count = rng.below(self.max_pieces + 1);
+        (0..count)
+            .map(|_| {
+                let color = _random_color(rng);
+                kinds[rng.below(kinds.len())](color)
+            })
+            .collect()
+    }
+}
+
+/// White or Black with equal probability
+fn _random_color(rng: &mut Rng) -> Color {
+    if rng.below(2) == 0 {
+        Color::White
+    } else {
+        Color::Black
+    }
+}
+
+impl Game {
+    /// Random legal position from seed with the pieces allowed by constraints, see
+    /// [`random`](crate::random)
+    ///
+    /// Return Err if the constraints allow no legal position, eg. with more than 8 pawns of a
+    /// color, or none was found after many attempts
+    pub fn random_position(seed: u64, constraints: &Constraints) -> Result<Game, &'static str> {
+        if constraints
+            .pieces
+            .as_ref()
+            .map_or(constraints.max_pieces, Vec::len)
+            > 62
+        {
+            return Err("At most 62 pieces fit on the board besides the kings");
+        }
+        let mut rng = Rng::new(seed);
+        for _ in 0..MAX_ATTEMPTS {
+            let mut builder = GameBuilder::new();
+            let mut pieces = vec![Piece::King(Color::White), Piece::King(Color::Black)];
+            pieces.extend(constraints._pieces(&mut rng));
+            for piece in pieces {
+                // Pawns cannot stand on the first or last rank
+                let ranks = match piece {
+                    Piece::Pawn(_) => 2..=7,
+                    _ => 1..=8,
+                };
+                let empty: Vec<Position> = (0..64)
+                    .filter_map(Position::from_index)
+                    .filter(|square| ranks.contains(&square.rank))
+                    .filter(|square| !builder.board.contains_key(square))
+                    .collect();
+                if empty.is_empty() {
+                    return Err("The pieces do not fit on the board");
+                }
+                builder.board.insert(empty[rng.below(empty.len())], piece);
+            }
+            let color = constraints
+                .side_to_move
+                .unwrap_or_else(|| _random_color(&mut rng));
+            if let Ok(game) = builder.active_color(color).build() {
+                return Ok(game);
+            }
+        }
+        Err("No legal position found for the constraints")
+    }
+}
@@ -0,0 +1,126 @@
+//! Lines of squares from a square in a direction, stopping at the first piece
+//!
+//! Rooks, bishops and queens move along rays, and pawns step forward along one. Move generation,
+//! attack detection and pins all cast the same rays.
+//!
+//! # Example
+//! ```
+//! use eliasfl_chess::config::BoardSize;
+//! use eliasfl_chess::ray::{self, Ray};
+//! use eliasfl_chess::{Game, Piece};
+//!
+//! let game = Game::from_fen("4k3/8/8/8/p7/8/8/R3K3 w - - 0 1").unwrap();
+//! let a1 = "a1".parse().unwrap();
+//! // The rook sees the pawn on a4 and nothing behind it
+//! let squares: Vec<String> = Ray::new(game.board(), BoardSize::STANDARD, a1, (0, 1))
+//!     .map(|square| square.to_string())
+//!     .collect();
+//! assert_eq!(squares, ["a2", "a3", "a4"]);
+//! assert_eq!(ray::direction(&a1, &"h8".parse().unwrap()), Some((1, 1)));
+//! assert_eq!(ray::sliding_directions(&game.board()[&a1]).len(), 4);
+//! ```
+
+use crate::config::BoardSize;
+use crate::{Board, Piece, Position};
+
+/// Step between neighbouring squares of a ray as (file, rank) offsets, eg. (1, 1) towards h8
+pub type Direction = (i32, i32);
+
+/// Directions of rooks, along files and ranks
+pub const ROOK_DIRECTIONS: [Direction; 4] = [(0, 1), (0, -1), (1, 0), (-1, 0)];
+
+/// Directions of bishops, along diagonals
+pub const BISHOP_DIRECTIONS: [Direction; 4] = [(1, 1), (1, -1), (-1, 1), (-1, -1)];
+
+/// Directions of queens and the steps of kings
+pub const QUEEN_DIRECTIONS: [Direction; 8] = [
+    (0, 1),
+    (0, -1),
+    (1, 0),
+    (-1, 0),
+    (1, 1),
+    (1, -1),
+    (-1, 1),
+    (-1, -1),
+];
+
+/// Jumps of knights as (file, rank) offsets
+pub const KNIGHT_OFFSETS: [Direction; 8] = [
+    (2, 1),
+    (2, -1),
+    (-2, 1),
+    (-2, -1),
+    (1, 2),
+    (1, -2),
+    (-1, 2),
+    (-1, -2),
+];
+
+/// Directions piece slides in, empty for kings, knights and pawns
+pub fn sliding_directions(piece: &Piece) -> &'static [Direction] {
+    match piece {
+        Piece::Queen(_) => &QUEEN_DIRECTIONS,
+        Piece::Rook(_) => &ROOK_DIRECTIONS,
+        Piece::Bishop(_) => &BISHOP_DIRECTIONS,
+        Piece::King(_) | Piece::Knight(_) | Piece::Pawn(_) => &[],
+    }
+}
+
+/// Direction of the ray from `from` through `to`, None if they are not on the same file, rank or
+/// diagonal or are the same square
+pub fn direction(from: &Position, to: &Position) -> Option<Direction> {
+    let on_line = from.is_same_file(to) || from.is_same_rank(to) || from.is_same_diagonal(to);
+    if !on_line || from == to {
+        return None;
+    }
+    Some((
+        (to.file as i32 - from.file as i32).signum(),
+        (to.rank as i32 - from.rank as i32).signum(),
+    ))
+}
+
+/// Squares from a square in a direction up to the edge of the board or the first occupied square,
+/// which is included
+#[derive(Debug, Clone)]
+pub struct Ray<'a> {
+    board: &'a Board,
+    board_size: BoardSize,
+    next: Option<Position>,
+    direction: Direction,
+}
+
+impl<'a> Ray<'a> {
+    /// Ray starting next to `from`, which is not included
+    pub fn new(
+        board: &'a Board,
+        board_size: BoardSize,
+        from: Position,
+        direction: Direction,
+    ) -> Self {
+        Self {
+            board,
+            board_size,
+            next: Self::_step(board_size, from, direction),
+            direction,
+        }
+    }
+
+    /// Square next to `from` in direction, None at the edge of the board
+    fn _step(board_size: BoardSize, from: Position, direction: Direction) -> Option<Position> {
+        board_size.relative_pos(&from, direction.0, direction.1)
+    }
+}
+
+impl Iterator for Ray<'_> {
+    type Item = Position;
+
+    fn next(&mut self) -> Option<Position> {
+        let square = self.next?;
+        self.next = if self.board.contains_key(&square) {
+            None
+        } else {
+            Self::_step(self.board_size, square, self.direction)
+        };
+        Some(square)
+    }
+}
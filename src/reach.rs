@@ -0,0 +1,255 @@
+//! Bounded search of the positions reachable by legal moves of both players, eg. to prove that
+//! checkmate is impossible in a dead position
+//!
+//! # Example
+//! ```
+//! use eliasfl_chess::reach::{explore, Explored, Step};
+//! use eliasfl_chess::Game;
+//!
+//! // Kings and a bishop locked behind a wall of pawns never run out of moves
+//! let game = Game::from_fen("4k3/8/8/8/p1p1p1p1/P1P1P1P1/8/2B1K3 w - - 0 1").unwrap();
+//! let mut moves = 0;
+//! let explored = explore(&game, 10_000, |_, _, after| {
+//!     moves += 1;
+//!     if after.legal_moves().is_empty() {
+//!         Step::Stop
+//!     } else {
+//!         Step::Continue
+//!     }
+//! });
+//! assert_eq!(explored, Explored::All);
+//! assert!(moves > 100);
+//! ```
+
+use alloc::collections::{BTreeSet, VecDeque};
+use alloc::vec::Vec;
+
+use crate::ray::QUEEN_DIRECTIONS;
+use crate::{Board, Color, Game, Move, OnceLock, Piece, PieceKind, Position, Variant};
+
+/// Most positions searched by [`Game::is_dead_position`] before giving up
+const MAX_DEAD_POSITIONS: usize = 10_000;
+
+/// If [`explore`] should go on after a move
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    /// Expand the position after the move
+    Continue,
+    /// End the search
+    Stop,
+}
+
+/// How a search of [`explore`] ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Explored {
+    /// Every reachable position was visited
+    All,
+    /// The visitor returned [`Step::Stop`]
+    Stopped,
+    /// More than the maximum number of positions can be reached
+    Limit,
+}
+
+/// Visit the positions reachable from game breadth-first, calling visit with the position before
+/// and after every legal move
+///
+/// Positions with the same [`Game::position_key`] are expanded once. The search ends when visit
+/// returns [`Step::Stop`] or more than `max_positions` positions were found. The visited games
+/// have only the position of game, without its moves, clock or rules for repetitions.
+pub fn explore(
+    game: &Game,
+    max_positions: usize,
+    mut visit: impl FnMut(&Game, Move, &Game) -> Step,
+) -> Explored {
+    // Only the position is copied, not the moves of the game
+    let start = Game {
+        board: game.board,
+        active_color: game.active_color,
+        castling: game.castling,
+        castling_files: game.castling_files,
+        chess960: game.chess960,
+        en_passant: game.en_passant,
+        halfmove_clock: game.halfmove_clock,
+        fullmove_number: game.fullmove_number,
+        config: game.config,
+        crazyhouse: game.crazyhouse,
+        variant: game.variant,
+        ..Game::new()
+    };
+    let mut seen = BTreeSet::new();
+    seen.insert(start.position_key());
+    let mut queue = VecDeque::new();
+    queue.push_back(start);
+    while let Some(position) = queue.pop_front() {
+        for &mov in position.legal_moves().iter() {
+            let mut after = position.clone();
+            after._make(mov);
+            if visit(&position, mov, &after) == Step::Stop {
+                return Explored::Stopped;
+            }
+            if seen.insert(after.position_key()) {
+                if seen.len() > max_positions {
+                    return Explored::Limit;
+                }
+                queue.push_back(after);
+            }
+        }
+    }
+    Explored::All
+}
+
+/// Verdict of [`Game::is_dead_position`], kept until a pawn moves or a piece is captured
+///
+/// The verdict is stored with its [`DeadPositionKey`], so writing the public fields directly
+/// decides again instead of returning a stale verdict.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct DeadPositionCache(OnceLock<(DeadPositionKey, bool)>);
+
+/// Pieces other than kings and bishops, and the number of bishops of each color on each shade.
+/// Only kings and bishops move in locked positions, and bishops stay on their shade.
+type DeadPositionKey = (Board, [usize; 4]);
+
+impl DeadPositionCache {
+    /// Cached verdict of the position with key, deciding it if the cache is empty
+    fn get(&self, key: DeadPositionKey, decide: impl Fn() -> bool) -> bool {
+        let (cached_key, dead) = self.0.get_or_init(|| (key, decide()));
+        if *cached_key == key {
+            *dead
+        } else {
+            decide()
+        }
+    }
+}
+
+impl Game {
+    /// If no sequence of legal moves can end in checkmate, eg. king and bishop against king or
+    /// kings and bishops locked behind a wall of pawns
+    ///
+    /// Locked positions are proven by trying every move of both players. They are assumed to be
+    /// alive when a pawn is not blocked by a pawn, a king can walk to an undefended pawn, a piece
+    /// can be captured or too many positions can be reached. Only standard chess without drops is
+    /// considered. The verdict is kept for the following moves until a pawn moves or a piece is
+    /// captured.
+    pub fn is_dead_position(&self) -> bool {
+        if self.variant != Variant::Standard || self.crazyhouse.is_some() {
+            return false;
+        }
+        if self._insufficient_material() {
+            return true;
+        }
+        self.dead_position
+            .get(self._dead_position_key(), || self._is_locked_dead())
+    }
+
+    /// Key of the position in the [`DeadPositionCache`]
+    fn _dead_position_key(&self) -> DeadPositionKey {
+        let mut others = Board::new();
+        let mut bishops = [0; 4];
+        for (position, piece) in self.pieces() {
+            match piece {
+                Piece::King(_) => {}
+                Piece::Bishop(color) => {
+                    bishops[color.index() * 2 + position.shade() as usize] += 1;
+                }
+                _ => {
+                    others.insert(position, piece);
+                }
+            }
+        }
+        (others, bishops)
+    }
+
+    /// If kings, bishops and pawns are locked so that no sequence of moves can checkmate
+    fn _is_locked_dead(&self) -> bool {
+        // Queens, rooks and knights can get past a wall of pawns, and pawns that are not blocked
+        // by a pawn can soon move
+        let locked = self.pieces().all(|(position, piece)| match piece {
+            Piece::Pawn(color) => {
+                let front = self
+                    .config
+                    .board_size
+                    .relative_pos(&position, 0, color.direction());
+                front.and_then(|front| self.board.get(&front)) == Some(&Piece::Pawn(!color))
+            }
+            Piece::King(_) | Piece::Bishop(_) => true,
+            _ => false,
+        });
+        if !locked
+            || self._is_checkmate(self.active_color)
+            || self._king_reaches_pawn(Color::White)
+            || self._king_reaches_pawn(Color::Black)
+        {
+            return false;
+        }
+        let explored = explore(self, MAX_DEAD_POSITIONS, |before, mov, after| {
+            let pawn_move = matches!(before.board.get(&mov.from), Some(Piece::Pawn(_)));
+            if pawn_move || before.board.contains_key(&mov.to) {
+                return Step::Stop;
+            }
+            // Only moves giving check can checkmate
+            let color = after.active_color;
+            if after._king_is_threatened(color) && after._is_checkmate(color) {
+                Step::Stop
+            } else {
+                Step::Continue
+            }
+        });
+        explored == Explored::All
+    }
+
+    /// If the king of color can walk to a pawn of the other player that no pawn defends, avoiding
+    /// squares attacked by pawns and ignoring the other pieces
+    fn _king_reaches_pawn(&self, color: Color) -> bool {
+        let attacked = |square: Position, by: Color| {
+            [-1, 1].iter().any(|&file| {
+                let pawn = self
+                    .config
+                    .board_size
+                    .relative_pos(&square, file, -by.direction());
+                pawn.and_then(|pawn| self.board.get(&pawn)) == Some(&Piece::Pawn(by))
+            })
+        };
+        let mut reached = [false; 64];
+        let mut squares: Vec<Position> = self
+            .pieces_of(color)
+            .filter(|(_, piece)| *piece == Piece::King(color))
+            .map(|(square, _)| square)
+            .collect();
+        while let Some(square) = squares.pop() {
+            for &(file, rank) in &QUEEN_DIRECTIONS {
+                let next = match self.config.board_size.relative_pos(&square, file, rank) {
+                    Some(next) if !reached[next.index()] => next,
+                    _ => continue,
+                };
+                reached[next.index()] = true;
+                match self.board.get(&next) {
+                    Some(Piece::Pawn(pawn)) if *pawn == color => {}
+                    Some(Piece::Pawn(_)) if !attacked(next, !color) => return true,
+                    Some(Piece::Pawn(_)) => {}
+                    _ if attacked(next, !color) => {}
+                    _ => squares.push(next),
+                }
+            }
+        }
+        false
+    }
+
+    /// If neither player has the pieces to checkmate: lone kings, a single knight or bishop, or
+    /// only bishops on squares of one color
+    fn _insufficient_material(&self) -> bool {
+        let (mut bishops, mut knights) = (0, 0);
+        let mut bishop_squares = [false; 2];
+        for (position, piece) in self.pieces() {
+            match piece.kind() {
+                PieceKind::King => {}
+                PieceKind::Bishop => {
+                    bishops += 1;
+                    bishop_squares[position.shade() as usize] = true;
+                }
+                PieceKind::Knight => knights += 1,
+                _ => return false,
+            }
+        }
+        bishops + knights <= 1 || (knights == 0 && !(bishop_squares[0] && bishop_squares[1]))
+    }
+}
@@ -0,0 +1,326 @@
+//! Terminal rendering of the board with annotations
+//!
+//! # Example
+//! ```
+//! use eliasfl_chess::render::RenderOptions;
+//! use eliasfl_chess::Game;
+//!
+//! let mut game = Game::new();
+//! game.make_move_san("e4").unwrap();
+//! let options = RenderOptions {
+//!     ascii: true,
+//!     last_move: true,
+//!     ..RenderOptions::default()
+//! };
+//! println!("{}", game.render(options));
+//! ```
+
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::fen::piece_char;
+use crate::{Color, Engine, Game, Move, Piece, Position};
+
+/// ANSI escape code resetting colors
+const RESET: &str = "\x1B[0m";
+
+/// Kind of highlight, decides the color of annotated squares
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Highlight {
+    /// Suggested move (green)
+    BestMove,
+    /// Threat against the player (red)
+    Threat,
+    /// Squares of the last move (yellow)
+    LastMove,
+    /// King in check (bright red)
+    Check,
+}
+impl Highlight {
+    /// ANSI escape code for the background color
+    fn background(&self) -> &'static str {
+        match self {
+            Self::BestMove => "\x1B[42m",
+            Self::Threat => "\x1B[41m",
+            Self::LastMove => "\x1B[43m",
+            Self::Check => "\x1B[101m",
+        }
+    }
+}
+
+/// Marking drawn on the board
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Annotation {
+    /// Arrow along a move, empty squares on the way are drawn with arrow characters
+    Arrow(Move, Highlight),
+    /// Colored square
+    Square(Position, Highlight),
+}
+
+/// Arrow character pointing in the direction of file and rank offsets
+fn arrow_char(file: i32, rank: i32) -> char {
+    match (file.signum(), rank.signum()) {
+        (0, 1) => '↑',
+        (0, -1) => '↓',
+        (1, 0) => '→',
+        (-1, 0) => '←',
+        (1, 1) => '↗',
+        (1, -1) => '↘',
+        (-1, 1) => '↖',
+        _ => '↙',
+    }
+}
+
+/// Squares after `from` up to and including `to` if they are on the same rank, file or diagonal
+fn path(mov: &Move) -> Vec<Position> {
+    let file = mov.to.file as i32 - mov.from.file as i32;
+    let rank = mov.to.rank as i32 - mov.from.rank as i32;
+    if file != 0 && rank != 0 && file.abs() != rank.abs() {
+        // Knight move -> no straight path
+        return vec![mov.to];
+    }
+    (1..=file.abs().max(rank.abs()))
+        .filter_map(|step| {
+            mov.from
+                .relative_pos(step * file.signum(), step * rank.signum())
+        })
+        .collect()
+}
+
+/// How to draw the board, see [`Game::render`]
+///
+/// The default is what the [`Display`](fmt::Display) of [`Game`] draws: Unicode pieces with White at
+/// the bottom, coordinates and no highlights.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RenderOptions {
+    /// Draw pieces as FEN letters (uppercase for White) instead of Unicode symbols
+    pub ascii: bool,
+    /// Draw the board from Black's side, with rank 1 at the top and the h file to the left
+    pub flipped: bool,
+    /// Draw the file letters and rank numbers, and the color to move in the corner
+    pub coordinates: bool,
+    /// Highlight the squares of the last move, see [`Game::last_move`]
+    pub last_move: bool,
+    /// Highlight the king of the player who's turn it is when in check
+    pub check: bool,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            ascii: false,
+            flipped: false,
+            coordinates: true,
+            last_move: false,
+            check: false,
+        }
+    }
+}
+
+/// Board drawn with [`RenderOptions`] and annotations, the [`Display`](fmt::Display) of [`Game`]
+/// uses the defaults
+///
+/// # Example
+/// ```
+/// use eliasfl_chess::render::BoardFormatter;
+/// use eliasfl_chess::Game;
+///
+/// let game = Game::new();
+/// let diagram = BoardFormatter::new(&game).ascii(true).flipped(true).to_string();
+/// assert_eq!(diagram.lines().nth(1), Some("1 R N B K Q B N R"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct BoardFormatter<'a> {
+    game: &'a Game,
+    options: RenderOptions,
+    annotations: Vec<Annotation>,
+}
+
+impl<'a> BoardFormatter<'a> {
+    /// Unicode pieces with White at the bottom and coordinates
+    pub fn new(game: &'a Game) -> Self {
+        Self {
+            game,
+            options: RenderOptions::default(),
+            annotations: Vec::new(),
+        }
+    }
+
+    /// Replace all options
+    pub fn options(mut self, options: RenderOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Draw pieces as FEN letters (uppercase for White) instead of Unicode symbols
+    pub fn ascii(mut self, ascii: bool) -> Self {
+        self.options.ascii = ascii;
+        self
+    }
+
+    /// Draw the board from Black's side, with rank 1 at the top and the h file to the left
+    pub fn flipped(mut self, flipped: bool) -> Self {
+        self.options.flipped = flipped;
+        self
+    }
+
+    /// Draw the board from the side of color, with its pieces at the bottom
+    pub fn perspective(self, color: Color) -> Self {
+        self.flipped(color == Color::Black)
+    }
+
+    /// Draw the file letters and rank numbers, and the color to move in the corner
+    pub fn coordinates(mut self, coordinates: bool) -> Self {
+        self.options.coordinates = coordinates;
+        self
+    }
+
+    /// Highlight the squares of the last move, see [`Game::last_move`]
+    pub fn last_move(mut self, last_move: bool) -> Self {
+        self.options.last_move = last_move;
+        self
+    }
+
+    /// Highlight the king of the player who's turn it is when in check
+    pub fn check(mut self, check: bool) -> Self {
+        self.options.check = check;
+        self
+    }
+
+    /// Draw annotations, later annotations are drawn on top of earlier ones
+    pub fn annotations(mut self, annotations: &[Annotation]) -> Self {
+        self.annotations.extend_from_slice(annotations);
+        self
+    }
+}
+
+impl fmt::Display for BoardFormatter<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let options = self.options;
+        // Background color and arrow character per square, indexed [rank - 1][file - 1]
+        let mut highlights: [[Option<Highlight>; 8]; 8] = [[None; 8]; 8];
+        let mut arrows: [[Option<char>; 8]; 8] = [[None; 8]; 8];
+        let mut highlighted = match self.game.last_move() {
+            Some(mov) if options.last_move => vec![
+                Annotation::Square(mov.from, Highlight::LastMove),
+                Annotation::Square(mov.to, Highlight::LastMove),
+            ],
+            _ => Vec::new(),
+        };
+        let color = self.game.active_color;
+        if options.check && self.game._king_is_threatened(color) {
+            if let Some((king, _)) = self
+                .game
+                .pieces_of(color)
+                .find(|(_, piece)| *piece == Piece::King(color))
+            {
+                highlighted.push(Annotation::Square(king, Highlight::Check));
+            }
+        }
+        for annotation in highlighted.iter().chain(&self.annotations) {
+            match annotation {
+                Annotation::Square(position, highlight) => {
+                    highlights[position.rank as usize - 1][position.file as usize - 1] =
+                        Some(*highlight);
+                }
+                Annotation::Arrow(mov, highlight) => {
+                    let symbol = arrow_char(
+                        mov.to.file as i32 - mov.from.file as i32,
+                        mov.to.rank as i32 - mov.from.rank as i32,
+                    );
+                    highlights[mov.from.rank as usize - 1][mov.from.file as usize - 1] =
+                        Some(*highlight);
+                    for position in path(mov) {
+                        let (r, f) = (position.rank as usize - 1, position.file as usize - 1);
+                        highlights[r][f] = Some(*highlight);
+                        arrows[r][f] = Some(symbol);
+                    }
+                }
+            }
+        }
+
+        let board_size = self.game.config.board_size;
+        let files: Vec<u8> = if options.flipped {
+            (1..=board_size.files()).rev().collect()
+        } else {
+            (1..=board_size.files()).collect()
+        };
+        let ranks: Vec<u8> = if options.flipped {
+            (1..=board_size.ranks()).collect()
+        } else {
+            (1..=board_size.ranks()).rev().collect()
+        };
+        if options.coordinates {
+            let color = match self.game.active_color {
+                Color::White => 'W',
+                Color::Black => 'B',
+            };
+            write!(f, "{}", color)?;
+            for file in &files {
+                write!(f, " {}", (b'a' + file - 1) as char)?;
+            }
+            writeln!(f)?;
+        }
+        for rank in ranks {
+            if options.coordinates {
+                write!(f, "{}", rank)?;
+            }
+            for (i, &file) in files.iter().enumerate() {
+                let position = Position { file, rank };
+                let (r, c) = (rank as usize - 1, file as usize - 1);
+                let symbol = match self.game.board.get(&position) {
+                    Some(piece) if options.ascii => piece_char(piece),
+                    Some(piece) => piece.symbol(),
+                    None => arrows[r][c].unwrap_or(' '),
+                };
+                let separator = if i == 0 && !options.coordinates {
+                    ""
+                } else {
+                    " "
+                };
+                match highlights[r][c] {
+                    Some(highlight) => write!(
+                        f,
+                        "{}{}{}{}",
+                        highlight.background(),
+                        separator,
+                        symbol,
+                        RESET
+                    )?,
+                    None => write!(f, "{}{}", separator, symbol)?,
+                }
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl Game {
+    /// Draw the board as text with ANSI colors for the highlights, see [`RenderOptions`]
+    pub fn render(&self, options: RenderOptions) -> String {
+        BoardFormatter::new(self).options(options).to_string()
+    }
+}
+
+/// Board diagram with White at the bottom, with annotations drawn using ANSI background colors
+/// and arrow characters on empty squares, see [`BoardFormatter::annotations`]
+pub fn annotated_diagram(game: &Game, annotations: &[Annotation]) -> String {
+    BoardFormatter::new(game)
+        .annotations(annotations)
+        .to_string()
+}
+
+/// Annotations showing the engine's best move (green arrow) and the opponent's threat (red arrow)
+pub fn analysis_annotations(engine: &Engine, game: &Game) -> Vec<Annotation> {
+    let mut annotations = Vec::new();
+    if let Some(threat) = engine.threat(game) {
+        annotations.push(Annotation::Arrow(threat, Highlight::Threat));
+    }
+    if let Some(best) = engine.best_move(game) {
+        annotations.push(Annotation::Arrow(best, Highlight::BestMove));
+    }
+    annotations
+}
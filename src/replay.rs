@@ -0,0 +1,86 @@
+//! Stepping through the positions of a game, eg. for a viewer or thumbnails of every move
+//!
+//! # Example
+//! ```
+//! use eliasfl_chess::Game;
+//!
+//! let game = Game::from_pgn("1. e4 e5 2. Qh5 Nc6 3. Bc4 Nf6 4. Qxf7#").unwrap();
+//! let replay = game.replay().unwrap();
+//! assert_eq!(replay.start(), &Game::new());
+//! for snapshot in replay {
+//!     println!("{}. {} {}", snapshot.ply, snapshot.mov, snapshot.game.to_fen());
+//! }
+//! let last = game.replay().unwrap().last().unwrap();
+//! assert_eq!((last.ply, last.game.to_fen()), (7, game.to_fen()));
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::{Game, Move};
+
+/// The position after a move of a game, see [`Game::replay`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    /// Number of half moves made, 1 after the first move
+    pub ply: usize,
+    /// The move leading to the position
+    pub mov: Move,
+    /// The game after the move
+    pub game: Game,
+}
+
+/// Iterator over the positions after every move of a game, created with [`Game::replay`]
+#[derive(Debug, Clone)]
+pub struct Replay {
+    start: Game,
+    game: Game,
+    moves: Vec<Move>,
+    ply: usize,
+}
+
+impl Replay {
+    /// The game before the first move
+    pub fn start(&self) -> &Game {
+        &self.start
+    }
+}
+
+impl Iterator for Replay {
+    type Item = Snapshot;
+
+    fn next(&mut self) -> Option<Snapshot> {
+        let mov = *self.moves.get(self.ply)?;
+        self.game
+            ._make_move(mov)
+            .expect("Moves of the history are legal");
+        self.ply += 1;
+        Some(Snapshot {
+            ply: self.ply,
+            mov,
+            game: self.game.clone(),
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let left = self.moves.len() - self.ply;
+        (left, Some(left))
+    }
+}
+
+impl ExactSizeIterator for Replay {}
+
+impl Game {
+    /// Positions after every move of the history, replayed from the starting position
+    ///
+    /// Returns Err if the history does not lead to the current position, eg. after editing the
+    /// board or in Crazyhouse
+    pub fn replay(&self) -> Result<Replay, &'static str> {
+        let start = self._start_game()?;
+        Ok(Replay {
+            game: start.clone(),
+            start,
+            moves: self.history().to_vec(),
+            ply: 0,
+        })
+    }
+}
@@ -0,0 +1,289 @@
+//! Post-game coaching reports based on engine analysis of every move
+//!
+//! Mistakes are classified by the phase of the game they were made in and if the engine's move
+//! was forcing. Openings are not recognized by name, a mistake in the first [`OPENING_PLIES`] half
+//! moves is reported as a deviation from sound opening play.
+//!
+//! Every move is also classified from best to blunder by the centipawns it lost, see
+//! [`Game::analyze_game`].
+//!
+//! # Example
+//! ```
+//! use eliasfl_chess::report::{coach_summary, AnalyzedGame, MoveClass};
+//! use eliasfl_chess::{Color, Engine, Game, Move};
+//!
+//! let moves: Vec<Move> = ["e2e4", "e7e5", "d1h5", "b8c6", "f1c4", "g8f6", "h5f7"]
+//!     .iter()
+//!     .map(|uci| Move::from_uci(uci).unwrap())
+//!     .collect();
+//! let analyzed = AnalyzedGame::new(&Game::new(), &moves, &Engine::new(2)).unwrap();
+//! let summary = coach_summary(&analyzed);
+//! // Black allowed the mate with 3...Nf6 instead of defending f7
+//! assert!(!summary.for_color(Color::Black).is_empty());
+//! assert_eq!(analyzed.moves[5].classification(), MoveClass::Blunder);
+//! assert_eq!(analyzed.count(Color::White, MoveClass::Blunder), 0);
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::{Color, Engine, Game, Move, Piece};
+
+/// Centipawns a move must lose compared to the best move to be an inaccuracy
+pub const INACCURACY_THRESHOLD: i32 = 50;
+
+/// Centipawns a move must lose compared to the best move to be reported, and to be a mistake
+pub const MISTAKE_THRESHOLD: i32 = 100;
+
+/// Centipawns a move must lose compared to the best move to be a blunder
+pub const BLUNDER_THRESHOLD: i32 = 300;
+
+/// Largest loss of a move counted by [`AnalyzedGame::average_loss`], so missing a mate does not
+/// outweigh every other move
+pub const MAX_COUNTED_LOSS: i32 = 1000;
+
+/// Number of half moves counted as the opening
+pub const OPENING_PLIES: usize = 20;
+
+/// Maximum number of pieces other than kings and pawns on the board in an endgame
+pub const ENDGAME_PIECES: usize = 4;
+
+/// A played move with the engine's opinion of it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnalyzedMove {
+    /// The move that was played
+    pub mov: Move,
+    /// The player who made the move
+    pub color: Color,
+    /// The engine's choice in the position, None if it agrees there is nothing better
+    pub best_move: Option<Move>,
+    /// If the engine's choice captures a piece or gives check
+    pub best_is_forcing: bool,
+    /// Score before the move with best play, in centipawns from White's perspective
+    pub score_before: i32,
+    /// Score after the played move, in centipawns from White's perspective
+    pub score_after: i32,
+    /// If the position before the move was an endgame, see [`ENDGAME_PIECES`]
+    pub endgame: bool,
+}
+
+impl AnalyzedMove {
+    /// Centipawns the move lost for the player compared to the best move, never negative
+    pub fn loss(&self) -> i32 {
+        let loss = match self.color {
+            Color::White => self.score_before - self.score_after,
+            Color::Black => self.score_after - self.score_before,
+        };
+        loss.max(0)
+    }
+
+    /// How the move compares to the engine's best move, by the centipawns it lost
+    pub fn classification(&self) -> MoveClass {
+        match self.loss() {
+            _ if self.best_move.is_none() => MoveClass::Best,
+            0 => MoveClass::Best,
+            loss if loss < INACCURACY_THRESHOLD => MoveClass::Good,
+            loss if loss < MISTAKE_THRESHOLD => MoveClass::Inaccuracy,
+            loss if loss < BLUNDER_THRESHOLD => MoveClass::Mistake,
+            _ => MoveClass::Blunder,
+        }
+    }
+}
+
+/// Quality of a played move, see [`AnalyzedMove::classification`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MoveClass {
+    /// The engine's move or one as good
+    Best,
+    /// Lost less than [`INACCURACY_THRESHOLD`] centipawns
+    Good,
+    /// Lost at least [`INACCURACY_THRESHOLD`] centipawns
+    Inaccuracy,
+    /// Lost at least [`MISTAKE_THRESHOLD`] centipawns
+    Mistake,
+    /// Lost at least [`BLUNDER_THRESHOLD`] centipawns
+    Blunder,
+}
+
+/// A game with every move analyzed by the engine
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnalyzedGame {
+    /// Position before the first move
+    pub start: Game,
+    /// The analyzed moves in the order they were played
+    pub moves: Vec<AnalyzedMove>,
+}
+
+impl AnalyzedGame {
+    /// Analyze `moves` played from `start` with `engine`
+    ///
+    /// Return Err if a move is illegal
+    pub fn new(start: &Game, moves: &[Move], engine: &Engine) -> Result<Self, &'static str> {
+        let mut game = start.clone();
+        let mut analyzed = Vec::with_capacity(moves.len());
+        let mut before = engine.search(&game);
+        for &mov in moves {
+            let color = game.active_color;
+            let endgame = is_endgame(&game);
+            let best_is_forcing = before.best_move.is_some_and(|best| is_forcing(&game, best));
+            game._make_move(mov)?;
+            let after = engine.search(&game);
+            analyzed.push(AnalyzedMove {
+                mov,
+                color,
+                best_move: before.best_move.filter(|best| *best != mov),
+                best_is_forcing,
+                score_before: before.score,
+                score_after: after.score,
+                endgame,
+            });
+            before = after;
+        }
+        Ok(Self {
+            start: start.clone(),
+            moves: analyzed,
+        })
+    }
+
+    /// Number of moves of color with the classification
+    pub fn count(&self, color: Color, class: MoveClass) -> usize {
+        self.moves
+            .iter()
+            .filter(|analyzed| analyzed.color == color && analyzed.classification() == class)
+            .count()
+    }
+
+    /// Mean centipawns lost per move of color, each counting at most [`MAX_COUNTED_LOSS`], 0
+    /// without moves
+    pub fn average_loss(&self, color: Color) -> i32 {
+        let losses: Vec<i32> = self
+            .moves
+            .iter()
+            .filter(|analyzed| analyzed.color == color)
+            .map(|analyzed| analyzed.loss().min(MAX_COUNTED_LOSS))
+            .collect();
+        if losses.is_empty() {
+            0
+        } else {
+            losses.iter().sum::<i32>() / losses.len() as i32
+        }
+    }
+}
+
+impl Game {
+    /// Analyze every move of the history with an engine searching depth plies, see
+    /// [`AnalyzedGame`] and [`AnalyzedMove::classification`]
+    ///
+    /// Returns Err if the history cannot be replayed, eg. in Crazyhouse
+    pub fn analyze_game(&self, depth: u32) -> Result<AnalyzedGame, &'static str> {
+        AnalyzedGame::new(&self._start_game()?, self.history(), &Engine::new(depth))
+    }
+}
+
+/// Kind of problem found in a move
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FindingKind {
+    /// Mistake during the first [`OPENING_PLIES`] half moves, ie. a deviation from sound opening play
+    OpeningDeviation,
+    /// A capture or check winning material or mating was missed
+    MissedTactic,
+    /// Mistake in an endgame, eg. letting a won ending slip
+    EndgameTechnique,
+    /// Any other mistake
+    Mistake,
+}
+
+/// A move a player should look at again
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Finding {
+    /// Number of half moves played before the move
+    pub ply: usize,
+    /// The player who made the move
+    pub color: Color,
+    /// What went wrong
+    pub kind: FindingKind,
+    /// The move that was played
+    pub played: Move,
+    /// The engine's suggestion
+    pub best_move: Option<Move>,
+    /// Centipawns lost by the move
+    pub loss: i32,
+}
+
+/// Findings of a game ready to be shown as a coaching card, see [`coach_summary`]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CoachSummary {
+    /// All findings in the order the moves were played
+    pub findings: Vec<Finding>,
+}
+
+impl CoachSummary {
+    /// Findings for the moves of color
+    pub fn for_color(&self, color: Color) -> Vec<Finding> {
+        self.findings
+            .iter()
+            .filter(|finding| finding.color == color)
+            .copied()
+            .collect()
+    }
+
+    /// Number of findings of `kind` for color
+    pub fn count(&self, color: Color, kind: FindingKind) -> usize {
+        self.findings
+            .iter()
+            .filter(|finding| finding.color == color && finding.kind == kind)
+            .count()
+    }
+
+    /// If color missed more than one tactic, which is worth practising
+    pub fn has_recurring_tactical_misses(&self, color: Color) -> bool {
+        self.count(color, FindingKind::MissedTactic) > 1
+    }
+}
+
+/// Collect the mistakes of an analyzed game, classified by game phase and kind
+pub fn coach_summary(game: &AnalyzedGame) -> CoachSummary {
+    let findings = game
+        .moves
+        .iter()
+        .enumerate()
+        .filter(|(_, analyzed)| analyzed.loss() >= MISTAKE_THRESHOLD)
+        .map(|(ply, analyzed)| {
+            let kind = if analyzed.best_is_forcing && analyzed.best_move.is_some() {
+                FindingKind::MissedTactic
+            } else if ply < OPENING_PLIES {
+                FindingKind::OpeningDeviation
+            } else if analyzed.endgame {
+                FindingKind::EndgameTechnique
+            } else {
+                FindingKind::Mistake
+            };
+            Finding {
+                ply,
+                color: analyzed.color,
+                kind,
+                played: analyzed.mov,
+                best_move: analyzed.best_move,
+                loss: analyzed.loss(),
+            }
+        })
+        .collect();
+    CoachSummary { findings }
+}
+
+/// If there are at most [`ENDGAME_PIECES`] pieces other than kings and pawns
+fn is_endgame(game: &Game) -> bool {
+    game.board
+        .iter()
+        .filter(|(_, piece)| !matches!(piece, Piece::King(_) | Piece::Pawn(_)))
+        .count()
+        <= ENDGAME_PIECES
+}
+
+/// If the move captures a piece or gives check
+fn is_forcing(game: &Game, mov: Move) -> bool {
+    if game.board.get(&mov.to).is_some() {
+        return true;
+    }
+    let mut after = game.clone();
+    after._make_move(mov).is_ok() && after._king_is_threatened(after.active_color)
+}
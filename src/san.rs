@@ -0,0 +1,228 @@
+//! Standard algebraic notation (SAN) eg. "Nf3", "exd5", "O-O" or "e8=Q+"
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::fen::piece_char;
+use crate::{Color, Game, GameState, Move, Piece, Position};
+
+/// Piece of color from SAN letter (pawns have no letter)
+pub(crate) fn piece_from_letter(letter: char, color: Color) -> Option<Piece> {
+    match letter {
+        'K' => Some(Piece::King(color)),
+        'Q' => Some(Piece::Queen(color)),
+        'R' => Some(Piece::Rook(color)),
+        'B' => Some(Piece::Bishop(color)),
+        'N' => Some(Piece::Knight(color)),
+        _ => None,
+    }
+}
+
+/// Find the legal move described by `san` for the player who's turn it is
+///
+/// Check (+), mate (#) and annotation (! and ?) suffixes are ignored. Castling can be written with
+/// letter O or digit 0. Returns Err if the notation is invalid, no legal move matches or several do.
+pub fn parse_san(game: &Game, san: &str) -> Result<Move, &'static str> {
+    let candidates = matching_moves(game, san)?;
+    match candidates[..] {
+        [mov] => Ok(mov),
+        [] => Err("No legal move matches"),
+        _ => Err("Ambiguous move, specify file or rank of piece"),
+    }
+}
+
+/// All legal moves matching `san`, more than one if the notation is ambiguous, see [`parse_san`]
+///
+/// Returns Err if the notation is invalid or castling is not allowed
+pub fn matching_moves(game: &Game, san: &str) -> Result<Vec<Move>, &'static str> {
+    let color = game.active_color;
+    let san = san.trim().trim_end_matches(['+', '#', '!', '?']);
+    let legal_moves = game.legal_moves().to_vec();
+
+    // Castling
+    let castling_file = match san {
+        "O-O" | "0-0" => Some(7),
+        "O-O-O" | "0-0-0" => Some(3),
+        _ => None,
+    };
+    if let Some(file) = castling_file {
+        let castling: Vec<Move> = legal_moves
+            .into_iter()
+            .filter(
+                |mov| matches!(game._castling(mov), Some((king_to, _, _)) if king_to.file == file),
+            )
+            .collect();
+        return if castling.is_empty() {
+            Err("Castling is not allowed")
+        } else {
+            Ok(castling)
+        };
+    }
+
+    // Promotion suffix, eg. "=Q" or "Q"
+    let (san, promotion) = match san.char_indices().last() {
+        Some((i, letter)) if letter.is_ascii_uppercase() && i > 0 => {
+            let promotion = piece_from_letter(letter, color)
+                .filter(|piece| !matches!(piece, Piece::King(_)))
+                .ok_or("Invalid promotion piece")?;
+            (san[..i].trim_end_matches('='), Some(promotion))
+        }
+        _ => (san, None),
+    };
+
+    // Piece letter, pawns have none
+    let mut chars: Vec<char> = san.chars().filter(|&c| c != 'x' && c != '-').collect();
+    let piece = match chars.first() {
+        Some(&letter) if letter.is_ascii_uppercase() => {
+            chars.remove(0);
+            piece_from_letter(letter, color).ok_or("Invalid piece letter")?
+        }
+        Some(_) => Piece::Pawn(color),
+        None => return Err("Empty move"),
+    };
+
+    // Destination is the last two characters, anything before disambiguates the start square
+    if chars.len() < 2 {
+        return Err("Move should end with a destination square");
+    }
+    let destination: String = chars[chars.len() - 2..].iter().collect();
+    let to: Position = destination
+        .parse()
+        .map_err(|_| "Invalid destination square")?;
+    let mut from_file = None;
+    let mut from_rank = None;
+    for c in &chars[..chars.len() - 2] {
+        match c {
+            'a'..='h' => from_file = Some(*c as u8 - 96),
+            '1'..='8' => from_rank = c.to_digit(10).map(|r| r as u8),
+            _ => return Err("Invalid character in move"),
+        }
+    }
+
+    let default_promotion = game.promotion_piece(color);
+    let candidates: Vec<Move> = legal_moves
+        .into_iter()
+        .filter(|mov| {
+            game.board.get(&mov.from) == Some(&piece)
+                && mov.to == to
+                && from_file.map_or(true, |file| mov.from.file == file)
+                && from_rank.map_or(true, |rank| mov.from.rank == rank)
+                && (mov.promotion.is_none()
+                    || mov.promotion == promotion.or(Some(default_promotion)))
+        })
+        .collect();
+    if promotion.is_some() && candidates.iter().any(|mov| mov.promotion.is_none()) {
+        return Err("Only pawns reaching the last rank can be promoted");
+    }
+    Ok(candidates)
+}
+
+/// Write the legal move `mov` of the player who's turn it is in standard algebraic notation,
+/// eg. "Nbd2", "exd5", "O-O" or "e8=Q#"
+///
+/// Returns Err if the move is illegal
+pub fn to_san(game: &Game, mov: Move) -> Result<String, &'static str> {
+    let mut after = game.clone();
+    after._make_move(mov)?;
+    let promoted = after.last_move().and_then(|mov| mov.promoted_to());
+    Ok(_san_before_move(game, mov, promoted) + _check_suffix(&mut after))
+}
+
+/// Notation of legal move without check suffix, `promoted` is the piece a pawn becomes
+pub(crate) fn _san_before_move(game: &Game, mov: Move, promoted: Option<Piece>) -> String {
+    if let Some((king_to, _, _)) = game._castling(&mov) {
+        return if king_to.file > mov.from.file {
+            "O-O".to_string()
+        } else {
+            "O-O-O".to_string()
+        };
+    }
+    let piece = game.board[&mov.from];
+    let capture = game.board.contains_key(&mov.to)
+        || (matches!(piece, Piece::Pawn(_)) && mov.from.file != mov.to.file);
+    let mut san = String::new();
+    if matches!(piece, Piece::Pawn(_)) {
+        if capture {
+            san.push(mov.from.file_char());
+        }
+    } else {
+        san.push(piece_char(&piece).to_ascii_uppercase());
+        // Other pieces of the same kind that can move to the destination
+        let others: Vec<Position> = game
+            ._legal_moves()
+            .into_iter()
+            .filter(|other| {
+                other.to == mov.to
+                    && other.from != mov.from
+                    && game.board.get(&other.from) == Some(&piece)
+            })
+            .map(|other| other.from)
+            .collect();
+        if !others.is_empty() {
+            let from = mov.from.to_string();
+            if others.iter().all(|other| other.file != mov.from.file) {
+                san.push_str(&from[..1]);
+            } else if others.iter().all(|other| other.rank != mov.from.rank) {
+                san.push_str(&from[1..]);
+            } else {
+                san.push_str(&from);
+            }
+        }
+    }
+    if capture {
+        san.push('x');
+    }
+    san.push_str(&mov.to.to_string());
+    if let Some(promoted) = promoted {
+        san.push('=');
+        san.push(piece_char(&promoted).to_ascii_uppercase());
+    }
+    san
+}
+
+impl Game {
+    /// Describe the legal move `mov` of the player who's turn it is in words, as announced in
+    /// blindfold games, eg. "knight from g1 to f3", "pawn from d5 to e6, capturing pawn en passant"
+    /// or "castles kingside, check"
+    ///
+    /// Returns Err if the move is illegal
+    pub fn describe_move(&self, mov: Move) -> Result<String, &'static str> {
+        let mut after = self.clone();
+        let captured = after._make_move(mov)?;
+        let piece = self.board[&mov.from];
+        let mut description = match self._castling(&mov) {
+            Some((king_to, _, _)) if king_to.file > mov.from.file => "castles kingside".to_string(),
+            Some(_) => "castles queenside".to_string(),
+            None => format!("{} from {} to {}", piece.kind().name(), mov.from, mov.to),
+        };
+        if let Some(captured) = captured {
+            description += ", capturing ";
+            description += captured.kind().name();
+            if !self.board.contains_key(&mov.to) {
+                description += " en passant";
+            }
+        }
+        if let Some(promoted) = after.last_move().and_then(|mov| mov.promoted_to()) {
+            description += ", promoting to ";
+            description += promoted.kind().name();
+        }
+        description += match _check_suffix(&mut after) {
+            "#" => ", checkmate",
+            "+" => ", check",
+            _ => "",
+        };
+        Ok(description)
+    }
+}
+
+/// "#" if the player who's turn it is has been checkmated, "+" if in check, otherwise empty
+pub(crate) fn _check_suffix(after: &mut Game) -> &'static str {
+    if !after._king_is_threatened(after.active_color) {
+        ""
+    } else if after.get_game_state() == GameState::CheckMate {
+        "#"
+    } else {
+        "+"
+    }
+}
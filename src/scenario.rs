@@ -0,0 +1,207 @@
+//! Scripted scenarios for interactive lessons, eg. "learn castling" or "learn forks"
+//!
+//! A [`Scenario`] is a starting position with a list of [`Step`]s. Every step lists the moves the
+//! user may play and the scripted reply of the opponent. A [`Lesson`] follows a scenario move by move
+//! and reports the progression, so any frontend only has to forward the user's moves.
+//!
+//! # Example
+//! ```
+//! use eliasfl_chess::scenario::{Goal, Lesson, Progress, Scenario};
+//!
+//! let scenario = Scenario::from_fen("Learn castling", "4k3/8/8/8/8/8/8/4K2R w K - 0 1", Goal::Castle)
+//!     .unwrap()
+//!     .step(&["e1g1"], None)
+//!     .unwrap();
+//! let mut lesson = Lesson::new(&scenario);
+//! assert_eq!(lesson.play_san("Rh2"), Ok(Progress::WrongMove));
+//! assert_eq!(lesson.play_san("O-O"), Ok(Progress::Success));
+//! ```
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::{Game, Move, Piece, Position};
+
+/// Condition for completing a scenario, checked after every move of the user
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Goal {
+    /// Checkmate the opponent
+    Checkmate,
+    /// Castle to either side
+    Castle,
+    /// Have the piece stand on the position
+    PieceOn(Position, Piece),
+    /// Play all steps of the script
+    CompleteScript,
+}
+
+/// One move of the user and the scripted reply
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    /// Moves the user may play, any legal move if empty
+    pub allowed: Vec<Move>,
+    /// Move the opponent replies with
+    pub reply: Option<Move>,
+}
+
+/// Starting position, script and goal of a lesson
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scenario {
+    /// Title shown to the user
+    pub title: String,
+    /// Starting position, the user plays the active color
+    pub start: Game,
+    /// Moves of the user and the opponent in order
+    pub steps: Vec<Step>,
+    /// Condition for completing the scenario
+    pub goal: Goal,
+}
+
+impl Scenario {
+    /// Scenario starting from `start` without any steps
+    pub fn new(title: &str, start: Game, goal: Goal) -> Self {
+        Self {
+            title: title.to_string(),
+            start,
+            steps: Vec::new(),
+            goal,
+        }
+    }
+
+    /// Scenario starting from a position in Forsyth–Edwards Notation
+    pub fn from_fen(title: &str, fen: &str, goal: Goal) -> Result<Self, &'static str> {
+        Ok(Self::new(title, Game::from_fen(fen)?, goal))
+    }
+
+    /// Add a step with the allowed user moves and the opponent's reply in UCI notation, eg. "e2e4"
+    ///
+    /// Return Err if any move is not valid UCI notation
+    pub fn step(mut self, allowed: &[&str], reply: Option<&str>) -> Result<Self, &'static str> {
+        let allowed = allowed
+            .iter()
+            .map(|uci| Move::from_uci(uci))
+            .collect::<Result<_, _>>()?;
+        let reply = reply.map(Move::from_uci).transpose()?;
+        self.steps.push(Step { allowed, reply });
+        Ok(self)
+    }
+}
+
+/// Result of a move played in a lesson
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Progress {
+    /// The move was accepted and the opponent replied with the move, if any
+    Continue(Option<Move>),
+    /// The move is legal but not part of the script, the position is unchanged
+    WrongMove,
+    /// The goal is reached
+    Success,
+    /// The script ended without reaching the goal
+    Failed,
+}
+
+/// A scenario being played by a user
+#[derive(Debug, Clone)]
+pub struct Lesson<'a> {
+    scenario: &'a Scenario,
+    /// Current position
+    pub game: Game,
+    /// Index of the next step
+    step: usize,
+    finished: bool,
+}
+
+impl<'a> Lesson<'a> {
+    /// Start the scenario from its starting position
+    pub fn new(scenario: &'a Scenario) -> Self {
+        Self {
+            scenario,
+            game: scenario.start.clone(),
+            step: 0,
+            finished: false,
+        }
+    }
+
+    /// The scenario being played
+    pub fn scenario(&self) -> &Scenario {
+        self.scenario
+    }
+
+    /// The step the user should play next, None when the lesson is finished
+    pub fn current_step(&self) -> Option<&Step> {
+        if self.finished {
+            return None;
+        }
+        self.scenario.steps.get(self.step)
+    }
+
+    /// If the lesson is finished by reaching or failing the goal
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Play a move written in standard algebraic notation (SAN), see [`Lesson::play`]
+    pub fn play_san(&mut self, san: &str) -> Result<Progress, &'static str> {
+        let mov = crate::san::parse_san(&self.game, san)?;
+        self.play(mov)
+    }
+
+    /// Play the user's move and the scripted reply
+    ///
+    /// Return Err if the lesson is finished, the move is illegal or the scripted reply is illegal
+    pub fn play(&mut self, mov: Move) -> Result<Progress, &'static str> {
+        let step = self.current_step().ok_or("Lesson is finished")?;
+        let legal = self.game._legal_moves();
+        if !legal.iter().any(|m| m.from == mov.from && m.to == mov.to) {
+            return Err("Illegal move");
+        }
+        let allowed = step.allowed.is_empty()
+            || step.allowed.iter().any(|a| {
+                a.from == mov.from
+                    && a.to == mov.to
+                    && (a.promotion.is_none() || a.promotion == mov.promotion)
+            });
+        if !allowed {
+            return Ok(Progress::WrongMove);
+        }
+        let reply = step.reply;
+
+        let piece = self.game.board.get(&mov.from).copied();
+        self.game._make_move(mov)?;
+        self.step += 1;
+        if self._goal_reached(mov, piece) {
+            self.finished = true;
+            return Ok(Progress::Success);
+        }
+        if let Some(reply) = reply {
+            self.game
+                ._make_move(reply)
+                .map_err(|_| "Scripted reply is illegal")?;
+        }
+        if self.step == self.scenario.steps.len() {
+            self.finished = true;
+            return Ok(if self.scenario.goal == Goal::CompleteScript {
+                Progress::Success
+            } else {
+                Progress::Failed
+            });
+        }
+        Ok(Progress::Continue(reply))
+    }
+
+    /// If the goal is reached after the user played `mov` with `piece`
+    fn _goal_reached(&self, mov: Move, piece: Option<Piece>) -> bool {
+        match self.scenario.goal {
+            Goal::Checkmate => {
+                self.game._king_is_threatened(self.game.active_color)
+                    && self.game._is_checkmate(self.game.active_color)
+            }
+            Goal::Castle => {
+                matches!(piece, Some(Piece::King(_)))
+                    && (mov.from.file as i32 - mov.to.file as i32).abs() == 2
+            }
+            Goal::PieceOn(position, piece) => self.game.board.get(&position) == Some(&piece),
+            Goal::CompleteScript => false,
+        }
+    }
+}
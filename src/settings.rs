@@ -0,0 +1,215 @@
+//! Preferences of the command-line program, stored in `~/.eliasfl-chess.toml`
+//!
+//! The file has one `key = value` line per setting, as a small part of TOML. Settings that are
+//! not set are left out. Lines starting with `#` are comments. Values are unquoted words or TOML
+//! strings, in double quotes with backslash escapes or in single quotes as written.
+//!
+//! ```toml
+//! fancy = true
+//! level = "casual"
+//! clock = "5+3"
+//! autosave = "/home/elias/game.pgn"
+//! notation = "swedish"
+//! ```
+//!
+//! # Example
+//! ```
+//! use eliasfl_chess::engine::EngineLevel;
+//! use eliasfl_chess::settings::Settings;
+//!
+//! let mut settings = Settings::parse("fancy = true\nlevel = \"casual\"").unwrap();
+//! assert_eq!(settings.level, Some(EngineLevel::Casual));
+//! settings.set("clock", "5+3").unwrap();
+//! assert_eq!(Settings::parse(&settings.to_string()), Ok(settings));
+//! ```
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use core::fmt;
+
+#[cfg(feature = "std")]
+use std::{fs, io, path::Path, path::PathBuf};
+
+use crate::config::TimeControl;
+use crate::engine::EngineLevel;
+use crate::notation::Notation;
+
+/// Name of the settings file in the home directory
+pub const FILE_NAME: &str = ".eliasfl-chess.toml";
+
+/// Preferences of the command-line program, see the [module documentation](self)
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Settings {
+    /// Draw the board with Unicode pieces instead of letters
+    pub fancy: bool,
+    /// Level of the computer when only its color is chosen, None for full strength
+    pub level: Option<EngineLevel>,
+    /// Chess clock of new games, None to play without a clock
+    pub clock: Option<TimeControl>,
+    /// File the game is saved to as PGN after every move, None to not save
+    pub autosave: Option<String>,
+    /// Piece letters of the moves
+    pub notation: Notation,
+}
+
+impl Settings {
+    /// Names of the settings, as written in the file and to [`Settings::set`]
+    pub const KEYS: [&'static str; 5] = ["fancy", "level", "clock", "autosave", "notation"];
+
+    /// Read settings written as `key = value` lines, see [`Settings::set`]
+    ///
+    /// Return Err if a line is not a setting or its value is invalid
+    pub fn parse(text: &str) -> Result<Settings, &'static str> {
+        let mut settings = Settings::default();
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or("Settings should be written as key = value")?;
+            settings._set_value(key, &_unquote(value.trim())?)?;
+        }
+        Ok(settings)
+    }
+
+    /// Change setting key to value, with or without quotes
+    ///
+    /// "fancy" is true or false, "level" a level of the computer, "clock" minutes and increment
+    /// (eg. "5+3"), "autosave" a file and "notation" english, german, swedish or figurine. The
+    /// level, clock and autosave are unset with "off".
+    ///
+    /// Return Err if the key is not a setting or the value is invalid, the setting is unchanged
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), &'static str> {
+        self._set_value(key, value.trim().trim_matches('"'))
+    }
+
+    /// Change setting key to value as it is, see [`Settings::set`]
+    fn _set_value(&mut self, key: &str, value: &str) -> Result<(), &'static str> {
+        let off = matches!(value, "" | "off" | "none");
+        match key.trim() {
+            "fancy" => {
+                self.fancy = match value {
+                    "true" | "on" => true,
+                    "false" | "off" => false,
+                    _ => return Err("Fancy should be true or false"),
+                }
+            }
+            "level" if off => self.level = None,
+            "level" => self.level = Some(value.parse()?),
+            "clock" if off => self.clock = None,
+            "clock" => self.clock = Some(value.parse()?),
+            "autosave" if off => self.autosave = None,
+            "autosave" => self.autosave = Some(value.to_string()),
+            "notation" => self.notation = value.parse()?,
+            _ => {
+                return Err("Unknown setting, should be fancy, level, clock, autosave or notation")
+            }
+        }
+        Ok(())
+    }
+
+    /// Default location of the settings file, in the home directory
+    #[cfg(feature = "std")]
+    pub fn default_path() -> Option<PathBuf> {
+        std::env::var_os("HOME")
+            .or_else(|| std::env::var_os("USERPROFILE"))
+            .map(|home| PathBuf::from(home).join(FILE_NAME))
+    }
+
+    /// Read the settings file at path, the default settings if there is none
+    ///
+    /// Return Err if the file cannot be read or is invalid
+    #[cfg(feature = "std")]
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Settings> {
+        match fs::read_to_string(path) {
+            Ok(text) => Settings::parse(&text)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Settings::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Write the settings to the file at path
+    #[cfg(feature = "std")]
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_string())
+    }
+}
+
+impl fmt::Display for Settings {
+    /// The settings as `key = value` lines, see [`Settings::parse`]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "fancy = {}", self.fancy)?;
+        if let Some(level) = self.level {
+            writeln!(f, "level = \"{}\"", level.name())?;
+        }
+        if let Some(clock) = self.clock {
+            writeln!(f, "clock = \"{}\"", clock)?;
+        }
+        if let Some(autosave) = &self.autosave {
+            writeln!(f, "autosave = {}", _quote(autosave))?;
+        }
+        writeln!(f, "notation = \"{}\"", self.notation.name())
+    }
+}
+
+/// Value as a TOML basic string in double quotes, with backslashes, quotes and control characters
+/// escaped
+fn _quote(value: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04X}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Value of a setting: the contents of a TOML basic string in double quotes with its escapes, a
+/// literal string in single quotes, or an unquoted word
+fn _unquote(value: &str) -> Result<String, &'static str> {
+    let unterminated = "Quoted setting should end with a quote";
+    if let Some(literal) = value.strip_prefix('\'') {
+        return literal
+            .strip_suffix('\'')
+            .map(ToString::to_string)
+            .ok_or(unterminated);
+    }
+    let mut chars = match value.strip_prefix('"') {
+        Some(quoted) => quoted.chars(),
+        None => return Ok(value.to_string()),
+    };
+    let mut unquoted = String::new();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if chars.as_str().is_empty() => return Ok(unquoted),
+            '"' => return Err("Unexpected characters after the quoted setting"),
+            '\\' => unquoted.push(match chars.next() {
+                Some('"') => '"',
+                Some('\\') => '\\',
+                Some('n') => '\n',
+                Some('r') => '\r',
+                Some('t') => '\t',
+                Some('u') => {
+                    let code = chars.as_str().get(..4).ok_or("Invalid escape in setting")?;
+                    chars.nth(3);
+                    u32::from_str_radix(code, 16)
+                        .ok()
+                        .and_then(char::from_u32)
+                        .ok_or("Invalid escape in setting")?
+                }
+                _ => return Err("Invalid escape in setting"),
+            }),
+            c => unquoted.push(c),
+        }
+    }
+    Err(unterminated)
+}
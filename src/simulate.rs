@@ -0,0 +1,181 @@
+//! Games played to completion by computer players, eg. to test the rules or generate training data
+//!
+//! Random players are reproducible: the same seed and starting position give the same game.
+//! Drops in Crazyhouse are never played.
+//!
+//! # Example
+//! ```
+//! use eliasfl_chess::simulate::{simulate, Player};
+//! use eliasfl_chess::{Engine, Game};
+//!
+//! let random = Game::new().play_random_game(42);
+//! assert_eq!(random, Game::new().play_random_game(42));
+//! println!("{} after {} moves", random.result(), random.moves.len());
+//!
+//! let simulation = simulate(&Game::new(), Player::Engine(Engine::new(1)), Player::Random, 7, 20);
+//! assert!(simulation.moves.len() <= 20);
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::{Color, DrawReason, Engine, Game, GameState, Move, FIFTY_MOVES};
+
+/// Maximum number of half moves of [`Game::play_random_game`]
+pub const MAX_PLIES: usize = 1000;
+
+/// Who chooses the moves of a color
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Player {
+    /// Uniformly random legal moves
+    Random,
+    /// The best move of the engine
+    Engine(Engine),
+}
+
+/// Why a simulated game ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Termination {
+    /// The player who's turn it is was checkmated (or lost the king in Atomic)
+    Checkmate,
+    /// The player who's turn it is has no legal moves but is not in check
+    Stalemate,
+    /// A hundred half moves were made without a capture or pawn move
+    FiftyMoves,
+    /// The same position occurred five times
+    Repetition,
+    /// Neither player can checkmate, see [`Game::is_dead_position`]
+    DeadPosition,
+    /// The maximum number of half moves was reached, or only drops were left in Crazyhouse
+    Unfinished,
+    /// The player who's turn it is gave up or chose an illegal move, only in
+    /// [`tournament`](crate::tournament) matches
+    Resignation,
+}
+
+/// A game played to completion, see [`simulate`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Simulation {
+    /// Position after the last move
+    pub game: Game,
+    /// Moves in the order they were played
+    pub moves: Vec<Move>,
+    /// Why the game ended
+    pub termination: Termination,
+}
+
+impl Simulation {
+    /// The winner, None for draws and unfinished games
+    pub fn winner(&self) -> Option<Color> {
+        match self.termination {
+            Termination::Checkmate | Termination::Resignation => Some(!self.game.active_color),
+            _ => None,
+        }
+    }
+
+    /// Result as in PGN: "1-0", "0-1", "1/2-1/2" or "*" for unfinished games
+    pub fn result(&self) -> &'static str {
+        match (self.termination, self.winner()) {
+            (_, Some(Color::White)) => "1-0",
+            (_, Some(Color::Black)) => "0-1",
+            (Termination::Unfinished, _) => "*",
+            _ => "1/2-1/2",
+        }
+    }
+}
+
+/// Small pseudo random number generator (SplitMix64), so simulations need no dependencies
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Rng(u64);
+
+impl Rng {
+    /// Generator producing the same numbers for the same seed
+    pub(crate) fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    /// Next pseudo random number
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Pseudo random number below `n`, which must not be zero
+    pub(crate) fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+}
+
+/// Play from `start` with `white` and `black` choosing the moves until the game ends or
+/// `max_plies` half moves were made
+///
+/// `seed` decides the moves of random players.
+pub fn simulate(
+    start: &Game,
+    white: Player,
+    black: Player,
+    seed: u64,
+    max_plies: usize,
+) -> Simulation {
+    let mut rng = Rng::new(seed);
+    let mut game = start.clone();
+    let mut moves = Vec::new();
+    loop {
+        if let Some(termination) = _termination(&game) {
+            return Simulation {
+                game,
+                moves,
+                termination,
+            };
+        }
+        let mut legal = game.legal_moves().to_vec();
+        if moves.len() >= max_plies || legal.is_empty() {
+            return Simulation {
+                game,
+                moves,
+                termination: Termination::Unfinished,
+            };
+        }
+        let player = match game.active_color {
+            Color::White => white,
+            Color::Black => black,
+        };
+        let mov = match player {
+            Player::Random => {
+                // Legal moves are not generated in a fixed order
+                legal.sort_unstable();
+                legal[rng.below(legal.len())]
+            }
+            Player::Engine(engine) => engine.best_move(&game).expect("There are legal moves"),
+        };
+        game._make_move(mov).expect("Chosen moves are legal");
+        moves.push(game.last_move().expect("Move was recorded"));
+    }
+}
+
+/// Why the game is over, None if the player who's turn it is can move
+pub(crate) fn _termination(game: &Game) -> Option<Termination> {
+    match game.get_game_state() {
+        GameState::CheckMate => return Some(Termination::Checkmate),
+        GameState::Draw(DrawReason::Stalemate) => return Some(Termination::Stalemate),
+        GameState::Draw(DrawReason::FivefoldRepetition | DrawReason::ThreefoldRepetition) => {
+            return Some(Termination::Repetition)
+        }
+        GameState::Draw(DrawReason::DeadPosition) => return Some(Termination::DeadPosition),
+        _ => {}
+    }
+    if game.halfmove_clock >= FIFTY_MOVES {
+        return Some(Termination::FiftyMoves);
+    }
+    None
+}
+
+impl Game {
+    /// Play random moves from the current position until the game ends, at most [`MAX_PLIES`]
+    /// half moves, see [`simulate`]
+    pub fn play_random_game(&self, seed: u64) -> Simulation {
+        simulate(self, Player::Random, Player::Random, seed, MAX_PLIES)
+    }
+}
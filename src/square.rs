@@ -0,0 +1,122 @@
+//! Named squares, eg. [`Square::E4`], instead of writing positions as strings or by file and rank
+//!
+//! A [`Square`] converts to and from a [`Position`] and can be matched on. Its number is the index
+//! of the square counting a1, b1, ..., h1, a2, ..., h8, so it can index arrays of 64 squares and
+//! the [`Board`](crate::Board).
+//!
+//! # Example
+//! ```
+//! use std::convert::TryFrom;
+//! use eliasfl_chess::{Color, Game, Piece, Position, Square};
+//!
+//! let game = Game::new();
+//! assert_eq!(game.board()[Square::E1], Piece::King(Color::White));
+//! let e4: Position = Square::E4.into();
+//! assert_eq!(e4, "e4".parse().unwrap());
+//! assert_eq!(Square::try_from(e4), Ok(Square::E4));
+//! assert_eq!(Square::E4 as usize, 28);
+//! assert_eq!("h8".parse(), Ok(Square::H8));
+//! ```
+
+use core::convert::TryFrom;
+use core::fmt;
+use core::str::FromStr;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::Position;
+
+/// A square of the board, numbered from a1 to h8, see the [module documentation](self)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[repr(u8)]
+#[rustfmt::skip]
+pub enum Square {
+    A1 = 0, B1, C1, D1, E1, F1, G1, H1,
+    A2, B2, C2, D2, E2, F2, G2, H2,
+    A3, B3, C3, D3, E3, F3, G3, H3,
+    A4, B4, C4, D4, E4, F4, G4, H4,
+    A5, B5, C5, D5, E5, F5, G5, H5,
+    A6, B6, C6, D6, E6, F6, G6, H6,
+    A7, B7, C7, D7, E7, F7, G7, H7,
+    A8, B8, C8, D8, E8, F8, G8, H8,
+}
+
+impl Square {
+    /// Every square from a1 to h8, rank by rank
+    #[rustfmt::skip]
+    pub const ALL: [Square; 64] = [
+        Square::A1, Square::B1, Square::C1, Square::D1, Square::E1, Square::F1, Square::G1, Square::H1,
+        Square::A2, Square::B2, Square::C2, Square::D2, Square::E2, Square::F2, Square::G2, Square::H2,
+        Square::A3, Square::B3, Square::C3, Square::D3, Square::E3, Square::F3, Square::G3, Square::H3,
+        Square::A4, Square::B4, Square::C4, Square::D4, Square::E4, Square::F4, Square::G4, Square::H4,
+        Square::A5, Square::B5, Square::C5, Square::D5, Square::E5, Square::F5, Square::G5, Square::H5,
+        Square::A6, Square::B6, Square::C6, Square::D6, Square::E6, Square::F6, Square::G6, Square::H6,
+        Square::A7, Square::B7, Square::C7, Square::D7, Square::E7, Square::F7, Square::G7, Square::H7,
+        Square::A8, Square::B8, Square::C8, Square::D8, Square::E8, Square::F8, Square::G8, Square::H8,
+    ];
+
+    /// Square with index 0-63 counting a1, b1, ..., h8, None if out of range
+    pub fn from_index(index: usize) -> Option<Square> {
+        Square::ALL.get(index).copied()
+    }
+
+    /// Index 0-63 of the square, the same as `square as usize`
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    /// File of the square, 1-8 for a-h
+    pub fn file(self) -> u8 {
+        self as u8 % 8 + 1
+    }
+
+    /// Rank of the square, 1-8
+    pub fn rank(self) -> u8 {
+        self as u8 / 8 + 1
+    }
+
+    /// The square as a [`Position`]
+    pub fn position(self) -> Position {
+        Position {
+            file: self.file(),
+            rank: self.rank(),
+        }
+    }
+}
+
+impl From<Square> for Position {
+    fn from(square: Square) -> Self {
+        square.position()
+    }
+}
+
+impl TryFrom<Position> for Square {
+    type Error = &'static str;
+
+    /// Fails for positions outside the board
+    fn try_from(position: Position) -> Result<Self, Self::Error> {
+        if position.is_valid() {
+            Ok(Square::ALL[position.index()])
+        } else {
+            Err("Position outside of board")
+        }
+    }
+}
+
+impl FromStr for Square {
+    type Err = &'static str;
+
+    /// Parse a square written like a [`Position`], eg. "e4"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Square::try_from(s.parse::<Position>()?)
+    }
+}
+
+impl fmt::Display for Square {
+    /// The square in lowercase, eg. "e4"
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.position())
+    }
+}
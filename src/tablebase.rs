@@ -0,0 +1,445 @@
+//! Exact results of the basic endgames king and queen, rook or pawn against a lone king
+//!
+//! The [`Tablebase`] stores the distance to mate of every KQK, KRK and KPK position, generated by
+//! retrograde analysis: starting from the checkmates, positions are solved backwards one ply at a
+//! time with moves generated in reverse. Pawn promotions lead into the KQK and KRK tables. Each
+//! table has one byte per position and takes a fraction of a second to generate in release builds.
+//!
+//! With the standard library the tables are generated once on first use by
+//! [`Game::endgame_result`] and [`Game::endgame_best_move`], which the [`Engine`](crate::Engine)
+//! uses to play these endgames perfectly. The fifty-move rule is ignored.
+//!
+//! # Example
+//! ```
+//! use eliasfl_chess::tablebase::EndgameResult;
+//! use eliasfl_chess::{Color, Game};
+//!
+//! // White mates in two moves with Kd6 and a rook move to the 8th rank
+//! let game = Game::from_fen("3k4/8/4K3/8/8/8/8/7R w - - 0 1").unwrap();
+//! assert_eq!(
+//!     game.endgame_result(),
+//!     Some(EndgameResult::Win { winner: Color::White, plies: 3 })
+//! );
+//! // The king in front of the rook pawn draws
+//! let game = Game::from_fen("k7/8/8/8/8/8/P7/K7 w - - 0 1").unwrap();
+//! assert_eq!(game.endgame_result(), Some(EndgameResult::Draw));
+//! ```
+
+use alloc::vec;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::sync::OnceLock;
+
+use crate::config::BoardSize;
+use crate::{Color, Game, Move, Piece, Variant};
+
+/// Number of positions per table: side to move, strong king, weak king and the extra piece
+const SIZE: usize = 2 * 64 * 64 * 64;
+
+/// Side to move in table indices, the strong side has the extra piece
+const STRONG: usize = 0;
+const WEAK: usize = 1;
+
+/// Steps of kings and queens, and rooks for the first four, as (file, rank) offsets
+const DIRECTIONS: [(i32, i32); 8] = [
+    (0, 1),
+    (0, -1),
+    (1, 0),
+    (-1, 0),
+    (1, 1),
+    (1, -1),
+    (-1, 1),
+    (-1, -1),
+];
+
+/// Result of a tablebase endgame with best play, see [`Game::endgame_result`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EndgameResult {
+    /// The winner mates after this many half moves, 0 if the loser is checkmated already
+    Win { winner: Color, plies: u32 },
+    /// Neither player can force a win
+    Draw,
+}
+
+/// Piece besides the kings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Extra {
+    Queen,
+    Rook,
+    Pawn,
+}
+
+/// Distance to mate of every KQK, KRK and KPK position, see the [module documentation](self)
+#[derive(Clone, PartialEq, Eq)]
+pub struct Tablebase {
+    kqk: Vec<u8>,
+    krk: Vec<u8>,
+    kpk: Vec<u8>,
+}
+
+impl core::fmt::Debug for Tablebase {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_str("Tablebase { KQK, KRK, KPK }")
+    }
+}
+
+impl Tablebase {
+    /// Generate the tables by retrograde analysis
+    pub fn generate() -> Self {
+        let kqk = _solve(Extra::Queen, None);
+        let krk = _solve(Extra::Rook, None);
+        let kpk = _solve(Extra::Pawn, Some((&kqk, &krk)));
+        Self { kqk, krk, kpk }
+    }
+
+    /// Result of the position of game, None if it is not one of the endgames
+    ///
+    /// Endgames are KQK, KRK and KPK in standard chess without castling rights, and the drawn
+    /// endgames of lone kings or a king and a knight or bishop against a king.
+    pub fn probe(&self, game: &Game) -> Option<EndgameResult> {
+        let (extra, index, winner) = match _entry(game)? {
+            Some(entry) => entry,
+            None => return Some(EndgameResult::Draw),
+        };
+        let table = match extra {
+            Extra::Queen => &self.kqk,
+            Extra::Rook => &self.krk,
+            Extra::Pawn => &self.kpk,
+        };
+        match table[index] {
+            0 => Some(EndgameResult::Draw),
+            value => Some(EndgameResult::Win {
+                winner,
+                plies: value as u32 - 1,
+            }),
+        }
+    }
+
+    /// Move with the best result for the player to move, None if the position is not one of the
+    /// endgames or there are no legal moves
+    ///
+    /// The winner mates as fast as possible and the loser delays mate as long as possible, or a
+    /// drawing move is played.
+    pub fn best_move(&self, game: &Game) -> Option<Move> {
+        self.probe(game)?;
+        let color = game.active_color;
+        // Higher is better for the player to move
+        let rank = |result: EndgameResult| match result {
+            EndgameResult::Win { winner, plies } if winner == color => i64::MAX - plies as i64,
+            EndgameResult::Win { plies, .. } => plies as i64 - i64::MAX,
+            EndgameResult::Draw => 0,
+        };
+        game.legal_moves()
+            .iter()
+            .map(|&mov| {
+                let mut after = game.clone();
+                after._make(mov);
+                let result = self.probe(&after).unwrap_or(EndgameResult::Draw);
+                (mov, rank(result))
+            })
+            .fold(None, |best: Option<(Move, i64)>, (mov, score)| match best {
+                Some((_, best_score)) if best_score >= score => best,
+                _ => Some((mov, score)),
+            })
+            .map(|(mov, _)| mov)
+    }
+}
+
+/// Tablebase shared by every game, generated on first use
+#[cfg(feature = "std")]
+pub fn tablebase() -> &'static Tablebase {
+    static TABLEBASE: OnceLock<Tablebase> = OnceLock::new();
+    TABLEBASE.get_or_init(Tablebase::generate)
+}
+
+#[cfg(feature = "std")]
+impl Game {
+    /// Exact result of a KQK, KRK or KPK endgame with best play, see [`tablebase`](crate::tablebase)
+    ///
+    /// Returns None for other positions. The tables are generated on the first call.
+    pub fn endgame_result(&self) -> Option<EndgameResult> {
+        // Other positions don't need the tables
+        _entry(self)?;
+        tablebase().probe(self)
+    }
+
+    /// Best move in a KQK, KRK or KPK endgame, see [`Tablebase::best_move`]
+    pub fn endgame_best_move(&self) -> Option<Move> {
+        _entry(self)?;
+        tablebase().best_move(self)
+    }
+}
+
+/// Table, index and strong side of the position of game, None inside if it is a drawn endgame
+/// without a table and None if it is not one of the endgames, see [`Tablebase::probe`]
+#[allow(clippy::type_complexity)]
+fn _entry(game: &Game) -> Option<Option<(Extra, usize, Color)>> {
+    if game.variant != Variant::Standard
+        || game.crazyhouse.is_some()
+        || game.config.board_size != BoardSize::STANDARD
+        || game
+            .castling
+            .iter()
+            .any(|rights| rights.kingside || rights.queenside)
+    {
+        return None;
+    }
+    let mut kings = [None; 2];
+    let mut extra = None;
+    for (position, piece) in game.pieces() {
+        match piece {
+            Piece::King(color) => kings[color.index()] = Some(position.index()),
+            _ if extra.is_some() => return None,
+            _ => extra = Some((position.index(), piece)),
+        }
+    }
+    let (white_king, black_king) = (kings[0]?, kings[1]?);
+    let (square, piece) = match extra {
+        Some(extra) => extra,
+        None => return Some(None),
+    };
+    let (extra, winner) = match piece {
+        Piece::Queen(color) => (Extra::Queen, color),
+        Piece::Rook(color) => (Extra::Rook, color),
+        Piece::Pawn(color) => (Extra::Pawn, color),
+        Piece::Knight(_) | Piece::Bishop(_) => return Some(None),
+        Piece::King(_) => unreachable!("Kings are counted separately"),
+    };
+    // Tables are from White's side, Black's pieces are mirrored vertically
+    let (strong_king, weak_king, square) = match winner {
+        Color::White => (white_king, black_king, square),
+        Color::Black => (black_king ^ 56, white_king ^ 56, square ^ 56),
+    };
+    let to_move = if game.active_color == winner {
+        STRONG
+    } else {
+        WEAK
+    };
+    Some(Some((
+        extra,
+        _index(to_move, strong_king, weak_king, square),
+        winner,
+    )))
+}
+
+/// Index of a position in a table, squares are 0-63 from a1 to h8
+fn _index(to_move: usize, strong_king: usize, weak_king: usize, extra: usize) -> usize {
+    to_move << 18 | strong_king << 12 | weak_king << 6 | extra
+}
+
+/// Square from a square and a (file, rank) step, None off the board
+fn _step(square: usize, (file, rank): (i32, i32)) -> Option<usize> {
+    let file = (square % 8) as i32 + file;
+    let rank = (square / 8) as i32 + rank;
+    if (0..8).contains(&file) && (0..8).contains(&rank) {
+        Some((rank * 8 + file) as usize)
+    } else {
+        None
+    }
+}
+
+/// If kings on the squares would touch each other
+fn _touching(a: usize, b: usize) -> bool {
+    let files = (a % 8) as i32 - (b % 8) as i32;
+    let ranks = (a / 8) as i32 - (b / 8) as i32;
+    files.abs() <= 1 && ranks.abs() <= 1
+}
+
+/// If the White extra piece on `from` attacks `target`, with the line blocked by `blocker`
+fn _attacks(extra: Extra, from: usize, target: usize, blocker: usize) -> bool {
+    let files = (target % 8) as i32 - (from % 8) as i32;
+    let ranks = (target / 8) as i32 - (from / 8) as i32;
+    let straight = (files == 0) != (ranks == 0);
+    let diagonal = files != 0 && files.abs() == ranks.abs();
+    match extra {
+        Extra::Pawn => ranks == 1 && files.abs() == 1,
+        Extra::Rook if !straight => false,
+        Extra::Queen if !straight && !diagonal => false,
+        _ => {
+            let direction = (files.signum(), ranks.signum());
+            let mut square = from;
+            loop {
+                square = _step(square, direction).expect("Target is on the line");
+                if square == target {
+                    return true;
+                }
+                if square == blocker {
+                    return false;
+                }
+            }
+        }
+    }
+}
+
+/// If the squares are distinct and make a legal position, with the weak king not in check when
+/// the strong side is to move
+fn _legal(
+    extra: Extra,
+    to_move: usize,
+    strong_king: usize,
+    weak_king: usize,
+    square: usize,
+) -> bool {
+    strong_king != weak_king
+        && square != strong_king
+        && square != weak_king
+        && !_touching(strong_king, weak_king)
+        && (extra != Extra::Pawn || (8..56).contains(&square))
+        && !(to_move == STRONG && _attacks(extra, square, weak_king, strong_king))
+}
+
+/// Number of legal moves of the weak king, where capturing the extra piece counts as a move
+fn _weak_moves(extra: Extra, strong_king: usize, weak_king: usize, square: usize) -> u8 {
+    DIRECTIONS
+        .iter()
+        .filter_map(|&direction| _step(weak_king, direction))
+        .filter(|&to| {
+            !_touching(to, strong_king)
+                && (to == square || !_attacks(extra, square, to, strong_king))
+        })
+        .count() as u8
+}
+
+/// Distance to mate plus one of every position with the extra piece, 0 for draws and illegal
+/// positions
+///
+/// `promotions` are the KQK and KRK tables that pawns promote into.
+fn _solve(extra: Extra, promotions: Option<(&[u8], &[u8])>) -> Vec<u8> {
+    let mut table = vec![0u8; SIZE];
+    // Legal moves of the weak king not yet known to lose
+    let mut moves_left = vec![0u8; SIZE];
+    // Positions to solve per number of plies to mate
+    let mut queues: Vec<Vec<usize>> = vec![Vec::new()];
+    for strong_king in 0..64 {
+        for weak_king in 0..64 {
+            for square in 0..64 {
+                if !_legal(extra, WEAK, strong_king, weak_king, square) {
+                    continue;
+                }
+                let index = _index(WEAK, strong_king, weak_king, square);
+                moves_left[index] = _weak_moves(extra, strong_king, weak_king, square);
+                if moves_left[index] == 0 && _attacks(extra, square, weak_king, strong_king) {
+                    queues[0].push(index);
+                }
+            }
+        }
+    }
+    if let Some((kqk, krk)) = promotions {
+        // Pawns on the 7th rank win by promoting to a queen or rook that wins
+        for strong_king in 0..64 {
+            for weak_king in 0..64 {
+                for square in 48..56 {
+                    let to = square + 8;
+                    if !_legal(extra, STRONG, strong_king, weak_king, square)
+                        || to == strong_king
+                        || to == weak_king
+                    {
+                        continue;
+                    }
+                    let after = _index(WEAK, strong_king, weak_king, to);
+                    let plies = [kqk[after], krk[after]]
+                        .iter()
+                        .filter(|&&value| value > 0)
+                        .map(|&value| value as usize)
+                        .min();
+                    if let Some(plies) = plies {
+                        if queues.len() <= plies {
+                            queues.resize(plies + 1, Vec::new());
+                        }
+                        queues[plies].push(_index(STRONG, strong_king, weak_king, square));
+                    }
+                }
+            }
+        }
+    }
+
+    let mut plies = 0;
+    while plies < queues.len() {
+        let positions = core::mem::take(&mut queues[plies]);
+        let mut next = Vec::new();
+        for index in positions {
+            if table[index] != 0 {
+                continue;
+            }
+            table[index] = plies as u8 + 1;
+            let (strong_king, weak_king, square) =
+                ((index >> 12) & 63, (index >> 6) & 63, index & 63);
+            if index >> 18 == WEAK {
+                // The strong side moved into this lost position
+                for previous in _strong_unmoves(extra, strong_king, weak_king, square) {
+                    if table[previous] == 0 {
+                        next.push(previous);
+                    }
+                }
+            } else {
+                // Every weak king move from the previous position that leads here loses
+                for direction in DIRECTIONS {
+                    let from = match _step(weak_king, direction) {
+                        Some(from) if from != square && !_touching(from, strong_king) => from,
+                        _ => continue,
+                    };
+                    let previous = _index(WEAK, strong_king, from, square);
+                    if table[previous] == 0 && moves_left[previous] > 0 {
+                        moves_left[previous] -= 1;
+                        if moves_left[previous] == 0 {
+                            next.push(previous);
+                        }
+                    }
+                }
+            }
+        }
+        if !next.is_empty() {
+            if queues.len() <= plies + 1 {
+                queues.push(Vec::new());
+            }
+            queues[plies + 1].extend(next);
+        }
+        plies += 1;
+    }
+    table
+}
+
+/// Legal positions with the strong side to move that lead to the position by a strong move
+fn _strong_unmoves(
+    extra: Extra,
+    strong_king: usize,
+    weak_king: usize,
+    square: usize,
+) -> Vec<usize> {
+    let mut previous = Vec::new();
+    let empty = |from: usize| from != strong_king && from != weak_king && from != square;
+    for direction in DIRECTIONS {
+        if let Some(from) = _step(strong_king, direction) {
+            if empty(from) && _legal(extra, STRONG, from, weak_king, square) {
+                previous.push(_index(STRONG, from, weak_king, square));
+            }
+        }
+    }
+    let directions: &[(i32, i32)] = match extra {
+        Extra::Queen => &DIRECTIONS,
+        Extra::Rook => &DIRECTIONS[..4],
+        Extra::Pawn => {
+            // One step back, or two from the 4th rank to the 2nd
+            let mut froms = vec![square - 8];
+            if (24..32).contains(&square) && empty(square - 8) {
+                froms.push(square - 16);
+            }
+            for from in froms {
+                if empty(from) && _legal(extra, STRONG, strong_king, weak_king, from) {
+                    previous.push(_index(STRONG, strong_king, weak_king, from));
+                }
+            }
+            return previous;
+        }
+    };
+    for &direction in directions {
+        let mut from = square;
+        while let Some(next) = _step(from, direction).filter(|&next| empty(next)) {
+            from = next;
+            if _legal(extra, STRONG, strong_king, weak_king, from) {
+                previous.push(_index(STRONG, strong_king, weak_king, from));
+            }
+        }
+    }
+    previous
+}
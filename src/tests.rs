@@ -1,7 +1,9 @@
 //! To run tests in order and print output: `cargo test -- --nocapture --test-threads=1`
 
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod tests {
+    use std::collections::HashSet;
     use std::iter::FromIterator;
 
     use crate::*;
@@ -9,17 +11,27 @@ mod tests {
     /// Test that game state is in progress after initialization
     #[test]
     fn game_in_progress_after_init() {
-        let mut game = Game::new();
+        let game = Game::new();
 
         assert_eq!(game.get_game_state(), GameState::InProgress);
     }
 
-    /// JSON Serialization
+    /// JSON Serialization, with squares always in the same order
+    #[cfg(feature = "serde")]
     #[test]
     fn board_to_json() {
         let game = Game::new();
         let json = serde_json::to_string(&game).unwrap();
         assert_eq!(game, serde_json::from_str(&json).unwrap());
+
+        let mut rebuilt = Board::new();
+        for (position, piece) in game.pieces().collect::<Vec<_>>().into_iter().rev() {
+            rebuilt.insert(position, piece);
+        }
+        assert_eq!(
+            serde_json::to_string(&rebuilt).unwrap(),
+            serde_json::to_string(&game.board).unwrap()
+        );
     }
 
     /// Test starting board
@@ -29,43 +41,43 @@ mod tests {
         use Piece::*;
         let game = Game::new();
 
-        let starting_board: HashMap<Position, Piece> = [
+        let starting_board: Board = [
             // caWhite
-            (Position { file: 1, rank: 1 }, Rook(White)),
-            (Position { file: 2, rank: 1 }, Knight(White)),
-            (Position { file: 3, rank: 1 }, Bishop(White)),
-            (Position { file: 4, rank: 1 }, Queen(White)),
-            (Position { file: 5, rank: 1 }, King(White)),
-            (Position { file: 6, rank: 1 }, Bishop(White)),
-            (Position { file: 7, rank: 1 }, Knight(White)),
-            (Position { file: 8, rank: 1 }, Rook(White)),
+            (Square::A1.position(), Rook(White)),
+            (Square::B1.position(), Knight(White)),
+            (Square::C1.position(), Bishop(White)),
+            (Square::D1.position(), Queen(White)),
+            (Square::E1.position(), King(White)),
+            (Square::F1.position(), Bishop(White)),
+            (Square::G1.position(), Knight(White)),
+            (Square::H1.position(), Rook(White)),
             // White Pawns
-            (Position { file: 1, rank: 2 }, Pawn(White)),
-            (Position { file: 2, rank: 2 }, Pawn(White)),
-            (Position { file: 3, rank: 2 }, Pawn(White)),
-            (Position { file: 4, rank: 2 }, Pawn(White)),
-            (Position { file: 5, rank: 2 }, Pawn(White)),
-            (Position { file: 6, rank: 2 }, Pawn(White)),
-            (Position { file: 7, rank: 2 }, Pawn(White)),
-            (Position { file: 8, rank: 2 }, Pawn(White)),
+            (Square::A2.position(), Pawn(White)),
+            (Square::B2.position(), Pawn(White)),
+            (Square::C2.position(), Pawn(White)),
+            (Square::D2.position(), Pawn(White)),
+            (Square::E2.position(), Pawn(White)),
+            (Square::F2.position(), Pawn(White)),
+            (Square::G2.position(), Pawn(White)),
+            (Square::H2.position(), Pawn(White)),
             // Black
-            (Position { file: 1, rank: 8 }, Rook(Black)),
-            (Position { file: 2, rank: 8 }, Knight(Black)),
-            (Position { file: 3, rank: 8 }, Bishop(Black)),
-            (Position { file: 4, rank: 8 }, Queen(Black)),
-            (Position { file: 5, rank: 8 }, King(Black)),
-            (Position { file: 6, rank: 8 }, Bishop(Black)),
-            (Position { file: 7, rank: 8 }, Knight(Black)),
-            (Position { file: 8, rank: 8 }, Rook(Black)),
+            (Square::A8.position(), Rook(Black)),
+            (Square::B8.position(), Knight(Black)),
+            (Square::C8.position(), Bishop(Black)),
+            (Square::D8.position(), Queen(Black)),
+            (Square::E8.position(), King(Black)),
+            (Square::F8.position(), Bishop(Black)),
+            (Square::G8.position(), Knight(Black)),
+            (Square::H8.position(), Rook(Black)),
             // Black Pawns
-            (Position { file: 1, rank: 7 }, Pawn(Black)),
-            (Position { file: 2, rank: 7 }, Pawn(Black)),
-            (Position { file: 3, rank: 7 }, Pawn(Black)),
-            (Position { file: 4, rank: 7 }, Pawn(Black)),
-            (Position { file: 5, rank: 7 }, Pawn(Black)),
-            (Position { file: 6, rank: 7 }, Pawn(Black)),
-            (Position { file: 7, rank: 7 }, Pawn(Black)),
-            (Position { file: 8, rank: 7 }, Pawn(Black)),
+            (Square::A7.position(), Pawn(Black)),
+            (Square::B7.position(), Pawn(Black)),
+            (Square::C7.position(), Pawn(Black)),
+            (Square::D7.position(), Pawn(Black)),
+            (Square::E7.position(), Pawn(Black)),
+            (Square::F7.position(), Pawn(Black)),
+            (Square::G7.position(), Pawn(Black)),
+            (Square::H7.position(), Pawn(Black)),
         ]
         .iter()
         .cloned()
@@ -73,15 +85,112 @@ mod tests {
         assert_eq!(game.board, starting_board);
     }
 
+    /// Test board insertion and removal
+    #[test]
+    fn board_insert_remove() {
+        let mut board = Board::new();
+        let e4 = Square::E4.position();
+        assert_eq!(board.insert(e4, Piece::Pawn(Color::White)), None);
+        assert_eq!(
+            board.insert(e4, Piece::Knight(Color::Black)),
+            Some(Piece::Pawn(Color::White))
+        );
+        assert_eq!(board.len(), 1);
+        assert_eq!(board.get(&Position { file: 9, rank: 4 }), None);
+        assert_eq!(board.remove(&e4), Some(Piece::Knight(Color::Black)));
+        assert!(board.is_empty());
+    }
+
+    /// Named squares convert to positions and index the board
+    #[test]
+    fn named_squares() {
+        use std::convert::TryFrom;
+
+        for (index, &square) in Square::ALL.iter().enumerate() {
+            let position = Position::from(square);
+            assert_eq!(square.index(), index);
+            assert_eq!(position.index(), index);
+            assert_eq!(Square::try_from(position), Ok(square));
+            assert_eq!(square.to_string().parse(), Ok(square));
+        }
+        assert_eq!(Square::from_index(64), None);
+        assert!(Square::try_from(Position { file: 9, rank: 4 }).is_err());
+        assert_eq!((Square::C6.file(), Square::C6.rank()), (3, 6));
+
+        let game = Game::new();
+        assert_eq!(game.board()[Square::D8], Piece::Queen(Color::Black));
+        let kings: Vec<Square> = game
+            .pieces()
+            .filter(|(_, piece)| piece.kind() == PieceKind::King)
+            .map(|(position, _)| Square::try_from(position).unwrap())
+            .collect();
+        assert!(matches!(kings[..], [Square::E1, Square::E8]));
+    }
+
     /// Test that a valid position can be made from a string
     #[test]
     fn position_from_string() {
         let position1 = Position::from_string("d2".to_string());
         assert!(position1.is_ok());
-        assert_eq!(position1.unwrap(), Position { file: 4, rank: 2 });
+        assert_eq!(position1.unwrap(), Square::D2.position());
 
         let position2 = Position::from_string("k15".to_string());
-        assert!(!position2.is_ok());
+        assert!(position2.is_err());
+    }
+
+    /// Test that coordinates are trimmed, accept uppercase files and locate errors by byte offset
+    #[test]
+    fn coordinate_parse_errors() {
+        use coordinate::{ParseError, ParseErrorKind};
+        let error = |kind, offset| Err(ParseError { kind, offset });
+
+        assert_eq!("  H8\t".parse(), Ok(Square::H8.position()));
+        assert_eq!(
+            "e2extra".parse::<Position>(),
+            error(ParseErrorKind::TrailingCharacters, 2)
+        );
+        assert_eq!(
+            "e+4".parse::<Position>(),
+            error(ParseErrorKind::InvalidRank, 1)
+        );
+        assert_eq!(
+            "a10".parse::<Position>(),
+            error(ParseErrorKind::RankOutOfRange, 1)
+        );
+        assert_eq!(
+            " a0".parse::<Position>(),
+            error(ParseErrorKind::RankOutOfRange, 2)
+        );
+        assert_eq!(
+            "i4".parse::<Position>(),
+            error(ParseErrorKind::InvalidFile, 0)
+        );
+        assert_eq!(
+            " e".parse::<Position>(),
+            error(ParseErrorKind::MissingRank, 2)
+        );
+        assert_eq!("   ".parse::<Position>(), error(ParseErrorKind::Empty, 3));
+        let message: &'static str = "e9".parse::<Position>().unwrap_err().into();
+        assert_eq!(message, "Rank out of range: [1, 8]");
+
+        let target = Position::parse_target("e8=Q+").unwrap();
+        assert_eq!(target.position, Square::E8.position());
+        assert_eq!(target.promotion, Some(PieceKind::Queen));
+        assert!(target.check && !target.checkmate);
+        let target = Position::parse_target(" a1n ").unwrap();
+        assert_eq!(target.promotion, Some(PieceKind::Knight));
+        assert_eq!(
+            Position::parse_target("d7#").map(|target| (target.promotion, target.checkmate)),
+            Ok((None, true))
+        );
+        assert_eq!(
+            Position::parse_target("e8=K").map(|target| target.position),
+            error(ParseErrorKind::InvalidPromotion, 3)
+        );
+        assert_eq!(
+            Position::parse_target("e8q+!").map(|target| target.position),
+            error(ParseErrorKind::TrailingCharacters, 4)
+        );
     }
 
     /// Test setting a promotion piece
@@ -89,10 +198,26 @@ mod tests {
     fn set_promotion_piece() {
         let mut game = Game::new();
         game.set_promotion("Knight".to_string()).unwrap();
-        assert!(game.promotion.contains(&Piece::Knight(Color::White)));
+        assert_eq!(
+            game.promotion_piece(Color::White),
+            Piece::Knight(Color::White)
+        );
         game.active_color = Color::Black;
         game.set_promotion("Rook".to_string()).unwrap();
-        assert!(game.promotion.contains(&Piece::Rook(Color::Black)));
+        assert_eq!(
+            game.promotion_piece(Color::Black),
+            Piece::Rook(Color::Black)
+        );
+        assert!(game.set_promotion_piece(Piece::King(Color::White)).is_err());
+        game.set_promotion_piece(Piece::Bishop(Color::White))
+            .unwrap();
+        assert_eq!(
+            game.promotion_piece(Color::White),
+            Piece::Bishop(Color::White)
+        );
+        assert_eq!(game.side_to_move(), Color::Black);
+        assert_eq!(game.state(), GameState::InProgress);
+        assert_eq!(game.board().len(), 32);
     }
 
     /// Test pawn promotion (and pawn diagonal capture)
@@ -115,11 +240,30 @@ mod tests {
             game.make_move(from.to_string(), to.to_string()).unwrap();
         }
         assert_eq!(
-            game.board.get(&Position { file: 2, rank: 8 }),
+            game.board.get(&Square::B8.position()),
             Some(&Piece::Knight(Color::White))
         );
     }
 
+    /// Promotions with the player's promotion piece are recorded with the piece in SAN and PGN
+    #[test]
+    fn promotion_record() {
+        let mut game = Game::from_fen("4k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        game.set_promotion_piece(Piece::Rook(Color::White)).unwrap();
+        let mov = Move::from_uci("a7a8").unwrap();
+        assert!(!mov.is_promotion());
+        assert_eq!(san::to_san(&game, mov), Ok("a8=R+".to_string()));
+        let outcome = game.make_move("a7".to_string(), "a8".to_string()).unwrap();
+        assert_eq!(outcome.san, "a8=R+");
+        let last = game.last_move().unwrap();
+        assert!(last.is_promotion());
+        assert_eq!(last.promoted_to(), Some(Piece::Rook(Color::White)));
+        assert_eq!(last.to_uci(), "a7a8r");
+        assert!(game.to_pgn().contains("1. a8=R+"));
+        game.make_move("e8".to_string(), "d7".to_string()).unwrap();
+        assert!(!game.last_move().unwrap().is_promotion());
+    }
+
     /// Test for possible moves on a piece
     #[test]
     fn possible_moves() {
@@ -127,20 +271,22 @@ mod tests {
         assert_eq!(game.get_possible_moves("e1".to_string()), Some(vec![]));
         // Test c2 white pawn
         assert_eq!(
-            game._get_possible_moves(&Position { file: 3, rank: 2 })
-                .unwrap(),
+            HashSet::<Position>::from_iter(
+                game._get_possible_moves(&Square::C2.position()).unwrap()
+            ),
             HashSet::from_iter(
-                [Position { file: 3, rank: 4 }, Position { file: 3, rank: 3 }]
+                [Square::C4.position(), Square::C3.position()]
                     .iter()
                     .cloned()
             )
         );
         // Test f7 black pawn
         assert_eq!(
-            game._get_possible_moves(&Position { file: 6, rank: 7 })
-                .unwrap(),
+            HashSet::<Position>::from_iter(
+                game._get_possible_moves(&Square::F7.position()).unwrap()
+            ),
             HashSet::from_iter(
-                [Position { file: 6, rank: 6 }, Position { file: 6, rank: 5 }]
+                [Square::F6.position(), Square::F5.position()]
                     .iter()
                     .cloned()
             )
@@ -151,14 +297,46 @@ mod tests {
         assert_eq!(game.get_possible_moves("e1".to_string()).unwrap().len(), 0);
     }
 
+    /// Test counting legal moves
+    #[test]
+    fn legal_move_count() {
+        let mut game = Game::new();
+        assert_eq!(game.legal_move_count(), 20);
+        game.make_move("e2".to_string(), "e4".to_string()).unwrap();
+        assert_eq!(game.legal_move_count(), 20);
+        game.make_move("e7".to_string(), "e5".to_string()).unwrap();
+        assert_eq!(game.legal_move_count(), 29);
+    }
+
+    /// Test that moves for all squares match moves for single squares
+    #[test]
+    fn all_moves_by_square() {
+        let mut game = Game::new();
+        game.make_move("e2".to_string(), "e4".to_string()).unwrap();
+        game.make_move("f7".to_string(), "f6".to_string()).unwrap();
+        game.make_move("d1".to_string(), "h5".to_string()).unwrap();
+        let all_moves = game.all_moves_by_square();
+        // Black is in check and can only block with the g7 pawn
+        let mut movable: Vec<String> = all_moves.keys().map(|p| p.to_string()).collect();
+        movable.sort_unstable();
+        assert_eq!(movable, vec!["g7"]);
+        for (from, moves) in all_moves {
+            let destinations: Vec<String> = moves.iter().map(|m| m.to.to_string()).collect();
+            assert_eq!(
+                game.get_possible_moves(from.to_string()),
+                Some(destinations)
+            );
+        }
+    }
+
     /// Test if piece in the way
     #[test]
     fn piece_in_way() {
         let game = Game::new();
         let res = game._is_piece_in_way(
             &Piece::Bishop(Color::White),
-            &Position { file: 6, rank: 1 },
-            &Position { file: 8, rank: 3 },
+            &Square::F1.position(),
+            &Square::H3.position(),
         );
         assert!(res);
     }
@@ -173,6 +351,40 @@ mod tests {
         assert!(game.make_move("b1".to_string(), "c3".to_string()).is_ok());
     }
 
+    /// Test that unmaking a move restores the game (capture and promotion)
+    #[test]
+    fn make_unmake() {
+        let mut game = Game::new();
+        let moves = [
+            ("a2", "a4"),
+            ("b7", "b5"),
+            ("a4", "b5"),
+            ("b8", "a6"),
+            ("b5", "b6"),
+            ("a6", "b4"),
+            ("b6", "b7"),
+            ("b4", "d5"),
+        ];
+        for (from, to) in moves {
+            game.make_move(from.to_string(), to.to_string()).unwrap();
+        }
+        let before = game.clone();
+        let pos = |s: &str| Position::from_string(s.to_string()).unwrap();
+        // Capture and promote on a8
+        let undo = game._make(Move {
+            from: pos("b7"),
+            to: pos("a8"),
+            promotion: None,
+        });
+        assert_eq!(
+            game.board.get(&pos("a8")),
+            Some(&Piece::Queen(Color::White))
+        );
+        assert_eq!(undo.captured, Some(Piece::Rook(Color::Black)));
+        game._unmake(undo);
+        assert!(game == before);
+    }
+
     // Test checkmate with [fool's mate](https://www.chess.com/terms/fools-mate)
     #[test]
     fn fools_mate() {
@@ -202,4 +414,2831 @@ mod tests {
         }
         assert_eq!(game.get_game_state(), GameState::CheckMate);
     }
+
+    /// Finished games have a result and accept no more moves
+    #[test]
+    fn game_results() {
+        let mut game = Game::new();
+        assert_eq!(game.result(), None);
+        for san in ["f3", "e5", "g4", "Qh4"] {
+            game.make_move_san(san).unwrap();
+        }
+        assert!(game.is_game_over());
+        assert_eq!(game.result(), Some(GameResult::BlackWins));
+        assert_eq!(
+            game.make_move("a2".to_string(), "a3".to_string()),
+            Err(ChessError::GameOver(GameResult::BlackWins))
+        );
+        let error: &'static str = game.make_move_san("a3").unwrap_err().into();
+        assert_eq!(error, "The game is over");
+        assert_eq!(
+            Game::new().make_move_san("Ke2"),
+            Err(ChessError::InvalidMove("No legal move matches"))
+        );
+
+        let stalemate = Game::from_fen("k7/8/1Q6/8/8/8/8/7K b - - 0 1").unwrap();
+        assert_eq!(
+            stalemate.get_game_state(),
+            GameState::Draw(DrawReason::Stalemate)
+        );
+        assert_eq!(
+            stalemate.result(),
+            Some(GameResult::Draw(DrawReason::Stalemate))
+        );
+        assert!(stalemate.to_pgn().trim_end().ends_with("1/2-1/2"));
+    }
+
+    /// Test that the engine finds mate in one (scholar's mate)
+    #[test]
+    fn engine_finds_mate() {
+        let mut game = Game::new();
+        let moves = [
+            ("e2", "e4"),
+            ("e7", "e5"),
+            ("d1", "h5"),
+            ("b8", "c6"),
+            ("f1", "c4"),
+            ("g8", "f6"),
+        ];
+        for (from, to) in moves {
+            game.make_move(from.to_string(), to.to_string()).unwrap();
+        }
+        let result = Engine::new(2).search(&game);
+        let best = result.best_move.unwrap();
+        assert_eq!(
+            (best.from.to_string(), best.to.to_string()),
+            ("h5".to_string(), "f7".to_string())
+        );
+        assert!(result.score > engine::MATE_SCORE - 10);
+        let probability = Engine::new(2).win_probability(&game);
+        assert_eq!(probability.white, 1.0);
+    }
+
+    /// Test endgame detection and attacks around the king
+    #[test]
+    fn king_zone() {
+        let game = Game::from_fen("6k1/5ppp/8/6NQ/8/8/8/6K1 w - - 0 1").unwrap();
+        assert!(game.is_endgame());
+        let zone = game.king_zone_attackers(Color::Black).unwrap();
+        let squares = |positions: &[Position]| -> Vec<String> {
+            positions.iter().map(|square| square.to_string()).collect()
+        };
+        assert_eq!(squares(&zone.attackers), ["g5", "h5"]);
+        assert_eq!(squares(&zone.attacked_squares), ["f7", "h7"]);
+        assert_eq!(zone.attacks, 4);
+        assert_eq!(game.king_zone_attackers(Color::White).unwrap().attacks, 0);
+
+        let queen_and_rook = Game::from_fen("6k1/5ppp/8/7Q/8/8/8/R5K1 w - - 0 1").unwrap();
+        assert!(!queen_and_rook.is_endgame());
+        assert!(Game::from_fen("r5k1/8/8/8/8/8/8/R5K1 w - - 0 1")
+            .unwrap()
+            .is_endgame());
+    }
+
+    /// Weak levels vary their moves by seed, the master level plays the best move
+    #[test]
+    fn engine_levels() {
+        use crate::engine::EngineLevel;
+
+        assert_eq!("Beginner".parse(), Ok(EngineLevel::Beginner));
+        assert!("grandmaster".parse::<EngineLevel>().is_err());
+        assert!(EngineLevel::Beginner < EngineLevel::Master);
+
+        let game = Game::new();
+        let engine = Engine::new(3);
+        let beginner: HashSet<Move> = (0..20)
+            .map(|seed| {
+                engine
+                    .best_move_with_level(&game, EngineLevel::Beginner, seed)
+                    .unwrap()
+            })
+            .collect();
+        assert!(beginner.len() > 2);
+        assert_eq!(
+            engine.best_move_with_level(&game, EngineLevel::Casual, 7),
+            engine.best_move_with_level(&game, EngineLevel::Casual, 7)
+        );
+
+        // Scholar's mate is found even with noise, as mate scores are far above it
+        let game =
+            Game::from_fen("r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4")
+                .unwrap();
+        let mate = Move::from_uci("h5f7").unwrap();
+        assert_eq!(
+            engine.best_move_with_level(&game, EngineLevel::Master, 0),
+            Some(mate)
+        );
+        assert_eq!(
+            engine.best_move_with_level(&game, EngineLevel::Advanced, 0),
+            Some(mate)
+        );
+    }
+
+    /// Tablebase results agree with the moves of the rules core and the engine mates in time
+    #[test]
+    fn tablebase_endgames() {
+        use crate::random::Constraints;
+        use crate::tablebase::EndgameResult;
+
+        for material in ["KRvK", "KvKP", "KQvK"] {
+            let constraints = Constraints::endgame(material).unwrap();
+            for seed in 0..20 {
+                let game = Game::random_position(seed, &constraints).unwrap();
+                let result = game.endgame_result().unwrap();
+                let after_best = |mov: Move| {
+                    let mut after = game.clone();
+                    after.make_move_uci(&mov.to_uci()).unwrap();
+                    after.endgame_result().unwrap_or(EndgameResult::Draw)
+                };
+                match (result, game.endgame_best_move()) {
+                    (EndgameResult::Win { winner, plies }, Some(mov)) => {
+                        assert_eq!(
+                            after_best(mov),
+                            EndgameResult::Win {
+                                winner,
+                                plies: plies - 1
+                            },
+                            "{}",
+                            game.to_fen()
+                        );
+                    }
+                    (EndgameResult::Draw, Some(mov)) => {
+                        assert_eq!(after_best(mov), EndgameResult::Draw, "{}", game.to_fen())
+                    }
+                    (_, None) => assert!(game.result().is_some()),
+                }
+            }
+        }
+
+        // Rook endgame with a long mate, 14 moves
+        let mut game = Game::from_fen("8/8/3k4/8/8/8/8/RK6 w - - 0 1").unwrap();
+        assert_eq!(
+            game.endgame_result(),
+            Some(EndgameResult::Win {
+                winner: Color::White,
+                plies: 27
+            })
+        );
+        let engine = Engine::new(1);
+        for _ in 0..27 {
+            let mov = engine.best_move(&game).unwrap();
+            game.make_move_uci(&mov.to_uci()).unwrap();
+        }
+        assert_eq!(game.result(), Some(GameResult::WhiteWins));
+        assert_eq!(
+            Game::from_fen("8/8/8/8/8/8/8/k1K5 w - - 0 1")
+                .unwrap()
+                .endgame_result(),
+            Some(EndgameResult::Draw)
+        );
+        assert_eq!(Game::new().endgame_result(), None);
+    }
+
+    /// Random positions are legal, follow the constraints and reload from FEN
+    #[test]
+    fn random_positions() {
+        use crate::random::Constraints;
+
+        let kpk = Constraints::endgame("KPvK")
+            .unwrap()
+            .side_to_move(Color::Black);
+        for seed in 0..50 {
+            let game = Game::random_position(seed, &Constraints::default()).unwrap();
+            assert!(game.validate().is_ok(), "{}", game.to_fen());
+            assert!(game.pieces().count() <= 18);
+            assert_eq!(Game::from_fen(&game.to_fen()).unwrap(), game);
+
+            let game = Game::random_position(seed, &kpk).unwrap();
+            assert_eq!(game.side_to_move(), Color::Black);
+            let (pawn, _) = game
+                .pieces()
+                .find(|(_, piece)| *piece == Piece::Pawn(Color::White))
+                .unwrap();
+            assert!((2..=7).contains(&pawn.rank));
+        }
+        assert_ne!(
+            Game::random_position(1, &Constraints::default()),
+            Game::random_position(2, &Constraints::default())
+        );
+        assert!(Constraints::endgame("KXvK").is_err());
+        assert!(Constraints::endgame("RvK").is_err());
+        let pawns = Constraints::endgame("KPPPPPPPPPvK").unwrap();
+        assert!(Game::random_position(0, &pawns).is_err());
+    }
+
+    /// Test a backward pawn behind its neighbours and passed pawns of both colors
+    #[test]
+    fn pawn_structure() {
+        let game = Game::from_fen("4k3/8/8/2p5/2P1P3/3P4/8/4K3 w - - 0 1").unwrap();
+        let squares = |positions: &[Position]| -> Vec<String> {
+            positions.iter().map(|square| square.to_string()).collect()
+        };
+        let white = game.pawn_structure(Color::White);
+        assert_eq!(squares(&white.backward), ["d3"]);
+        assert_eq!(squares(&white.passed), ["e4"]);
+        assert!(white.doubled.is_empty() && white.isolated.is_empty());
+        let black = game.pawn_structure(Color::Black);
+        assert_eq!(squares(&black.isolated), ["c5"]);
+        assert!(black.passed.is_empty() && black.backward.is_empty());
+
+        // Without the pawn attacking d4, d3 may advance
+        let game = Game::from_fen("4k3/8/8/8/2P1P3/3P4/8/4K3 w - - 0 1").unwrap();
+        assert!(game.pawn_structure(Color::White).backward.is_empty());
+    }
+
+    /// Test attack counts through blocked lines and mobility of pinned pieces
+    #[test]
+    fn heatmap_counts() {
+        // The knight on e2 is pinned by the rook on e8
+        let game = Game::from_fen("4r1k1/8/8/8/8/8/4N3/4K2R w K - 0 1").unwrap();
+        let heatmap = game.heatmap();
+        let square = |name: &str| {
+            let position: Position = name.parse().unwrap();
+            (position.rank as usize - 1, position.file as usize - 1)
+        };
+        let (rank, file) = square("e2");
+        assert_eq!(heatmap.mobility[rank][file], 0);
+        assert_eq!(heatmap.black[rank][file], 1);
+        assert_eq!(heatmap.white[rank][file], 1);
+        // The knight blocks the rook from e1
+        let (rank, file) = square("e1");
+        assert_eq!(heatmap.black[rank][file], 0);
+        // Defended by the rook on h1
+        assert_eq!(heatmap.control("e1".parse().unwrap()), 1);
+        assert_eq!(heatmap.control("f1".parse().unwrap()), 2);
+        // Kingside castling, Kd1, Kd2, Kf1 and Kf2
+        let (rank, file) = square("e1");
+        assert_eq!(heatmap.mobility[rank][file], 5);
+        // The rook on h1 guards h7 and h8
+        let (rank, file) = square("g8");
+        assert_eq!(heatmap.mobility[rank][file], 3);
+        assert_eq!(heatmap.attacks(Color::Black), &heatmap.black);
+    }
+
+    /// Test that the starting position is estimated as equal
+    #[test]
+    fn win_probability_equal_start() {
+        // White's first move gains a tempo at odd depths, so both players move in the search
+        let engine = Engine::new(2);
+        assert_eq!(engine.evaluate(&Game::new()), 0);
+        let probability = engine.win_probability(&Game::new());
+        assert_eq!(
+            probability,
+            engine::WinProbability::from_score(engine.search(&Game::new()).score)
+        );
+        assert!((probability.white - probability.black).abs() < 1e-9);
+        assert!((probability.expected_score() - 0.5).abs() < 1e-9);
+        assert!((probability.white + probability.draw + probability.black - 1.0).abs() < 1e-9);
+        let better = engine::WinProbability::from_score(300);
+        assert!(better.white > better.black && better.expected_score() > 0.5);
+    }
+
+    /// Test that the threat of scholar's mate is found and drawn as an arrow
+    #[test]
+    fn threat_arrow() {
+        let mut game = Game::new();
+        let moves = [
+            ("e2", "e4"),
+            ("e7", "e5"),
+            ("d1", "h5"),
+            ("b8", "c6"),
+            ("f1", "c4"),
+        ];
+        for (from, to) in moves {
+            game.make_move(from.to_string(), to.to_string()).unwrap();
+        }
+        let threat = Engine::new(2).threat(&game).unwrap();
+        assert_eq!(threat.to, Square::F7.position());
+        let diagram = render::annotated_diagram(
+            &game,
+            &[render::Annotation::Arrow(threat, render::Highlight::Threat)],
+        );
+        // Empty g6 is on the path from h5 to f7
+        assert!(diagram.contains('↖'));
+        assert!(diagram.contains("\x1B[41m"));
+        assert!(Engine::new(2).threat(&Game::new()).is_none());
+    }
+
+    /// Test moves in algebraic notation including castling and disambiguation
+    #[test]
+    fn make_move_san() {
+        let mut game = Game::new();
+        for san in [
+            "e4", "e5", "Nf3", "Nc6", "Bc4", "Nf6", "O-O", "d5", "exd5", "Nxd5", "Nc3",
+        ] {
+            game.make_move_san(san).unwrap();
+        }
+        let pos = |s: &str| Position::from_string(s.to_string()).unwrap();
+        assert_eq!(game.board.get(&pos("g1")), Some(&Piece::King(Color::White)));
+        assert_eq!(game.board.get(&pos("f1")), Some(&Piece::Rook(Color::White)));
+        assert!(!game.castling[0].kingside && !game.castling[0].queenside);
+        // Both black knights can move to b4
+        assert!(game.make_move_san("Nb4").is_err());
+        game.make_move_san("Ncb4").unwrap();
+        game.make_move_san("Nxd5").unwrap();
+        game.make_move_san("Nxd5").unwrap();
+        // White has already castled
+        assert!(game.make_move_san("O-O-O").is_err());
+        assert!(game.make_move_san("Zf3").is_err());
+    }
+
+    /// Test promotion with piece given in algebraic notation
+    #[test]
+    fn san_promotion() {
+        let mut game = Game::new();
+        for san in [
+            "a4", "b5", "axb5", "Na6", "b6", "Nb4", "b7", "Nd5", "bxa8=N+",
+        ] {
+            game.make_move_san(san).unwrap();
+        }
+        let a8 = Square::A8.position();
+        assert_eq!(game.board.get(&a8), Some(&Piece::Knight(Color::White)));
+    }
+
+    /// Moves written and read with German, Swedish and figurine piece letters
+    #[test]
+    fn localized_notation() {
+        use notation::Notation;
+        let mut game = Game::new();
+        for san in ["e4", "e5", "Sf3", "Sc6", "Lb5", "a6", "Lxc6", "dxc6", "O-O"] {
+            let mov = Notation::German.parse_san(&game, san).unwrap();
+            assert_eq!(Notation::German.to_san(&game, mov).unwrap(), san);
+            game.make_move_uci(&mov.to_uci()).unwrap();
+        }
+        let mov = Notation::Swedish.parse_san(&game, "Dd6").unwrap();
+        assert_eq!(mov, Move::from_uci("d8d6").unwrap());
+        assert_eq!(Notation::Figurine.to_san(&game, mov).unwrap(), "♛d6");
+        // Figurines of either color and English letters are understood
+        assert_eq!(Notation::Figurine.parse_san(&game, "♕d6"), Ok(mov));
+        assert_eq!(Notation::German.parse_san(&game, "Qd6"), Ok(mov));
+        assert!(Notation::German.parse_san(&game, "Zd6").is_err());
+
+        let promotion = Game::from_fen("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let mov = Notation::Swedish.parse_san(&promotion, "b8=S").unwrap();
+        assert_eq!(mov.promotion, Some(Piece::Knight(Color::White)));
+        assert_eq!(Notation::Figurine.localize("b8=N"), "b8=♞");
+        assert_eq!("Swedish".parse(), Ok(Notation::Swedish));
+        assert!("klingon".parse::<Notation>().is_err());
+    }
+
+    /// Test self-play training data ending in fool's mate and its binary round trip
+    #[test]
+    fn training_data() {
+        let mut game = Game::new();
+        for san in ["f3", "e5", "g4"] {
+            game.make_move_san(san).unwrap();
+        }
+        let samples = training::self_play(&Engine::new(1), &game, 10);
+        // Black mates with Qh4
+        assert_eq!(samples.len(), 1);
+        assert_eq!(samples[0].side_to_move, Color::Black);
+        assert_eq!(samples[0].result, 1);
+        // d8 = 59, h4 = 31
+        assert_eq!(samples[0].move_index, 59 * 64 + 31);
+        assert_eq!(samples[0].planes[0], 1 << 4); // White king on e1
+
+        let mut bytes = Vec::new();
+        training::write_samples(&mut bytes, &samples).unwrap();
+        assert_eq!(bytes.len(), 5 + training::RECORD_SIZE);
+        assert_eq!(training::read_samples(&bytes[..]).unwrap(), samples);
+        assert!(training::read_samples(&bytes[..50]).is_err());
+    }
+
+    /// Test UCI move strings
+    #[test]
+    fn uci_moves() {
+        let mov = Move::from_uci("e7e8q").unwrap();
+        assert_eq!(mov.promotion, Some(Piece::Queen(Color::White)));
+        assert_eq!(mov.to_uci(), "e7e8q");
+        assert_eq!(
+            Move::from_uci("a2a1n").unwrap().promotion,
+            Some(Piece::Knight(Color::Black))
+        );
+        assert!(Move::from_uci("e2e9").is_err());
+        assert!(Move::from_uci("e7e8k").is_err());
+
+        let mut game = Game::new();
+        assert!(game.make_move_uci("e2e4q").is_err());
+        assert!(game.make_move_uci("g1f3n").is_err());
+        for uci in ["e2e4", "e7e5", "g1f3", "b8c6", "f1c4", "g8f6", "e1g1"] {
+            game.make_move_uci(uci).unwrap();
+        }
+        assert_eq!(
+            game.board.get(&Square::F1.position()),
+            Some(&Piece::Rook(Color::White))
+        );
+        assert!(game.make_move_uci("e5e4").is_err());
+    }
+
+    /// Test FEN export and import
+    #[test]
+    fn fen_round_trip() {
+        let mut game = Game::new();
+        assert_eq!(game.to_fen(), fen::STARTING_FEN);
+        game.make_move_san("e4").unwrap();
+        assert_eq!(
+            game.to_fen(),
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"
+        );
+        let loaded = Game::from_fen(&game.to_fen()).unwrap();
+        assert!(loaded == game);
+        assert!(Game::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP w KQkq - 0 1").is_err());
+        assert!(
+            Game::from_fen("rnbqkbnr/pppppppp/9/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").is_err()
+        );
+        assert!(
+            Game::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR x KQkq - 0 1").is_err()
+        );
+    }
+
+    /// Test en passant capture
+    #[test]
+    fn en_passant() {
+        let mut game = Game::new();
+        for san in ["e4", "a6", "e5", "d5"] {
+            game.make_move_san(san).unwrap();
+        }
+        assert_eq!(game.en_passant, Some(Square::D6.position()));
+        assert_eq!(
+            game.get_possible_moves("e5".to_string()),
+            Some(vec!["d6".to_string(), "e6".to_string()])
+        );
+        assert_eq!(
+            game.make_move_san("exd6").unwrap().captured,
+            Some(Piece::Pawn(Color::Black))
+        );
+        assert!(!game.board.contains_key(&Square::D5.position()));
+        // En passant is only possible directly after the two square move
+        let mut game = Game::new();
+        for san in ["e4", "a6", "e5", "d5", "h3", "h6"] {
+            game.make_move_san(san).unwrap();
+        }
+        assert!(game.make_move_san("exd6").is_err());
+    }
+
+    /// Test move generation against known-good perft vectors
+    #[test]
+    fn verify_installation() {
+        assert_eq!(verification::verify_installation(), Ok(()));
+    }
+
+    /// Test building custom positions and their validation
+    #[test]
+    fn game_builder() {
+        use Color::*;
+        use Piece::*;
+        let both_sides = CastlingRights {
+            kingside: true,
+            queenside: true,
+        };
+        let game = Game::builder()
+            .piece("e1", King(White))
+            .piece("a1", Rook(White))
+            .piece("h1", Rook(White))
+            .piece("e8", King(Black))
+            .castling(White, both_sides)
+            .build()
+            .unwrap();
+        assert_eq!(game.to_fen(), "4k3/8/8/8/8/8/8/R3K2R w KQ - 0 1");
+        let mut castled = game.clone();
+        castled.make_move_san("O-O-O").unwrap();
+
+        assert!(Game::builder().piece("e1", King(White)).build().is_err());
+        assert!(Game::builder()
+            .piece("e1", King(White))
+            .piece("e8", King(Black))
+            .piece("d8", Pawn(Black))
+            .build()
+            .is_err());
+        assert!(Game::builder()
+            .piece("e1", King(White))
+            .piece("e8", King(Black))
+            .castling(Black, both_sides)
+            .build()
+            .is_err());
+        assert!(Game::builder().piece("i9", King(White)).build().is_err());
+        // Black is in check with White to move
+        assert!(Game::builder()
+            .piece("e1", King(White))
+            .piece("e8", King(Black))
+            .piece("e4", Rook(White))
+            .build()
+            .is_err());
+        let check = Game::builder()
+            .piece("e1", King(White))
+            .piece("e8", King(Black))
+            .piece("e4", Rook(White))
+            .active_color(Black)
+            .build()
+            .unwrap();
+        assert_eq!(check.get_game_state(), GameState::Check);
+
+        // Building from a game keeps its variant and rules
+        let atomic = GameBuilder::from(&Game::new_atomic()).build().unwrap();
+        assert_eq!(atomic.variant, Variant::Atomic);
+        let chess960 = Game::new_chess960(0).unwrap();
+        let rebuilt = GameBuilder::from(&chess960).build().unwrap();
+        assert!(rebuilt.chess960);
+        assert_eq!(rebuilt.castling_files, chess960.castling_files);
+        assert_eq!(rebuilt.to_fen(), chess960.to_fen());
+    }
+
+    /// Test that a side configured to not castle cannot castle
+    #[test]
+    fn asymmetric_config() {
+        use config::{GameConfig, TimeControl};
+        use std::time::Duration;
+        let odds = TimeControl::new(Duration::from_secs(60), Duration::from_secs(1));
+        let config = GameConfig::new()
+            .time_control(Color::Black, odds)
+            .forbid_castling(Color::White);
+        let mut game = Game::with_config(config);
+        assert_eq!(game.config.side(Color::Black).time_control, Some(odds));
+        assert_eq!(game.config.side(Color::White).time_control, None);
+        for san in ["e4", "e5", "Nf3", "Nf6", "Bc4", "Bc5"] {
+            game.make_move_san(san).unwrap();
+        }
+        // Restoring the rights does not allow castling either
+        game.castling[0].kingside = true;
+        assert!(game.make_move_san("O-O").is_err());
+        game.make_move_san("d3").unwrap();
+        game.make_move_san("O-O").unwrap();
+    }
+
+    /// Test a scripted mate in two lesson
+    #[test]
+    fn scenario_lesson() {
+        use scenario::{Goal, Lesson, Progress, Scenario};
+        // Back rank mate
+        let scenario = Scenario::from_fen(
+            "Back rank mate",
+            "6k1/5ppp/8/8/8/8/8/3RK3 w - - 0 1",
+            Goal::Checkmate,
+        )
+        .unwrap()
+        .step(&["d1d8"], None)
+        .unwrap();
+        let mut lesson = Lesson::new(&scenario);
+        assert_eq!(lesson.play_san("Kf1"), Ok(Progress::WrongMove));
+        assert!(lesson.play_san("Rd9").is_err());
+        assert_eq!(lesson.play_san("Rd8"), Ok(Progress::Success));
+        assert!(lesson.is_finished());
+        assert!(lesson.play_san("Kf1").is_err());
+
+        let scenario =
+            Scenario::from_fen("Follow the script", fen::STARTING_FEN, Goal::CompleteScript)
+                .unwrap()
+                .step(&["e2e4"], Some("e7e5"))
+                .unwrap()
+                .step(&[], Some("b8c6"))
+                .unwrap();
+        let mut lesson = Lesson::new(&scenario);
+        let reply = Move::from_uci("e7e5").unwrap();
+        assert_eq!(lesson.play_san("e4"), Ok(Progress::Continue(Some(reply))));
+        assert_eq!(lesson.play_san("Nf3"), Ok(Progress::Success));
+        assert_eq!(
+            lesson.game.board.get(&Square::C6.position()),
+            Some(&Piece::Knight(Color::Black))
+        );
+    }
+
+    /// Test importing Lichess puzzles and accepting another mate than the solution's
+    #[test]
+    fn lichess_puzzle() {
+        use puzzle::{Puzzle, Verdict};
+        let csv = "PuzzleId,FEN,Moves,Rating,RatingDeviation,Popularity,NbPlays,Themes,GameUrl,OpeningTags
+            test1,6k1/p4ppp/8/8/8/8/8/3RR1K1 b - - 0 1,a7a6 d1d8,600,75,90,100,backRankMate mate mateIn1 oneMove,,
+
+            test2,6k1/p4ppp/8/8/8/8/8/3RR1K1 b - - 0 1,a7a6 d1d9";
+        assert_eq!(
+            Puzzle::parse_lichess_csv(csv),
+            Err("Invalid destination square")
+        );
+        let puzzles = Puzzle::parse_lichess_csv(csv.rsplit_once('\n').unwrap().0).unwrap();
+        assert_eq!(puzzles.len(), 1);
+        let puzzle = &puzzles[0];
+        assert_eq!(puzzle.user_moves(), 1);
+        assert_eq!(puzzle.themes[0], "backRankMate");
+        assert_eq!(puzzle.game.side_to_move(), Color::White);
+
+        let mov = |uci| Move::from_uci(uci).unwrap();
+        assert_eq!(puzzle.verify_solution(&[]), Verdict::Correct);
+        assert_eq!(puzzle.verify_solution(&[mov("d1d8")]), Verdict::Solved);
+        assert_eq!(puzzle.verify_solution(&[mov("e1e8")]), Verdict::Solved);
+        assert_eq!(puzzle.verify_solution(&[mov("e1e7")]), Verdict::Wrong(0));
+        assert_eq!(
+            puzzle.verify_solution(&[mov("d1d8"), mov("g8f8")]),
+            Verdict::Wrong(1)
+        );
+        assert_eq!(
+            Puzzle::from_lichess_csv(
+                "test3,6k1/p4ppp/8/8/8/8/8/3RR1K1 b - - 0 1,a7a6 d1d7 g8h8 d7d9"
+            ),
+            Err("Invalid destination square")
+        );
+        assert_eq!(
+            Puzzle::from_lichess_csv("test3,6k1/p4ppp/8/8/8/8/8/3RR1K1 b - - 0 1,a7a6 d1d7 h7h8"),
+            Err("Solution has an illegal move")
+        );
+    }
+
+    /// Test comparing a blunder with the best move
+    #[test]
+    fn engine_compare() {
+        // White can capture the undefended queen or move the king
+        let game = Game::from_fen("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1").unwrap();
+        let capture = Move::from_uci("d2d5").unwrap();
+        let push = Move::from_uci("e1f1").unwrap();
+        let comparison = Engine::new(3).compare(&game, push, capture, 3).unwrap();
+        assert!(comparison.difference() < -500);
+        assert_eq!(comparison.b.moves[0], capture);
+        assert_eq!(comparison.b.moves.len(), 3);
+        assert!(comparison.b.score > 400);
+        let illegal = Move::from_uci("d2e3").unwrap();
+        assert!(Engine::new(3).compare(&game, illegal, capture, 3).is_err());
+    }
+
+    /// Test Crazyhouse drops and demotion of captured promoted pieces
+    #[test]
+    fn crazyhouse_drops() {
+        let mut game = Game::new_crazyhouse();
+        for san in ["e4", "d5", "exd5", "Qxd5"] {
+            game.make_move_san(san).unwrap();
+        }
+        let reserves = game.crazyhouse.unwrap().reserves;
+        assert_eq!(reserves[0].count(&Piece::Pawn(Color::White)), 1);
+        assert_eq!(reserves[1].count(&Piece::Pawn(Color::Black)), 1);
+        assert!(game.drop_piece("N@e4").is_err());
+        assert!(game.drop_piece("P@d8").is_err());
+        assert!(game.drop_piece("P@d5").is_err());
+        game.drop_piece("P@c4").unwrap();
+        assert_eq!(
+            game.board.get(&Square::C4.position()),
+            Some(&Piece::Pawn(Color::White))
+        );
+        assert!(game.crazyhouse.unwrap().reserves[0].is_empty());
+        assert!(Game::new().drop_piece("P@e4").is_err());
+
+        // A promoted queen returns to the reserve as a pawn
+        let mut game = Game::from_fen("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        game.crazyhouse = Some(crazyhouse::Crazyhouse::new(true));
+        game.make_move_san("b8=Q+").unwrap();
+        let promoted = Square::B8.position();
+        assert!(game.crazyhouse.unwrap().is_promoted(&promoted));
+        game.make_move_san("Kd7").unwrap();
+        game.make_move_san("Qc8+").unwrap();
+        game.make_move_san("Kxc8").unwrap();
+        let reserve = game.crazyhouse.unwrap().reserves[1];
+        assert_eq!(reserve.count(&Piece::Pawn(Color::Black)), 1);
+        assert_eq!(reserve.count(&Piece::Queen(Color::Black)), 0);
+    }
+
+    /// Test that captures go to the partner's reserve and the first mate ends the session
+    #[test]
+    fn bughouse_session() {
+        use bughouse::{Bughouse, BughouseResult, Team};
+        use config::TimeControl;
+        use std::time::Duration;
+        let minute = Duration::from_secs(60);
+        let second = Duration::from_secs(1);
+        let mut session = Bughouse::new(Some(TimeControl::new(minute, Duration::ZERO)));
+        session.play(0, "e4", second).unwrap();
+        session.play(0, "d5", second).unwrap();
+        session.play(0, "exd5", second).unwrap();
+        assert!(session.boards[0].crazyhouse.unwrap().reserves[0].is_empty());
+        let reserve = session.boards[1].crazyhouse.unwrap().reserves[1];
+        assert_eq!(reserve.count(&Piece::Pawn(Color::Black)), 1);
+        assert_eq!(
+            session.remaining(0, Color::White),
+            Some(minute - 2 * second)
+        );
+        assert!(session.play(2, "e4", second).is_err());
+        let mut endless = Bughouse::new(Some(TimeControl::new(minute, Duration::MAX)));
+        endless.play(0, "e4", second).unwrap();
+        assert_eq!(endless.remaining(0, Color::White), Some(Duration::MAX));
+
+        // Fool's mate on board 1 wins for team A (Black on board 1)
+        for mov in ["f3", "e5", "g4"] {
+            session.play(1, mov, second).unwrap();
+        }
+        assert_eq!(
+            session.play(1, "Qh4", second),
+            Ok(BughouseResult::Won(Team::A))
+        );
+        assert!(session.play(0, "Qxd5", second).is_err());
+
+        // Running out of time loses for the team
+        let mut session = Bughouse::new(Some(TimeControl::new(minute, Duration::ZERO)));
+        assert_eq!(
+            session.play(1, "e4", minute),
+            Ok(BughouseResult::Won(Team::A))
+        );
+    }
+
+    /// Test detection of pinned and checking pieces
+    #[test]
+    fn pins_and_checks() {
+        let pos = |s: &str| Position::from_string(s.to_string()).unwrap();
+        // The knight on d2 is pinned by the bishop, the e-pawn by the rook. The queen gives check
+        let game = Game::from_fen("4r2k/8/8/8/1b6/7q/3NP3/4K3 w - - 0 1").unwrap();
+        assert_eq!(game.pinned_pieces(Color::White), vec![pos("d2"), pos("e2")]);
+        assert!(game.checking_pieces(Color::White).is_empty());
+        assert!(game.pinned_pieces(Color::Black).is_empty());
+
+        let game = Game::from_fen("4r2k/8/8/8/1b6/5N2/3N4/4K2q w - - 0 1").unwrap();
+        // Double check by the rook and the queen
+        assert_eq!(
+            game.checking_pieces(Color::White),
+            vec![pos("e8"), pos("h1")]
+        );
+        assert_eq!(game.pinned_pieces(Color::White), vec![pos("d2")]);
+        // Only the king can move
+        assert_eq!(game.legal_move_count(), 1);
+    }
+
+    /// Test Chess960 starting positions and castling
+    #[test]
+    fn chess960() {
+        let standard = Game::new_chess960(chess960::STANDARD_POSITION_ID).unwrap();
+        assert_eq!(standard.board, Game::new().board);
+        assert!(Game::new_chess960(960).is_err());
+        let game = Game::new_chess960(0).unwrap();
+        assert_eq!(
+            game.to_fen(),
+            "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w HFhf - 0 1"
+        );
+        // Every position has bishops on opposite colors and the king between the rooks
+        for id in 0..chess960::POSITION_COUNT {
+            let rank = chess960::back_rank(id, Color::White).unwrap();
+            let file_of = |f: &dyn Fn(&Piece) -> bool| -> Vec<usize> {
+                (0..8).filter(|&i| f(&rank[i])).collect()
+            };
+            let bishops = file_of(&|p| matches!(p, Piece::Bishop(_)));
+            let rooks = file_of(&|p| matches!(p, Piece::Rook(_)));
+            let king = file_of(&|p| matches!(p, Piece::King(_)))[0];
+            assert_ne!(bishops[0] % 2, bishops[1] % 2);
+            assert!(rooks[0] < king && king < rooks[1]);
+        }
+
+        // King on b1 and rook on a1 castle queenside with the king moving one square
+        let mut game =
+            Game::from_fen("rk4r1/pppppppp/8/8/8/8/PPPPPPPP/RK4R1 w GAga - 0 1").unwrap();
+        game.make_move_uci("b1a1").unwrap();
+        assert_eq!(
+            game.board.get(&Square::C1.position()),
+            Some(&Piece::King(Color::White))
+        );
+        assert_eq!(
+            game.board.get(&Square::D1.position()),
+            Some(&Piece::Rook(Color::White))
+        );
+        assert_eq!(
+            game.to_fen(),
+            "rk4r1/pppppppp/8/8/8/8/PPPPPPPP/2KR2R1 b ga - 1 1"
+        );
+        game.make_move_san("O-O").unwrap();
+        assert_eq!(
+            game.to_fen(),
+            "r4rk1/pppppppp/8/8/8/8/PPPPPPPP/2KR2R1 w - - 2 2"
+        );
+    }
+
+    /// Minichess and Silverman 4x5 keep pieces on their smaller boards
+    #[test]
+    fn small_boards() {
+        use config::{BoardSize, GameConfig};
+        let minichess = Game::with_config(GameConfig::new().board_size(BoardSize::MINICHESS));
+        assert_eq!(minichess.to_fen(), "rnbqk/ppppp/5/PPPPP/RNBQK w - - 0 1");
+        // Five single pawn steps and two knight moves
+        assert_eq!(minichess.legal_moves().len(), 7);
+        assert!(minichess
+            .legal_moves()
+            .iter()
+            .all(|mov| BoardSize::MINICHESS.contains(&mov.to)));
+        assert_eq!(Game::from_fen(&minichess.to_fen()), Ok(minichess.clone()));
+        assert_eq!(
+            Game::from_bytes(&minichess.to_bytes()),
+            Ok(minichess.clone())
+        );
+        let diagram = minichess.render(render::RenderOptions::default());
+        assert_eq!(diagram.lines().next(), Some("W a b c d e"));
+        assert_eq!(diagram.lines().count(), 6);
+
+        let silverman = Game::with_config(GameConfig::new().board_size(BoardSize::SILVERMAN));
+        assert_eq!(silverman.to_fen(), "rqkr/pppp/4/PPPP/RQKR w - - 0 1");
+        assert_eq!(silverman.legal_moves().len(), 4);
+
+        // Pawns promote on the last rank of the board
+        let mut game = Game::from_fen("4k/P4/5/5/K4 w - - 0 1").unwrap();
+        assert_eq!(game.legal_moves_from("a4".parse().unwrap()).len(), 4);
+        game.make_move_uci("a4a5q").unwrap();
+        assert_eq!(
+            game.board.get(&"a5".parse().unwrap()),
+            Some(&Piece::Queen(Color::White))
+        );
+        assert_eq!(game.get_game_state(), GameState::Check);
+        assert!(game.make_move_uci("e5f5").is_err());
+
+        // Pawns cannot stand on the last rank of a small board
+        let back_rank = Game::from_fen("P3k/5/5/5/K4 w - - 0 1").map(|game| game.validate());
+        assert_eq!(
+            back_rank,
+            Ok(Err(vec![SetupProblem::PawnOnBackRank(
+                Square::A5.position()
+            )]))
+        );
+        assert!(Game::from_fen("k/1/1/1/1/1/1/1/K w - - 0 1").is_err());
+
+        assert!(BoardSize::new(3, 8).is_err());
+        assert!(Game::from_fen("4k/5/5/5/K4 w K - 0 1").is_err());
+        assert!(Game::from_fen("4k/6/5/5/K4 w - - 0 1").is_err());
+
+        // Both players' pieces get the same bonuses, mirrored on the small board
+        assert_eq!(eval::evaluate(&minichess).total(), 0);
+        assert_eq!(
+            eval::piece_square(
+                &Piece::Knight(Color::White),
+                &"b1".parse().unwrap(),
+                BoardSize::MINICHESS
+            ),
+            eval::piece_square(
+                &Piece::Knight(Color::Black),
+                &"b5".parse().unwrap(),
+                BoardSize::MINICHESS
+            )
+        );
+        let mut editor = minichess.clone();
+        assert!(editor.remove_piece("a8".parse().unwrap()).is_err());
+    }
+
+    /// Deserialized board sizes are checked like constructed ones
+    #[cfg(feature = "serde")]
+    #[test]
+    fn board_size_serde() {
+        use config::BoardSize;
+        let json = serde_json::to_string(&BoardSize::MINICHESS).unwrap();
+        assert_eq!(serde_json::from_str(&json).ok(), Some(BoardSize::MINICHESS));
+        assert!(serde_json::from_str::<BoardSize>(r#"{"files":8,"ranks":9}"#).is_err());
+        assert!(serde_json::from_str::<BoardSize>(r#"{"files":20,"ranks":20}"#).is_err());
+    }
+
+    /// Handicap games start without the piece and keep the odds through PGN
+    #[test]
+    fn odds_games() {
+        use odds::Odds;
+        let mut game = Game::new_with_odds(Odds::RookOdds);
+        assert_eq!(
+            game.to_fen(),
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/1NBQKBNR w Kkq - 0 1"
+        );
+        game.make_move_san("e4").unwrap();
+        let loaded = Game::from_pgn(&game.to_pgn()).unwrap();
+        assert_eq!(loaded.odds, Some(Odds::RookOdds));
+        assert_eq!(loaded, game);
+
+        let pawn_and_move = Game::new_with_odds(Odds::PawnAndMove);
+        assert_eq!(pawn_and_move.odds.unwrap().giver(), Color::Black);
+        assert_eq!(pawn_and_move.active_color, Color::White);
+        assert!(!pawn_and_move.board.contains_key(&"f7".parse().unwrap()));
+        for odds in Odds::ALL {
+            assert_eq!(odds.to_string().parse(), Ok(odds));
+            assert_eq!(Game::new_with_odds(odds).board.len(), 31);
+        }
+        assert!("bishop".parse::<Odds>().is_err());
+    }
+
+    /// Moves are described in words for blindfold games
+    #[test]
+    fn move_descriptions() {
+        let describe = |fen: &str, uci: &str| {
+            let game = Game::from_fen(fen).unwrap();
+            game.describe_move(Move::from_uci(uci).unwrap())
+        };
+        let start = fen::STARTING_FEN;
+        assert_eq!(describe(start, "g1f3").unwrap(), "knight from g1 to f3");
+        assert!(describe(start, "e2e5").is_err());
+        assert_eq!(
+            describe("4k3/8/8/3Pp3/8/8/8/4K3 w - e6 0 1", "d5e6").unwrap(),
+            "pawn from d5 to e6, capturing pawn en passant"
+        );
+        assert_eq!(
+            describe("3rk3/4P3/8/8/8/8/8/4K2R w K - 0 1", "e1g1").unwrap(),
+            "castles kingside"
+        );
+        assert_eq!(
+            describe("3rk3/4P3/8/8/8/8/8/4K2R w K - 0 1", "e7d8q").unwrap(),
+            "pawn from e7 to d8, capturing rook, promoting to queen, check"
+        );
+        assert_eq!(
+            describe("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", "a1a8").unwrap(),
+            "rook from a1 to a8, checkmate"
+        );
+    }
+
+    /// Attacks found from the target square are the same as when asking every piece
+    #[test]
+    fn attack_detection() {
+        let scan = |game: &Game, target: Position, by: Color| {
+            game.pieces_of(by)
+                .any(|(from, _)| game._attacks(&from, &target))
+        };
+        let mut games = vec![
+            Game::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+                .unwrap(),
+            Game::from_fen("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1").unwrap(),
+        ];
+        for seed in 0..2 {
+            let simulation = Game::new().play_random_game(seed);
+            let mut game = Game::new();
+            for mov in simulation.moves.iter().take(120) {
+                game._make_move(*mov).unwrap();
+                games.push(game.clone());
+            }
+        }
+        for game in &games {
+            for index in 0..64 {
+                let target = Position::from_index(index).unwrap();
+                for by in [Color::White, Color::Black] {
+                    assert_eq!(
+                        game._is_attacked(&target, by),
+                        scan(game, target, by),
+                        "{} attacked by {:?} in {}",
+                        target,
+                        by,
+                        game.to_fen()
+                    );
+                }
+            }
+            for color in [Color::White, Color::Black] {
+                assert_eq!(
+                    game._king_is_threatened(color),
+                    !game.checking_pieces(color).is_empty()
+                );
+            }
+        }
+    }
+
+    /// Quiz questions are answered from the attackers and legal moves of the position
+    #[test]
+    fn quiz_questions() {
+        use quiz::{Question, Quiz};
+        let game = Game::from_fen("4k3/8/8/3p4/4P3/2N5/8/4K3 w - - 0 1").unwrap();
+        let d5: Position = "d5".parse().unwrap();
+        assert_eq!(
+            game.attackers_of(d5, Color::White),
+            vec!["c3".parse().unwrap(), "e4".parse().unwrap()]
+        );
+        assert!(game.attackers_of(d5, Color::Black).is_empty());
+
+        let defended = Question::Defended(d5);
+        assert_eq!(defended.text(&game), "Is the pawn on d5 defended?");
+        assert_eq!(defended.answer(&game), "no");
+        assert!(Question::Defended("e4".parse().unwrap()).is_correct(&game, "Yes"));
+        let knight = Question::LegalMoves("c3".parse().unwrap());
+        assert!(knight.is_correct(&game, "a4, b5 d5 e2 a2 b1 d1"));
+        assert!(!knight.is_correct(&game, "a4 b5 d5 e2 a2 b1"));
+        assert!(!knight.is_correct(&game, "a4 b5 d5 e2 a2 b1 d1 x9"));
+        assert!(Question::LegalMoves("e4".parse().unwrap()).is_correct(&game, "d5, e5"));
+        assert_eq!(
+            Question::SquareColor("a1".parse().unwrap()).answer(&game),
+            "dark"
+        );
+        assert_eq!(
+            Question::SquareColor("h1".parse().unwrap()).answer(&game),
+            "light"
+        );
+
+        let mut quiz = Quiz::new(3);
+        for _ in 0..10 {
+            let question = quiz.next_question(&game);
+            assert!(quiz.answer(&game, &question, &question.answer(&game)));
+            assert!(!quiz.answer(&game, &question, "maybe"));
+        }
+        assert_eq!((quiz.correct, quiz.asked), (10, 20));
+    }
+
+    /// Test correspondence deadlines, including the check when loading a saved game
+    #[cfg(feature = "serde")]
+    #[test]
+    fn correspondence_deadlines() {
+        use config::GameConfig;
+        use correspondence::{CorrespondenceGame, DAY};
+        let config = GameConfig::new().days_per_move(Color::Black, 2);
+        let mut game = CorrespondenceGame::new(Game::with_config(config), 0);
+        // White has no deadline
+        assert_eq!(game.deadline, None);
+        game.make_move_san("e4", 30 * DAY).unwrap();
+        assert_eq!(game.time_left(31 * DAY), Some(DAY));
+        game.make_move_san("e5", 32 * DAY).unwrap();
+        game.make_move_san("Nf3", 33 * DAY).unwrap();
+
+        let saved = game.to_json();
+        let loaded = CorrespondenceGame::from_json(&saved, 34 * DAY).unwrap();
+        assert_eq!(loaded.timed_out, None);
+        assert_eq!(loaded.game, game.game);
+        let mut late = CorrespondenceGame::from_json(&saved, 36 * DAY).unwrap();
+        assert_eq!(late.timed_out, Some(Color::Black));
+        assert!(late.make_move_san("Nc6", 36 * DAY).is_err());
+        assert!(game.make_move_san("Nc6", 35 * DAY + 1).is_err());
+    }
+
+    /// Test network sessions staying in sync, rejected moves and draw offers
+    #[cfg(feature = "net")]
+    #[test]
+    fn network_session() {
+        use config::{BoardSize, GameConfig};
+        use net::{Connection, Ending, Message, Session};
+        use std::io::Cursor;
+        let mut wire = Connection::new(Cursor::new(Vec::new()));
+        let (mut host, hello) = Session::host(Game::new(), Color::Black);
+        wire.send(&hello).unwrap();
+        let json = wire.into_inner().into_inner();
+        assert!(json.ends_with(b"}\n"));
+        let mut wire = Connection::new(Cursor::new(json));
+        let mut guest = Session::join(&wire.receive().unwrap()).unwrap();
+        assert!(wire.receive().is_err());
+        assert_eq!(guest.color(), Color::White);
+        assert!(!host.is_my_turn());
+
+        let e4 = Move::from_uci("e2e4").unwrap();
+        assert_eq!(host.propose_move(e4), Err("It is not your turn"));
+        let proposal = guest.propose_move(e4).unwrap();
+        assert!(!guest.is_my_turn());
+        // The host has not got the move yet
+        let early = host.receive(&Message::Move {
+            ply: 1,
+            uci: "e7e5".to_string(),
+        });
+        assert!(matches!(early, Ok(Some(Message::Reject { ply: 1, .. }))));
+        let accept = host.receive(&proposal).unwrap().unwrap();
+        assert_eq!(accept, Message::Accept { ply: 0 });
+        assert_eq!(guest.receive(&accept), Ok(None));
+        assert_eq!(host.game().to_fen(), guest.game().to_fen());
+
+        // Rejected moves are taken back
+        let proposal = host.propose_move(Move::from_uci("e7e5").unwrap()).unwrap();
+        let reject = Message::Reject {
+            ply: 1,
+            reason: "Illegal move".to_string(),
+            fen: host.game().to_fen(),
+        };
+        assert_eq!(host.receive(&reject), Ok(None));
+        assert_eq!(host.pending_move(), None);
+        let accept = guest.receive(&proposal).unwrap().unwrap();
+        assert_eq!(host.receive(&accept), Err("No move was proposed"));
+        host.propose_move(Move::from_uci("e7e5").unwrap()).unwrap();
+        assert_eq!(host.receive(&accept), Ok(None));
+        assert_eq!(host.game().to_fen(), guest.game().to_fen());
+
+        let offer = guest.offer_draw().unwrap();
+        assert_eq!(host.receive(&offer), Ok(None));
+        assert_eq!(host.draw_offer(), Some(Color::White));
+        let answer = host.answer_draw(true).unwrap();
+        assert_eq!(
+            Message::from_json(&answer.to_json()),
+            Ok(Message::AcceptDraw)
+        );
+        guest.receive(&answer).unwrap();
+        assert_eq!(guest.ending(), Some(Ending::DrawAgreed));
+        assert!(guest.resign().is_err());
+
+        // Games hosted after some moves keep the history and the variant
+        let mut game = Game::new_atomic();
+        game.make_move_san("e4").unwrap();
+        game.make_move_san("e5").unwrap();
+        let (mut host, hello) = Session::host(game, Color::Black);
+        let mut guest = Session::join(&Message::from_json(&hello.to_json()).unwrap()).unwrap();
+        assert_eq!(guest.game().history().len(), 2);
+        assert_eq!(guest.game().variant, Variant::Atomic);
+        let proposal = guest.propose_move(Move::from_uci("g1f3").unwrap()).unwrap();
+        let accept = host.receive(&proposal).unwrap().unwrap();
+        assert_eq!(accept, Message::Accept { ply: 2 });
+        assert_eq!(guest.receive(&accept), Ok(None));
+        assert_eq!(host.game(), guest.game());
+
+        // Games from the peer are checked before they are played
+        let join = |game: &Game| {
+            let (_, hello) = Session::host(game.clone(), Color::White);
+            Session::join(&hello).map(|_| ())
+        };
+        let mut game = Game::new();
+        game.castling_files.kingside = 9;
+        assert_eq!(join(&game), Err("Invalid castling files"));
+        let mut game = Game::new();
+        game.en_passant = Some(Position { file: 0, rank: 3 });
+        assert_eq!(join(&game), Err("En passant square outside of the board"));
+        let mut game = Game::with_config(GameConfig::new().board_size(BoardSize::MINICHESS));
+        assert_eq!(join(&game), Ok(()));
+        game.board
+            .insert("h8".parse().unwrap(), Piece::Rook(Color::Black));
+        assert_eq!(join(&game), Err("Piece outside of the board"));
+        let game = Game::from_fen("4k3/8/8/8/8/8/8/4R1K1 w - - 0 1").unwrap();
+        assert_eq!(join(&game), Err("Invalid position"));
+        let board_size = r#""board_size":{"files":8,"ranks":8}"#;
+        let huge =
+            hello
+                .to_json()
+                .replacen(board_size, r#""board_size":{"files":20,"ranks":20}"#, 1);
+        assert_ne!(huge, hello.to_json());
+        assert!(Message::from_json(&huge).is_err());
+
+        // Lines are read up to a limit
+        let mut line = vec![b' '; net::MAX_LINE_LENGTH];
+        line.push(b'\n');
+        assert!(Connection::new(Cursor::new(line)).receive().is_err());
+        let mut wire = Connection::new(Cursor::new(vec![b' '; net::MAX_LINE_LENGTH + 1]));
+        assert_eq!(
+            wire.receive().unwrap_err().kind(),
+            std::io::ErrorKind::InvalidData
+        );
+    }
+
+    /// Test Atomic explosions, king captures and winning by exploding the king
+    #[test]
+    fn atomic_explosions() {
+        let mut game = Game::new_atomic();
+        for san in ["Nf3", "d5", "Ne5", "a6"] {
+            game.make_move_san(san).unwrap();
+        }
+        let before = game.clone();
+        // Capturing on f7 explodes the knight and the pieces around f7, including the king on e8
+        assert_eq!(
+            game.make_move_san("Nxf7").unwrap().captured,
+            Some(Piece::Pawn(Color::Black))
+        );
+        for square in ["f7", "e8", "g8", "f8"] {
+            let position = Position::from_string(square.to_string()).unwrap();
+            assert_eq!(game.board.get(&position), None, "{}", square);
+        }
+        assert_eq!(
+            game.board.get(&Square::E7.position()),
+            Some(&Piece::Pawn(Color::Black))
+        );
+        assert_eq!(game.get_game_state(), GameState::CheckMate);
+        assert_eq!(
+            game.castling[1],
+            CastlingRights {
+                kingside: false,
+                queenside: false
+            }
+        );
+
+        // Taking back the move restores the exploded pieces
+        let mut unmade = before.clone();
+        let mov = Move::from_uci("e5f7").unwrap();
+        let undo = unmade._make(mov);
+        unmade._unmake(undo);
+        assert_eq!(unmade, before);
+
+        // Kings cannot capture and adjacent kings do not give check
+        let game = Game {
+            variant: Variant::Atomic,
+            ..Game::from_fen("8/8/8/8/8/8/4r3/k3K3 w - - 0 1").unwrap()
+        };
+        let moves = game.get_possible_moves("e1".to_string()).unwrap();
+        assert!(!moves.contains(&"e2".to_string()));
+        assert!(moves.contains(&"d1".to_string()));
+        let game = Game {
+            variant: Variant::Atomic,
+            ..Game::from_fen("8/8/8/8/8/8/3kr3/4K3 w - - 0 1").unwrap()
+        };
+        assert!(!game._king_is_threatened(Color::White));
+    }
+
+    /// Test that moves keep positions valid and that illegal imported positions are found
+    #[test]
+    fn validate() {
+        for start in [Game::new(), Game::new_chess960(0).unwrap()] {
+            for seed in 0..5 {
+                let mut game = start.clone();
+                for mov in start.play_random_game(seed).moves {
+                    game.make_move_uci(&mov.to_uci()).unwrap();
+                    assert_eq!(game.validate(), Ok(()), "{}", game.to_fen());
+                }
+            }
+        }
+        let two_kings = Game::from_fen("4k3/8/8/8/8/8/8/4K2K w - - 0 1").unwrap();
+        assert_eq!(
+            two_kings.validate(),
+            Err(vec![SetupProblem::KingCount(Color::White, 2)])
+        );
+        let waiting_in_check = Game::from_fen("4k3/8/8/8/8/8/4q3/4K3 b - - 0 1").unwrap();
+        assert_eq!(
+            waiting_in_check.validate(),
+            Err(vec![SetupProblem::WaitingPlayerInCheck(Color::White)])
+        );
+        // Three queens need two promotions, but no pawn is missing
+        let promotions = Game::from_fen("QQQ5/8/8/8/7k/8/PPPPPPPP/4K3 w - - 0 1").unwrap();
+        let problems = promotions.validate().unwrap_err();
+        assert_eq!(problems, [SetupProblem::TooManyPromotions(Color::White, 2)]);
+        assert_eq!(
+            problems[0].to_string(),
+            "White has 2 pieces more than its missing pawns could have promoted to"
+        );
+        let pawns = Game::from_fen("4k3/pppppppp/p7/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(
+            pawns.validate(),
+            Err(vec![SetupProblem::TooManyPawns(Color::Black, 9)])
+        );
+        // The rooks of this Chess960 position start on b1 and g1
+        let chess960 = Game::from_fen("brkqnnrb/pppppppp/8/8/8/8/PPPPPPPP/BRKQNNRB w GBgb - 0 1");
+        assert_eq!(chess960.unwrap().validate(), Ok(()));
+    }
+
+    /// Test editor undo/redo and legality hints
+    #[test]
+    fn board_editor() {
+        use editor::BoardEditor;
+        let mut editor = BoardEditor::new();
+        assert_eq!(
+            editor.hints(),
+            vec![
+                SetupProblem::KingCount(Color::White, 0),
+                SetupProblem::KingCount(Color::Black, 0)
+            ]
+        );
+        editor.put("e1", Piece::King(Color::White)).unwrap();
+        editor.put("e8", Piece::King(Color::Black)).unwrap();
+        assert!(editor.hints().is_empty());
+        editor.put("a8", Piece::Pawn(Color::White)).unwrap();
+        let pawn = Square::A8.position();
+        assert_eq!(editor.hints(), vec![SetupProblem::PawnOnBackRank(pawn)]);
+        assert!(editor.build().is_err());
+        assert_eq!(editor.remove("a8"), Ok(Some(Piece::Pawn(Color::White))));
+        assert_eq!(editor.remove("a8"), Ok(None));
+        assert!(editor.put("z9", Piece::Pawn(Color::White)).is_err());
+
+        editor.put("e2", Piece::Queen(Color::Black)).unwrap();
+        editor.set_active_color(Color::Black);
+        assert_eq!(
+            editor.hints(),
+            vec![SetupProblem::WaitingPlayerInCheck(Color::White)]
+        );
+        assert!(editor.undo());
+        assert_eq!(editor.active_color(), Color::White);
+        assert_eq!(editor.build().unwrap().get_game_state(), GameState::Check);
+        assert!(editor.redo());
+        assert!(!editor.redo());
+        assert_eq!(editor.active_color(), Color::Black);
+        while editor.undo() {}
+        assert!(editor.board().is_empty());
+
+        let editor = BoardEditor::from_game(&Game::new());
+        assert_eq!(editor.build().unwrap().to_fen(), fen::STARTING_FEN);
+    }
+
+    /// Test transcribing a messy score sheet
+    #[test]
+    fn transcribe_score_sheet() {
+        use transcribe::{transcribe, transcribe_from, NoteKind};
+        let sheet = "1.e4 e5 2. nf3, Nc6 3.Bb5 a6; o-o Nf6 5 d3 bb4?! c3 ½-½";
+        let transcription = transcribe(sheet);
+        assert!(transcription.is_complete());
+        assert_eq!(transcription.moves.len(), 11);
+        assert_eq!(transcription.result.as_deref(), Some("1/2-1/2"));
+        let corrected: Vec<&NoteKind> = transcription.notes.iter().map(|note| &note.kind).collect();
+        assert_eq!(
+            corrected,
+            vec![
+                &NoteKind::Corrected("Nf3".to_string()),
+                &NoteKind::Corrected("O-O".to_string()),
+                &NoteKind::Corrected("Bb4?!".to_string()),
+            ]
+        );
+
+        // Both knights can go to d2, only the f3 knight can go to g5 afterwards
+        let start = Game::from_fen("4k3/8/8/8/8/5N2/8/1N2K3 w - - 0 1").unwrap();
+        let transcription = transcribe_from(&start, "Nd2 Kd7 Ng5");
+        let ambiguity = transcription.ambiguities().next().unwrap();
+        assert_eq!(ambiguity.ply, 0);
+        assert_eq!(transcription.moves[0], Move::from_uci("b1d2").unwrap());
+        assert!(transcription.is_complete());
+
+        let transcription = transcribe("e4 e5 Ke3 Nc6");
+        assert!(!transcription.is_complete());
+        assert_eq!(transcription.moves.len(), 2);
+        assert_eq!(transcription.remaining, vec!["Nc6".to_string()]);
+    }
+
+    /// Test chess clock with increment and time forfeit
+    #[test]
+    fn chess_clock() {
+        use config::{GameConfig, TimeControl};
+        use std::time::Duration;
+        let config = GameConfig::new()
+            .time_control(
+                Color::White,
+                TimeControl::new(Duration::from_secs(60), Duration::from_secs(2)),
+            )
+            .time_control(
+                Color::Black,
+                TimeControl::new(Duration::from_secs(30), Duration::ZERO),
+            );
+        let mut game = Game::with_config(config);
+        game.make_move_timed("e2".to_string(), "e4".to_string(), Duration::from_secs(5))
+            .unwrap();
+        let clock = game.clock.unwrap();
+        assert_eq!(clock.remaining(Color::White), Some(Duration::from_secs(57)));
+        assert_eq!(clock.remaining(Color::Black), Some(Duration::from_secs(30)));
+        assert_eq!(clock.to_string(), "White 0:57 Black 0:30");
+
+        // Illegal moves are not charged, the caller keeps timing the turn
+        assert!(game
+            .make_move_timed("e7".to_string(), "e4".to_string(), Duration::from_secs(1))
+            .is_err());
+        assert_eq!(game.get_game_state(), GameState::InProgress);
+
+        assert!(game
+            .make_move_timed("e7".to_string(), "e5".to_string(), Duration::from_secs(30))
+            .is_err());
+        assert!(game.clock.unwrap().is_flagged(Color::Black));
+        assert_eq!(game.get_game_state(), GameState::TimeForfeit);
+        assert!(game.make_move("e7".to_string(), "e5".to_string()).is_err());
+
+        // Without a clock the time is ignored
+        let mut game = Game::new();
+        game.make_move_timed(
+            "e2".to_string(),
+            "e4".to_string(),
+            Duration::from_secs(3600),
+        )
+        .unwrap();
+        assert_eq!(game.clock, None);
+        let outcome = game
+            .make_move_uci_timed("e7e5", Duration::from_secs(1))
+            .unwrap();
+        assert_eq!(outcome.san, "e5");
+
+        // Huge increments saturate the remaining time
+        let endless = TimeControl::new(Duration::MAX, Duration::MAX);
+        let mut game = Game::with_config(GameConfig::new().time_control(Color::White, endless));
+        game.make_move_uci_timed("e2e4", Duration::from_secs(1))
+            .unwrap();
+        assert_eq!(
+            game.clock.unwrap().remaining(Color::White),
+            Some(Duration::MAX)
+        );
+    }
+
+    /// Settings of the program are read, changed and written back
+    #[test]
+    fn program_settings() {
+        use config::TimeControl;
+        use settings::Settings;
+        use std::time::Duration;
+        let clock: TimeControl = "5+3".parse().unwrap();
+        assert_eq!(clock.initial, Duration::from_secs(300));
+        assert_eq!(clock.to_string(), "5+3");
+        assert_eq!(
+            "10".parse::<TimeControl>().unwrap().increment,
+            Duration::ZERO
+        );
+        assert!("5+x".parse::<TimeControl>().is_err());
+        assert!("18446744073709551615".parse::<TimeControl>().is_err());
+        assert!("5+18446744073709551615".parse::<TimeControl>().is_err());
+
+        let text = "# Preferences\nfancy = true\nclock = \"3+2\"\nautosave = \"game.pgn\"\n";
+        let mut settings = Settings::parse(text).unwrap();
+        assert!(settings.fancy);
+        assert_eq!(settings.clock, "3+2".parse().ok());
+        assert_eq!(settings.autosave.as_deref(), Some("game.pgn"));
+        assert_eq!(settings.level, None);
+
+        settings.set("level", "master").unwrap();
+        settings.set("autosave", "off").unwrap();
+        assert_eq!(settings.autosave, None);
+        assert!(settings.set("fancy", "maybe").is_err());
+        assert!(settings.set("colour", "blue").is_err());
+        assert!(settings.fancy);
+        assert_eq!(Settings::parse(&settings.to_string()), Ok(settings));
+        assert!(Settings::parse("fancy").is_err());
+
+        // Values are escaped and read back
+        let mut settings = Settings::default();
+        settings
+            .set("autosave", r#"C:\Users\"me"\game.pgn"#)
+            .unwrap();
+        assert!(settings
+            .to_string()
+            .contains(r#"autosave = "C:\\Users\\\"me\"\\game.pgn""#));
+        assert_eq!(Settings::parse(&settings.to_string()), Ok(settings));
+        let settings = Settings::parse(r"autosave = 'C:\games\a.pgn'").unwrap();
+        assert_eq!(settings.autosave.as_deref(), Some(r"C:\games\a.pgn"));
+        assert!(Settings::parse(r#"autosave = "game.pgn"#).is_err());
+        assert!(Settings::parse(r#"autosave = "C:\games""#).is_err());
+
+        let path = std::env::temp_dir().join("eliasfl-chess-settings-test.toml");
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(Settings::load(&path).unwrap(), Settings::default());
+        let settings = Settings::parse("notation = german").unwrap();
+        settings.save(&path).unwrap();
+        assert_eq!(Settings::load(&path).unwrap(), settings);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    /// Test classification of mistakes in coach summary
+    #[test]
+    fn coach_summary_findings() {
+        use report::{coach_summary, AnalyzedGame, FindingKind};
+        // White ignores the hanging queen
+        let start = Game::from_fen("4k3/8/8/3q4/8/8/8/3RK3 w - - 0 1").unwrap();
+        let moves = [Move::from_uci("e1e2").unwrap()];
+        let analyzed = AnalyzedGame::new(&start, &moves, &Engine::new(2)).unwrap();
+        assert!(analyzed.moves[0].endgame);
+        assert_eq!(
+            analyzed.moves[0].best_move,
+            Some(Move::from_uci("d1d5").unwrap())
+        );
+        let summary = coach_summary(&analyzed);
+        assert_eq!(summary.count(Color::White, FindingKind::MissedTactic), 1);
+        assert!(!summary.has_recurring_tactical_misses(Color::White));
+        assert!(summary.for_color(Color::Black).is_empty());
+
+        // Taking the queen is not a finding
+        let moves = [Move::from_uci("d1d5").unwrap()];
+        let analyzed = AnalyzedGame::new(&start, &moves, &Engine::new(2)).unwrap();
+        assert_eq!(analyzed.moves[0].best_move, None);
+        assert!(coach_summary(&analyzed).findings.is_empty());
+
+        assert!(
+            AnalyzedGame::new(&start, &[Move::from_uci("d1d8").unwrap()], &Engine::new(2)).is_err()
+        );
+    }
+
+    /// Test classifying the moves of a game from best to blunder
+    #[test]
+    fn analyze_game_classification() {
+        use report::MoveClass;
+
+        let mut game = Game::from_fen("4k3/8/8/3q4/8/8/8/3RK3 w - - 0 1").unwrap();
+        for uci in ["e1e2", "d5d1", "e2d1"] {
+            game.make_move_uci(uci).unwrap();
+        }
+        let report = game.analyze_game(2).unwrap();
+        let classes: Vec<MoveClass> = report
+            .moves
+            .iter()
+            .map(|analyzed| analyzed.classification())
+            .collect();
+        assert_eq!(classes[0], MoveClass::Blunder);
+        assert_eq!(classes[2], MoveClass::Best);
+        assert_eq!(report.count(Color::White, MoveClass::Blunder), 1);
+        assert_eq!(report.average_loss(Color::Black), report.moves[1].loss());
+        assert!(report.average_loss(Color::White) <= report::MAX_COUNTED_LOSS);
+        assert_eq!(Game::new().analyze_game(2).unwrap().moves.len(), 0);
+    }
+
+    /// Test that moves are recorded in the history with promotions
+    #[test]
+    fn move_history() {
+        let mut game = Game::from_fen("4k3/1P5p/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(game.last_move(), None);
+        game.set_promotion("knight".to_string()).unwrap();
+        game.make_move("b7".to_string(), "b8".to_string()).unwrap();
+        game.make_move_san("Kf7").unwrap();
+        assert!(game.make_move_san("Kf6").is_err());
+        assert_eq!(
+            game.history(),
+            &[
+                Move {
+                    promotion: Some(Piece::Knight(Color::White)),
+                    ..Move::from_uci("b7b8").unwrap()
+                },
+                Move::from_uci("e8f7").unwrap()
+            ]
+        );
+        assert_eq!(game.last_move(), Some(Move::from_uci("e8f7").unwrap()));
+    }
+
+    /// Test converting between pieces and piece kinds
+    #[test]
+    fn piece_kinds() {
+        for piece in [
+            Piece::King(Color::White),
+            Piece::Knight(Color::Black),
+            Piece::Pawn(Color::Black),
+        ] {
+            assert_eq!(Piece::new(piece.kind(), piece.color()), piece);
+        }
+        assert_eq!(Piece::Queen(Color::Black).kind(), PieceKind::Queen);
+    }
+
+    /// Test iterating over the pieces of a game
+    #[test]
+    fn piece_iterators() {
+        let pos = |s: &str| Position::from_string(s.to_string()).unwrap();
+        let game = Game::new();
+        assert_eq!(game.pieces().count(), 32);
+        assert_eq!(
+            game.pieces().next(),
+            Some((pos("a1"), Piece::Rook(Color::White)))
+        );
+        assert!(game
+            .pieces_of(Color::Black)
+            .all(|(position, piece)| position.rank >= 7 && piece.color() == Color::Black));
+        assert_eq!(game.pieces_of(Color::Black).count(), 16);
+        let mut kings = 0;
+        for (_, piece) in &game {
+            if piece.kind() == PieceKind::King {
+                kings += 1;
+            }
+        }
+        assert_eq!(kings, 2);
+    }
+
+    /// Test board rendering options
+    #[test]
+    fn board_formatter() {
+        use render::BoardFormatter;
+        let mut game = Game::new();
+        game.make_move_san("e4").unwrap();
+        let diagram = game.to_string();
+        assert_eq!(diagram.lines().next(), Some("B a b c d e f g h"));
+        assert_eq!(diagram.lines().nth(5), Some("4         ♟      "));
+
+        let diagram = BoardFormatter::new(&game)
+            .ascii(true)
+            .flipped(true)
+            .coordinates(false)
+            .to_string();
+        assert_eq!(diagram.lines().next(), Some("R N B K Q B N R"));
+        assert_eq!(diagram.lines().nth(3), Some("      P        "));
+        assert_eq!(diagram.lines().count(), 8);
+
+        let diagram = BoardFormatter::new(&game).last_move(true).to_string();
+        assert_eq!(diagram.matches("\x1B[43m").count(), 2);
+    }
+
+    /// Flipped and mirrored positions have the same moves and mirrored evaluations
+    #[test]
+    fn orientation() {
+        use config::{BoardSize, GameConfig};
+        use render::BoardFormatter;
+        use verification::perft;
+        let square = |s: &str| s.parse::<Position>().unwrap();
+        let standard = BoardSize::STANDARD;
+        assert_eq!(square("e2").flipped(standard), Some(square("e7")));
+        assert_eq!(square("b1").mirrored(standard), Some(square("g1")));
+        assert_eq!(
+            square("c6").flipped(standard).unwrap().flipped(standard),
+            Some(square("c6"))
+        );
+        assert_eq!(
+            square("b1").mirrored(BoardSize::MINICHESS),
+            Some(square("d1"))
+        );
+        assert_eq!(Position { file: 1, rank: 10 }.flipped(standard), None);
+        assert_eq!(square("f6").flipped(BoardSize::MINICHESS), None);
+
+        let kiwipete =
+            Game::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+                .unwrap();
+        let flipped = kiwipete.flipped();
+        assert_eq!(
+            flipped.to_fen(),
+            "r3k2r/pppbbppp/2n2q1P/1P2p3/3pn3/BN2PNP1/P1PPQPB1/R3K2R b KQkq - 0 1"
+        );
+        assert_eq!(perft(&flipped, 3), 97_862);
+        assert_eq!(flipped.flipped(), kiwipete);
+        let engine = Engine::new(1);
+        assert_eq!(engine.evaluate(&flipped), -engine.evaluate(&kiwipete));
+
+        let mut game = Game::new();
+        game.make_move_san("e4").unwrap();
+        let mirrored = game.mirrored();
+        assert_eq!(
+            mirrored.to_fen(),
+            "rnbkqbnr/pppppppp/8/8/3P4/8/PPP1PPPP/RNBKQBNR b - d3 0 1"
+        );
+        assert_eq!(perft(&mirrored, 2), perft(&game, 2));
+
+        // Small boards are flipped within their ranks
+        let los_alamos = Game::with_config(GameConfig::new().board_size(BoardSize::LOS_ALAMOS));
+        assert_eq!(
+            los_alamos.flipped().to_fen(),
+            "rnqknr/pppppp/6/6/PPPPPP/RNQKNR b - - 0 1"
+        );
+        assert_eq!(
+            los_alamos.mirrored().to_fen(),
+            "rnkqnr/pppppp/6/6/PPPPPP/RNKQNR w - - 0 1"
+        );
+
+        let diagram = BoardFormatter::new(&game)
+            .ascii(true)
+            .perspective(Color::Black)
+            .to_string();
+        assert_eq!(diagram.lines().nth(1), Some("1 R N B K Q B N R"));
+    }
+
+    /// Positions and games are drawn as SVG and GIF images
+    #[cfg(feature = "render-image")]
+    #[test]
+    fn board_images() {
+        use crate::image::ImageOptions;
+
+        let options = ImageOptions {
+            square_size: 10,
+            ..ImageOptions::default()
+        };
+        let game = Game::new();
+        let svg = game.to_svg(&options);
+        assert!(svg.starts_with("<svg") && svg.ends_with("</svg>\n"));
+        assert_eq!(svg.matches("<rect").count(), 64);
+        // The cross of the kings is drawn separately
+        assert_eq!(svg.matches("<polygon").count(), 34);
+
+        let gif = game.to_gif(&options);
+        assert!(gif.starts_with(b"GIF89a"));
+        assert_eq!(gif[6..10], [80, 0, 80, 0]);
+        assert_eq!(gif.last(), Some(&0x3b));
+
+        let mut game = Game::from_pgn("1. e4 e5 2. Qh5 Nc6 3. Bc4 Nf6 4. Qxf7#").unwrap();
+        let animated = game.to_animated_gif(&options).unwrap();
+        assert!(animated.windows(11).any(|window| window == b"NETSCAPE2.0"));
+        // One graphic control extension per position
+        let frames = animated
+            .windows(3)
+            .filter(|w| w == &[0x21, 0xf9, 0x04])
+            .count();
+        assert_eq!(frames, 8);
+        assert!(animated.len() > gif.len());
+        game.put_piece("h1".parse().unwrap(), Piece::Queen(Color::White))
+            .unwrap();
+        assert!(game.to_animated_gif(&options).is_err());
+    }
+
+    /// Games created, played and read through the C functions
+    #[cfg(feature = "ffi")]
+    #[test]
+    fn c_bindings() {
+        use ffi::*;
+        use std::ffi::{CStr, CString};
+        use std::ptr;
+        unsafe {
+            let game = chess_game_new();
+            let e4 = CString::new("e2e4").unwrap();
+            assert_eq!(chess_game_make_move(game, e4.as_ptr()), CHESS_OK);
+            assert_eq!(chess_game_make_move(game, e4.as_ptr()), CHESS_ILLEGAL_MOVE);
+            assert_eq!(
+                chess_game_make_move(game, ptr::null()),
+                CHESS_INVALID_ARGUMENT
+            );
+
+            let mut buffer = [0; 128];
+            let length = chess_game_fen(game, buffer.as_mut_ptr(), buffer.len());
+            let fen = CStr::from_ptr(buffer.as_ptr()).to_str().unwrap();
+            assert_eq!(fen.len(), length);
+            assert_eq!(fen, (*game).to_fen());
+            // Short buffers get as much as fits
+            let mut short = [0; 9];
+            assert_eq!(
+                chess_game_fen(game, short.as_mut_ptr(), short.len()),
+                length
+            );
+            let start = CStr::from_ptr(short.as_ptr()).to_str().unwrap();
+            assert_eq!(start, "rnbqkbnr");
+
+            let mut moves = [ChessMove::default(); CHESS_MAX_MOVES];
+            assert_eq!(chess_game_legal_moves(game, moves.as_mut_ptr(), 3), 20);
+            assert_eq!(chess_game_legal_moves(game, ptr::null_mut(), 0), 20);
+            assert_eq!(chess_game_state(game), 0);
+            let mut best = ChessMove::default();
+            assert_eq!(chess_game_best_move(game, 1, &mut best), CHESS_OK);
+            chess_game_legal_moves(game, moves.as_mut_ptr(), moves.len());
+            assert!(moves[..20].contains(&best));
+            chess_game_free(game);
+
+            let fen = CString::new("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+            let stalemate = chess_game_from_fen(fen.as_ptr());
+            assert_eq!(chess_game_state(stalemate), 3);
+            chess_game_free(stalemate);
+            let invalid = CString::new("8/8 w").unwrap();
+            assert!(chess_game_from_fen(invalid.as_ptr()).is_null());
+        }
+    }
+
+    /// Test the game handle exported to WebAssembly
+    #[cfg(feature = "wasm")]
+    #[test]
+    fn wasm_game_handle() {
+        let mut handle = wasm::GameHandle::new();
+        assert_eq!(handle.possible_moves("e2"), vec!["e3", "e4"]);
+        assert!(handle.possible_moves("e4").is_empty());
+        handle.make_move("e2", "e4").unwrap();
+        assert_eq!(handle.state(), "InProgress");
+        assert_eq!(
+            handle.fen(),
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"
+        );
+        assert_eq!(handle.active_color(), "Black");
+        let moves = wasm::_legal_destinations(&Game::new());
+        assert_eq!(moves.len(), 10);
+        assert_eq!(moves["g1"], ["f3", "h3"]);
+        let promotion = Game::from_fen("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(wasm::_legal_destinations(&promotion)["b7"], ["b8"]);
+        assert!(handle.undo());
+        assert!(!handle.undo());
+        assert_eq!(handle.fen(), fen::STARTING_FEN);
+
+        for (from, to) in [("f2", "f3"), ("e7", "e5"), ("g2", "g4"), ("d8", "h4")] {
+            handle.make_move(from, to).unwrap();
+        }
+        assert_eq!(handle.state(), "CheckMate");
+        assert_eq!(handle.result(), "0-1");
+        let stalemate = wasm::GameHandle::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+        assert_eq!(stalemate.result(), "1/2-1/2");
+        handle.set_promotion("knight").unwrap();
+    }
+
+    /// Test the outcome returned when making moves
+    #[test]
+    fn move_outcomes() {
+        let mut game = Game::from_fen("3qk3/2P5/8/6N1/8/8/8/R3K1N1 w Q - 0 1").unwrap();
+        let outcome = game.make_move_uci("c7d8q").unwrap();
+        assert_eq!(outcome.captured, Some(Piece::Queen(Color::Black)));
+        assert_eq!(outcome.promotion, Some(Piece::Queen(Color::White)));
+        assert_eq!(outcome.state, GameState::Check);
+        assert!(outcome.check);
+        assert_eq!(outcome.san, "cxd8=Q+");
+        assert_eq!(game.make_move_san("Kxd8").unwrap().san, "Kxd8");
+        assert_eq!(game.make_move_san("O-O-O+").unwrap().san, "O-O-O+");
+        game.make_move_san("Ke7").unwrap();
+        // Both knights on the g file can go to f3
+        assert_eq!(game.make_move_san("N1f3").unwrap().san, "N1f3");
+
+        let mut game = Game::new();
+        for (san, expected) in [("f3", "f3"), ("e5", "e5"), ("g4", "g4"), ("Qh4", "Qh4#")] {
+            assert_eq!(game.make_move_san(san).unwrap().san, expected);
+        }
+        assert_eq!(game.get_game_state(), GameState::CheckMate);
+        assert_eq!(
+            san::to_san(&Game::new(), Move::from_uci("g1f3").unwrap()),
+            Ok("Nf3".to_string())
+        );
+        assert!(san::to_san(&Game::new(), Move::from_uci("g1g3").unwrap()).is_err());
+    }
+
+    /// Test PGN import with comments, variations and a starting position
+    #[test]
+    fn pgn_import() {
+        let text = r#"[Event "Casual \"blitz\""]
+[Site "Cup [A] \\ 1"]
+
+1.e4 {King's pawn} e5 2. Nf3 (2. f4 exf4 (2... d5) 3. Nf3) 2... Nc6 $1
+3. Bb5 a6 ; Morphy defence
+4. Bxc6 dxc6 1/2-1/2"#;
+        let pgn = pgn::parse(text).unwrap();
+        assert_eq!(pgn.tag("Event"), Some("Casual \"blitz\""));
+        assert_eq!(pgn.tag("Site"), Some("Cup [A] \\ 1"));
+        assert_eq!(pgn.moves.len(), 8);
+        assert_eq!(pgn.positions.len(), 9);
+        assert_eq!(
+            pgn.positions[2].to_fen(),
+            "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e6 0 2"
+        );
+        assert_eq!(pgn.result.as_deref(), Some("1/2-1/2"));
+        assert_eq!(pgn.game().history(), &pgn.moves[..]);
+
+        let game = Game::from_pgn("[FEN \"4k3/8/8/8/8/8/8/R3K3 w Q - 0 1\"]\n1. O-O-O *").unwrap();
+        assert_eq!(game.to_fen(), "4k3/8/8/8/8/8/8/2KR4 b - - 1 1");
+
+        assert_eq!(Game::from_pgn("1. e4 e4"), Err("No legal move matches"));
+        assert!(Game::from_pgn("1. e4 (1. d4").is_err());
+        assert!(Game::from_pgn("[Event Casual] 1. e4").is_err());
+        assert!(Game::from_pgn("[Event \"Casual] 1. e4").is_err());
+    }
+
+    /// Test that book lines are legal and recognized
+    #[test]
+    fn opening_book() {
+        for opening in book::openings() {
+            let mut game = Game::new();
+            for mov in opening.moves() {
+                game.make_move_uci(&mov.to_uci()).unwrap();
+            }
+            assert_eq!(game.opening(), Some(opening));
+        }
+        let mut game = Game::new();
+        assert!(game.book_moves().contains(&Move::from_uci("e2e4").unwrap()));
+        assert_eq!(game.opening(), None);
+        for san in ["e4", "e5", "Nf3", "Nc6", "Bb5", "a6", "Ba4"] {
+            game.make_move_san(san).unwrap();
+        }
+        assert_eq!(game.opening().unwrap().eco, "C70");
+        assert!(game.book_moves().is_empty());
+        assert_eq!(book::book_move(&game), None);
+
+        let game =
+            Game::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1").unwrap();
+        assert!(game.book_moves().is_empty());
+    }
+
+    /// Test that ECO lines are legal and classified by position
+    #[cfg(feature = "eco")]
+    #[test]
+    fn eco_classification() {
+        for opening in eco::lines() {
+            let mut game = Game::new();
+            for mov in opening.moves() {
+                game.make_move_uci(&mov.to_uci()).unwrap();
+            }
+            assert_eq!(game.eco(), Some(opening));
+        }
+        assert_eq!(Game::new().eco(), None);
+        // The Scotch Four Knights reached from the Scotch Game
+        let game = Game::from_pgn("1. e4 e5 2. Nf3 Nc6 3. d4 Nf6 4. Nc3").unwrap();
+        assert_eq!(game.opening().unwrap().eco, "C44");
+        assert_eq!(
+            game.eco().unwrap().to_string(),
+            "C47 Four Knights Game: Scotch Variation"
+        );
+        let game = Game::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1");
+        assert_eq!(game.unwrap().eco(), None);
+    }
+
+    /// Cached legal moves are cleared when a move is made or taken back
+    #[test]
+    fn legal_move_cache() {
+        let mut game = Game::new();
+        assert_eq!(game.legal_moves().len(), 20);
+        assert_eq!(
+            game.get_possible_moves("b1".to_string()),
+            Some(vec!["a3".to_string(), "c3".to_string()])
+        );
+        game.make_move("e2".to_string(), "e4".to_string()).unwrap();
+        assert_eq!(game.legal_moves().len(), 20);
+        assert!(game
+            .legal_moves()
+            .iter()
+            .all(|mov| game.board.get(&mov.from).unwrap().color() == Color::Black));
+        let mut uncached = game.clone();
+        uncached.clear_move_cache();
+        assert_eq!(game.all_moves_by_square(), uncached.all_moves_by_square());
+
+        // Changing the fields directly does not return the moves of the previous position
+        let mut game = Game::new();
+        assert_eq!(game.legal_move_count(), 20);
+        game.board.remove(&Square::E2.position());
+        assert_eq!(game.legal_move_count(), 29);
+        game.active_color = Color::Black;
+        assert_eq!(game.legal_move_count(), 20);
+        game.board
+            .insert(Square::E2.position(), Piece::Pawn(Color::White));
+        assert_eq!(game.legal_move_count(), 20);
+        let mut game = Game::from_fen("4k3/8/8/8/8/8/8/4K2R w K - 0 1").unwrap();
+        assert!(game
+            .legal_moves()
+            .contains(&Move::from_uci("e1g1").unwrap()));
+        game.chess960 = true;
+        assert!(game
+            .legal_moves()
+            .contains(&Move::from_uci("e1h1").unwrap()));
+        assert!(!game
+            .legal_moves()
+            .contains(&Move::from_uci("e1g1").unwrap()));
+
+        let mut game = Game::from_fen("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(
+            game.get_possible_moves("b7".to_string()),
+            Some(vec!["b8".to_string()])
+        );
+        game.make_move("b7".to_string(), "b8".to_string()).unwrap();
+        assert_eq!(game.get_game_state(), GameState::Check);
+        assert!(game
+            .legal_moves()
+            .iter()
+            .all(|mov| mov.from.to_string() == "e8"));
+    }
+
+    /// Parallel perft and search agree with the sequential versions
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn parallel_search() {
+        use crate::verification::perft_parallel;
+
+        let kiwipete =
+            Game::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+                .unwrap();
+        assert_eq!(perft_parallel(&kiwipete, 2), 2_039);
+        assert_eq!(perft_parallel(&kiwipete, 3), 97_862);
+
+        let engine = Engine::new(2);
+        let mut game = Game::new();
+        for _ in 0..4 {
+            let result = engine.search_parallel(&game);
+            assert_eq!(result.score, engine.search(&game).score);
+            let mov = result.best_move.unwrap();
+            assert_eq!(
+                engine.compare(&game, mov, mov, 2).unwrap().a.score,
+                result.score
+            );
+            game._make_move(mov).unwrap();
+        }
+        let mate = Game::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+        assert_eq!(
+            engine.best_move_parallel(&mate),
+            Some(Move::from_uci("a1a8").unwrap())
+        );
+
+        // Endgames are looked up in the tablebase like in the single-threaded search
+        let constraints = crate::random::Constraints::endgame("KRvK").unwrap();
+        let rook = Game::random_position(1, &constraints).unwrap();
+        assert_eq!(engine.search_parallel(&rook), engine.search(&rook));
+    }
+
+    /// The game state is derived from a shared reference, including checkmate
+    #[test]
+    fn game_state_from_shared_reference() {
+        let state = |game: &Game| game.get_game_state();
+        let mut game = Game::new();
+        assert_eq!(state(&game), GameState::InProgress);
+        for san in ["f3", "e5", "g4"] {
+            game.make_move_san(san).unwrap();
+        }
+        let before_mate = game.clone();
+        game.make_move_san("Qh4").unwrap();
+        assert_eq!(state(&game), GameState::CheckMate);
+        assert_eq!(state(&before_mate), GameState::InProgress);
+
+        let check = Game::from_fen("R3k3/8/8/8/8/8/8/4K3 b - - 0 1").unwrap();
+        assert_eq!(state(&check), GameState::Check);
+        let mut game = Game::new();
+        game.make_move_san("e4").unwrap();
+        assert_eq!(state(&game), GameState::InProgress);
+    }
+
+    /// Editing a game keeps the position legal and the cached moves up to date
+    #[test]
+    fn edit_game_position() {
+        let pos = |s: &str| Position::from_string(s.to_string()).unwrap();
+        let mut game = Game::new();
+        assert_eq!(game.legal_move_count(), 20);
+        assert_eq!(
+            game.remove_piece(pos("h1")),
+            Ok(Some(Piece::Rook(Color::White)))
+        );
+        assert!(!game.castling[0].kingside && game.castling[0].queenside);
+        assert_eq!(game.remove_piece(pos("h3")), Ok(None));
+
+        // Black queen checks the king, which is fine with White to move
+        let replaced = game.put_piece(pos("e2"), Piece::Queen(Color::Black));
+        assert_eq!(replaced, Ok(Some(Piece::Pawn(Color::White))));
+        assert_eq!(game.get_game_state(), GameState::Check);
+        assert!(game.legal_moves().iter().all(|mov| mov.to == pos("e2")));
+        let before = game.clone();
+        assert!(game.set_active_color(Color::Black).is_err());
+        assert_eq!(game.remove_piece(pos("e1")), Err("Cannot remove king"));
+        assert!(game
+            .put_piece(pos("a8"), Piece::Pawn(Color::White))
+            .is_err());
+        assert_eq!(game, before);
+
+        // Placing a king moves it
+        game.remove_piece(pos("e2")).unwrap();
+        game.put_piece(pos("e3"), Piece::King(Color::White))
+            .unwrap();
+        assert_eq!(game.board.get(&pos("e1")), None);
+        assert!(!game.castling[0].queenside);
+        assert_eq!(game.get_game_state(), GameState::InProgress);
+        game.set_active_color(Color::Black).unwrap();
+        assert_eq!(game.legal_move_count(), 20);
+
+        // Replacing the board drops the castling rights of moved rooks
+        let mut game = Game::new();
+        let mut board = *game.board();
+        board.remove(&pos("a1"));
+        board.remove(&pos("e2"));
+        game.set_board(board).unwrap();
+        assert_eq!(game.board(), &board);
+        assert!(game.castling[0].kingside && !game.castling[0].queenside);
+        assert_eq!(game.legal_move_count(), 29);
+        board.remove(&pos("e8"));
+        assert_eq!(
+            game.set_board(board),
+            Err(SetupProblem::KingCount(Color::Black, 0).error())
+        );
+        board.insert(pos("e8"), Piece::King(Color::Black));
+        board.insert(pos("e7"), Piece::Rook(Color::White));
+        assert!(game.set_board(board).is_err());
+        assert_eq!(game.legal_move_count(), 29);
+    }
+
+    /// Rendering highlights the last move and the checked king
+    #[test]
+    fn render_highlights() {
+        use crate::render::RenderOptions;
+
+        let mut game = Game::new();
+        assert_eq!(game.render(RenderOptions::default()), game.to_string());
+        for san in ["e4", "f5", "Qh5"] {
+            game.make_move_san(san).unwrap();
+        }
+        let options = RenderOptions {
+            ascii: true,
+            coordinates: false,
+            last_move: true,
+            check: true,
+            ..RenderOptions::default()
+        };
+        let rendered = game.render(options);
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(lines[0], "r n b q\x1B[101m k\x1B[0m b n r");
+        assert!(lines[3].ends_with("\x1B[43m Q\x1B[0m"));
+        assert_eq!(lines[7], "R N B\x1B[43m  \x1B[0m K B N R");
+        assert!(!game.render(RenderOptions::default()).contains('\x1B'));
+    }
+
+    /// Games are written as PGN that reads back to the same game
+    #[test]
+    fn pgn_export() {
+        let mut game = Game::new();
+        for san in ["e4", "e5", "Bc4", "Nc6", "Qh5", "Nf6", "Qxf7"] {
+            game.make_move_san(san).unwrap();
+        }
+        let pgn = game.to_pgn();
+        assert!(pgn.contains("[Result \"1-0\"]"));
+        assert!(pgn.ends_with("\n1. e4 e5 2. Bc4 Nc6 3. Qh5 Nf6 4. Qxf7# 1-0\n"));
+        assert!(!pgn.contains("FEN"));
+        let read = pgn::parse(&pgn).unwrap();
+        assert_eq!(read.game(), &game);
+        assert_eq!(read.moves, game.history());
+
+        let fen = "4k3/8/8/8/8/8/4P3/4K3 b - - 0 40";
+        let mut game = Game::from_fen(fen).unwrap();
+        game.make_move_san("Kd7").unwrap();
+        game.make_move_san("e4").unwrap();
+        let pgn = game.to_pgn();
+        assert!(pgn.contains(&format!("[FEN \"{}\"]", fen)));
+        assert!(pgn.ends_with("\n40... Kd7 41. e4 *\n"));
+        assert_eq!(Game::from_pgn(&pgn).unwrap(), game);
+
+        // After editing only the current position is written
+        let pos = |s: &str| Position::from_string(s.to_string()).unwrap();
+        game.put_piece(pos("a2"), Piece::Pawn(Color::White))
+            .unwrap();
+        let pgn = game.to_pgn();
+        assert!(pgn.contains(&format!("[FEN \"{}\"]", game.to_fen())));
+        assert!(pgn.ends_with("\n*\n"));
+    }
+
+    /// Glyphs and comments of moves survive PGN export and import
+    #[test]
+    fn pgn_annotations() {
+        use pgn::MoveAnnotation;
+        let pgn = pgn::parse("1. e4! {Best by test} e5 (1... c5 {Sicilian}) 2. Nf3 $5 $14 Nc6?? *")
+            .unwrap();
+        let game = pgn.game();
+        assert_eq!(game.annotation(0).unwrap().nags, vec![1]);
+        assert_eq!(
+            game.annotation(0).unwrap().comment.as_deref(),
+            Some("Best by test")
+        );
+        assert_eq!(game.annotation(1), None);
+        assert_eq!(game.annotation(2).unwrap().nags, vec![5, 14]);
+        assert_eq!(
+            game.annotation(3),
+            MoveAnnotation::from_suffix("??").as_ref()
+        );
+        assert_eq!(
+            game.movetext(),
+            "1. e4 $1 {Best by test} e5 (1... c5 {Sicilian}) 2. Nf3 $5 $14 Nc6 $4"
+        );
+        let read = Game::from_pgn(&game.to_pgn()).unwrap();
+        assert_eq!(read.movetext(), game.movetext());
+
+        let mut game = Game::new();
+        game.make_move_san("d4").unwrap();
+        let comment = MoveAnnotation {
+            nags: Vec::new(),
+            comment: Some("Closed {game}".to_string()),
+        };
+        game.annotate(0, comment).unwrap();
+        game.annotate(0, MoveAnnotation::from_suffix("!?").unwrap())
+            .unwrap();
+        assert_eq!(game.movetext(), "1. d4 $5 {Closed {game)}");
+        assert!(game.annotate(1, MoveAnnotation::default()).is_err());
+    }
+
+    /// Test replaying a game from a custom position, with the rules of the game
+    #[test]
+    fn replay_positions() {
+        let fen = "4k3/P6p/8/8/8/8/8/4K3 w - - 0 1";
+        let mut game = Game::from_fen(fen).unwrap();
+        game.set_promotion_piece(Piece::Knight(Color::White))
+            .unwrap();
+        for san in ["a8", "Kd7", "Nb6+"] {
+            game.make_move_san(san).unwrap();
+        }
+        let replay = game.replay().unwrap();
+        assert_eq!(replay.start().to_fen(), fen);
+        assert_eq!(replay.len(), 3);
+        let snapshots: Vec<_> = replay.collect();
+        assert_eq!(
+            snapshots[0].game.board().get(&"a8".parse().unwrap()),
+            Some(&Piece::Knight(Color::White))
+        );
+        assert_eq!(snapshots[1].ply, 2);
+        assert_eq!(snapshots[1].mov, Move::from_uci("e8d7").unwrap());
+        assert_eq!(snapshots[2].game, game);
+
+        game.put_piece("h1".parse().unwrap(), Piece::Rook(Color::White))
+            .unwrap();
+        assert!(game.replay().is_err());
+        assert_eq!(Game::new().replay().unwrap().count(), 0);
+
+        // Replays keep the variant, here the capture explodes the pawns
+        let mut game = Game::new_atomic();
+        for san in ["e4", "d5", "exd5"] {
+            game.make_move_san(san).unwrap();
+        }
+        assert_eq!(game.replay().unwrap().last().unwrap().game, game);
+        assert_eq!(game.movetext(), "1. e4 d5 2. exd5");
+        game.go_to(variation::MoveTree::ROOT).unwrap();
+        assert_eq!(game.to_fen(), Game::new().to_fen());
+    }
+
+    /// Side lines can be added, promoted, visited and written as PGN variations
+    #[test]
+    fn variation_trees() {
+        use pgn::MoveAnnotation;
+        use variation::MoveTree;
+        let mut game = Game::new();
+        for san in ["e4", "e5", "Nf3"] {
+            game.make_move_san(san).unwrap();
+        }
+        let e5 = game.move_tree()[game.current_node()].parent.unwrap();
+        let nc3 = game
+            .add_variation(
+                e5,
+                &[
+                    Move::from_uci("b1c3").unwrap(),
+                    Move::from_uci("g8f6").unwrap(),
+                ],
+            )
+            .unwrap();
+        // The current position is unchanged
+        assert_eq!(game.history().len(), 3);
+        assert_eq!(game.move_tree().len(), 6);
+        assert_eq!(game.movetext(), "1. e4 e5 2. Nf3 (2. Nc3 Nf6)");
+        // Moves already in the tree are followed
+        assert_eq!(
+            game.add_variation(e5, &[Move::from_uci("b1c3").unwrap()]),
+            Ok(game.move_tree()[nc3].parent.unwrap())
+        );
+        assert!(game
+            .add_variation(e5, &[Move::from_uci("e1e2").unwrap()])
+            .is_ok());
+        assert!(game
+            .add_variation(e5, &[Move::from_uci("a1a5").unwrap()])
+            .is_err());
+
+        game.go_to(nc3).unwrap();
+        assert_eq!(game.current_node(), nc3);
+        assert_eq!(game.fullmove_number, 3);
+        game.make_move_san("Bc4").unwrap();
+        game.annotate(4, MoveAnnotation::from_suffix("!").unwrap())
+            .unwrap();
+        game.promote_variation(game.current_node()).unwrap();
+        let movetext = "1. e4 e5 2. Nc3 (2. Nf3) (2. Ke2) 2... Nf6 3. Bc4 $1";
+        assert_eq!(game.movetext(), movetext);
+        assert!(game.promote_variation(100).is_err());
+        assert!(game.go_to(100).is_err());
+
+        let pgn = pgn::parse(&game.to_pgn()).unwrap();
+        assert_eq!(pgn.moves.len(), 5);
+        assert_eq!(pgn.game().movetext(), movetext);
+        assert_eq!(pgn.game().move_tree().main_line().len(), 5);
+        assert!(pgn::parse("1. e4 (e5) *").is_err());
+        assert!(pgn::parse("1. e4 e5 (1... c5 2. Nf3 (2. c3) d6) 2. Nf3 *").is_ok());
+
+        game.go_to(MoveTree::ROOT).unwrap();
+        assert_eq!(game, Game::new());
+        assert!(game.history().is_empty());
+    }
+
+    /// Simulated games are reproducible, legal and end for the stated reason
+    #[test]
+    fn simulated_games() {
+        use crate::simulate::{simulate, Player, Termination};
+
+        for seed in 0..4 {
+            let simulation = Game::new().play_random_game(seed);
+            assert_eq!(simulation, Game::new().play_random_game(seed));
+            assert_eq!(simulation.game.history(), &simulation.moves[..]);
+            // Replaying the moves through PGN gives the same game
+            let replayed = Game::from_pgn(&simulation.game.to_pgn()).unwrap();
+            assert_eq!(replayed, simulation.game);
+            match simulation.termination {
+                Termination::Checkmate | Termination::Stalemate => {
+                    assert!(simulation.game.legal_moves().is_empty())
+                }
+                Termination::FiftyMoves => assert_eq!(simulation.game.halfmove_clock, 100),
+                Termination::Repetition => assert_eq!(simulation.game.repetition_count(), 5),
+                Termination::DeadPosition => assert!(simulation.game.is_dead_position()),
+                Termination::Unfinished => assert_eq!(simulation.moves.len(), 1000),
+                Termination::Resignation => unreachable!("Random players never resign"),
+            }
+        }
+
+        let mate_in_one = Game::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+        let engine = Player::Engine(Engine::new(2));
+        let simulation = simulate(&mate_in_one, engine, Player::Random, 0, 10);
+        assert_eq!(simulation.termination, Termination::Checkmate);
+        assert_eq!(simulation.winner(), Some(Color::White));
+        assert_eq!(simulation.result(), "1-0");
+        assert_eq!(simulation.moves, vec![Move::from_uci("a1a8").unwrap()]);
+    }
+
+    /// Positions are the same regardless of move counters and unusable en passant squares
+    #[test]
+    fn position_keys() {
+        let mut game = Game::new();
+        for san in ["Nf3", "Nf6", "Ng1", "Ng8"] {
+            game.make_move_san(san).unwrap();
+        }
+        assert!(game.is_same_position(&Game::new()));
+        assert_eq!(game.position_key(), Game::new().position_key());
+        assert_ne!(game, Game::new());
+        // Keys are stored with games, so they may not depend on the platform or program run
+        assert_eq!(Game::new().position_key(), 0x485A_A046_C944_9399);
+
+        // No black pawn can capture on e3
+        let mut e4 = Game::new();
+        e4.make_move_san("e4").unwrap();
+        assert_eq!(e4.en_passant, Some("e3".parse().unwrap()));
+        let without = Game::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1");
+        assert!(e4.is_same_position(&without.unwrap()));
+
+        let capturable = Game::from_fen("4k3/8/8/8/3pP3/8/8/4K3 b - e3 0 1").unwrap();
+        let without = Game::from_fen("4k3/8/8/8/3pP3/8/8/4K3 b - - 0 1").unwrap();
+        assert!(!capturable.is_same_position(&without));
+        assert_ne!(capturable.position_key(), without.position_key());
+
+        let no_castling = Game::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w kq - 0 1");
+        assert!(!Game::new().is_same_position(&no_castling.unwrap()));
+    }
+
+    /// Fivefold repetition and the 75-move rule end the game without a claim
+    #[test]
+    fn automatic_draws() {
+        let mut game = Game::new();
+        for cycle in 1..=4 {
+            for san in ["Nf3", "Nf6", "Ng1", "Ng8"] {
+                assert_eq!(game.get_game_state(), GameState::InProgress);
+                game.make_move_san(san).unwrap();
+            }
+            assert_eq!(game.repetition_count(), cycle + 1);
+        }
+        assert_eq!(
+            game.get_game_state(),
+            GameState::Draw(DrawReason::FivefoldRepetition)
+        );
+        assert_eq!(
+            game.make_move_san("Nf3").map_err(|error| error.message()),
+            Err("The game is drawn")
+        );
+        assert!(game.to_pgn().ends_with("1/2-1/2\n"));
+
+        // A pawn move makes earlier positions unrepeatable
+        let mut game = Game::new();
+        for san in ["Nf3", "Nf6", "Ng1", "Ng8", "e4"] {
+            game.make_move_san(san).unwrap();
+        }
+        assert_eq!(game.repetition_count(), 1);
+
+        let mut game = Game::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 149 100").unwrap();
+        assert_eq!(game.get_game_state(), GameState::InProgress);
+        game.make_move_san("Ra2").unwrap();
+        assert_eq!(
+            game.get_game_state(),
+            GameState::Draw(DrawReason::SeventyFiveMoves)
+        );
+    }
+
+    /// Threefold repetition and the fifty-move rule only end the game when claimed
+    #[test]
+    fn draw_claims() {
+        let mut game = Game::new();
+        assert_eq!(
+            game.claim_draw(DrawReason::ThreefoldRepetition),
+            Err("The position has not occurred three times")
+        );
+        for _ in 0..2 {
+            for san in ["Nf3", "Nf6", "Ng1", "Ng8"] {
+                game.make_move_san(san).unwrap();
+            }
+        }
+        assert_eq!(game.get_game_state(), GameState::InProgress);
+        assert_eq!(game.claimable_draw(), Some(DrawReason::ThreefoldRepetition));
+        assert!(game.claim_draw(DrawReason::Stalemate).is_err());
+        assert!(game.claim_draw(DrawReason::FiftyMoves).is_err());
+        game.claim_draw(DrawReason::ThreefoldRepetition).unwrap();
+        assert_eq!(
+            game.get_game_state(),
+            GameState::Draw(DrawReason::ThreefoldRepetition)
+        );
+        assert_eq!(
+            game.make_move_san("Nf3"),
+            Err(ChessError::GameOver(GameResult::Draw(
+                DrawReason::ThreefoldRepetition
+            )))
+        );
+        assert_eq!(
+            game.claim_draw(DrawReason::ThreefoldRepetition),
+            Err("The game is over")
+        );
+
+        let mut game = Game::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 99 80").unwrap();
+        assert_eq!(game.claimable_draw(), None);
+        game.make_move_san("Ra2").unwrap();
+        assert_eq!(game.claimable_draw(), Some(DrawReason::FiftyMoves));
+        game.claim_draw(DrawReason::FiftyMoves).unwrap();
+        assert_eq!(
+            game.result(),
+            Some(GameResult::Draw(DrawReason::FiftyMoves))
+        );
+    }
+
+    /// Positions where neither player can checkmate are drawn without a claim
+    #[test]
+    fn dead_positions() {
+        for fen in [
+            "4k3/8/8/8/8/8/8/4K3 w - - 0 1",
+            "4k3/8/8/8/8/8/8/2B1K3 w - - 0 1",
+            "4kb2/8/8/8/8/8/1B6/2B1K3 w - - 0 1",
+            "4k3/8/8/8/p1p1p1p1/P1P1P1P1/8/2B1K3 w - - 0 1",
+        ] {
+            let game = Game::from_fen(fen).unwrap();
+            assert!(game.is_dead_position(), "{}", fen);
+            assert_eq!(
+                game.get_game_state(),
+                GameState::Draw(DrawReason::DeadPosition)
+            );
+            assert_eq!(game.find_mate(3), None);
+        }
+        for fen in [
+            "4k1b1/8/8/8/8/8/8/2B1K3 w - - 0 1",
+            "4kn2/8/8/8/8/8/8/1N2K3 w - - 0 1",
+            "4k3/8/8/8/p1p1p1p1/P1P1P1P1/8/3BK3 w - - 0 1",
+            "4k3/8/8/8/p1p1p1p1/P1P1P2P/8/2B1K3 w - - 0 1",
+        ] {
+            let game = Game::from_fen(fen).unwrap();
+            assert!(!game.is_dead_position(), "{}", fen);
+        }
+
+        let mut game = Game::from_fen("4k3/8/8/8/8/8/3r4/2B1K3 w - - 0 1").unwrap();
+        game.make_move_san("Kxd2").unwrap();
+        assert_eq!(
+            game.result(),
+            Some(GameResult::Draw(DrawReason::DeadPosition))
+        );
+
+        // The verdict is kept after king moves and decided again for other pieces
+        let mut game = Game::from_fen("4k3/8/8/8/p1p1p1p1/P1P1P1P1/8/2B1K3 w - - 0 1").unwrap();
+        assert!(game.is_dead_position());
+        game._make(Move::from_uci("e1d1").unwrap());
+        assert!(game.is_dead_position());
+        let bishop = game.board.remove(&"c1".parse().unwrap()).unwrap();
+        game.board.insert("b1".parse().unwrap(), bishop);
+        assert!(!game.is_dead_position());
+        game.clear_move_cache();
+        assert!(!game.is_dead_position());
+    }
+
+    /// Illegal moves are explained with the piece in the way or attacking the king
+    #[test]
+    fn illegal_move_reasons() {
+        use crate::illegal::IllegalReason;
+
+        let square = |name: &str| name.parse::<Position>().unwrap();
+        let why = |game: &Game, from: &str, to: &str| game.why_illegal(square(from), square(to));
+        let game = Game::new();
+        assert_eq!(why(&game, "g1", "f3"), None);
+        assert_eq!(why(&game, "e3", "e4"), Some(IllegalReason::NoPiece));
+        assert_eq!(
+            why(&game, "b1", "b3"),
+            Some(IllegalReason::WrongPieceMovement)
+        );
+        assert_eq!(
+            why(&game, "e2", "e5"),
+            Some(IllegalReason::WrongPieceMovement)
+        );
+        assert_eq!(
+            why(&game, "e2", "d3"),
+            Some(IllegalReason::WrongPieceMovement)
+        );
+        assert_eq!(why(&game, "a1", "a2"), Some(IllegalReason::CaptureOwnPiece));
+        assert_eq!(
+            why(&game, "e1", "g1"),
+            Some(IllegalReason::CastlingNotAllowed)
+        );
+        let blocked = why(&game, "c1", "e3").unwrap();
+        assert_eq!(blocked, IllegalReason::Blocked { by: square("d2") });
+        assert_eq!(blocked.to_string(), "The piece on d2 is in the way");
+
+        let pinned = Game::from_fen("4k3/4rr2/8/8/8/8/4B3/4K3 w - - 0 1").unwrap();
+        assert_eq!(
+            why(&pinned, "e2", "d3"),
+            Some(IllegalReason::WouldExposeKing { by: square("e7") })
+        );
+        assert_eq!(
+            why(&pinned, "e1", "f1"),
+            Some(IllegalReason::WouldExposeKing { by: square("f7") })
+        );
+        assert_eq!(why(&pinned, "e1", "d1"), None);
+
+        let check = Game::from_fen("4k3/4r3/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+        assert_eq!(
+            why(&check, "a1", "a2"),
+            Some(IllegalReason::MustResolveCheck { by: square("e7") })
+        );
+        assert_eq!(why(&check, "e1", "d1"), None);
+        for mov in check.legal_moves().iter() {
+            assert_eq!(check.why_illegal(mov.from, mov.to), None);
+        }
+
+        let mut mated = Game::new();
+        for san in ["f3", "e5", "g4", "Qh4#"] {
+            mated.make_move_san(san).unwrap();
+        }
+        assert_eq!(why(&mated, "e2", "e4"), Some(IllegalReason::GameOver));
+    }
+
+    /// Moves of the player who is not to move can be checked for pins too
+    #[test]
+    fn legal_moves_for_either_color() {
+        let game = Game::from_fen("4k3/4n3/8/8/8/8/8/K3R3 w - - 0 1").unwrap();
+        // The pinned knight may move when only asking for possible moves
+        assert_eq!(game.get_possible_moves("e7".to_string()).unwrap().len(), 6);
+        let black = game.legal_moves_for(Color::Black);
+        assert_eq!(black.len(), 4);
+        assert!(black.iter().all(|mov| mov.from == "e8".parse().unwrap()));
+        let mut white = game.legal_moves().to_vec();
+        white.sort_unstable();
+        assert_eq!(game.legal_moves_for(Color::White), white);
+    }
+
+    /// Filtering pseudo-legal moves with is_legal gives the legal moves
+    #[test]
+    fn pseudo_legal_moves() {
+        let pos = |s: &str| Position::from_string(s.to_string()).unwrap();
+        let pinned = Game::from_fen("4k3/4r3/8/8/8/8/4B3/4K3 w - - 0 1").unwrap();
+        assert_eq!(pinned.pseudo_legal_moves(pos("e2")).len(), 9);
+        assert!(pinned.legal_moves_from(pos("e2")).is_empty());
+        assert!(!pinned.is_legal(Move::from_uci("e2d3").unwrap()));
+        // Opponent's pieces have pseudo-legal but no legal moves
+        assert_eq!(pinned.pseudo_legal_moves(pos("e7")).len(), 12);
+        assert!(pinned.legal_moves_from(pos("e7")).is_empty());
+
+        let kiwipete =
+            Game::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+                .unwrap();
+        let mut count = 0;
+        for (position, _) in kiwipete.pieces_of(Color::White) {
+            let legal: Vec<Move> = kiwipete
+                .pseudo_legal_moves(position)
+                .into_iter()
+                .filter(|mov| kiwipete.is_legal(*mov))
+                .collect();
+            assert_eq!(legal, kiwipete.legal_moves_from(position));
+            count += legal.len();
+        }
+        assert_eq!(count, 48);
+    }
+
+    /// Piece-square tables prefer central knights, a central king in the endgame and a sheltered
+    /// king in the middlegame, with tunable weights
+    #[test]
+    fn eval_details() {
+        let details =
+            |fen: &str| engine::Engine::default().evaluate_details(&Game::from_fen(fen).unwrap());
+        let start = details("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+        assert_eq!(
+            start,
+            eval::EvalDetails {
+                phase: eval::MAX_PHASE,
+                ..Default::default()
+            }
+        );
+
+        let rim = details("4k3/8/8/8/8/8/8/N3K3 w - - 0 1");
+        let center = details("4k3/8/8/8/3N4/8/8/4K3 w - - 0 1");
+        assert_eq!(rim.material, 320);
+        assert!(center.piece_squares > rim.piece_squares);
+        assert!(center.mobility > rim.mobility);
+
+        // With only kings and pawns the endgame table rewards the active king
+        let active = details("8/8/3k4/8/3K4/8/4P3/8 w - - 0 1");
+        assert_eq!(active.phase, 0);
+        let passive = details("8/8/3k4/8/8/8/4P3/K7 w - - 0 1");
+        assert!(active.total() > passive.total());
+
+        // Pawns in front of the castled king count in the middlegame
+        let castled = "rnbq1rk1/ppppbppp/5n2/4p3/4P3/5N2/PPPPBPPP/RNBQ1RK1 w - - 0 1";
+        let advanced = "rnbq1rk1/ppppbp1p/5np1/4p3/4P3/5N2/PPPPBPPP/RNBQ1RK1 w - - 0 1";
+        assert_eq!(details(castled).king_safety, 0);
+        assert!(details(advanced).king_safety > 0);
+        let weights = eval::EvalWeights {
+            king_shelter: 0,
+            knight: 300,
+            ..Default::default()
+        };
+        let tuned = engine::Engine::new(1).with_weights(weights);
+        let game = Game::from_fen(advanced).unwrap();
+        assert_eq!(tuned.evaluate_details(&game).king_safety, 0);
+        let knight = Game::from_fen("4k3/8/8/8/8/8/8/N3K3 w - - 0 1").unwrap();
+        assert_eq!(tuned.evaluate_details(&knight).material, 300);
+    }
+
+    /// Iterative deepening stops at node and time limits with a legal move
+    #[test]
+    fn search_limits() {
+        use engine::{Engine, SearchLimit};
+        use std::time::Duration;
+
+        let game = Game::new();
+        let engine = Engine::default();
+        for limit in [
+            SearchLimit::Nodes(500),
+            SearchLimit::Time(Duration::from_millis(20)),
+        ] {
+            let result = engine.search_with_limit(&game, limit);
+            assert!(game.is_legal(result.best_move.unwrap()));
+        }
+        let fixed = engine.search_with_limit(&game, SearchLimit::Depth(2));
+        assert_eq!(fixed.depth, 2);
+        // The weights of the engine are kept
+        let pawns = Game::from_fen("4k3/pp6/8/8/8/8/PPP5/4K3 w - - 0 1").unwrap();
+        let weights = eval::EvalWeights {
+            pawn: 300,
+            ..Default::default()
+        };
+        let weighted = Engine::new(2).with_weights(weights);
+        let fixed = weighted.search_with_limit(&pawns, SearchLimit::Depth(2));
+        assert_eq!(fixed.score, weighted.search(&pawns).score);
+        assert_ne!(fixed.score, Engine::new(2).search(&pawns).score);
+
+        // The search stops deepening once a mate is found
+        let mate = Game::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+        let result = engine.search_with_limit(&mate, SearchLimit::Nodes(1_000_000));
+        assert_eq!(result.best_move, Move::from_uci("a1a8").ok());
+        assert_eq!(result.depth, 1);
+    }
+
+    /// Candidate moves are ranked for the player to move with their principal variations
+    #[test]
+    fn analyze_candidates() {
+        let game = Game::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+        let lines = game.analyze(2);
+        assert_eq!(lines.len(), game.legal_moves().len());
+        assert_eq!(lines[0].moves, vec![Move::from_uci("a1a8").unwrap()]);
+        assert!(lines.windows(2).all(|pair| pair[0].score >= pair[1].score));
+
+        // Black prefers low scores
+        let black = Game::from_fen("r5k1/8/8/8/8/8/5PPP/6K1 b - - 0 1").unwrap();
+        let lines = black.analyze(2);
+        assert_eq!(lines[0].moves[0], Move::from_uci("a8a1").unwrap());
+        assert!(lines.windows(2).all(|pair| pair[0].score <= pair[1].score));
+        assert!(lines.iter().skip(1).all(|line| line.moves.len() == 2));
+    }
+
+    /// Rays stop at the first piece and at the edge of small boards
+    #[test]
+    fn ray_casting() {
+        use config::BoardSize;
+        use ray::Ray;
+        let game = Game::from_fen("4k3/8/8/8/8/2p5/8/B3K3 w - - 0 1").unwrap();
+        let a1 = "a1".parse().unwrap();
+        let diagonal: Vec<Position> =
+            Ray::new(game.board(), BoardSize::STANDARD, a1, (1, 1)).collect();
+        assert_eq!(diagonal.len(), 2);
+        let rank: Vec<Position> =
+            Ray::new(game.board(), BoardSize::MINICHESS, a1, (1, 0)).collect();
+        assert_eq!(rank.len(), 4);
+        assert_eq!(
+            Ray::new(game.board(), BoardSize::STANDARD, a1, (-1, 0)).count(),
+            0
+        );
+        assert_eq!(ray::direction(&a1, &"c2".parse().unwrap()), None);
+        assert!(game
+            .attackers_of("c3".parse().unwrap(), Color::White)
+            .contains(&a1));
+        assert!(!game
+            .attackers_of("d4".parse().unwrap(), Color::White)
+            .contains(&a1));
+    }
+
+    /// Square colors, distances and the squares between two squares
+    #[test]
+    fn position_geometry() {
+        let pos = |s: &str| s.parse::<Position>().unwrap();
+        assert_eq!(pos("a1").shade(), SquareShade::Dark);
+        assert_eq!(pos("h1").shade(), SquareShade::Light);
+        assert_eq!((pos("e4").file_char(), pos("e4").rank_char()), ('e', '4'));
+        assert_eq!(pos("b1").distance(&pos("g3")), 5);
+        assert_eq!(pos("b1").manhattan_distance(&pos("g3")), 7);
+        assert!(pos("c1").is_same_diagonal(&pos("h6")));
+        assert!(pos("c1").is_same_diagonal(&pos("a3")));
+        assert!(!pos("c1").is_same_diagonal(&pos("c3")));
+        assert!(pos("c1").is_same_file(&pos("c3")) && pos("c1").is_same_rank(&pos("h1")));
+
+        let between = |a: &str, b: &str| -> Vec<String> {
+            pos(a).between(&pos(b)).map(|p| p.to_string()).collect()
+        };
+        assert_eq!(between("a1", "d4"), ["b2", "c3"]);
+        assert_eq!(between("e8", "e5"), ["e7", "e6"]);
+        assert_eq!(between("h3", "e3"), ["g3", "f3"]);
+        assert!(between("e4", "e5").is_empty());
+        assert!(between("g1", "f3").is_empty());
+        assert!(between("e4", "e4").is_empty());
+    }
+
+    /// Positions and moves convert to and from strings and indices
+    #[test]
+    fn position_conversions() {
+        use std::convert::TryFrom;
+
+        let e4 = Position::new(5, 4).unwrap();
+        assert_eq!("e4".parse(), Ok(e4));
+        assert_eq!(Position::try_from("E4"), Ok(e4));
+        assert_eq!(e4.to_string(), "e4");
+        assert_eq!(format!("{}", Position::from_index(63).unwrap()), "h8");
+        assert!(Position::new(0, 4).is_err());
+        assert!(Position::from_index(64).is_none());
+        assert!("e9".parse::<Position>().is_err());
+        assert!("e".parse::<Position>().is_err());
+        for index in 0..64 {
+            assert_eq!(Position::from_index(index).unwrap().index(), index);
+        }
+
+        let promotion: Move = "e7e8q".parse().unwrap();
+        assert_eq!(promotion.promotion, Some(Piece::Queen(Color::White)));
+        assert_eq!(promotion.to_string(), "e7e8q");
+        assert_eq!(Move::try_from("e2e4"), Move::from_uci("e2e4"));
+    }
+
+    /// Forced mates are found with the shortest length and the longest defence, stalemate is no mate
+    #[test]
+    fn mate_in_n() {
+        let back_rank = Game::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+        assert_eq!(
+            back_rank.find_mate(5),
+            Some(vec![Move::from_uci("a1a8").unwrap()])
+        );
+
+        let game = Game::from_fen("k7/8/2K5/8/8/8/8/7R w - - 0 1").unwrap();
+        assert_eq!(game.find_mate(2), None);
+        let mate = game.find_mate(3).unwrap();
+        assert_eq!(mate.len(), 3);
+        let mut replay = game.clone();
+        for mov in mate {
+            replay._make_move(mov).unwrap();
+        }
+        assert_eq!(replay.get_game_state(), GameState::CheckMate);
+        assert!(replay._king_is_threatened(Color::Black));
+
+        // Qb6 stalemates, there is no mate
+        let stalemate = Game::from_fen("k7/8/8/1Q6/8/8/8/7K w - - 0 1").unwrap();
+        assert_eq!(stalemate.find_mate(1), None);
+        assert_eq!(Game::new().find_mate(3), None);
+    }
+
+    /// Positions survive the binary format and transpositions give the same bytes
+    #[test]
+    fn binary_positions() {
+        let play = |moves: &[&str]| {
+            let mut game = Game::new();
+            for san in moves {
+                game.make_move_san(san).unwrap();
+            }
+            game
+        };
+        let a = play(&["e4", "e6", "d4", "d5"]);
+        let b = play(&["d4", "e6", "e4", "d5"]);
+        assert_eq!(a.to_bytes(), b.to_bytes());
+        assert_eq!(a.to_bytes().len(), binary::POSITION_SIZE);
+        let passant = play(&["e4", "c5", "e5", "d5"]);
+        assert_eq!(
+            Game::from_bytes(&passant.to_bytes()).unwrap().to_fen(),
+            passant.to_fen()
+        );
+
+        let chess960 = Game::new_chess960(0).unwrap();
+        assert_eq!(Game::from_bytes(&chess960.to_bytes()).unwrap(), chess960);
+        // Odds describe how the game was set up, not the position
+        let handicap = Game::new_with_odds(odds::Odds::KnightOdds);
+        assert_eq!(Game::from_bytes(&handicap.to_bytes()).unwrap(), handicap);
+
+        let mut crazyhouse = Game::new_crazyhouse();
+        for san in ["e4", "d5", "exd5", "Qxd5"] {
+            crazyhouse.make_move_san(san).unwrap();
+        }
+        let bytes = crazyhouse.to_bytes();
+        assert_eq!(bytes.len(), binary::CRAZYHOUSE_SIZE);
+        assert_eq!(Game::from_bytes(&bytes).unwrap(), crazyhouse);
+
+        assert!(Game::from_bytes(&[]).is_err());
+        assert!(Game::from_bytes(&bytes[..binary::POSITION_SIZE]).is_err());
+        let mut invalid = a.to_bytes();
+        invalid[1] = 7;
+        assert!(Game::from_bytes(&invalid).is_err());
+    }
+
+    /// Moves queue events for promotions, en passant captures, check and checkmate
+    #[test]
+    fn move_events() {
+        use GameEvent::*;
+        let at = |square: &str| square.parse::<Position>().unwrap();
+
+        let mut game = Game::from_fen("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        game.make_move_uci("b7b8q").unwrap();
+        assert_eq!(
+            game.events().collect::<Vec<_>>(),
+            vec![
+                PieceMoved {
+                    piece: Piece::Pawn(Color::White),
+                    from: at("b7"),
+                    to: at("b8")
+                },
+                Promotion {
+                    position: at("b8"),
+                    piece: Piece::Queen(Color::White)
+                },
+                Check(Color::Black),
+            ]
+        );
+
+        let mut game = Game::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+        game.make_move_san("exd6").unwrap();
+        let events: Vec<GameEvent> = game.events().collect();
+        assert_eq!(
+            events[1],
+            PieceCaptured {
+                piece: Piece::Pawn(Color::Black),
+                position: at("d5")
+            }
+        );
+
+        let mut game = Game::new();
+        for san in ["f3", "e5", "g4", "Qh4#"] {
+            game.make_move_san(san).unwrap();
+        }
+        let events: Vec<GameEvent> = game.events().collect();
+        assert_eq!(events.len(), 6);
+        assert_eq!(
+            events[4..],
+            [Check(Color::White), GameOver(GameState::CheckMate)]
+        );
+
+        // Events that are never taken are dropped oldest first
+        let mut game = Game::new();
+        for color in [Color::White, Color::Black]
+            .iter()
+            .cycle()
+            .take(events::MAX_EVENTS + 10)
+        {
+            game._push_event(Check(*color));
+        }
+        let events: Vec<GameEvent> = game.events().collect();
+        assert_eq!(events.len(), events::MAX_EVENTS);
+        assert_eq!(events[0], Check(Color::White));
+        assert_eq!(events.last(), Some(&Check(Color::Black)));
+    }
+
+    /// Match players alternate colors and resigning loses the game
+    #[test]
+    fn match_series() {
+        use tournament::{Format, Match};
+
+        let resign = |_: &Game| None;
+        let mut series =
+            Match::new(Engine::new(1), resign, Format::BestOf(5)).names("Engine", "Quitter");
+        assert_eq!(series.play().len(), 3);
+        assert!(series.is_finished());
+        assert_eq!(series.winner(), Some(0));
+        assert_eq!(series.summary(), "Engine 3 - 0 Quitter");
+        assert_eq!(series.record(1), (0, 0, 3));
+        assert_eq!(series.elo_difference(0), None);
+        let second = &series.games()[1];
+        assert_eq!(second.white, 1);
+        assert_eq!(
+            second.simulation.termination,
+            simulate::Termination::Resignation
+        );
+        assert!(second.pgn.contains("[Round \"2\"]"));
+        assert!(second.pgn.contains("[White \"Quitter\"]"));
+        assert!(second.pgn.trim_end().ends_with("0-1"));
+
+        // Mate in one from the start position decides every game for the player with White
+        let start = Game::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+        let mut round_robin = Match::new(Engine::new(2), Engine::new(2), Format::RoundRobin(2))
+            .start_position(start)
+            .max_plies(10);
+        assert_eq!(round_robin.play().len(), 4);
+        assert_eq!((round_robin.points(0), round_robin.points(1)), (2.0, 2.0));
+        assert_eq!(round_robin.record(0), (2, 0, 2));
+        assert_eq!(round_robin.elo_difference(1), Some(0.0));
+        assert_eq!(round_robin.winner(), None);
+        assert!(round_robin.play_game().is_none());
+    }
 }
@@ -0,0 +1,266 @@
+//! Matches between two players, eg. to test a change to the engine against the previous version
+//!
+//! Players are engines or callbacks choosing a move, for example asking a human. The players
+//! change colors after every game and every game is kept as PGN.
+//!
+//! # Example
+//! ```
+//! use eliasfl_chess::tournament::{Format, Match};
+//! use eliasfl_chess::Engine;
+//!
+//! let mut series = Match::new(Engine::new(1), Engine::new(2), Format::BestOf(3))
+//!     .names("Depth 1", "Depth 2")
+//!     .max_plies(20);
+//! series.play();
+//! assert!(series.games().len() <= 3);
+//! println!("{} - {}", series.points(0), series.points(1));
+//! println!("{}", series.games()[0].pgn);
+//! ```
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::simulate::{self, Simulation, Termination, MAX_PLIES};
+use crate::{Color, Engine, Game, Move};
+
+/// Chooses the moves of one player in a [`Match`]
+///
+/// Implemented for [`Engine`] and for closures, eg. `|game: &Game| ask_human(game)`.
+pub trait MatchPlayer {
+    /// The move to make in `game`, None to resign
+    ///
+    /// Illegal moves also lose the game.
+    fn choose_move(&mut self, game: &Game) -> Option<Move>;
+}
+
+impl MatchPlayer for Engine {
+    fn choose_move(&mut self, game: &Game) -> Option<Move> {
+        self.best_move(game)
+    }
+}
+
+impl<F: FnMut(&Game) -> Option<Move>> MatchPlayer for F {
+    fn choose_move(&mut self, game: &Game) -> Option<Move> {
+        self(game)
+    }
+}
+
+/// How many games are played
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// At most this many games, ending early once a player has more than half of the points
+    BestOf(u32),
+    /// This many games with each color for both players
+    RoundRobin(u32),
+}
+
+impl Format {
+    /// Most games the format can have
+    fn max_games(&self) -> usize {
+        match *self {
+            Format::BestOf(games) => games as usize,
+            Format::RoundRobin(rounds) => 2 * rounds as usize,
+        }
+    }
+}
+
+/// A finished game of a [`Match`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchGame {
+    /// Index of the player who had White, 0 for the first player and 1 for the second
+    pub white: usize,
+    /// The moves and how the game ended
+    pub simulation: Simulation,
+    /// The game in Portable Game Notation with the player names and round
+    pub pgn: String,
+}
+
+impl MatchGame {
+    /// Index of the player who won, None for draws and unfinished games
+    pub fn winner(&self) -> Option<usize> {
+        self.simulation.winner().map(|color| match color {
+            Color::White => self.white,
+            Color::Black => 1 - self.white,
+        })
+    }
+}
+
+/// A series of games between two players who alternate colors, the first player starts with White
+#[derive(Debug, Clone)]
+pub struct Match<A, B> {
+    first: A,
+    second: B,
+    names: [String; 2],
+    format: Format,
+    start: Game,
+    max_plies: usize,
+    games: Vec<MatchGame>,
+}
+
+impl<A: MatchPlayer, B: MatchPlayer> Match<A, B> {
+    /// Match from the standard starting position with at most [`MAX_PLIES`] half moves per game
+    pub fn new(first: A, second: B, format: Format) -> Self {
+        Self {
+            first,
+            second,
+            names: ["Player 1".to_string(), "Player 2".to_string()],
+            format,
+            start: Game::new(),
+            max_plies: MAX_PLIES,
+            games: Vec::new(),
+        }
+    }
+
+    /// Names of the players in the PGN of the games
+    pub fn names(mut self, first: &str, second: &str) -> Self {
+        self.names = [first.to_string(), second.to_string()];
+        self
+    }
+
+    /// Start every game from `start` instead of the standard starting position
+    pub fn start_position(mut self, start: Game) -> Self {
+        self.start = start;
+        self
+    }
+
+    /// Stop games after `max_plies` half moves, they count as draws
+    pub fn max_plies(mut self, max_plies: usize) -> Self {
+        self.max_plies = max_plies;
+        self
+    }
+
+    /// The games played so far
+    pub fn games(&self) -> &[MatchGame] {
+        &self.games
+    }
+
+    /// Points of player 0 (first) or 1 (second): 1 per win and 0.5 per draw or unfinished game
+    pub fn points(&self, player: usize) -> f64 {
+        self.games
+            .iter()
+            .map(|game| match game.winner() {
+                Some(winner) if winner == player => 1.0,
+                Some(_) => 0.0,
+                None => 0.5,
+            })
+            .sum()
+    }
+
+    /// Wins, draws and losses of player 0 (first) or 1 (second), unfinished games count as draws
+    pub fn record(&self, player: usize) -> (u32, u32, u32) {
+        self.games
+            .iter()
+            .fold((0, 0, 0), |(wins, draws, losses), game| {
+                match game.winner() {
+                    Some(winner) if winner == player => (wins + 1, draws, losses),
+                    Some(_) => (wins, draws, losses + 1),
+                    None => (wins, draws + 1, losses),
+                }
+            })
+    }
+
+    /// Estimated Elo rating difference of player 0 (first) or 1 (second) over the other player
+    /// from the share of points, None before the first game or if a player has every point
+    ///
+    /// Needs the `std` feature for floating point math
+    #[cfg(feature = "std")]
+    pub fn elo_difference(&self, player: usize) -> Option<f64> {
+        let score = self.points(player) / self.games.len() as f64;
+        if !(score > 0.0 && score < 1.0) {
+            return None;
+        }
+        Some(-400.0 * (1.0 / score - 1.0).log10())
+    }
+
+    /// If all games of the format were played or a best-of match is decided
+    pub fn is_finished(&self) -> bool {
+        let max_games = self.format.max_games();
+        if self.games.len() >= max_games {
+            return true;
+        }
+        match self.format {
+            Format::BestOf(_) => (0..2).any(|player| self.points(player) > max_games as f64 / 2.0),
+            Format::RoundRobin(_) => false,
+        }
+    }
+
+    /// Index of the player with the most points once the match is finished, None if tied or
+    /// still playing
+    pub fn winner(&self) -> Option<usize> {
+        if !self.is_finished() || self.points(0) == self.points(1) {
+            return None;
+        }
+        Some(if self.points(0) > self.points(1) {
+            0
+        } else {
+            1
+        })
+    }
+
+    /// Score line, eg. "Player 1 1.5 - 0.5 Player 2"
+    pub fn summary(&self) -> String {
+        format!(
+            "{} {} - {} {}",
+            self.names[0],
+            self.points(0),
+            self.points(1),
+            self.names[1]
+        )
+    }
+
+    /// Play the remaining games and return all games
+    pub fn play(&mut self) -> &[MatchGame] {
+        while self.play_game().is_some() {}
+        &self.games
+    }
+
+    /// Play the next game, None if the match is finished
+    pub fn play_game(&mut self) -> Option<&MatchGame> {
+        if self.is_finished() {
+            return None;
+        }
+        let round = self.games.len() + 1;
+        let white = self.games.len() % 2;
+        let mut game = self.start.clone();
+        let mut moves = Vec::new();
+        let termination = loop {
+            if let Some(termination) = simulate::_termination(&game) {
+                break termination;
+            }
+            if moves.len() >= self.max_plies || game.legal_moves().is_empty() {
+                break Termination::Unfinished;
+            }
+            let player = match game.active_color {
+                Color::White => white,
+                Color::Black => 1 - white,
+            };
+            let mov = match player {
+                0 => self.first.choose_move(&game),
+                _ => self.second.choose_move(&game),
+            };
+            match mov.map(|mov| game._make_move(mov)) {
+                Some(Ok(_)) => moves.push(game.last_move().expect("Move was recorded")),
+                _ => break Termination::Resignation,
+            }
+        };
+        let simulation = Simulation {
+            game,
+            moves,
+            termination,
+        };
+        let round = round.to_string();
+        let pgn = simulation.game._to_pgn_with(&[
+            ("Round", &round),
+            ("White", &self.names[white]),
+            ("Black", &self.names[1 - white]),
+            ("Result", simulation.result()),
+        ]);
+        self.games.push(MatchGame {
+            white,
+            simulation,
+            pgn,
+        });
+        self.games.last()
+    }
+}
@@ -0,0 +1,188 @@
+//! Training data for policy/value networks from engine self-play
+//!
+//! # Binary format
+//! A file starts with the 4 magic bytes `EFCT` and a version byte (currently 1), followed by
+//! records of [`RECORD_SIZE`] bytes. All integers are little endian:
+//!
+//! | Bytes  | Content                                                                   |
+//! | ------ | ------------------------------------------------------------------------- |
+//! | 0..96  | 12 `u64` piece planes: White king, queen, rook, bishop, knight, pawn, then Black in the same order. Bit `(rank - 1) * 8 + (file - 1)` is set if the piece stands there |
+//! | 96     | `u8` side to move: 0 for White, 1 for Black                               |
+//! | 97..99 | `u16` move index, see [`move_index`]                                      |
+//! | 99     | `i8` game result for the side to move: 1 win, 0 draw, -1 loss             |
+
+use std::io::{self, Read, Write};
+
+use crate::{Color, Engine, Game, Move, Piece, Position};
+
+/// Magic bytes at the start of a training data file
+pub const MAGIC: &[u8; 4] = b"EFCT";
+
+/// Version of the binary format
+pub const VERSION: u8 = 1;
+
+/// Size of one record in bytes
+pub const RECORD_SIZE: usize = 100;
+
+/// One position from a game with the move that was played and the final result
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrainingSample {
+    /// Piece planes, see the [module documentation](self)
+    pub planes: [u64; 12],
+    /// The player who made the move
+    pub side_to_move: Color,
+    /// The played move, see [`move_index`]
+    pub move_index: u16,
+    /// Result for the side to move: 1 win, 0 draw, -1 loss
+    pub result: i8,
+}
+
+/// Index of a square from 0 (a1) to 63 (h8)
+fn square_index(position: &Position) -> u16 {
+    (position.rank as u16 - 1) * 8 + (position.file as u16 - 1)
+}
+
+/// Index of the piece's plane
+fn plane_index(piece: &Piece) -> usize {
+    let kind = match piece {
+        Piece::King(_) => 0,
+        Piece::Queen(_) => 1,
+        Piece::Rook(_) => 2,
+        Piece::Bishop(_) => 3,
+        Piece::Knight(_) => 4,
+        Piece::Pawn(_) => 5,
+    };
+    kind + 6 * piece.color().index()
+}
+
+/// Policy index of a move: `from * 64 + to` using square indices from 0 (a1) to 63 (h8),
+/// plus 4096 times 1, 2 or 3 for promotion to rook, bishop or knight (queen and no promotion add 0)
+pub fn move_index(mov: &Move) -> u16 {
+    let promotion = match mov.promotion {
+        Some(Piece::Rook(_)) => 1,
+        Some(Piece::Bishop(_)) => 2,
+        Some(Piece::Knight(_)) => 3,
+        _ => 0,
+    };
+    promotion * 4096 + square_index(&mov.from) * 64 + square_index(&mov.to)
+}
+
+impl TrainingSample {
+    /// Sample for `mov` being played in `game`, with result from White's perspective (1, 0 or -1)
+    pub fn new(game: &Game, mov: &Move, white_result: i8) -> Self {
+        let mut planes = [0; 12];
+        for (position, piece) in game.board.iter() {
+            planes[plane_index(piece)] |= 1 << square_index(&position);
+        }
+        Self {
+            planes,
+            side_to_move: game.active_color,
+            move_index: move_index(mov),
+            result: match game.active_color {
+                Color::White => white_result,
+                Color::Black => -white_result,
+            },
+        }
+    }
+
+    /// Record in the binary format
+    pub fn to_bytes(&self) -> [u8; RECORD_SIZE] {
+        let mut bytes = [0; RECORD_SIZE];
+        for (i, plane) in self.planes.iter().enumerate() {
+            bytes[i * 8..i * 8 + 8].copy_from_slice(&plane.to_le_bytes());
+        }
+        bytes[96] = self.side_to_move.index() as u8;
+        bytes[97..99].copy_from_slice(&self.move_index.to_le_bytes());
+        bytes[99] = self.result as u8;
+        bytes
+    }
+
+    /// Sample from a record in the binary format
+    pub fn from_bytes(bytes: &[u8; RECORD_SIZE]) -> Self {
+        let mut planes = [0; 12];
+        for (i, plane) in planes.iter_mut().enumerate() {
+            let mut plane_bytes = [0; 8];
+            plane_bytes.copy_from_slice(&bytes[i * 8..i * 8 + 8]);
+            *plane = u64::from_le_bytes(plane_bytes);
+        }
+        Self {
+            planes,
+            side_to_move: if bytes[96] == 0 {
+                Color::White
+            } else {
+                Color::Black
+            },
+            move_index: u16::from_le_bytes([bytes[97], bytes[98]]),
+            result: bytes[99] as i8,
+        }
+    }
+}
+
+/// Play a game from `start` with `engine` making the moves for both players
+///
+/// Returns one sample per move. The game is a draw if it ends in stalemate or lasts `max_plies` half moves
+pub fn self_play(engine: &Engine, start: &Game, max_plies: usize) -> Vec<TrainingSample> {
+    let mut game = start.clone();
+    let mut played = Vec::new();
+    let mut white_result = 0;
+    for _ in 0..max_plies {
+        match engine.best_move(&game) {
+            Some(mov) => {
+                played.push((game.clone(), mov));
+                game._make_move(mov).expect("Engine made illegal move");
+            }
+            None => {
+                // No legal moves: checkmate or stalemate
+                if game._king_is_threatened(game.active_color) {
+                    white_result = match game.active_color {
+                        Color::White => -1,
+                        Color::Black => 1,
+                    };
+                }
+                break;
+            }
+        }
+    }
+    played
+        .iter()
+        .map(|(position, mov)| TrainingSample::new(position, mov, white_result))
+        .collect()
+}
+
+/// Write samples in the binary format, including the file header
+pub fn write_samples<W: Write>(mut writer: W, samples: &[TrainingSample]) -> io::Result<()> {
+    writer.write_all(MAGIC)?;
+    writer.write_all(&[VERSION])?;
+    for sample in samples {
+        writer.write_all(&sample.to_bytes())?;
+    }
+    Ok(())
+}
+
+/// Read all samples written by [`write_samples`]
+pub fn read_samples<R: Read>(mut reader: R) -> io::Result<Vec<TrainingSample>> {
+    let mut header = [0; 5];
+    reader.read_exact(&mut header)?;
+    if &header[..4] != MAGIC || header[4] != VERSION {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Not a training data file of a supported version",
+        ));
+    }
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data)?;
+    if data.len() % RECORD_SIZE != 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "Truncated training data record",
+        ));
+    }
+    Ok(data
+        .chunks(RECORD_SIZE)
+        .map(|chunk| {
+            let mut record = [0; RECORD_SIZE];
+            record.copy_from_slice(chunk);
+            TrainingSample::from_bytes(&record)
+        })
+        .collect())
+}
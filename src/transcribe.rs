@@ -0,0 +1,238 @@
+//! Tolerant transcription of hand-typed score sheets into games
+//!
+//! Accepts moves with or without move numbers (eg. "1.e4", "1. e4" or just "e4"), castling with
+//! zeros or lowercase letters, lowercase piece letters and results anywhere in the text.
+//! Everything that had to be corrected or guessed is reported in [`Transcription::notes`].
+//!
+//! # Example
+//! ```
+//! use eliasfl_chess::transcribe::transcribe;
+//!
+//! let transcription = transcribe("1.e4 e5 nf3 Nc6 3 bb5 a6 0-0 1-0");
+//! assert_eq!(transcription.moves.len(), 7);
+//! assert_eq!(transcription.result.as_deref(), Some("1-0"));
+//! assert!(transcription.is_complete());
+//! ```
+
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::san::matching_moves;
+use crate::{Game, Move};
+
+/// What happened to a token of the score sheet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NoteKind {
+    /// The token was read as the corrected notation
+    Corrected(String),
+    /// Several legal moves match the token, the chosen one is the move played
+    Ambiguous {
+        /// The move that was played
+        chosen: Move,
+        /// All matching moves including the chosen one
+        candidates: Vec<Move>,
+    },
+    /// The token is not a legal move, transcription stopped here
+    Unreadable(&'static str),
+}
+
+/// Remark about a token of the score sheet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Note {
+    /// Number of half moves played before the token
+    pub ply: usize,
+    /// The token as written
+    pub token: String,
+    /// What happened to the token
+    pub kind: NoteKind,
+}
+
+/// A game reconstructed from a score sheet
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transcription {
+    /// Position after the last readable move
+    pub game: Game,
+    /// Moves in the order they were played
+    pub moves: Vec<Move>,
+    /// Result written on the sheet, eg. "1-0", "0-1", "1/2-1/2" or "*"
+    pub result: Option<String>,
+    /// Corrections, ambiguities and the first unreadable token
+    pub notes: Vec<Note>,
+    /// Tokens after the first unreadable one, which were not transcribed
+    pub remaining: Vec<String>,
+}
+
+impl Transcription {
+    /// If every token was transcribed
+    pub fn is_complete(&self) -> bool {
+        !self
+            .notes
+            .iter()
+            .any(|note| matches!(note.kind, NoteKind::Unreadable(_)))
+    }
+
+    /// Notes about ambiguous moves, which should be checked by a person
+    pub fn ambiguities(&self) -> impl Iterator<Item = &Note> {
+        self.notes
+            .iter()
+            .filter(|note| matches!(note.kind, NoteKind::Ambiguous { .. }))
+    }
+}
+
+/// Transcribe a score sheet starting from the standard position
+pub fn transcribe(text: &str) -> Transcription {
+    transcribe_from(&Game::new(), text)
+}
+
+/// Transcribe a score sheet starting from `start`
+pub fn transcribe_from(start: &Game, text: &str) -> Transcription {
+    let tokens = tokenize(text);
+    let mut transcription = Transcription {
+        game: start.clone(),
+        moves: Vec::new(),
+        result: None,
+        notes: Vec::new(),
+        remaining: Vec::new(),
+    };
+    for (i, token) in tokens.iter().enumerate() {
+        if is_result(token) {
+            transcription.result = Some(token.replace('½', "1/2"));
+            continue;
+        }
+        let ply = transcription.moves.len();
+        let game = &transcription.game;
+        let read =
+            candidate_spellings(token).into_iter().find_map(|spelling| {
+                match matching_moves(game, &spelling) {
+                    Ok(moves) if !moves.is_empty() => Some((spelling, moves)),
+                    _ => None,
+                }
+            });
+        let (spelling, candidates) = match read {
+            Some(read) => read,
+            None => {
+                let error = crate::san::parse_san(game, token)
+                    .err()
+                    .unwrap_or("No legal move matches");
+                transcription.notes.push(Note {
+                    ply,
+                    token: token.clone(),
+                    kind: NoteKind::Unreadable(error),
+                });
+                transcription.remaining = tokens[i + 1..].to_vec();
+                break;
+            }
+        };
+        if spelling != *token {
+            transcription.notes.push(Note {
+                ply,
+                token: token.clone(),
+                kind: NoteKind::Corrected(spelling),
+            });
+        }
+        let chosen = if candidates.len() == 1 {
+            candidates[0]
+        } else {
+            let chosen = resolve_ambiguity(game, &candidates, &tokens[i + 1..]);
+            transcription.notes.push(Note {
+                ply,
+                token: token.clone(),
+                kind: NoteKind::Ambiguous {
+                    chosen,
+                    candidates: candidates.clone(),
+                },
+            });
+            chosen
+        };
+        transcription
+            .game
+            ._make_move(chosen)
+            .expect("Matching moves are legal");
+        transcription.moves.push(chosen);
+    }
+    transcription
+}
+
+/// Split text into move tokens, dropping move numbers and punctuation between moves
+fn tokenize(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    for word in text.split(|c: char| c.is_whitespace() || matches!(c, ',' | ';')) {
+        // Move numbers may be attached to the move, eg. "12.Nf3" or "12...Nf6"
+        let word = match word.rfind('.') {
+            Some(i) if word[..i].chars().all(|c| c.is_ascii_digit() || c == '.') => &word[i + 1..],
+            _ => word,
+        };
+        let is_number = word.chars().all(|c| c.is_ascii_digit());
+        let has_move_characters = word.chars().any(|c| c.is_alphanumeric());
+        if has_move_characters && !is_number {
+            tokens.push(word.to_string());
+        }
+    }
+    tokens
+}
+
+/// If token is a game result
+fn is_result(token: &str) -> bool {
+    matches!(token, "1-0" | "0-1" | "1/2-1/2" | "½-½" | "*")
+}
+
+/// Ways the token may have been meant, starting with the token as written
+fn candidate_spellings(token: &str) -> Vec<String> {
+    let mut spellings = vec![token.to_string()];
+    let castling = token
+        .trim_end_matches(['+', '#', '!', '?'])
+        .replace(['0', 'o'], "O");
+    if castling.chars().all(|c| matches!(c, 'O' | '-')) {
+        match castling.matches('O').count() {
+            2 => spellings.push("O-O".to_string()),
+            3 => spellings.push("O-O-O".to_string()),
+            _ => {}
+        }
+    }
+    // Lowercase piece letter, "b" is tried as a pawn on the b file first
+    let mut chars = token.chars();
+    if let Some(first) = chars.next() {
+        if matches!(first, 'k' | 'q' | 'r' | 'b' | 'n') {
+            spellings.push(first.to_ascii_uppercase().to_string() + chars.as_str());
+        }
+    }
+    spellings
+}
+
+/// Number of following tokens looked at to resolve an ambiguous move
+const LOOKAHEAD: usize = 4;
+
+/// Choose among ambiguous moves the one after which most of the following tokens are legal moves
+///
+/// Ties are resolved by taking the first candidate
+fn resolve_ambiguity(game: &Game, candidates: &[Move], following: &[String]) -> Move {
+    let readable = |mov: Move| {
+        let mut after = game.clone();
+        after._make_move(mov).expect("Matching moves are legal");
+        let mut count = 0;
+        for token in following.iter().take(LOOKAHEAD) {
+            if is_result(token) {
+                break;
+            }
+            let read = candidate_spellings(token)
+                .iter()
+                .find_map(|spelling| matching_moves(&after, spelling).ok()?.first().copied());
+            match read {
+                Some(next) if after._make_move(next).is_ok() => count += 1,
+                _ => break,
+            }
+        }
+        count
+    };
+    let mut best = candidates[0];
+    let mut best_count = readable(best);
+    for mov in candidates[1..].iter().copied() {
+        let count = readable(mov);
+        if count > best_count {
+            best = mov;
+            best_count = count;
+        }
+    }
+    best
+}
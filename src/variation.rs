@@ -0,0 +1,263 @@
+//! Tree of the moves of a game with side lines, eg. for analysis
+//!
+//! Every move made is a node of the game's [`MoveTree`]. Going back with [`Game::go_to`] and playing
+//! another move starts a variation, the first child of a node continues the main line. PGN export
+//! writes the variations in parentheses.
+//!
+//! # Example
+//! ```
+//! use eliasfl_chess::variation::MoveTree;
+//! use eliasfl_chess::{Game, Move};
+//!
+//! let mut game = Game::new();
+//! game.make_move_san("e4").unwrap();
+//! let e4 = game.current_node();
+//! game.make_move_san("e5").unwrap();
+//!
+//! // Try the Sicilian as a side line
+//! let c5 = game.add_variation(e4, &[Move::from_uci("c7c5").unwrap()]).unwrap();
+//! assert_eq!(game.movetext(), "1. e4 e5 (1... c5)");
+//! game.promote_variation(c5).unwrap();
+//! assert_eq!(game.movetext(), "1. e4 c5 (1... e5)");
+//!
+//! game.go_to(MoveTree::ROOT).unwrap();
+//! assert_eq!(game, Game::new());
+//! ```
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::mem;
+use core::ops::Index;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::pgn::MoveAnnotation;
+use crate::{Color, Game, Move};
+
+/// Index of a node in a [`MoveTree`]
+pub type NodeId = usize;
+
+/// A move of the tree with the moves continuing from it
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Node {
+    /// The move leading to the node, None for the root
+    pub mov: Option<Move>,
+    /// Node before the move, None for the root
+    pub parent: Option<NodeId>,
+    /// Moves played after this one, the main line first
+    pub children: Vec<NodeId>,
+    /// Glyphs and comment of the move, see [`Game::annotate`]
+    pub annotation: Option<MoveAnnotation>,
+}
+
+/// Moves played from the starting position of a game, including side lines
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MoveTree {
+    nodes: Vec<Node>,
+}
+
+impl MoveTree {
+    /// The starting position, before any move
+    pub const ROOT: NodeId = 0;
+
+    /// Tree with only the root
+    pub fn new() -> Self {
+        Self {
+            nodes: vec![Node {
+                mov: None,
+                parent: None,
+                children: Vec::new(),
+                annotation: None,
+            }],
+        }
+    }
+
+    /// Node with id, None if there is no such node
+    pub fn node(&self, id: NodeId) -> Option<&Node> {
+        self.nodes.get(id)
+    }
+
+    /// Number of nodes including the root
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// If there are no moves in the tree
+    pub fn is_empty(&self) -> bool {
+        self.nodes.len() == 1
+    }
+
+    /// Nodes from the first move to the node, empty for the root and None for unknown nodes
+    pub fn path(&self, id: NodeId) -> Option<Vec<NodeId>> {
+        let mut path = Vec::new();
+        let mut node = self.node(id)?;
+        let mut id = id;
+        while let Some(parent) = node.parent {
+            path.push(id);
+            id = parent;
+            node = &self.nodes[parent];
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// Moves from the starting position to the node, None for unknown nodes
+    pub fn moves_to(&self, id: NodeId) -> Option<Vec<Move>> {
+        let path = self.path(id)?;
+        Some(
+            path.iter()
+                .map(|&id| self.nodes[id].mov.expect("Only the root has no move"))
+                .collect(),
+        )
+    }
+
+    /// Nodes of the main line, following the first child from the root
+    pub fn main_line(&self) -> Vec<NodeId> {
+        let mut line = Vec::new();
+        let mut id = Self::ROOT;
+        while let Some(&child) = self.nodes[id].children.first() {
+            line.push(child);
+            id = child;
+        }
+        line
+    }
+
+    /// Child of parent with mov, which is added as the last child if it is new
+    pub(crate) fn add(&mut self, parent: NodeId, mov: Move) -> NodeId {
+        if let Some(&child) = self.nodes[parent]
+            .children
+            .iter()
+            .find(|&&child| self.nodes[child].mov == Some(mov))
+        {
+            return child;
+        }
+        let id = self.nodes.len();
+        self.nodes.push(Node {
+            mov: Some(mov),
+            parent: Some(parent),
+            children: Vec::new(),
+            annotation: None,
+        });
+        self.nodes[parent].children.push(id);
+        id
+    }
+
+    /// Make every node on the path to id the first child of its parent
+    fn promote(&mut self, id: NodeId) {
+        let mut id = id;
+        while let Some(parent) = self.nodes[id].parent {
+            let children = &mut self.nodes[parent].children;
+            let index = children
+                .iter()
+                .position(|&child| child == id)
+                .expect("Node is a child of its parent");
+            let child = children.remove(index);
+            children.insert(0, child);
+            id = parent;
+        }
+    }
+
+    /// Annotation of node to change
+    pub(crate) fn annotation_mut(&mut self, id: NodeId) -> &mut MoveAnnotation {
+        self.nodes[id]
+            .annotation
+            .get_or_insert_with(Default::default)
+    }
+}
+
+impl Index<NodeId> for MoveTree {
+    type Output = Node;
+
+    /// Node with id, panics if there is no such node
+    fn index(&self, id: NodeId) -> &Node {
+        &self.nodes[id]
+    }
+}
+
+impl Default for MoveTree {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Game {
+    /// All moves made in the game with their side lines
+    pub fn move_tree(&self) -> &MoveTree {
+        &self.tree
+    }
+
+    /// Node of the last move made, [`MoveTree::ROOT`] before the first move
+    pub fn current_node(&self) -> NodeId {
+        self.node
+    }
+
+    /// Go to the position after the move of node, where the next move continues
+    ///
+    /// The game is replayed from its starting position, so the clock is not turned back. Returns
+    /// Err if there is no such node or the game cannot be replayed, eg. after editing the board or
+    /// in Crazyhouse.
+    pub fn go_to(&mut self, node: NodeId) -> Result<(), &'static str> {
+        let moves = self.tree.moves_to(node).ok_or("No such node")?;
+        if self.crazyhouse.is_some() {
+            return Err("Variations are not supported in Crazyhouse");
+        }
+        let mut game = self._start_game()?;
+        for mov in moves {
+            game._make_move(mov)?;
+        }
+        game.tree = mem::take(&mut self.tree);
+        game.node = node;
+        game.events = mem::take(&mut self.events);
+        *self = game;
+        Ok(())
+    }
+
+    /// The game before the first move of the history, with the same rules
+    ///
+    /// Returns Err if the history does not lead to the current position, eg. after editing the
+    /// board or in Crazyhouse
+    pub(crate) fn _start_game(&self) -> Result<Game, &'static str> {
+        if self.crazyhouse.is_some() {
+            return Err("Drops in Crazyhouse cannot be replayed");
+        }
+        let start = self
+            ._replayable_history()
+            .ok_or("The moves do not lead to the current position")?;
+        let mut game = self._start_position(&start)?;
+        game.odds = self.odds;
+        for color in [Color::White, Color::Black] {
+            game.set_promotion_piece(self.promotion_piece(color))?;
+        }
+        game.clock = self.clock;
+        Ok(game)
+    }
+
+    /// Add the legal `moves` as a side line after the move of node, without changing the current
+    /// position
+    ///
+    /// Moves that are already in the tree are followed. Returns the node of the last move, or Err
+    /// if a move is illegal, see [`Game::go_to`].
+    pub fn add_variation(&mut self, node: NodeId, moves: &[Move]) -> Result<NodeId, &'static str> {
+        let mut game = self.clone();
+        game.go_to(node)?;
+        for &mov in moves {
+            game._make_move(mov)?;
+        }
+        self.tree = game.tree;
+        Ok(game.node)
+    }
+
+    /// Make the line leading to node the main line
+    ///
+    /// Returns Err if there is no such node
+    pub fn promote_variation(&mut self, node: NodeId) -> Result<(), &'static str> {
+        if self.tree.node(node).is_none() {
+            return Err("No such node");
+        }
+        self.tree.promote(node);
+        Ok(())
+    }
+}
@@ -0,0 +1,156 @@
+//! Known-good perft vectors and a quick self-check of the move generator
+//!
+//! Perft counts the leaf nodes of the legal move tree to a given depth. The counts of the
+//! positions below are well established, so any difference means the rules are implemented
+//! differently on the current build or target.
+//! Positions are from <https://www.chessprogramming.org/Perft_Results>.
+
+use core::fmt;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::Game;
+
+/// A position with its known perft node counts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PerftVector {
+    /// Name of the position
+    pub name: &'static str,
+    /// Position in Forsyth–Edwards Notation
+    pub fen: &'static str,
+    /// Number of leaf nodes at depth 1, 2, 3, ...
+    pub nodes: &'static [u64],
+}
+
+/// Perft vectors covering castling (including Chess960), en passant, promotions and checks
+pub const PERFT_VECTORS: &[PerftVector] = &[
+    PerftVector {
+        name: "Starting position",
+        fen: "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        nodes: &[20, 400, 8_902, 197_281, 4_865_609],
+    },
+    PerftVector {
+        name: "Kiwipete",
+        fen: "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        nodes: &[48, 2_039, 97_862, 4_085_603],
+    },
+    PerftVector {
+        name: "Position 3",
+        fen: "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        nodes: &[14, 191, 2_812, 43_238, 674_624],
+    },
+    PerftVector {
+        name: "Position 4",
+        fen: "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        nodes: &[6, 264, 9_467, 422_333],
+    },
+    PerftVector {
+        name: "Position 5",
+        fen: "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        nodes: &[44, 1_486, 62_379, 2_103_487],
+    },
+    PerftVector {
+        name: "Chess960 position 1",
+        fen: "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9",
+        nodes: &[21, 528, 12_189, 326_672],
+    },
+    PerftVector {
+        name: "Chess960 position 2",
+        fen: "2nnrbkr/p1qppppp/8/1ppb4/6PP/3PP3/PPP2P2/BQNNRBKR w HEhe - 1 9",
+        nodes: &[21, 807, 18_002],
+    },
+];
+
+/// Largest node count checked by [`verify_installation`]
+pub const QUICK_NODE_LIMIT: u64 = 3_000;
+
+/// A perft count that differs from the known-good value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PerftMismatch {
+    /// Name of the position
+    pub name: &'static str,
+    /// Searched depth
+    pub depth: u32,
+    /// Known-good node count
+    pub expected: u64,
+    /// Node count of this build
+    pub actual: u64,
+}
+impl fmt::Display for PerftMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Perft mismatch for {} at depth {}: expected {} nodes, got {}",
+            self.name, self.depth, self.expected, self.actual
+        )
+    }
+}
+#[cfg(feature = "std")]
+impl std::error::Error for PerftMismatch {}
+
+/// Number of leaf nodes of the legal move tree `depth` plies deep
+pub fn perft(game: &Game, depth: u32) -> u64 {
+    fn perft_inner(game: &mut Game, depth: u32) -> u64 {
+        let moves = game._legal_moves();
+        if depth == 1 {
+            return moves.len() as u64;
+        }
+        let mut nodes = 0;
+        for mov in moves {
+            let undo = game._make(mov);
+            nodes += perft_inner(game, depth - 1);
+            game._unmake(undo);
+        }
+        nodes
+    }
+    if depth == 0 {
+        return 1;
+    }
+    perft_inner(&mut game.clone(), depth)
+}
+
+/// [`perft`] with the moves from the root position counted on separate threads
+#[cfg(feature = "parallel")]
+pub fn perft_parallel(game: &Game, depth: u32) -> u64 {
+    if depth <= 1 {
+        return perft(game, depth);
+    }
+    game._legal_moves()
+        .into_par_iter()
+        .map(|mov| {
+            let mut game = game.clone();
+            game._make(mov);
+            perft(&game, depth - 1)
+        })
+        .sum()
+}
+
+/// Check every perft vector at the depths with at most `max_nodes` nodes
+pub fn verify(max_nodes: u64) -> Result<(), PerftMismatch> {
+    for vector in PERFT_VECTORS {
+        let game = Game::from_fen(vector.fen).expect("Invalid FEN in perft vector");
+        for (depth, &expected) in (1..).zip(vector.nodes) {
+            if expected > max_nodes {
+                break;
+            }
+            let actual = perft(&game, depth);
+            if actual != expected {
+                return Err(PerftMismatch {
+                    name: vector.name,
+                    depth,
+                    expected,
+                    actual,
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Quick correctness self-check of the move generator, see [`verify`] and [`QUICK_NODE_LIMIT`]
+///
+/// Useful for packagers and wasm deployments to confirm that the build behaves like other targets
+pub fn verify_installation() -> Result<(), PerftMismatch> {
+    verify(QUICK_NODE_LIMIT)
+}
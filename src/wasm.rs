@@ -0,0 +1,215 @@
+//! WebAssembly bindings, enabled with the "wasm" feature
+//!
+//! [`GameHandle`] keeps the game in WebAssembly memory. The older functions pass the whole game
+//! as a JavaScript object on every call and are kept for existing frontends. Illegal moves and
+//! invalid games throw an `Error` with the reason.
+//!
+//! ```js
+//! const game = new GameHandle();
+//! game.makeMove("e2", "e4");
+//! console.log(game.possibleMoves("e7"), game.status(), game.fen());
+//! console.log(game.legalMoves()); // { b1: ["a3", "c3"], ..., g8: ["f6", "h6"] }
+//!
+//! let object = new_game();
+//! object = make_move(object, "e2", "e4");
+//! console.log(get_possible_moves(object, "e7"), object.active_color);
+//! ```
+
+use std::collections::BTreeMap;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::{Color, Game, GameState};
+
+/// State of a game as returned by [`GameHandle::status`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Status {
+    /// Eg. "InProgress" or `{ Draw: "FivefoldRepetition" }`
+    state: GameState,
+    active_color: Color,
+    /// If the king of the player who's turn it is is attacked, also when it is checkmate
+    check: bool,
+    /// Result as in PGN, see [`GameHandle::result`]
+    result: &'static str,
+}
+
+/// Convert value to a JavaScript value, position keys need 64 bits so they become BigInts and
+/// maps become plain objects
+fn _to_js<T: Serialize>(value: &T) -> Result<JsValue, JsError> {
+    let serializer = serde_wasm_bindgen::Serializer::new()
+        .serialize_large_number_types_as_bigints(true)
+        .serialize_maps_as_objects(true);
+    value
+        .serialize(&serializer)
+        .map_err(|error| JsError::new(&error.to_string()))
+}
+
+/// Convert a JavaScript value created by [`_to_js`] back
+fn _from_js<T: DeserializeOwned>(value: JsValue) -> Result<T, JsError> {
+    serde_wasm_bindgen::from_value(value).map_err(|error| JsError::new(&error.to_string()))
+}
+
+/// Destinations of the legal moves of the player who's turn it is by the square of the moving
+/// piece, eg. `{ "e2": ["e3", "e4"] }`, see [`Game::all_moves_by_square`]
+///
+/// A pawn reaching the last rank has its destination once, for any promotion piece.
+pub(crate) fn _legal_destinations(game: &Game) -> BTreeMap<String, Vec<String>> {
+    game.all_moves_by_square()
+        .into_iter()
+        .map(|(from, moves)| {
+            let mut destinations: Vec<String> =
+                moves.iter().map(|mov| mov.to.to_string()).collect();
+            destinations.dedup();
+            (from.to_string(), destinations)
+        })
+        .collect()
+}
+
+/// A game owned by WebAssembly, JavaScript callers hold an opaque handle to it
+#[wasm_bindgen]
+#[derive(Debug, Clone, Default)]
+pub struct GameHandle {
+    game: Game,
+    /// Games before each move, for undo
+    previous: Vec<Game>,
+}
+
+#[wasm_bindgen]
+impl GameHandle {
+    /// Game with the standard piece arrangement
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Game from a position in Forsyth–Edwards Notation, throws an error if it is invalid
+    #[wasm_bindgen(js_name = fromFen)]
+    pub fn from_fen(fen: &str) -> Result<GameHandle, JsError> {
+        let game = Game::from_fen(fen).map_err(JsError::new)?;
+        Ok(Self {
+            game,
+            previous: Vec::new(),
+        })
+    }
+
+    /// Possible destinations of the piece on `pos` (eg. "e2"), empty if there is no piece
+    #[wasm_bindgen(js_name = possibleMoves)]
+    pub fn possible_moves(&self, pos: &str) -> Vec<String> {
+        self.game
+            .get_possible_moves(pos.to_string())
+            .unwrap_or_default()
+    }
+
+    /// Object from the square of every piece that can move to its destinations, eg.
+    /// `{ e2: ["e3", "e4"], ... }`, in one call instead of [`GameHandle::possible_moves`] per
+    /// square
+    #[wasm_bindgen(js_name = legalMoves)]
+    pub fn legal_moves(&self) -> Result<JsValue, JsError> {
+        _to_js(&_legal_destinations(&self.game))
+    }
+
+    /// Move the piece on `from` to `to`, throws an error with the reason if the move is illegal
+    #[wasm_bindgen(js_name = makeMove)]
+    pub fn make_move(&mut self, from: &str, to: &str) -> Result<(), JsError> {
+        let before = self.game.clone();
+        self.game
+            .make_move(from.to_string(), to.to_string())
+            .map_err(|error| JsError::new(error.message()))?;
+        self.previous.push(before);
+        Ok(())
+    }
+
+    /// Take back the last move, returns false if no move has been made
+    pub fn undo(&mut self) -> bool {
+        match self.previous.pop() {
+            Some(game) => {
+                self.game = game;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Set the piece pawns of the player who's turn it is become ("queen", "rook", "bishop"
+    /// or "knight"), throws an error for other pieces
+    #[wasm_bindgen(js_name = setPromotion)]
+    pub fn set_promotion(&mut self, piece: &str) -> Result<(), JsError> {
+        self.game
+            .set_promotion(piece.to_string())
+            .map_err(JsError::new)
+    }
+
+    /// The color who's turn it is, "White" or "Black"
+    #[wasm_bindgen(js_name = activeColor)]
+    pub fn active_color(&self) -> String {
+        format!("{:?}", self.game.active_color)
+    }
+
+    /// Game state, eg. "InProgress", "Check" or "CheckMate"
+    pub fn state(&self) -> String {
+        format!("{:?}", self.game.get_game_state())
+    }
+
+    /// Object with the state, the color who's turn it is, if the king is in check and the result,
+    /// eg. `{ state: "Check", activeColor: "Black", check: true, result: "*" }`
+    pub fn status(&self) -> Result<JsValue, JsError> {
+        _to_js(&Status {
+            state: self.game.get_game_state(),
+            active_color: self.game.active_color,
+            check: self.game._king_is_threatened(self.game.active_color),
+            result: self.game._pgn_result(),
+        })
+    }
+
+    /// Result as in PGN: "1-0" or "0-1" when a player has won, "1/2-1/2" for draws and "*"
+    /// while the game is in progress
+    pub fn result(&self) -> String {
+        self.game._pgn_result().to_string()
+    }
+
+    /// Position in Forsyth–Edwards Notation
+    pub fn fen(&self) -> String {
+        self.game.to_fen()
+    }
+
+    /// Attacks of both colors and mobility of the pieces per square, as
+    /// `{ white, black, mobility }` with an array per rank from rank 1, see [`Game::heatmap`]
+    pub fn heatmap(&self) -> Result<JsValue, JsError> {
+        _to_js(&self.game.heatmap())
+    }
+}
+
+/// New game as a JavaScript object
+#[wasm_bindgen]
+pub fn new_game() -> Result<JsValue, JsError> {
+    _to_js(&Game::new())
+}
+
+/// Possible destinations of the piece on `pos` in `game` (from [`new_game`] or [`make_move`]),
+/// empty if there is no piece. Throws an error if `game` is not a game object
+#[wasm_bindgen]
+pub fn get_possible_moves(game: JsValue, pos: &str) -> Result<Vec<String>, JsError> {
+    let game: Game = _from_js(game)?;
+    Ok(game.get_possible_moves(pos.to_string()).unwrap_or_default())
+}
+
+/// Destinations of every piece that can move in `game`, see [`GameHandle::legal_moves`]. Throws
+/// an error if `game` is not a game object
+#[wasm_bindgen]
+pub fn all_legal_moves(game: JsValue) -> Result<JsValue, JsError> {
+    let game: Game = _from_js(game)?;
+    _to_js(&_legal_destinations(&game))
+}
+
+/// The game after moving the piece on `pos` to `dest`, throws an error with the reason if the
+/// move is illegal
+#[wasm_bindgen]
+pub fn make_move(game: JsValue, pos: &str, dest: &str) -> Result<JsValue, JsError> {
+    let mut game: Game = _from_js(game)?;
+    game.make_move(pos.to_string(), dest.to_string())
+        .map_err(|error| JsError::new(error.message()))?;
+    _to_js(&game)
+}